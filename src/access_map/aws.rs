@@ -2,7 +2,7 @@ use std::collections::BTreeSet;
 use std::path::Path;
 
 use anyhow::{anyhow, Context, Result};
-use aws_config::{BehaviorVersion, SdkConfig};
+use aws_config::{retry::RetryConfig, BehaviorVersion, SdkConfig};
 use aws_credential_types::Credentials;
 use aws_sdk_dynamodb::Client as DynamoClient;
 use aws_sdk_ec2::Client as Ec2Client;
@@ -16,7 +16,7 @@ use percent_encoding::percent_decode_str;
 use serde_json::Value;
 use tracing::warn;
 
-use crate::cli::commands::access_map::AccessMapArgs;
+use crate::{cli::commands::access_map::AccessMapArgs, validation::rate_limit};
 
 use super::{
     build_default_account_resource, build_recommendations, AccessMapResult, AccessSummary,
@@ -65,6 +65,7 @@ async fn map_access_with_config(config: SdkConfig) -> Result<AccessMapResult> {
     let sts = StsClient::new(&config);
     let iam = IamClient::new(&config);
 
+    throttle("sts").await?;
     let caller =
         sts.get_caller_identity().send().await.context("Failed to call sts:GetCallerIdentity")?;
 
@@ -244,6 +245,7 @@ async fn collect_role_actions(
 ) -> Result<Vec<String>> {
     let mut actions = Vec::new();
 
+    throttle("iam").await?;
     let attached =
         iam.list_attached_role_policies().role_name(role_name).send().await.map_err(|err| {
             map_iam_error(
@@ -259,11 +261,13 @@ async fn collect_role_actions(
         }
     }
 
+    throttle("iam").await?;
     let inline = iam.list_role_policies().role_name(role_name).send().await.map_err(|err| {
         map_iam_error(err, risk_notes, &format!("list_role_policies failed for role {role_name}"))
     })?;
 
     for name in inline.policy_names() {
+        throttle("iam").await?;
         let policy =
             iam.get_role_policy().role_name(role_name).policy_name(name).send().await.map_err(
                 |err| {
@@ -288,6 +292,7 @@ async fn collect_user_actions(
 ) -> Result<Vec<String>> {
     let mut actions = Vec::new();
 
+    throttle("iam").await?;
     let attached =
         iam.list_attached_user_policies().user_name(user_name).send().await.map_err(|err| {
             map_iam_error(
@@ -303,11 +308,13 @@ async fn collect_user_actions(
         }
     }
 
+    throttle("iam").await?;
     let inline = iam.list_user_policies().user_name(user_name).send().await.map_err(|err| {
         map_iam_error(err, risk_notes, &format!("list_user_policies failed for user {user_name}"))
     })?;
 
     for name in inline.policy_names() {
+        throttle("iam").await?;
         let policy =
             iam.get_user_policy().user_name(user_name).policy_name(name).send().await.map_err(
                 |err| {
@@ -331,6 +338,7 @@ async fn collect_managed_policy_actions(
     actions: &mut Vec<String>,
     risk_notes: &mut Vec<String>,
 ) -> Result<()> {
+    throttle("iam").await?;
     let policy = iam.get_policy().policy_arn(policy_arn).send().await.map_err(|err| {
         map_iam_error(err, risk_notes, &format!("get_policy failed for {policy_arn}"))
     })?;
@@ -339,6 +347,7 @@ async fn collect_managed_policy_actions(
         .and_then(|p| p.default_version_id())
         .ok_or_else(|| anyhow!("Managed policy {policy_arn} missing default version"))?;
 
+    throttle("iam").await?;
     let document =
         iam.get_policy_version().policy_arn(policy_arn).version_id(version).send().await.map_err(
             |err| {
@@ -489,6 +498,7 @@ async fn enumerate_resources(
 
     if no_permissions || can_read(permissions, "s3.") {
         let client = S3Client::new(config);
+        throttle("s3").await?;
         match client.list_buckets().send().await {
             Ok(resp) => {
                 for bucket in resp.buckets() {
@@ -514,6 +524,7 @@ async fn enumerate_resources(
 
     if no_permissions || can_read(permissions, "ec2.") {
         let ec2 = Ec2Client::new(config);
+        throttle("ec2").await?;
         match ec2.describe_instances().send().await {
             Ok(resp) => {
                 let region = config
@@ -547,6 +558,7 @@ async fn enumerate_resources(
 
     if no_permissions || can_read(permissions, "iam.") {
         let iam = IamClient::new(config);
+        throttle("iam").await?;
         match iam.list_roles().send().await {
             Ok(resp) => {
                 for role in resp.roles() {
@@ -571,6 +583,7 @@ async fn enumerate_resources(
 
     if no_permissions || can_read(permissions, "lambda.") {
         let lambda = LambdaClient::new(config);
+        throttle("lambda").await?;
         match lambda.list_functions().send().await {
             Ok(resp) => {
                 for function in resp.functions() {
@@ -596,6 +609,7 @@ async fn enumerate_resources(
 
     if no_permissions || can_read(permissions, "dynamodb.") {
         let dynamo = DynamoClient::new(config);
+        throttle("dynamodb").await?;
         match dynamo.list_tables().send().await {
             Ok(resp) => {
                 for table in resp.table_names() {
@@ -619,6 +633,7 @@ async fn enumerate_resources(
 
     if no_permissions || can_read(permissions, "kms.") {
         let kms = KmsClient::new(config);
+        throttle("kms").await?;
         match kms.list_keys().send().await {
             Ok(resp) => {
                 let region = config.region().map(|r| r.as_ref().to_string());
@@ -661,6 +676,7 @@ async fn enumerate_resources(
 
     if no_permissions || can_read(permissions, "secretsmanager.") {
         let sm = SecretsManagerClient::new(config);
+        throttle("secretsmanager").await?;
         match sm.list_secrets().send().await {
             Ok(resp) => {
                 for secret in resp.secret_list() {
@@ -687,6 +703,12 @@ async fn enumerate_resources(
     Ok(resources)
 }
 
+/// Pace an AWS API call through the shared validation rate limiter.
+async fn throttle(service: &str) -> Result<()> {
+    rate_limit::acquire(&format!("{service}.amazonaws.com")).await?;
+    Ok(())
+}
+
 async fn load_config_from_path(path: Option<&Path>) -> Result<SdkConfig> {
     if let Some(path) = path {
         let creds = load_credentials_from_file(path)?;
@@ -697,7 +719,9 @@ async fn load_config_from_path(path: Option<&Path>) -> Result<SdkConfig> {
 }
 
 async fn load_config(credentials: Option<Credentials>) -> Result<SdkConfig> {
-    let mut loader = aws_config::defaults(BehaviorVersion::latest());
+    // Adaptive retries back off client-side when AWS starts throttling.
+    let mut loader = aws_config::defaults(BehaviorVersion::latest())
+        .retry_config(RetryConfig::adaptive().with_max_attempts(3));
 
     if let Some(creds) = credentials {
         loader = loader.credentials_provider(creds);
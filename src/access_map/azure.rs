@@ -7,7 +7,7 @@ use reqwest::{header::HeaderValue, Client};
 use serde_json::Value as JsonValue;
 use sha2::Sha256;
 
-use crate::cli::commands::access_map::AccessMapArgs;
+use crate::{cli::commands::access_map::AccessMapArgs, validation::rate_limit};
 
 use super::{
     build_recommendations, AccessMapResult, AccessSummary, PermissionSummary, ResourceExposure,
@@ -171,7 +171,7 @@ async fn list_containers(storage_account: &str, storage_key: &str) -> Result<Vec
         );
 
         let client = Client::builder().build()?;
-        let resp = client.get(url).headers(headers).send().await?;
+        let resp = rate_limit::send(client.get(url).headers(headers)).await?;
         let status = resp.status();
         let body_txt = resp.text().await?;
 
@@ -4,7 +4,7 @@ use reqwest::{header, Client, Url};
 use serde::Deserialize;
 use tracing::warn;
 
-use crate::validation::GLOBAL_USER_AGENT;
+use crate::validation::{rate_limit, GLOBAL_USER_AGENT};
 
 use super::{
     build_recommendations, AccessMapResult, AccessSummary, AccessTokenDetails, PermissionSummary,
@@ -277,10 +277,7 @@ async fn fetch_profile(
     auth_header: header::HeaderValue,
 ) -> Result<(AzureDevopsProfile, Vec<String>, AzureDevopsUserData)> {
     let profile_url = Url::parse(AZURE_DEVOPS_PROFILE_API).expect("valid Azure DevOps profile URL");
-    let resp = client
-        .get(profile_url)
-        .header(header::AUTHORIZATION, auth_header)
-        .send()
+    let resp = rate_limit::send(client.get(profile_url).header(header::AUTHORIZATION, auth_header))
         .await
         .context("Azure DevOps access-map: failed to fetch user profile")?;
 
@@ -349,13 +346,14 @@ async fn fetch_pat_details(
     .ok()?;
     url.path_segments_mut().ok()?.push(&subject_descriptor);
     url.query_pairs_mut().append_pair("api-version", AZURE_DEVOPS_TOKEN_ADMIN_VERSION);
-    let resp = client
-        .get(url)
-        .header(header::ACCEPT, "application/json")
-        .header(header::AUTHORIZATION, auth_header)
-        .send()
-        .await
-        .ok()?;
+    let resp = rate_limit::send(
+        client
+            .get(url)
+            .header(header::ACCEPT, "application/json")
+            .header(header::AUTHORIZATION, auth_header),
+    )
+    .await
+    .ok()?;
 
     if !resp.status().is_success() {
         return None;
@@ -402,13 +400,14 @@ async fn fetch_subject_descriptor(
                 .append_pair("filterValue", search_value);
         }
 
-        let resp = client
-            .get(url)
-            .header(header::ACCEPT, "application/json")
-            .header(header::AUTHORIZATION, auth_header.clone())
-            .send()
-            .await
-            .ok()?;
+        let resp = rate_limit::send(
+            client
+                .get(url)
+                .header(header::ACCEPT, "application/json")
+                .header(header::AUTHORIZATION, auth_header.clone()),
+        )
+        .await
+        .ok()?;
 
         if !resp.status().is_success() {
             continue;
@@ -497,13 +496,14 @@ async fn list_repositories(
     let url = format!(
         "https://dev.azure.com/{organization}/_apis/git/repositories?api-version={AZURE_DEVOPS_API_VERSION}"
     );
-    let resp = client
-        .get(url)
-        .header(header::ACCEPT, "application/json")
-        .header(header::AUTHORIZATION, auth_header.clone())
-        .send()
-        .await
-        .context("Azure DevOps access-map: failed to list repositories")?;
+    let resp = rate_limit::send(
+        client
+            .get(url)
+            .header(header::ACCEPT, "application/json")
+            .header(header::AUTHORIZATION, auth_header.clone()),
+    )
+    .await
+    .context("Azure DevOps access-map: failed to list repositories")?;
 
     let mut repos = if resp.status().is_success() {
         let payload: AzureDevopsListResponse<AzureDevopsRepo> =
@@ -548,13 +548,14 @@ async fn list_project_repositories(
     let url = format!(
         "https://dev.azure.com/{organization}/{project}/_apis/git/repositories?api-version={AZURE_DEVOPS_API_VERSION}"
     );
-    let resp = client
-        .get(url)
-        .header(header::ACCEPT, "application/json")
-        .header(header::AUTHORIZATION, auth_header)
-        .send()
-        .await
-        .context("Azure DevOps access-map: failed to list project repositories")?;
+    let resp = rate_limit::send(
+        client
+            .get(url)
+            .header(header::ACCEPT, "application/json")
+            .header(header::AUTHORIZATION, auth_header),
+    )
+    .await
+    .context("Azure DevOps access-map: failed to list project repositories")?;
 
     if !resp.status().is_success() {
         return Err(anyhow!(
@@ -576,13 +577,14 @@ async fn list_projects(
     let url = format!(
         "https://dev.azure.com/{organization}/_apis/projects?api-version={AZURE_DEVOPS_API_VERSION}"
     );
-    let resp = client
-        .get(url)
-        .header(header::ACCEPT, "application/json")
-        .header(header::AUTHORIZATION, auth_header)
-        .send()
-        .await
-        .context("Azure DevOps access-map: failed to list projects")?;
+    let resp = rate_limit::send(
+        client
+            .get(url)
+            .header(header::ACCEPT, "application/json")
+            .header(header::AUTHORIZATION, auth_header),
+    )
+    .await
+    .context("Azure DevOps access-map: failed to list projects")?;
 
     if !resp.status().is_success() {
         warn!("Azure DevOps access-map: project enumeration failed with HTTP {}", resp.status());
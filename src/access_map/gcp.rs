@@ -15,7 +15,7 @@ macro_rules! verbose_warn {
     };
 }
 
-use crate::validation::gcp::GcpValidator;
+use crate::validation::{gcp::GcpValidator, rate_limit};
 
 #[derive(Debug, Clone)]
 struct Ancestor {
@@ -214,12 +214,13 @@ async fn fetch_project_policy(
     let url =
         format!("https://cloudresourcemanager.googleapis.com/v1/projects/{}:getIamPolicy", project);
 
-    let resp_v3 = client
-        .post(&url)
-        .bearer_auth(token)
-        .json(&serde_json::json!({ "options": { "requestedPolicyVersion": 3 } }))
-        .send()
-        .await?;
+    let resp_v3 = rate_limit::send(
+        client
+            .post(&url)
+            .bearer_auth(token)
+            .json(&serde_json::json!({ "options": { "requestedPolicyVersion": 3 } })),
+    )
+    .await?;
     let status_v3 = resp_v3.status();
     let body_v3 = resp_v3.bytes().await?;
 
@@ -237,7 +238,8 @@ async fn fetch_project_policy(
 
     if status_v3 == StatusCode::FORBIDDEN || status_v3 == StatusCode::BAD_REQUEST {
         let resp_v1 =
-            client.post(&url).bearer_auth(token).json(&serde_json::json!({})).send().await?;
+            rate_limit::send(client.post(&url).bearer_auth(token).json(&serde_json::json!({})))
+                .await?;
         let status_v1 = resp_v1.status();
         let body_v1 = resp_v1.bytes().await?;
 
@@ -282,7 +284,8 @@ async fn fetch_project_ancestry(
         project_id
     );
 
-    let resp = client.post(url).bearer_auth(token).json(&serde_json::json!({})).send().await?;
+    let resp =
+        rate_limit::send(client.post(url).bearer_auth(token).json(&serde_json::json!({}))).await?;
     let status = resp.status();
     let body = resp.bytes().await?;
 
@@ -338,12 +341,13 @@ async fn fetch_ancestor_policy(
         _ => return Ok(None),
     };
 
-    let resp_v3 = client
-        .post(&url)
-        .bearer_auth(token)
-        .json(&serde_json::json!({ "options": { "requestedPolicyVersion": 3 } }))
-        .send()
-        .await?;
+    let resp_v3 = rate_limit::send(
+        client
+            .post(&url)
+            .bearer_auth(token)
+            .json(&serde_json::json!({ "options": { "requestedPolicyVersion": 3 } })),
+    )
+    .await?;
     let status_v3 = resp_v3.status();
     let body_v3 = resp_v3.bytes().await?;
 
@@ -363,7 +367,8 @@ async fn fetch_ancestor_policy(
 
     if status_v3 == StatusCode::FORBIDDEN || status_v3 == StatusCode::BAD_REQUEST {
         let resp_v1 =
-            client.post(&url).bearer_auth(token).json(&serde_json::json!({})).send().await?;
+            rate_limit::send(client.post(&url).bearer_auth(token).json(&serde_json::json!({})))
+                .await?;
         let status_v1 = resp_v1.status();
         let body_v1 = resp_v1.bytes().await?;
 
@@ -414,7 +419,7 @@ async fn fetch_service_account_project(
     let encoded_email = utf8_percent_encode(client_email, NON_ALPHANUMERIC);
     let url = format!("https://iam.googleapis.com/v1/projects/-/serviceAccounts/{}", encoded_email);
 
-    let resp = client.get(url).bearer_auth(token).send().await?;
+    let resp = rate_limit::send(client.get(url).bearer_auth(token)).await?;
     let status = resp.status();
     let body = resp.bytes().await?;
 
@@ -481,7 +486,7 @@ async fn fetch_role_permissions(
         format!("https://iam.googleapis.com/v1/roles/{role_name}")
     };
 
-    let resp = client.get(url).bearer_auth(token).send().await?;
+    let resp = rate_limit::send(client.get(url).bearer_auth(token)).await?;
     let status = resp.status();
     let body = resp.bytes().await?;
 
@@ -644,7 +649,7 @@ async fn enumerate_resources(
 
     if add_storage {
         let url = format!("https://storage.googleapis.com/storage/v1/b?project={}", project_id);
-        let resp = client.get(&url).bearer_auth(token).send().await?;
+        let resp = rate_limit::send(client.get(&url).bearer_auth(token)).await?;
         let status = resp.status();
         let body = resp.bytes().await?;
 
@@ -693,7 +698,7 @@ async fn enumerate_resources(
             "https://compute.googleapis.com/compute/v1/projects/{}/aggregated/instances",
             project_id
         );
-        let resp = client.get(&url).bearer_auth(token).send().await?;
+        let resp = rate_limit::send(client.get(&url).bearer_auth(token)).await?;
         let status = resp.status();
         let body = resp.bytes().await?;
 
@@ -747,7 +752,7 @@ async fn enumerate_resources(
             "https://sqladmin.googleapis.com/sql/v1beta4/projects/{}/instances",
             project_id
         );
-        let resp = client.get(&url).bearer_auth(token).send().await?;
+        let resp = rate_limit::send(client.get(&url).bearer_auth(token)).await?;
         let status = resp.status();
         let body = resp.bytes().await?;
 
@@ -809,7 +814,7 @@ async fn enumerate_resources(
         for (url, resource_type) in
             [(topics_url, "pubsub_topic"), (subs_url, "pubsub_subscription")]
         {
-            let resp = client.get(&url).bearer_auth(token).send().await?;
+            let resp = rate_limit::send(client.get(&url).bearer_auth(token)).await?;
             let status = resp.status();
             let body = resp.bytes().await?;
 
@@ -857,7 +862,7 @@ async fn enumerate_resources(
     if add_cloud_run {
         let url =
             format!("https://run.googleapis.com/v2/projects/{}/locations/-/services", project_id);
-        let resp = client.get(&url).bearer_auth(token).send().await?;
+        let resp = rate_limit::send(client.get(&url).bearer_auth(token)).await?;
         let status = resp.status();
         let body = resp.bytes().await?;
 
@@ -902,7 +907,7 @@ async fn enumerate_resources(
             "https://artifactregistry.googleapis.com/v1/projects/{}/locations/-/repositories",
             project_id
         );
-        let resp = client.get(&url).bearer_auth(token).send().await?;
+        let resp = rate_limit::send(client.get(&url).bearer_auth(token)).await?;
         let status = resp.status();
         let body = resp.bytes().await?;
 
@@ -948,7 +953,7 @@ async fn enumerate_resources(
             "https://container.googleapis.com/v1/projects/{}/locations/-/clusters",
             project_id
         );
-        let resp = client.get(&url).bearer_auth(token).send().await?;
+        let resp = rate_limit::send(client.get(&url).bearer_auth(token)).await?;
         let status = resp.status();
         let body = resp.bytes().await?;
 
@@ -990,7 +995,7 @@ async fn enumerate_resources(
     if add_bigquery {
         let url =
             format!("https://bigquery.googleapis.com/bigquery/v2/projects/{}/datasets", project_id);
-        let resp = client.get(&url).bearer_auth(token).send().await?;
+        let resp = rate_limit::send(client.get(&url).bearer_auth(token)).await?;
         let status = resp.status();
         let body = resp.bytes().await?;
 
@@ -1032,7 +1037,7 @@ async fn enumerate_resources(
     if add_secret_manager {
         let url =
             format!("https://secretmanager.googleapis.com/v1/projects/{}/secrets", project_id);
-        let resp = client.get(&url).bearer_auth(token).send().await?;
+        let resp = rate_limit::send(client.get(&url).bearer_auth(token)).await?;
         let status = resp.status();
         let body = resp.bytes().await?;
 
@@ -1084,12 +1089,13 @@ async fn fetch_service_account_iam_policy(
         project_id, encoded_email
     );
 
-    let resp = client
-        .post(&url)
-        .bearer_auth(token)
-        .json(&serde_json::json!({ "options": { "requestedPolicyVersion": 3 } }))
-        .send()
-        .await?;
+    let resp = rate_limit::send(
+        client
+            .post(&url)
+            .bearer_auth(token)
+            .json(&serde_json::json!({ "options": { "requestedPolicyVersion": 3 } })),
+    )
+    .await?;
     let status = resp.status();
     let body = resp.bytes().await?;
 
@@ -1219,12 +1225,10 @@ async fn test_project_permissions(
         project_id
     );
 
-    let resp = client
-        .post(url)
-        .bearer_auth(token)
-        .json(&serde_json::json!({ "permissions": candidates }))
-        .send()
-        .await?;
+    let resp = rate_limit::send(
+        client.post(url).bearer_auth(token).json(&serde_json::json!({ "permissions": candidates })),
+    )
+    .await?;
 
     if resp.status() == StatusCode::FORBIDDEN {
         verbose_warn!("GCP access-map: testIamPermissions forbidden for project {project_id}");
@@ -1263,12 +1267,10 @@ async fn test_service_account_permissions(
 
     let url = format!("https://iam.googleapis.com/v1/{}:testIamPermissions", resource);
 
-    let resp = client
-        .post(url)
-        .bearer_auth(token)
-        .json(&serde_json::json!({ "permissions": candidates }))
-        .send()
-        .await?;
+    let resp = rate_limit::send(
+        client.post(url).bearer_auth(token).json(&serde_json::json!({ "permissions": candidates })),
+    )
+    .await?;
 
     if resp.status() == StatusCode::FORBIDDEN {
         verbose_warn!(
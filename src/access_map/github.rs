@@ -3,7 +3,10 @@ use reqwest::{header, Client, Url};
 use serde::Deserialize;
 use tracing::warn;
 
-use crate::{cli::commands::access_map::AccessMapArgs, validation::GLOBAL_USER_AGENT};
+use crate::{
+    cli::commands::access_map::AccessMapArgs,
+    validation::{rate_limit, GLOBAL_USER_AGENT},
+};
 
 use super::{
     build_recommendations, AccessMapResult, AccessSummary, AccessTokenDetails, PermissionSummary,
@@ -77,13 +80,14 @@ pub async fn map_access_from_token(token: &str) -> Result<AccessMapResult> {
         .build()
         .context("Failed to build GitHub HTTP client")?;
 
-    let user_resp = client
-        .get(api_url.join("user")?)
-        .header(header::AUTHORIZATION, format!("token {token}"))
-        .header(header::ACCEPT, "application/vnd.github+json")
-        .send()
-        .await
-        .context("GitHub access-map: failed to fetch user info")?;
+    let user_resp = rate_limit::send(
+        client
+            .get(api_url.join("user")?)
+            .header(header::AUTHORIZATION, format!("token {token}"))
+            .header(header::ACCEPT, "application/vnd.github+json"),
+    )
+    .await
+    .context("GitHub access-map: failed to fetch user info")?;
 
     if !user_resp.status().is_success() {
         return Err(anyhow!(
@@ -314,13 +318,14 @@ async fn list_accessible_repos(
             .append_pair("per_page", &per_page.to_string())
             .append_pair("page", &page.to_string());
 
-        let resp = client
-            .get(url)
-            .header(header::AUTHORIZATION, format!("token {token}"))
-            .header(header::ACCEPT, "application/vnd.github+json")
-            .send()
-            .await
-            .context("GitHub access-map: failed to list repositories")?;
+        let resp = rate_limit::send(
+            client
+                .get(url)
+                .header(header::AUTHORIZATION, format!("token {token}"))
+                .header(header::ACCEPT, "application/vnd.github+json"),
+        )
+        .await
+        .context("GitHub access-map: failed to list repositories")?;
 
         if !resp.status().is_success() {
             warn!("GitHub access-map: repo enumeration failed with HTTP {}", resp.status());
@@ -356,13 +361,14 @@ async fn list_org_memberships(
             .append_pair("per_page", &per_page.to_string())
             .append_pair("page", &page.to_string());
 
-        let resp = client
-            .get(url)
-            .header(header::AUTHORIZATION, format!("token {token}"))
-            .header(header::ACCEPT, "application/vnd.github+json")
-            .send()
-            .await
-            .context("GitHub access-map: failed to list org memberships")?;
+        let resp = rate_limit::send(
+            client
+                .get(url)
+                .header(header::AUTHORIZATION, format!("token {token}"))
+                .header(header::ACCEPT, "application/vnd.github+json"),
+        )
+        .await
+        .context("GitHub access-map: failed to list org memberships")?;
 
         if !resp.status().is_success() {
             warn!(
@@ -3,7 +3,10 @@ use reqwest::{header, Client, Url};
 use serde::Deserialize;
 use tracing::warn;
 
-use crate::{cli::commands::access_map::AccessMapArgs, validation::GLOBAL_USER_AGENT};
+use crate::{
+    cli::commands::access_map::AccessMapArgs,
+    validation::{rate_limit, GLOBAL_USER_AGENT},
+};
 
 use super::{
     build_recommendations, AccessMapResult, AccessSummary, AccessTokenDetails, PermissionSummary,
@@ -184,13 +187,14 @@ pub async fn map_access_from_token(token: &str) -> Result<AccessMapResult> {
 }
 
 async fn fetch_token_info(client: &Client, api_url: &Url, token: &str) -> Option<GitLabTokenInfo> {
-    let resp = client
-        .get(api_url.join("personal_access_tokens/self").ok()?)
-        .header("PRIVATE-TOKEN", token)
-        .header(header::ACCEPT, "application/json")
-        .send()
-        .await
-        .ok()?;
+    let resp = rate_limit::send(
+        client
+            .get(api_url.join("personal_access_tokens/self").ok()?)
+            .header("PRIVATE-TOKEN", token)
+            .header(header::ACCEPT, "application/json"),
+    )
+    .await
+    .ok()?;
 
     if !resp.status().is_success() {
         return None;
@@ -204,13 +208,14 @@ async fn fetch_instance_metadata(
     api_url: &Url,
     token: &str,
 ) -> Option<GitLabMetadata> {
-    let resp = client
-        .get(api_url.join("metadata").ok()?)
-        .header("PRIVATE-TOKEN", token)
-        .header(header::ACCEPT, "application/json")
-        .send()
-        .await
-        .ok()?;
+    let resp = rate_limit::send(
+        client
+            .get(api_url.join("metadata").ok()?)
+            .header("PRIVATE-TOKEN", token)
+            .header(header::ACCEPT, "application/json"),
+    )
+    .await
+    .ok()?;
 
     if !resp.status().is_success() {
         return None;
@@ -235,13 +240,14 @@ async fn list_accessible_projects(
             .append_pair("per_page", &per_page.to_string())
             .append_pair("page", &page.to_string());
 
-        let resp = client
-            .get(url)
-            .header("PRIVATE-TOKEN", token)
-            .header(header::ACCEPT, "application/json")
-            .send()
-            .await
-            .context("GitLab access-map: failed to list projects")?;
+        let resp = rate_limit::send(
+            client
+                .get(url)
+                .header("PRIVATE-TOKEN", token)
+                .header(header::ACCEPT, "application/json"),
+        )
+        .await
+        .context("GitLab access-map: failed to list projects")?;
 
         if !resp.status().is_success() {
             warn!("GitLab access-map: project enumeration failed with HTTP {}", resp.status());
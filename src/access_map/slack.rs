@@ -2,6 +2,8 @@ use anyhow::{anyhow, Result};
 use reqwest::header::AUTHORIZATION;
 use serde::Deserialize;
 
+use crate::validation::rate_limit;
+
 use super::{
     build_recommendations, AccessMapArgs, AccessMapResult, AccessSummary, AccessTokenDetails,
     PermissionSummary, ProviderMetadata, ResourceExposure, RoleBinding, Severity,
@@ -23,11 +25,12 @@ pub async fn map_access(args: &AccessMapArgs) -> Result<AccessMapResult> {
 
 pub async fn map_access_from_token(token: &str) -> Result<AccessMapResult> {
     let client = reqwest::Client::new();
-    let resp = client
-        .post("https://slack.com/api/auth.test")
-        .header(AUTHORIZATION, format!("Bearer {token}"))
-        .send()
-        .await?;
+    let resp = rate_limit::send(
+        client
+            .post("https://slack.com/api/auth.test")
+            .header(AUTHORIZATION, format!("Bearer {token}")),
+    )
+    .await?;
 
     let headers = resp.headers().clone();
     let scopes_header =
@@ -0,0 +1,403 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+};
+
+use anyhow::{bail, Context, Result};
+use chrono::{Local, NaiveDate};
+use globset::{Glob, GlobMatcher};
+use regex::Regex;
+use serde::Deserialize;
+use tracing::{debug, info, warn};
+
+use crate::{findings_store::FindingsStore, matcher::Match, origin::Origin};
+
+/// On-disk allowlist document.
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AllowlistFile {
+    #[serde(default)]
+    pub entries: Vec<AllowlistEntry>,
+}
+
+/// A single reviewable suppression. Every criterion that is set must match for
+/// the entry to apply.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AllowlistEntry {
+    /// Optional name used when reporting on the entry
+    #[serde(default)]
+    pub id: Option<String>,
+    /// Rule id, or a rule id prefix such as `kingfisher.aws` or `kingfisher.aws.*`
+    #[serde(default)]
+    pub rule: Option<String>,
+    /// Glob matched against the path relative to the scan root
+    #[serde(default)]
+    pub path: Option<String>,
+    /// Regex matched against the captured secret values
+    #[serde(default)]
+    pub secret: Option<String>,
+    /// Commit id (or prefix) the finding must have been introduced in
+    #[serde(default)]
+    pub commit: Option<String>,
+    /// Branch the scan must target (`--branch`) for the entry to apply
+    #[serde(default)]
+    pub branch: Option<String>,
+    /// Why the suppression is acceptable
+    pub justification: String,
+    /// Last day (`YYYY-MM-DD`) the entry is honoured
+    #[serde(default)]
+    pub expires: Option<String>,
+}
+
+struct CompiledEntry {
+    label: String,
+    entry: AllowlistEntry,
+    path: Option<GlobMatcher>,
+    secret: Option<Regex>,
+    expired: bool,
+    hits: AtomicUsize,
+}
+
+impl CompiledEntry {
+    fn matches(&self, origin: &Origin, m: &Match, roots: &[PathBuf], branch: Option<&str>) -> bool {
+        if let Some(rule) = &self.entry.rule {
+            if !rule_matches(rule, m.rule.id()) {
+                return false;
+            }
+        }
+        if let Some(wanted) = &self.entry.branch {
+            if branch != Some(wanted.as_str()) {
+                return false;
+            }
+        }
+        if let Some(glob) = &self.path {
+            match origin.relative_path(roots) {
+                Some(rel) if glob.is_match(&rel) => {}
+                _ => return false,
+            }
+        }
+        if let Some(prefix) = &self.entry.commit {
+            let prefix = prefix.to_ascii_lowercase();
+            let in_commit = match origin {
+                Origin::GitRepo(e) => e
+                    .first_commit
+                    .as_ref()
+                    .is_some_and(|c| c.commit_metadata.commit_id.to_string().starts_with(&prefix)),
+                _ => false,
+            };
+            if !in_commit {
+                return false;
+            }
+        }
+        if let Some(re) = &self.secret {
+            if !m.groups.captures.iter().any(|c| re.is_match(c.raw_value())) {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+/// Match a rule id against an exact id or a dotted prefix such as
+/// `kingfisher.aws` or `kingfisher.aws.*`, ignoring case.
+pub(crate) fn rule_matches(pattern: &str, rule_id: &str) -> bool {
+    let pattern = pattern.to_ascii_lowercase();
+    let rule_id = rule_id.to_ascii_lowercase();
+    let prefix = pattern.trim_end_matches('*').trim_end_matches('.');
+    rule_id == prefix || rule_id.starts_with(&format!("{prefix}."))
+}
+
+/// A loaded allowlist, shared across every repository scanned in one run so
+/// that hit counts cover the whole scan.
+pub struct Allowlist {
+    entries: Vec<CompiledEntry>,
+    branch: Option<String>,
+}
+
+impl Allowlist {
+    pub fn load(path: &Path, branch: Option<&str>) -> Result<Self> {
+        let data = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read allowlist {}", path.display()))?;
+        let file: AllowlistFile = serde_yaml::from_str(&data)
+            .with_context(|| format!("Failed to parse allowlist {}", path.display()))?;
+        Self::from_file(file, branch, Local::now().date_naive())
+    }
+
+    pub fn from_file(file: AllowlistFile, branch: Option<&str>, today: NaiveDate) -> Result<Self> {
+        let mut entries = Vec::with_capacity(file.entries.len());
+        for (idx, entry) in file.entries.into_iter().enumerate() {
+            let label = entry.id.clone().unwrap_or_else(|| format!("#{}", idx + 1));
+            if entry.justification.trim().is_empty() {
+                bail!("Allowlist entry {label} needs a justification");
+            }
+            if entry.rule.is_none()
+                && entry.path.is_none()
+                && entry.secret.is_none()
+                && entry.commit.is_none()
+            {
+                bail!(
+                    "Allowlist entry {label} must set at least one of rule, path, secret or commit"
+                );
+            }
+            let path = match &entry.path {
+                Some(p) => Some(
+                    Glob::new(p)
+                        .with_context(|| format!("Invalid path glob in allowlist entry {label}"))?
+                        .compile_matcher(),
+                ),
+                None => None,
+            };
+            let secret = match &entry.secret {
+                Some(s) => {
+                    let re = Regex::new(s).with_context(|| {
+                        format!("Invalid secret regex in allowlist entry {label}")
+                    })?;
+                    Some(re)
+                }
+                None => None,
+            };
+            let expired = match &entry.expires {
+                Some(date) => {
+                    let expires =
+                        NaiveDate::parse_from_str(date, "%Y-%m-%d").with_context(|| {
+                            format!("Invalid expires date '{date}' in allowlist entry {label}")
+                        })?;
+                    expires < today
+                }
+                None => false,
+            };
+            if expired {
+                warn!(
+                    "Allowlist entry {label} expired on {}; it no longer suppresses findings",
+                    entry.expires.as_deref().unwrap_or_default()
+                );
+            }
+            entries.push(CompiledEntry {
+                label,
+                entry,
+                path,
+                secret,
+                expired,
+                hits: AtomicUsize::new(0),
+            });
+        }
+        Ok(Self { entries, branch: branch.map(str::to_string) })
+    }
+
+    /// Hide every visible finding matched by an unexpired entry, recording the
+    /// entry on the match so reports can show why it was suppressed.
+    pub fn apply(&self, store: &mut FindingsStore, roots: &[PathBuf]) {
+        for arc_msg in store.get_matches_mut() {
+            let (origin, _blob, m) = Arc::make_mut(arc_msg);
+            if !m.visible {
+                continue;
+            }
+            let hit =
+                self.entries.iter().filter(|e| !e.expired).find(|e| {
+                    origin.iter().any(|o| e.matches(o, m, roots, self.branch.as_deref()))
+                });
+            if let Some(entry) = hit {
+                entry.hits.fetch_add(1, Ordering::Relaxed);
+                debug!("Suppressing {} finding via allowlist entry {}", m.rule.id(), entry.label);
+                m.visible = false;
+                m.suppressed_by = Some(format!(
+                    "allowlist entry {} ({})",
+                    entry.label, entry.entry.justification
+                ));
+            }
+        }
+    }
+
+    /// Log how many findings each entry suppressed and warn about entries that
+    /// never matched anything.
+    pub fn report_usage(&self) {
+        for entry in self.entries.iter().filter(|e| !e.expired) {
+            match entry.hits.load(Ordering::Relaxed) {
+                0 => warn!("Allowlist entry {} did not match any finding", entry.label),
+                hits => info!("Allowlist entry {} suppressed {} finding(s)", entry.label, hits),
+            }
+        }
+    }
+
+    pub fn hits(&self, label: &str) -> Option<usize> {
+        self.entries.iter().find(|e| e.label == label).map(|e| e.hits.load(Ordering::Relaxed))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        blob::{BlobId, BlobMetadata},
+        location::{Location, OffsetSpan, SourcePoint, SourceSpan},
+        matcher::{SerializableCapture, SerializableCaptures},
+        origin::OriginSet,
+        rules::rule::{Confidence, Rule, RuleSyntax},
+    };
+    use smallvec::smallvec;
+
+    fn rule(id: &str) -> Arc<Rule> {
+        Arc::new(Rule::new(RuleSyntax {
+            name: "test".to_string(),
+            id: id.to_string(),
+            pattern: "test".to_string(),
+            min_entropy: 0.0,
+            confidence: Confidence::Low,
+            visible: true,
+            examples: vec![],
+            negative_examples: vec![],
+            references: vec![],
+            validation: None,
+            depends_on_rule: vec![],
+            extract: Default::default(),
+            revocation: None,
+            pattern_requirements: None,
+        }))
+    }
+
+    fn store_with(rule_id: &str, path: &Path, secret: &'static str) -> FindingsStore {
+        let mut store = FindingsStore::new(PathBuf::from("."));
+        let m = Match {
+            location: Location::with_source_span(
+                OffsetSpan { start: 0, end: 1 },
+                Some(SourceSpan {
+                    start: SourcePoint { line: 1, column: 0 },
+                    end: SourcePoint { line: 1, column: 1 },
+                }),
+            ),
+            groups: SerializableCaptures {
+                captures: smallvec![SerializableCapture {
+                    name: None,
+                    match_number: 0,
+                    start: 0,
+                    end: secret.len(),
+                    value: secret,
+                }],
+            },
+            blob_id: BlobId::default(),
+            finding_fingerprint: 1,
+            rule: rule(rule_id),
+            validation_response_body: None,
+            validation_response_status: 0,
+            validation_success: false,
+            validation_identity: Default::default(),
+            dependency_sources: Default::default(),
+            calculated_entropy: 0.0,
+            visible: true,
+            is_base64: false,
+            suppressed_by: None,
+        };
+        let origin = OriginSet::from(Origin::from_file(path.to_path_buf()));
+        let blob = Arc::new(BlobMetadata {
+            id: BlobId::default(),
+            num_bytes: 0,
+            mime_essence: None,
+            language: None,
+        });
+        store.get_matches_mut().push(Arc::new((Arc::new(origin), blob, m)));
+        store
+    }
+
+    fn allowlist(yaml: &str, today: NaiveDate) -> Allowlist {
+        let file: AllowlistFile = serde_yaml::from_str(yaml).unwrap();
+        Allowlist::from_file(file, None, today).unwrap()
+    }
+
+    fn today() -> NaiveDate {
+        NaiveDate::from_ymd_opt(2026, 1, 15).unwrap()
+    }
+
+    #[test]
+    fn rule_prefixes_match_families() {
+        assert!(rule_matches("kingfisher.aws", "kingfisher.aws.1"));
+        assert!(rule_matches("kingfisher.aws.*", "kingfisher.aws.2"));
+        assert!(rule_matches("KINGFISHER.AWS.1", "kingfisher.aws.1"));
+        assert!(!rule_matches("kingfisher.aws", "kingfisher.awsbedrock.1"));
+    }
+
+    #[test]
+    fn scoped_entry_suppresses_matching_finding() {
+        let root = PathBuf::from("/repo");
+        let mut store = store_with("kingfisher.aws.1", &root.join("tests/keys.txt"), "AKIAEXAMPLE");
+        let list = allowlist(
+            r#"
+entries:
+  - id: fixtures
+    rule: kingfisher.aws
+    path: "tests/**"
+    secret: "EXAMPLE$"
+    justification: test fixtures
+"#,
+            today(),
+        );
+
+        list.apply(&mut store, &[root]);
+
+        let (_, _, m) = &*store.get_matches()[0];
+        assert!(!m.visible);
+        assert_eq!(m.suppressed_by.as_deref(), Some("allowlist entry fixtures (test fixtures)"));
+        assert_eq!(list.hits("fixtures"), Some(1));
+    }
+
+    #[test]
+    fn entry_scoped_to_other_path_does_not_apply() {
+        let root = PathBuf::from("/repo");
+        let mut store = store_with("kingfisher.aws.1", &root.join("src/main.rs"), "AKIAEXAMPLE");
+        let list =
+            allowlist("entries:\n  - path: \"tests/**\"\n    justification: fixtures\n", today());
+
+        list.apply(&mut store, &[root]);
+
+        let (_, _, m) = &*store.get_matches()[0];
+        assert!(m.visible);
+        assert_eq!(list.hits("#1"), Some(0));
+    }
+
+    #[test]
+    fn expired_entries_are_ignored() {
+        let root = PathBuf::from("/repo");
+        let mut store = store_with("kingfisher.aws.1", &root.join("tests/keys.txt"), "AKIAEXAMPLE");
+        let list = allowlist(
+            "entries:\n  - rule: kingfisher.aws\n    justification: temp\n    expires: 2025-12-31\n",
+            today(),
+        );
+
+        list.apply(&mut store, &[root]);
+
+        let (_, _, m) = &*store.get_matches()[0];
+        assert!(m.visible);
+    }
+
+    #[test]
+    fn entries_require_justification_and_scope() {
+        let file: AllowlistFile =
+            serde_yaml::from_str("entries:\n  - justification: everything\n").unwrap();
+        assert!(Allowlist::from_file(file, None, today()).is_err());
+
+        let file: AllowlistFile =
+            serde_yaml::from_str("entries:\n  - rule: kingfisher.aws\n    justification: \"\"\n")
+                .unwrap();
+        assert!(Allowlist::from_file(file, None, today()).is_err());
+    }
+
+    #[test]
+    fn branch_scoped_entries_need_matching_branch() {
+        let root = PathBuf::from("/repo");
+        let file: AllowlistFile = serde_yaml::from_str(
+            "entries:\n  - rule: kingfisher.aws\n    branch: release\n    justification: legacy\n",
+        )
+        .unwrap();
+        let list = Allowlist::from_file(file, Some("main"), today()).unwrap();
+        let mut store = store_with("kingfisher.aws.1", &root.join("a.txt"), "AKIAEXAMPLE");
+
+        list.apply(&mut store, &[root]);
+
+        let (_, _, m) = &*store.get_matches()[0];
+        assert!(m.visible);
+    }
+}
@@ -6,15 +6,25 @@ use std::{
 };
 
 use anyhow::{Context, Result};
-use chrono::Local;
+use chrono::{Local, NaiveDate};
+use ring::rand::{SecureRandom, SystemRandom};
 use serde::{Deserialize, Serialize};
-use tracing::debug;
+use sha2::{Digest, Sha256};
+use tracing::{debug, info, warn};
 
-use crate::findings_store::FindingsStore;
+use crate::{findings_store::FindingsStore, matcher::Match};
 
 #[derive(Debug, Default, Serialize, Deserialize)]
 pub struct BaselineFile {
-    #[serde(rename = "ExactFindings", default)]
+    /// Per-baseline salt mixed into every `secret_hash`, so the file never
+    /// contains a hash that could be checked against a guessed secret elsewhere.
+    #[serde(rename = "Salt", default, skip_serializing_if = "String::is_empty")]
+    pub salt: String,
+    #[serde(rename = "Findings", default, skip_serializing_if = "Vec::is_empty")]
+    pub findings: Vec<BaselineEntry>,
+    /// Legacy offset-based entries. Still honoured when scanning, and converted
+    /// to `Findings` entries the next time the baseline is managed.
+    #[serde(rename = "ExactFindings", default, skip_serializing_if = "ExactFindings::is_empty")]
     pub exact_findings: ExactFindings,
 }
 
@@ -24,6 +34,12 @@ pub struct ExactFindings {
     pub matches: Vec<BaselineFinding>,
 }
 
+impl ExactFindings {
+    fn is_empty(&self) -> bool {
+        self.matches.is_empty()
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct BaselineFinding {
     pub filepath: String,
@@ -32,6 +48,55 @@ pub struct BaselineFinding {
     pub lastupdated: String,
 }
 
+/// A known finding identified by rule, secret and file rather than by byte
+/// offsets, so unrelated edits to the file do not invalidate it.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct BaselineEntry {
+    pub rule: String,
+    pub filepath: String,
+    pub secret_hash: String,
+    pub linenum: usize,
+    /// When set, the finding must stay within this many lines of `linenum`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub line_tolerance: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub owner: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub justification: Option<String>,
+    /// Last day (YYYY-MM-DD) on which the entry suppresses its finding.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires: Option<String>,
+    pub lastupdated: String,
+}
+
+impl BaselineEntry {
+    fn matches(&self, rule: &str, filepath: &str, secret_hash: &str, line: usize) -> bool {
+        self.rule == rule
+            && self.filepath == filepath
+            && self.secret_hash == secret_hash
+            && self.line_tolerance.map_or(true, |tol| self.linenum.abs_diff(line) <= tol)
+    }
+
+    fn is_expired(&self, today: NaiveDate) -> Result<bool> {
+        match &self.expires {
+            Some(date) => {
+                let expires = NaiveDate::parse_from_str(date, "%Y-%m-%d").with_context(|| {
+                    format!("Invalid expires date '{date}' in baseline entry for {}", self.filepath)
+                })?;
+                Ok(expires < today)
+            }
+            None => Ok(false),
+        }
+    }
+
+    fn suppression_reason(&self) -> String {
+        match &self.justification {
+            Some(justification) => format!("baseline ({justification})"),
+            None => "baseline".to_string(),
+        }
+    }
+}
+
 pub fn load_baseline(path: &Path) -> Result<BaselineFile> {
     let data = fs::read_to_string(path).context("read baseline file")?;
     Ok(serde_yaml::from_str(&data).context("parse baseline yaml")?)
@@ -42,6 +107,33 @@ pub fn save_baseline(path: &Path, baseline: &BaselineFile) -> Result<()> {
     fs::write(path, data).context("write baseline file")
 }
 
+fn generate_salt() -> String {
+    let mut bytes = [0u8; 16];
+    SystemRandom::new().fill(&mut bytes).expect("system randomness unavailable");
+    hex::encode(bytes)
+}
+
+/// Hash a secret value for storage in the baseline. The rule id is included so
+/// the same value matched by two rules yields two distinct entries.
+pub fn secret_hash(salt: &str, rule_id: &str, secret: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(salt.as_bytes());
+    hasher.update([0u8]);
+    hasher.update(rule_id.as_bytes());
+    hasher.update([0u8]);
+    hasher.update(secret.as_bytes());
+    hex::encode(hasher.finalize())
+}
+
+fn secret_value(m: &Match) -> &'static str {
+    m.groups
+        .captures
+        .get(1)
+        .or_else(|| m.groups.captures.get(0))
+        .map(|capture| capture.raw_value())
+        .unwrap_or("")
+}
+
 fn normalize_path(p: &Path, roots: &[PathBuf]) -> String {
     for root in roots {
         if let Ok(stripped) = p.strip_prefix(root) {
@@ -58,56 +150,129 @@ pub fn apply_baseline(
     baseline_path: &Path,
     manage: bool,
     roots: &[PathBuf],
+) -> Result<()> {
+    apply_baseline_on(store, baseline_path, manage, roots, Local::now().date_naive())
+}
+
+fn apply_baseline_on(
+    store: &mut FindingsStore,
+    baseline_path: &Path,
+    manage: bool,
+    roots: &[PathBuf],
+    today: NaiveDate,
 ) -> Result<()> {
     let mut baseline = if baseline_path.exists() {
         load_baseline(baseline_path)?
     } else {
         BaselineFile::default()
     };
+    if manage && baseline.salt.is_empty() && baseline.findings.is_empty() {
+        baseline.salt = generate_salt();
+    }
+
+    let mut expired = Vec::with_capacity(baseline.findings.len());
+    for entry in &baseline.findings {
+        let is_expired = entry.is_expired(today)?;
+        if is_expired {
+            warn!(
+                "Baseline entry for {} in {} expired on {}; it no longer suppresses findings",
+                entry.rule,
+                entry.filepath,
+                entry.expires.as_deref().unwrap_or_default()
+            );
+        }
+        expired.push(is_expired);
+    }
 
-    let mut known: HashSet<String> =
+    let legacy: HashSet<String> =
         baseline.exact_findings.matches.iter().map(|m| m.fingerprint.clone()).collect();
 
-    let mut encountered: HashSet<String> = HashSet::new();
-    let mut new_entries = Vec::new();
+    let mut encountered = vec![false; baseline.findings.len()];
+    let mut legacy_kept: HashSet<String> = HashSet::new();
+    let mut new_entries: Vec<BaselineEntry> = Vec::new();
+    let mut migrated = 0usize;
     for arc_msg in store.get_matches_mut() {
         let (origin, _blob, m) = Arc::make_mut(arc_msg);
         let file_path = origin.iter().filter_map(|o| o.full_path()).next();
-        let hash = format!("{:016x}", m.finding_fingerprint);
+        let fingerprint = format!("{:016x}", m.finding_fingerprint);
 
-        if let Some(fp) = file_path {
-            let normalized = normalize_path(&fp, roots);
-            if known.contains(&hash) {
-                debug!("Skipping {} due to baseline (hash {})", normalized, hash);
+        let Some(fp) = file_path else {
+            // Without a path there is nothing to migrate to, so keep the legacy entry.
+            if legacy.contains(&fingerprint) {
                 m.visible = false;
-                if manage {
-                    encountered.insert(hash.clone());
-                }
-            } else if manage {
-                known.insert(hash.clone());
-                encountered.insert(hash.clone());
-                let entry = BaselineFinding {
-                    filepath: normalized,
-                    fingerprint: hash,
-                    linenum: m.location.resolved_source_span().start.line,
-                    lastupdated: Local::now().to_rfc2822(),
-                };
-                new_entries.push(entry);
+                m.suppressed_by.get_or_insert_with(|| "baseline".to_string());
+                legacy_kept.insert(fingerprint);
             }
-        } else if known.contains(&hash) {
+            continue;
+        };
+
+        let normalized = normalize_path(&fp, roots);
+        let rule_id = m.rule.id().to_string();
+        let hash = secret_hash(&baseline.salt, &rule_id, secret_value(m));
+        let line = m.location.resolved_source_span().start.line;
+
+        let known =
+            baseline.findings.iter().position(|e| e.matches(&rule_id, &normalized, &hash, line));
+        if !m.visible {
+            // Already hidden (allowlist, inline directive, config override): keep any
+            // existing entry, but never record a suppressed finding as a new one.
+            if let Some(idx) = known {
+                encountered[idx] = true;
+            }
+            continue;
+        }
+        if let Some(idx) = known {
+            encountered[idx] = true;
+            if expired[idx] {
+                continue;
+            }
+            debug!("Skipping {} due to baseline ({})", normalized, rule_id);
             m.visible = false;
-            if manage {
-                encountered.insert(hash.clone());
+            m.suppressed_by.get_or_insert_with(|| baseline.findings[idx].suppression_reason());
+            continue;
+        }
+
+        let is_legacy = legacy.contains(&fingerprint);
+        if is_legacy {
+            debug!("Skipping {} due to baseline (hash {})", normalized, fingerprint);
+            m.visible = false;
+            m.suppressed_by.get_or_insert_with(|| "baseline".to_string());
+        }
+        if manage && !new_entries.iter().any(|e| e.matches(&rule_id, &normalized, &hash, line)) {
+            if is_legacy {
+                migrated += 1;
             }
+            new_entries.push(BaselineEntry {
+                rule: rule_id,
+                filepath: normalized,
+                secret_hash: hash,
+                linenum: line,
+                line_tolerance: None,
+                owner: None,
+                justification: None,
+                expires: None,
+                lastupdated: Local::now().to_rfc2822(),
+            });
         }
     }
+
     if manage {
-        let original_len = baseline.exact_findings.matches.len();
-        baseline.exact_findings.matches.retain(|m| encountered.contains(&m.fingerprint));
-        let mut changed = baseline.exact_findings.matches.len() != original_len;
+        let original_len = baseline.findings.len();
+        let mut seen = encountered.into_iter();
+        baseline.findings.retain(|_| seen.next().unwrap_or(false));
+        let mut changed = baseline.findings.len() != original_len;
+
+        if !baseline.exact_findings.is_empty() {
+            if migrated > 0 {
+                info!("Migrated {} baseline entries from ExactFindings", migrated);
+            }
+            let legacy_len = baseline.exact_findings.matches.len();
+            baseline.exact_findings.matches.retain(|m| legacy_kept.contains(&m.fingerprint));
+            changed |= baseline.exact_findings.matches.len() != legacy_len;
+        }
 
         if !new_entries.is_empty() {
-            baseline.exact_findings.matches.extend(new_entries);
+            baseline.findings.extend(new_entries);
             changed = true;
         }
 
@@ -147,35 +312,52 @@ mod tests {
             references: vec![],
             validation: None,
             depends_on_rule: vec![],
+            extract: Default::default(),
+            revocation: None,
             pattern_requirements: None,
         }))
     }
 
-    fn empty_captures() -> SerializableCaptures {
-        SerializableCaptures { captures: SmallVec::<[SerializableCapture; 2]>::new() }
+    fn secret_captures() -> SerializableCaptures {
+        let mut captures = SmallVec::<[SerializableCapture; 2]>::new();
+        captures.push(SerializableCapture {
+            name: None,
+            match_number: 0,
+            start: 0,
+            end: 11,
+            value: "hunter2-key",
+        });
+        SerializableCaptures { captures }
     }
 
     fn make_store_with_match(fingerprint: u64, file_path: &Path) -> FindingsStore {
+        make_store_at_line(fingerprint, file_path, 1)
+    }
+
+    fn make_store_at_line(fingerprint: u64, file_path: &Path, line: usize) -> FindingsStore {
         let mut store = FindingsStore::new(PathBuf::from("."));
         let rule = test_rule();
         let match_item = Match {
             location: Location::with_source_span(
                 OffsetSpan { start: 0, end: 1 },
                 Some(SourceSpan {
-                    start: SourcePoint { line: 1, column: 0 },
-                    end: SourcePoint { line: 1, column: 1 },
+                    start: SourcePoint { line, column: 0 },
+                    end: SourcePoint { line, column: 1 },
                 }),
             ),
-            groups: empty_captures(),
+            groups: secret_captures(),
             blob_id: BlobId::default(),
             finding_fingerprint: fingerprint,
             rule: Arc::clone(&rule),
             validation_response_body: None,
             validation_response_status: 0,
             validation_success: false,
+            validation_identity: Default::default(),
+            dependency_sources: Default::default(),
             calculated_entropy: 0.0,
             visible: true,
             is_base64: false,
+            suppressed_by: None,
         };
 
         let origin = OriginSet::from(Origin::from_file(file_path.to_path_buf()));
@@ -212,10 +394,12 @@ mod tests {
         apply_baseline(&mut store, &baseline_path, true, &roots)?;
 
         let baseline = load_baseline(&baseline_path)?;
-        assert_eq!(baseline.exact_findings.matches.len(), 1);
-        let entry = &baseline.exact_findings.matches[0];
-        assert_eq!(entry.fingerprint, format!("{:016x}", fingerprint));
+        assert_eq!(baseline.findings.len(), 1);
+        let entry = &baseline.findings[0];
+        assert_eq!(entry.rule, "test.rule");
+        assert_eq!(entry.secret_hash, secret_hash(&baseline.salt, "test.rule", "hunter2-key"));
         assert_eq!(entry.filepath, expected_relative_path(roots[0].as_path(), &secret_file));
+        assert!(!fs::read_to_string(&baseline_path)?.contains("hunter2-key"));
 
         let (_, _, recorded) = store.get_matches()[0].as_ref();
         assert!(recorded.visible);
@@ -251,4 +435,139 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn entries_survive_line_shifts() -> Result<()> {
+        let tmp = TempDir::new()?;
+        let roots = [tmp.path().to_path_buf()];
+        let secret_file = tmp.path().join("secret.txt");
+        let baseline_path = tmp.path().join("baseline.yaml");
+
+        let mut initial = make_store_at_line(0x1111, &secret_file, 3);
+        apply_baseline(&mut initial, &baseline_path, true, &roots)?;
+        let baseline_before = fs::read_to_string(&baseline_path)?;
+
+        // Inserting lines above the secret changes its offsets and fingerprint.
+        let mut moved = make_store_at_line(0x2222, &secret_file, 40);
+        apply_baseline(&mut moved, &baseline_path, true, &roots)?;
+        let (_, _, m) = moved.get_matches()[0].as_ref();
+        assert!(!m.visible);
+        assert_eq!(m.suppressed_by.as_deref(), Some("baseline"));
+        assert_eq!(baseline_before, fs::read_to_string(&baseline_path)?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn line_tolerance_limits_movement() -> Result<()> {
+        let tmp = TempDir::new()?;
+        let roots = [tmp.path().to_path_buf()];
+        let secret_file = tmp.path().join("secret.txt");
+        let baseline_path = tmp.path().join("baseline.yaml");
+
+        let mut initial = make_store_at_line(0x1111, &secret_file, 10);
+        apply_baseline(&mut initial, &baseline_path, true, &roots)?;
+        let mut baseline = load_baseline(&baseline_path)?;
+        baseline.findings[0].line_tolerance = Some(2);
+        save_baseline(&baseline_path, &baseline)?;
+
+        let mut near = make_store_at_line(0x2222, &secret_file, 12);
+        apply_baseline(&mut near, &baseline_path, false, &roots)?;
+        assert!(!near.get_matches()[0].2.visible);
+
+        let mut far = make_store_at_line(0x3333, &secret_file, 13);
+        apply_baseline(&mut far, &baseline_path, false, &roots)?;
+        assert!(far.get_matches()[0].2.visible);
+
+        Ok(())
+    }
+
+    #[test]
+    fn hidden_findings_are_not_recorded() -> Result<()> {
+        let tmp = TempDir::new()?;
+        let roots = [tmp.path().to_path_buf()];
+        let secret_file = tmp.path().join("secret.txt");
+        let baseline_path = tmp.path().join("baseline.yaml");
+
+        let mut store = make_store_with_match(0x1111, &secret_file);
+        {
+            let (_, _, m) = Arc::make_mut(&mut store.get_matches_mut()[0]);
+            m.visible = false;
+            m.suppressed_by = Some("allowlist: test".to_string());
+        }
+        apply_baseline(&mut store, &baseline_path, true, &roots)?;
+        assert!(!baseline_path.exists() || load_baseline(&baseline_path)?.findings.is_empty());
+        assert_eq!(store.get_matches()[0].2.suppressed_by.as_deref(), Some("allowlist: test"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn expired_entries_no_longer_suppress() -> Result<()> {
+        let tmp = TempDir::new()?;
+        let roots = [tmp.path().to_path_buf()];
+        let secret_file = tmp.path().join("secret.txt");
+        let baseline_path = tmp.path().join("baseline.yaml");
+
+        let mut initial = make_store_with_match(0x1111, &secret_file);
+        apply_baseline(&mut initial, &baseline_path, true, &roots)?;
+        let mut baseline = load_baseline(&baseline_path)?;
+        baseline.findings[0].owner = Some("security@example.com".to_string());
+        baseline.findings[0].justification = Some("rotation tracked in SEC-12".to_string());
+        baseline.findings[0].expires = Some("2026-03-31".to_string());
+        save_baseline(&baseline_path, &baseline)?;
+
+        let before = NaiveDate::from_ymd_opt(2026, 3, 31).unwrap();
+        let mut store = make_store_with_match(0x1111, &secret_file);
+        apply_baseline_on(&mut store, &baseline_path, false, &roots, before)?;
+        let (_, _, m) = store.get_matches()[0].as_ref();
+        assert!(!m.visible);
+        assert_eq!(m.suppressed_by.as_deref(), Some("baseline (rotation tracked in SEC-12)"));
+
+        // Managing after expiry keeps the entry (and its metadata) without renewing it.
+        let after = NaiveDate::from_ymd_opt(2026, 4, 1).unwrap();
+        let mut store = make_store_with_match(0x1111, &secret_file);
+        apply_baseline_on(&mut store, &baseline_path, true, &roots, after)?;
+        assert!(store.get_matches()[0].2.visible);
+        let baseline = load_baseline(&baseline_path)?;
+        assert_eq!(baseline.findings.len(), 1);
+        assert_eq!(baseline.findings[0].owner.as_deref(), Some("security@example.com"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn exact_findings_are_migrated() -> Result<()> {
+        let tmp = TempDir::new()?;
+        let roots = [tmp.path().to_path_buf()];
+        let secret_file = tmp.path().join("secret.txt");
+        let baseline_path = tmp.path().join("baseline.yaml");
+        let fingerprint = 0xabcd_u64;
+
+        let mut legacy = BaselineFile::default();
+        legacy.exact_findings.matches.push(BaselineFinding {
+            filepath: expected_relative_path(&roots[0], &secret_file),
+            fingerprint: format!("{:016x}", fingerprint),
+            linenum: 1,
+            lastupdated: Local::now().to_rfc2822(),
+        });
+        save_baseline(&baseline_path, &legacy)?;
+
+        // Legacy entries still apply to plain scans.
+        let mut store = make_store_with_match(fingerprint, &secret_file);
+        apply_baseline(&mut store, &baseline_path, false, &roots)?;
+        assert!(!store.get_matches()[0].2.visible);
+
+        let mut store = make_store_with_match(fingerprint, &secret_file);
+        apply_baseline(&mut store, &baseline_path, true, &roots)?;
+        assert!(!store.get_matches()[0].2.visible);
+
+        let migrated = load_baseline(&baseline_path)?;
+        assert!(migrated.exact_findings.matches.is_empty());
+        assert_eq!(migrated.findings.len(), 1);
+        assert!(!migrated.salt.is_empty());
+        assert!(!fs::read_to_string(&baseline_path)?.contains("ExactFindings"));
+
+        Ok(())
+    }
 }
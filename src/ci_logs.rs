@@ -0,0 +1,202 @@
+//! Helpers shared by the GitHub Actions and GitLab CI log fetchers used by
+//! `--ci-logs`.
+
+use std::{
+    fs,
+    path::Path,
+    sync::{Arc, Mutex},
+};
+
+use anyhow::Result;
+use chrono::{DateTime, Duration, Utc};
+use reqwest::{RequestBuilder, StatusCode};
+use tracing::{debug, warn};
+
+use crate::{
+    decompress::{decompress_file, CompressedContent},
+    findings_store,
+};
+
+/// Artifacts larger than this are skipped rather than downloaded.
+pub const MAX_ARTIFACT_BYTES: u64 = 256 * 1024 * 1024;
+
+/// How far back, and how many pipeline or workflow runs per repository,
+/// `--ci-logs` looks.
+#[derive(Debug, Clone, Copy)]
+pub struct CiLogLimits {
+    pub max_age_days: u32,
+    pub max_runs: usize,
+}
+
+impl CiLogLimits {
+    /// Runs created before this instant are ignored.
+    pub fn cutoff(&self) -> DateTime<Utc> {
+        Utc::now() - Duration::days(i64::from(self.max_age_days))
+    }
+}
+
+/// Send `req` and read the body of a successful response. A transport error
+/// or a body over `MAX_ARTIFACT_BYTES` is logged as a warning about `what` and
+/// returned as `Err(None)`, an unsuccessful status as `Err(Some(status))`, so
+/// one unreachable or oversized log or artifact does not abort the scan.
+pub async fn download(req: RequestBuilder, what: &str) -> Result<Vec<u8>, Option<StatusCode>> {
+    download_at_most(req, what, MAX_ARTIFACT_BYTES).await
+}
+
+/// `download`, abandoning the body once more than `max_bytes` have arrived,
+/// whatever size the API reported for it.
+async fn download_at_most(
+    req: RequestBuilder,
+    what: &str,
+    max_bytes: u64,
+) -> Result<Vec<u8>, Option<StatusCode>> {
+    let mut resp = req.send().await.map_err(|e| {
+        warn!("Skipping {what}: {e}");
+        None
+    })?;
+    if !resp.status().is_success() {
+        return Err(Some(resp.status()));
+    }
+    let too_large = || {
+        warn!("Skipping {what}: larger than {max_bytes} bytes");
+        None
+    };
+    if resp.content_length().is_some_and(|len| len > max_bytes) {
+        return Err(too_large());
+    }
+    let mut body = Vec::new();
+    loop {
+        match resp.chunk().await {
+            Ok(Some(chunk)) => {
+                if (body.len() + chunk.len()) as u64 > max_bytes {
+                    return Err(too_large());
+                }
+                body.extend_from_slice(&chunk);
+            }
+            Ok(None) => return Ok(body),
+            Err(e) => {
+                warn!("Skipping {what}: {e}");
+                return Err(None);
+            }
+        }
+    }
+}
+
+/// Write a job log to `path` and link it back to the job page.
+pub fn write_log(
+    path: &Path,
+    log: &[u8],
+    link: String,
+    datastore: &Arc<Mutex<findings_store::FindingsStore>>,
+) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(path, log)?;
+    let mut ds = datastore.lock().unwrap();
+    ds.register_repo_link(path.to_path_buf(), link);
+    Ok(())
+}
+
+/// Unpack a downloaded artifact archive into `dest`, link every extracted
+/// file with `link(relative_path)` and remove the archive so it is not
+/// scanned twice.
+///
+/// Returns the number of files extracted.
+pub fn unpack_artifact(
+    archive: &Path,
+    dest: &Path,
+    link: impl Fn(&str) -> String,
+    datastore: &Arc<Mutex<findings_store::FindingsStore>>,
+) -> Result<usize> {
+    fs::create_dir_all(dest)?;
+    let entries = match decompress_file(archive, Some(dest))? {
+        CompressedContent::ArchiveFiles(entries) => entries,
+        other => {
+            debug!("artifact {} is not a zip archive: {other:?}", archive.display());
+            return Ok(0);
+        }
+    };
+    let mut ds = datastore.lock().unwrap();
+    for (_, path) in &entries {
+        let rel = path.strip_prefix(dest).unwrap_or(path).to_string_lossy().replace('\\', "/");
+        ds.register_repo_link(path.clone(), link(&rel));
+    }
+    drop(ds);
+    fs::remove_file(archive)?;
+    Ok(entries.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use tempfile::tempdir;
+    use wiremock::{
+        matchers::{method, path},
+        Mock, MockServer, ResponseTemplate,
+    };
+    use zip::{write::SimpleFileOptions, ZipWriter};
+
+    use super::*;
+    use crate::findings_store::FindingsStore;
+
+    #[test]
+    fn unpacks_artifact_zips_and_links_each_file() -> Result<()> {
+        let tmp = tempdir()?;
+        let archive = tmp.path().join("42.zip");
+        let mut zip = ZipWriter::new(fs::File::create(&archive)?);
+        zip.start_file("build/.env", SimpleFileOptions::default())?;
+        zip.write_all(b"TOKEN=secret\n")?;
+        zip.start_file("report.txt", SimpleFileOptions::default())?;
+        zip.write_all(b"ok\n")?;
+        zip.finish()?;
+
+        let datastore = Arc::new(Mutex::new(FindingsStore::new(tmp.path().to_path_buf())));
+        let dest = tmp.path().join("42");
+        let count = unpack_artifact(
+            &archive,
+            &dest,
+            |rel| format!("https://ci.example/jobs/7/artifacts/file/{rel}"),
+            &datastore,
+        )?;
+
+        assert_eq!(count, 2);
+        assert!(!archive.exists());
+        let env = dest.join("build/.env");
+        assert_eq!(fs::read_to_string(&env)?, "TOKEN=secret\n");
+        let ds = datastore.lock().unwrap();
+        assert_eq!(
+            ds.repo_links().get(&env).map(String::as_str),
+            Some("https://ci.example/jobs/7/artifacts/file/build/.env")
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn downloads_stop_at_the_byte_limit() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/artifact"))
+            .respond_with(ResponseTemplate::new(200).set_body_bytes(vec![b'x'; 64]))
+            .mount(&server)
+            .await;
+        let client = reqwest::Client::new();
+        let url = format!("{}/artifact", server.uri());
+
+        let body = download_at_most(client.get(&url), "artifact", 64).await.unwrap();
+        assert_eq!(body.len(), 64);
+        assert_eq!(download_at_most(client.get(&url), "artifact", 63).await, Err(None));
+        assert_eq!(
+            download_at_most(client.get(format!("{}/missing", server.uri())), "artifact", 64).await,
+            Err(Some(StatusCode::NOT_FOUND))
+        );
+    }
+
+    #[test]
+    fn cutoff_honours_max_age() {
+        let limits = CiLogLimits { max_age_days: 7, max_runs: 1 };
+        let age = Utc::now() - limits.cutoff();
+        assert!(age >= Duration::days(7) && age < Duration::days(7) + Duration::minutes(1));
+    }
+}
@@ -314,6 +314,14 @@ pub struct InputSpecifierArgs {
     #[arg(long = "docker-image", hide = true)]
     pub docker_image: Vec<String>,
 
+    /// Container registry repositories (HOST/REPOSITORY) whose tags are all scanned
+    #[arg(long = "registry-repository", hide = true)]
+    pub registry_repository: Vec<String>,
+
+    /// Treat each registry repository as a HOST and scan every repository in its catalog
+    #[arg(long, requires = "registry_repository", hide = true)]
+    pub all_registry_repositories: bool,
+
     /// Select how to clone Git repositories
     #[arg(long, default_value_t=GitCloneMode::Bare, alias="git-clone-mode")]
     pub git_clone: GitCloneMode,
@@ -331,6 +339,18 @@ pub struct InputSpecifierArgs {
     #[arg(long, help_heading = "Git Options")]
     pub repo_artifacts: bool,
 
+    /// Also download and scan CI job logs and uploaded artifacts from GitHub Actions and GitLab CI
+    #[arg(long, help_heading = "Git Options")]
+    pub ci_logs: bool,
+
+    /// Only fetch CI runs created within this many days
+    #[arg(long, default_value_t = 30, requires = "ci_logs", help_heading = "Git Options")]
+    pub ci_logs_max_age_days: u32,
+
+    /// Maximum number of CI workflow runs or pipelines to fetch per repository
+    #[arg(long, default_value_t = 20, requires = "ci_logs", help_heading = "Git Options")]
+    pub ci_logs_max_runs: usize,
+
     /// Enable or disable scanning nested git repositories
     #[arg(long, default_value_t = true)]
     pub scan_nested_repos: bool,
@@ -418,6 +438,7 @@ impl InputSpecifierArgs {
             || self.s3_bucket.is_some()
             || self.gcs_bucket.is_some()
             || !self.docker_image.is_empty()
+            || !self.registry_repository.is_empty()
     }
 
     /// Emit deprecation warnings for legacy top-level provider flags.
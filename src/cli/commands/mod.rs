@@ -7,6 +7,14 @@ pub mod gitlab;
 pub mod huggingface;
 pub mod inputs;
 pub mod output;
+pub mod revoke;
 pub mod rules;
 pub mod scan;
+pub mod validate;
+pub mod validation_cache;
+pub mod validation_dependency;
+pub mod validation_dry_run;
+pub mod validation_egress;
+pub mod validation_rate;
+pub mod validation_ssh;
 pub mod view;
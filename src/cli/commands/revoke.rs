@@ -0,0 +1,43 @@
+use std::path::PathBuf;
+
+use clap::{Args, ValueHint};
+
+use crate::cli::commands::rules::RuleSpecifierArgs;
+
+/// Revoke confirmed secrets from a Kingfisher report through their rule's
+/// `revocation` block.
+#[derive(Args, Debug)]
+pub struct RevokeArgs {
+    /// JSON or JSONL report written by `kingfisher scan --format json|jsonl`
+    #[arg(long, value_name = "REPORT", value_hint = ValueHint::FilePath)]
+    pub report: PathBuf,
+
+    /// Fingerprint of a finding to revoke (repeatable)
+    #[arg(long = "fingerprint", value_name = "FINGERPRINT", required = true)]
+    pub fingerprints: Vec<String>,
+
+    #[command(flatten)]
+    pub rules: RuleSpecifierArgs,
+
+    /// Revoke without asking for confirmation
+    #[arg(long, short = 'y')]
+    pub yes: bool,
+
+    /// Append a JSON line describing each revocation attempt to this file
+    #[arg(
+        long,
+        value_name = "PATH",
+        default_value = "kingfisher-revocations.jsonl",
+        value_hint = ValueHint::FilePath
+    )]
+    pub audit_log: PathBuf,
+
+    /// Timeout for revocation requests in seconds (1-60)
+    #[arg(
+        long = "revocation-timeout",
+        default_value_t = 10,
+        value_name = "SECONDS",
+        value_parser = clap::value_parser!(u64).range(1..=60)
+    )]
+    pub revocation_timeout: u64,
+}
@@ -1,5 +1,8 @@
 use anyhow::bail;
-use clap::{Args, Subcommand, ValueEnum, ValueHint};
+use clap::{
+    parser::ValueSource, ArgMatches, Args, FromArgMatches, Subcommand, ValueEnum, ValueHint,
+};
+use rustc_hash::FxHashSet;
 use std::path::{Path, PathBuf};
 use strum::Display;
 use tracing::debug;
@@ -17,10 +20,17 @@ use crate::{
             inputs::{ContentFilteringArgs, InputSpecifierArgs},
             output::{OutputArgs, ReportOutputFormat},
             rules::RuleSpecifierArgs,
+            validation_cache::ValidationCacheArgs,
+            validation_dependency::ValidationDependencyArgs,
+            validation_dry_run::ValidationDryRunArgs,
+            validation_egress::ValidationEgressArgs,
+            validation_rate::ValidationRateArgs,
+            validation_ssh::ValidationSshArgs,
         },
         global::RAM_GB,
     },
     git_url::GitUrl,
+    project_config::{self, PathOverride},
     rules::rule::Confidence,
 };
 
@@ -102,6 +112,24 @@ pub struct ScanArgs {
     )]
     pub validation_retries: u32,
 
+    #[command(flatten)]
+    pub validation_cache: ValidationCacheArgs,
+
+    #[command(flatten)]
+    pub validation_rate: ValidationRateArgs,
+
+    #[command(flatten)]
+    pub validation_egress: ValidationEgressArgs,
+
+    #[command(flatten)]
+    pub validation_dry_run: ValidationDryRunArgs,
+
+    #[command(flatten)]
+    pub validation_dependency: ValidationDependencyArgs,
+
+    #[command(flatten)]
+    pub validation_ssh: ValidationSshArgs,
+
     /// Map validated cloud credentials to their effective identities; use only when
     /// authorized for the target account because this triggers additional network
     /// requests to determine granted access
@@ -154,6 +182,15 @@ pub struct ScanArgs {
     #[arg(global = true, long, default_value_t = false)]
     pub manage_baseline: bool,
 
+    /// YAML allowlist of scoped, justified suppressions
+    #[arg(global = true, long, value_name = "FILE")]
+    pub allowlist: Option<PathBuf>,
+
+    /// Include findings hidden by the allowlist in the report, annotated with the
+    /// entry that suppressed them
+    #[arg(global = true, long, default_value_t = false)]
+    pub show_suppressed: bool,
+
     /// Regex patterns to allow-list secret matches (repeatable)
     #[arg(global = true, long = "skip-regex", value_name = "PATTERN")]
     pub skip_regex: Vec<String>,
@@ -186,6 +223,64 @@ pub struct ScanArgs {
     /// Disable rule-level `ignore_if_contains` filtering for pattern requirements
     #[arg(global = true, long = "no-ignore-if-contains", default_value_t = false)]
     pub no_ignore_if_contains: bool,
+
+    /// Project config file; defaults to `.kingfisher.toml` or `.kingfisher.yml` at the scan root
+    #[arg(global = true, long, value_name = "FILE")]
+    pub config: Option<PathBuf>,
+
+    /// Per-subdirectory overrides loaded from the project config
+    #[arg(skip)]
+    pub path_overrides: Vec<PathOverride>,
+
+    /// Arguments given explicitly on the command line
+    #[command(flatten)]
+    pub explicit_args: ExplicitArgs,
+}
+
+/// Ids of the `kingfisher scan` arguments given explicitly on the command line,
+/// so project config values never replace them even when they equal the default.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ExplicitArgs(FxHashSet<String>);
+
+impl ExplicitArgs {
+    pub fn contains(&self, id: &str) -> bool {
+        self.0.contains(id)
+    }
+
+    fn collect(matches: &ArgMatches, ids: &mut FxHashSet<String>) {
+        for id in matches.ids() {
+            if matches.value_source(id.as_str()) == Some(ValueSource::CommandLine) {
+                ids.insert(id.as_str().to_string());
+            }
+        }
+        // Global flags may be given after a provider subcommand.
+        if let Some((_, sub)) = matches.subcommand() {
+            Self::collect(sub, ids);
+        }
+    }
+}
+
+impl FromArgMatches for ExplicitArgs {
+    fn from_arg_matches(matches: &ArgMatches) -> Result<Self, clap::Error> {
+        let mut ids = FxHashSet::default();
+        Self::collect(matches, &mut ids);
+        Ok(Self(ids))
+    }
+
+    fn update_from_arg_matches(&mut self, matches: &ArgMatches) -> Result<(), clap::Error> {
+        Self::collect(matches, &mut self.0);
+        Ok(())
+    }
+}
+
+impl Args for ExplicitArgs {
+    fn augment_args(cmd: clap::Command) -> clap::Command {
+        cmd
+    }
+
+    fn augment_args_for_update(cmd: clap::Command) -> clap::Command {
+        cmd
+    }
 }
 
 /// Confidence levels for findings
@@ -443,6 +538,15 @@ impl ScanCommandArgs {
                     scan_args.input_specifier_args.docker_image = args.images;
                     None
                 }
+                ScanInputCommand::Registry(args) => {
+                    if args.repositories.is_empty() {
+                        bail!("Provide at least one repository when using the registry subcommand");
+                    }
+                    scan_args.input_specifier_args.registry_repository = args.repositories;
+                    scan_args.input_specifier_args.all_registry_repositories =
+                        args.all_repositories;
+                    None
+                }
             };
 
             if let Some(list_command) = maybe_list {
@@ -470,6 +574,8 @@ impl ScanCommandArgs {
             self.scan_args.input_specifier_args.emit_deprecated_warnings();
         }
 
+        project_config::apply_project_config(&mut self.scan_args)?;
+
         if self.scan_args.manage_baseline {
             self.scan_args.no_dedup = true;
         }
@@ -523,6 +629,9 @@ pub enum ScanInputCommand {
 
     /// Scan Docker or OCI images
     Docker(DockerScanArgs),
+
+    /// Enumerate and scan every tag of container registry repositories
+    Registry(RegistryScanArgs),
 }
 
 #[derive(Args, Debug, Clone, Default)]
@@ -743,7 +852,19 @@ pub struct GcsScanArgs {
 
 #[derive(Args, Debug, Clone)]
 pub struct DockerScanArgs {
-    /// Docker or OCI images to scan
+    /// Docker or OCI images to scan: registry references, or
+    /// `oci-archive:PATH` / `oci-dir:PATH` for images saved on disk
     #[arg(value_name = "IMAGE", num_args = 1..)]
     pub images: Vec<String>,
 }
+
+#[derive(Args, Debug, Clone)]
+pub struct RegistryScanArgs {
+    /// Repositories to scan as HOST/REPOSITORY, e.g. `ghcr.io/acme/api`
+    #[arg(value_name = "REPOSITORY", num_args = 1..)]
+    pub repositories: Vec<String>,
+
+    /// Treat each argument as a registry HOST and scan every repository in its catalog
+    #[arg(long)]
+    pub all_repositories: bool,
+}
@@ -0,0 +1,85 @@
+use std::path::PathBuf;
+
+use clap::{Args, ValueHint};
+
+use crate::cli::commands::{
+    rules::RuleSpecifierArgs, validation_cache::ValidationCacheArgs,
+    validation_dry_run::ValidationDryRunArgs, validation_egress::ValidationEgressArgs,
+    validation_rate::ValidationRateArgs, validation_ssh::ValidationSshArgs,
+};
+
+/// Re-run validation for the findings in an existing Kingfisher report, or for
+/// a single secret read from stdin.
+#[derive(Args, Debug)]
+pub struct ValidateArgs {
+    /// JSON or JSONL report written by `kingfisher scan --format json|jsonl`,
+    /// or `-` to validate a single secret read from stdin against `--rule`
+    #[arg(value_name = "REPORT", value_hint = ValueHint::FilePath)]
+    pub report: PathBuf,
+
+    /// Value for a variable the rule takes from a `depends_on_rule` dependency
+    /// when validating a secret from stdin (e.g. `--var AKID=AKIA...`)
+    #[arg(long = "var", value_name = "NAME=VALUE", value_parser = parse_variable)]
+    pub vars: Vec<(String, String)>,
+
+    /// Map the access granted by a secret from stdin once it validates
+    #[arg(long)]
+    pub access_map: bool,
+
+    #[command(flatten)]
+    pub rules: RuleSpecifierArgs,
+
+    /// Write the updated report to this file instead of stdout
+    #[arg(long, short, value_name = "PATH")]
+    pub output: Option<PathBuf>,
+
+    /// Write the findings whose validation status changed to this file as JSON
+    #[arg(long, value_name = "PATH")]
+    pub diff_output: Option<PathBuf>,
+
+    /// Number of findings to validate concurrently
+    #[arg(long = "jobs", short = 'j', default_value_t = 8)]
+    pub num_jobs: usize,
+
+    /// Timeout for validation requests in seconds (1-60)
+    #[arg(
+        long = "validation-timeout",
+        default_value_t = 10,
+        value_name = "SECONDS",
+        value_parser = clap::value_parser!(u64).range(1..=60)
+    )]
+    pub validation_timeout: u64,
+
+    /// Number of retries for validation requests (0-5)
+    #[arg(
+        long = "validation-retries",
+        default_value_t = 1,
+        value_name = "N",
+        value_parser = clap::value_parser!(u32).range(0..=5)
+    )]
+    pub validation_retries: u32,
+
+    #[command(flatten)]
+    pub validation_cache: ValidationCacheArgs,
+
+    #[command(flatten)]
+    pub validation_rate: ValidationRateArgs,
+
+    #[command(flatten)]
+    pub validation_egress: ValidationEgressArgs,
+
+    #[command(flatten)]
+    pub validation_dry_run: ValidationDryRunArgs,
+
+    #[command(flatten)]
+    pub validation_ssh: ValidationSshArgs,
+}
+
+fn parse_variable(s: &str) -> Result<(String, String), String> {
+    match s.split_once('=') {
+        Some((name, value)) if !name.trim().is_empty() => {
+            Ok((name.trim().to_uppercase(), value.to_string()))
+        }
+        _ => Err(format!("expected NAME=VALUE, got `{s}`")),
+    }
+}
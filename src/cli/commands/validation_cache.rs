@@ -0,0 +1,125 @@
+use std::{path::PathBuf, time::Duration};
+
+use clap::{Args, Subcommand, ValueEnum, ValueHint};
+use strum::Display;
+
+use crate::{cli::commands::output::OutputArgs, validation_cache::ResultClass};
+
+// -----------------------------------------------------------------------------
+// Persistent cache options for `scan` and `validate`
+// -----------------------------------------------------------------------------
+#[derive(Args, Debug, Clone)]
+pub struct ValidationCacheArgs {
+    /// Encrypted file that keeps validation results between runs
+    ///
+    /// The encryption key is read from `KINGFISHER_VALIDATION_CACHE_KEY` when set,
+    /// otherwise a random key is stored next to the cache as `<FILE>.key`.
+    #[arg(
+        global = true,
+        long = "validation-cache",
+        value_name = "FILE",
+        value_hint = ValueHint::FilePath
+    )]
+    pub path: Option<PathBuf>,
+
+    /// How long active credentials stay cached (e.g. `30m`, `12h`, `7d`; `0` disables)
+    #[arg(
+        global = true,
+        long = "validation-cache-ttl-valid",
+        value_name = "DURATION",
+        default_value = "24h",
+        value_parser = humantime::parse_duration
+    )]
+    pub ttl_valid: Duration,
+
+    /// How long inactive credentials stay cached
+    #[arg(
+        global = true,
+        long = "validation-cache-ttl-invalid",
+        value_name = "DURATION",
+        default_value = "7d",
+        value_parser = humantime::parse_duration
+    )]
+    pub ttl_invalid: Duration,
+
+    /// How long timeouts, rate limits and server errors stay cached
+    #[arg(
+        global = true,
+        long = "validation-cache-ttl-error",
+        value_name = "DURATION",
+        default_value = "1h",
+        value_parser = humantime::parse_duration
+    )]
+    pub ttl_error: Duration,
+}
+
+impl Default for ValidationCacheArgs {
+    fn default() -> Self {
+        Self {
+            path: None,
+            ttl_valid: Duration::from_secs(24 * 60 * 60),
+            ttl_invalid: Duration::from_secs(7 * 24 * 60 * 60),
+            ttl_error: Duration::from_secs(60 * 60),
+        }
+    }
+}
+
+// -----------------------------------------------------------------------------
+// `kingfisher validation-cache`
+// -----------------------------------------------------------------------------
+#[derive(Args, Debug)]
+pub struct ValidationCacheCommandArgs {
+    #[command(subcommand)]
+    pub command: ValidationCacheCommand,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum ValidationCacheCommand {
+    /// Show what a validation cache file contains
+    Inspect(ValidationCacheInspectArgs),
+
+    /// Remove entries from a validation cache file
+    Purge(ValidationCachePurgeArgs),
+}
+
+#[derive(Args, Debug)]
+pub struct ValidationCacheInspectArgs {
+    /// Cache file written with `--validation-cache`
+    #[arg(value_name = "FILE", value_hint = ValueHint::FilePath)]
+    pub path: PathBuf,
+
+    /// List every entry instead of per-rule totals
+    #[arg(long)]
+    pub entries: bool,
+
+    #[command(flatten)]
+    pub output_args: OutputArgs<ValidationCacheOutputFormat>,
+}
+
+#[derive(Args, Debug)]
+pub struct ValidationCachePurgeArgs {
+    /// Cache file written with `--validation-cache`
+    #[arg(value_name = "FILE", value_hint = ValueHint::FilePath)]
+    pub path: PathBuf,
+
+    /// Only purge entries for this rule id or rule id prefix (repeatable)
+    #[arg(long, value_name = "RULE")]
+    pub rule: Vec<String>,
+
+    /// Only purge entries of this result class (repeatable)
+    #[arg(long, value_name = "CLASS")]
+    pub class: Vec<ResultClass>,
+
+    /// Only purge entries whose TTL has passed
+    #[arg(long)]
+    pub expired: bool,
+}
+
+#[derive(Copy, Clone, Debug, Display, PartialEq, Eq, PartialOrd, Ord, ValueEnum)]
+#[strum(serialize_all = "kebab-case")]
+pub enum ValidationCacheOutputFormat {
+    /// A human-friendly text-based format
+    Pretty,
+    /// Pretty-printed JSON
+    Json,
+}
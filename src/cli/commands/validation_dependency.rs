@@ -0,0 +1,49 @@
+use clap::{Args, ValueEnum};
+
+use crate::validation::dependency_scope::DEFAULT_MAX_CANDIDATES;
+
+// -----------------------------------------------------------------------------
+// Where `depends_on_rule` values may come from
+// -----------------------------------------------------------------------------
+#[derive(Args, Debug, Clone)]
+pub struct ValidationDependencyArgs {
+    /// Where a dependent rule (e.g. an AWS secret key) looks for the values it
+    /// depends on when the file it was found in does not contain them
+    #[arg(
+        global = true,
+        long = "dependency-scope",
+        value_name = "SCOPE",
+        value_enum,
+        default_value_t = DependencyScope::Blob
+    )]
+    pub scope: DependencyScope,
+
+    /// Leave a dependency unresolved when its scope holds more than N distinct
+    /// candidate values for it
+    #[arg(
+        global = true,
+        long = "dependency-max-candidates",
+        value_name = "N",
+        default_value_t = DEFAULT_MAX_CANDIDATES
+    )]
+    pub max_candidates: usize,
+}
+
+impl Default for ValidationDependencyArgs {
+    fn default() -> Self {
+        Self { scope: DependencyScope::Blob, max_candidates: DEFAULT_MAX_CANDIDATES }
+    }
+}
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum DependencyScope {
+    /// Only the file (blob) the dependent secret was found in
+    #[default]
+    Blob,
+    /// Files in the same directory
+    Directory,
+    /// Files introduced by the same Git commit; plain files use their directory
+    Commit,
+    /// Any file in the same Git repository; plain files share one scope
+    Repository,
+}
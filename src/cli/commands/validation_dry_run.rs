@@ -0,0 +1,44 @@
+use std::path::PathBuf;
+
+use clap::{Args, ValueEnum, ValueHint};
+
+// -----------------------------------------------------------------------------
+// Exporting validation requests instead of sending them
+// -----------------------------------------------------------------------------
+#[derive(Args, Debug, Clone, Default)]
+pub struct ValidationDryRunArgs {
+    /// Render HTTP validation requests and export them, with secrets redacted,
+    /// instead of sending them. Other validators are skipped.
+    #[arg(global = true, long = "validation-dry-run")]
+    pub enabled: bool,
+
+    /// Format of the exported requests
+    #[arg(
+        id = "validation_dry_run_format",
+        global = true,
+        long = "validation-dry-run-format",
+        value_name = "FORMAT",
+        value_enum,
+        default_value_t = DryRunFormat::Curl
+    )]
+    pub format: DryRunFormat,
+
+    /// Write the exported requests to FILE instead of stderr
+    #[arg(
+        id = "validation_dry_run_output",
+        global = true,
+        long = "validation-dry-run-output",
+        value_name = "FILE",
+        value_hint = ValueHint::FilePath
+    )]
+    pub output: Option<PathBuf>,
+}
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum DryRunFormat {
+    /// One shell-quoted `curl` command per request
+    #[default]
+    Curl,
+    /// An HTTP Archive (HAR 1.2) document
+    Har,
+}
@@ -0,0 +1,20 @@
+use clap::Args;
+
+// -----------------------------------------------------------------------------
+// Validation egress policy for `scan` and `validate`
+// -----------------------------------------------------------------------------
+#[derive(Args, Debug, Clone, Default)]
+pub struct ValidationEgressArgs {
+    /// Only send validation requests to this host; accepts `*.example.com` (repeatable)
+    #[arg(global = true, long = "validation-allow-host", value_name = "HOST")]
+    pub allow_hosts: Vec<String>,
+
+    /// Never send validation requests to this host; accepts `*.example.com` (repeatable)
+    #[arg(global = true, long = "validation-deny-host", value_name = "HOST")]
+    pub deny_hosts: Vec<String>,
+
+    /// Only run validators that cannot change state on the remote service
+    /// (read-only HTTP methods, identity lookups and database logins)
+    #[arg(global = true, long = "validation-side-effect-free")]
+    pub side_effect_free: bool,
+}
@@ -0,0 +1,74 @@
+use clap::Args;
+
+use crate::validation::rate_limit::DEFAULT_REQUESTS_PER_SECOND;
+
+// -----------------------------------------------------------------------------
+// Validation request pacing for `scan` and `validate`
+// -----------------------------------------------------------------------------
+#[derive(Args, Debug, Clone)]
+pub struct ValidationRateArgs {
+    /// Maximum validation requests per second sent to any one host (`0` disables pacing)
+    #[arg(
+        global = true,
+        long = "validation-rps",
+        value_name = "N",
+        default_value_t = DEFAULT_REQUESTS_PER_SECOND,
+        value_parser = parse_rate
+    )]
+    pub requests_per_second: f64,
+
+    /// Requests per second for one rule family, e.g. `kingfisher.github=2` (repeatable)
+    #[arg(
+        global = true,
+        long = "validation-rps-rule",
+        value_name = "RULE=N",
+        value_parser = parse_rule_rate
+    )]
+    pub rule_rates: Vec<(String, f64)>,
+
+    /// Stop sending validation requests after N requests in this run
+    #[arg(global = true, long = "validation-budget", value_name = "N")]
+    pub budget: Option<u64>,
+}
+
+impl Default for ValidationRateArgs {
+    fn default() -> Self {
+        Self {
+            requests_per_second: DEFAULT_REQUESTS_PER_SECOND,
+            rule_rates: Vec::new(),
+            budget: None,
+        }
+    }
+}
+
+fn parse_rate(s: &str) -> Result<f64, String> {
+    match s.trim().parse::<f64>() {
+        Ok(rate) if rate.is_finite() && rate >= 0.0 => Ok(rate),
+        _ => Err(format!("expected a non-negative number of requests per second, got `{s}`")),
+    }
+}
+
+fn parse_rule_rate(s: &str) -> Result<(String, f64), String> {
+    match s.split_once('=') {
+        Some((rule, rate)) if !rule.trim().is_empty() => {
+            Ok((rule.trim().to_string(), parse_rate(rate)?))
+        }
+        _ => Err(format!("expected RULE=N, got `{s}`")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_rule_rates() {
+        assert_eq!(
+            parse_rule_rate("kingfisher.github=2.5"),
+            Ok(("kingfisher.github".to_string(), 2.5))
+        );
+        assert!(parse_rule_rate("kingfisher.github").is_err());
+        assert!(parse_rule_rate("=3").is_err());
+        assert!(parse_rule_rate("kingfisher.slack=-1").is_err());
+    }
+}
@@ -0,0 +1,82 @@
+use std::fmt;
+
+use clap::Args;
+
+// -----------------------------------------------------------------------------
+// Hosts that found SSH private keys are tried against
+// -----------------------------------------------------------------------------
+#[derive(Args, Debug, Clone, Default)]
+pub struct ValidationSshArgs {
+    /// Try public-key authentication with found SSH private keys against
+    /// USER@HOST[:PORT]; no shell or command is started (repeatable)
+    #[arg(
+        global = true,
+        long = "ssh-validate-host",
+        value_name = "USER@HOST[:PORT]",
+        value_parser = parse_ssh_host
+    )]
+    pub hosts: Vec<SshHost>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SshHost {
+    pub user: String,
+    pub host: String,
+    pub port: u16,
+}
+
+impl fmt::Display for SshHost {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.host.contains(':') {
+            write!(f, "{}@[{}]:{}", self.user, self.host, self.port)
+        } else {
+            write!(f, "{}@{}:{}", self.user, self.host, self.port)
+        }
+    }
+}
+
+fn parse_ssh_host(s: &str) -> Result<SshHost, String> {
+    let invalid = || format!("expected USER@HOST[:PORT], got `{s}`");
+    let (user, rest) = s.trim().split_once('@').ok_or_else(invalid)?;
+    let (host, port) = if let Some(bracketed) = rest.strip_prefix('[') {
+        // [v6-address] or [v6-address]:port
+        let (host, after) = bracketed.split_once(']').ok_or_else(invalid)?;
+        match after {
+            "" => (host, None),
+            _ => (host, Some(after.strip_prefix(':').ok_or_else(invalid)?)),
+        }
+    } else {
+        match rest.split_once(':') {
+            Some((host, port)) => (host, Some(port)),
+            None => (rest, None),
+        }
+    };
+    if user.is_empty() || host.is_empty() {
+        return Err(invalid());
+    }
+    let port = match port {
+        Some(port) => port.parse::<u16>().ok().filter(|p| *p > 0).ok_or_else(invalid)?,
+        None => 22,
+    };
+    Ok(SshHost { user: user.to_string(), host: host.to_string(), port })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_ssh_hosts() {
+        let host = parse_ssh_host("git@github.com").unwrap();
+        assert_eq!(host, SshHost { user: "git".into(), host: "github.com".into(), port: 22 });
+        assert_eq!(host.to_string(), "git@github.com:22");
+
+        let host = parse_ssh_host("deploy@[2001:db8::10]:2222").unwrap();
+        assert_eq!((host.host.as_str(), host.port), ("2001:db8::10", 2222));
+        assert_eq!(host.to_string(), "deploy@[2001:db8::10]:2222");
+
+        for bad in ["github.com", "@github.com", "git@", "git@host:0", "git@host:ssh", "a@[::1"] {
+            assert!(parse_ssh_host(bad).is_err(), "{bad} should be rejected");
+        }
+    }
+}
@@ -7,7 +7,8 @@ use sysinfo::{MemoryRefreshKind, RefreshKind, System};
 use tracing::Level;
 
 use crate::cli::commands::{
-    access_map::AccessMapArgs, rules::RulesArgs, scan::ScanCommandArgs, view::ViewArgs,
+    access_map::AccessMapArgs, revoke::RevokeArgs, rules::RulesArgs, scan::ScanCommandArgs,
+    validate::ValidateArgs, validation_cache::ValidationCacheCommandArgs, view::ViewArgs,
 };
 
 #[deny(missing_docs)]
@@ -71,6 +72,16 @@ pub enum Command {
     /// View Kingfisher JSON/JSONL reports in a local web UI
     View(ViewArgs),
 
+    /// Re-validate the findings in a JSON/JSONL report, or a single secret from stdin
+    Validate(ValidateArgs),
+
+    /// Revoke validated secrets from a JSON/JSONL report through their provider
+    Revoke(RevokeArgs),
+
+    /// Inspect or purge a persistent validation cache
+    #[command(name = "validation-cache")]
+    ValidationCache(ValidationCacheCommandArgs),
+
     /// Update the Kingfisher binary
     #[command(name = "update", alias = "self-update")]
     SelfUpdate,
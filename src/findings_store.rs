@@ -19,6 +19,7 @@ use crate::{
     matcher::Match,
     origin::{Origin, OriginSet},
     rules::rule::Rule,
+    scanner::DockerLayer,
     util::intern,
 };
 
@@ -56,6 +57,7 @@ pub struct FindingsStore {
     blob_meta: FxHashMap<BlobId, Arc<BlobMetadata>>,
     origin_meta: FxHashMap<u64, Arc<OriginSet>>,
     docker_images: FxHashMap<PathBuf, String>,
+    docker_layers: FxHashMap<PathBuf, DockerLayer>,
     slack_links: FxHashMap<PathBuf, String>,
     confluence_links: FxHashMap<PathBuf, String>,
     s3_buckets: FxHashMap<PathBuf, String>,
@@ -81,6 +83,7 @@ impl FindingsStore {
             bloom_items: 0,
             dependent_rule_ids: FxHashSet::default(),
             docker_images: FxHashMap::default(),
+            docker_layers: FxHashMap::default(),
             slack_links: FxHashMap::default(),
             confluence_links: FxHashMap::default(),
             s3_buckets: FxHashMap::default(),
@@ -103,6 +106,8 @@ impl FindingsStore {
                 existing.validation_success = updated.validation_success;
                 existing.validation_response_status = updated.validation_response_status;
                 existing.validation_response_body = updated.validation_response_body.clone();
+                existing.validation_identity = updated.validation_identity.clone();
+                existing.dependency_sources = updated.dependency_sources.clone();
             }
         }
     }
@@ -192,11 +197,16 @@ impl FindingsStore {
                 };
 
                 let rule_id = m.rule.id().to_uppercase();
-                let key_string = if self.dependent_rule_ids.contains(&rule_id) {
+                let mut key_string = if self.dependent_rule_ids.contains(&rule_id) {
                     format!("{}|{}|{}|{}", rule_id, origin_kind, snippet, blob_md.id.hex())
                 } else {
                     format!("{}|{}|{}", rule_id, origin_kind, snippet)
                 };
+                // With `--show-suppressed`, an inline-suppressed copy must not
+                // swallow an unsuppressed one.
+                if m.suppressed_by.is_some() {
+                    key_string.push_str("|suppressed");
+                }
                 let key = xxh3_64(key_string.as_bytes());
 
                 if self.seen_bloom.check(&key) {
@@ -339,6 +349,14 @@ impl FindingsStore {
         &self.docker_images
     }
 
+    pub fn register_docker_layer(&mut self, dir: PathBuf, layer: DockerLayer) {
+        self.docker_layers.insert(dir, layer);
+    }
+
+    pub fn docker_layers(&self) -> &FxHashMap<PathBuf, DockerLayer> {
+        &self.docker_layers
+    }
+
     pub fn register_slack_message(&mut self, path: PathBuf, permalink: String) {
         self.slack_links.insert(path, permalink);
     }
@@ -384,6 +402,10 @@ impl FindingsStore {
             self.docker_images.entry(dir.clone()).or_insert_with(|| image.clone());
         }
 
+        for (dir, layer) in other.docker_layers() {
+            self.docker_layers.entry(dir.clone()).or_insert_with(|| layer.clone());
+        }
+
         for (dir, link) in other.slack_links() {
             self.slack_links.entry(dir.clone()).or_insert_with(|| link.clone());
         }
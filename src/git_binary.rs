@@ -574,6 +574,55 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_clone_fetches_pull_request_refs() -> Result<(), GitError> {
+        // Hugging Face publishes pull requests only under `refs/pr/*`.
+        let temp_dir = TempDir::new()?;
+        let origin = temp_dir.path().join("origin");
+        let clone = temp_dir.path().join("clone");
+        std::fs::create_dir(&origin)?;
+        let git_in = |dir: &Path, args: &[&str]| {
+            let output = Command::new("git")
+                .arg("-C")
+                .arg(dir)
+                .args(["-c", "user.name=kingfisher", "-c", "user.email=kf@example.com"])
+                .args(args)
+                .output()
+                .unwrap();
+            assert!(output.status.success(), "git {args:?} failed");
+            String::from_utf8(output.stdout).unwrap().trim().to_string()
+        };
+        let pr_commit = |n: u32| {
+            let sha = git_in(&origin, &["commit-tree", "HEAD^{tree}", "-p", "HEAD", "-m", "pr"]);
+            git_in(&origin, &["update-ref", &format!("refs/pr/{n}"), &sha]);
+            sha
+        };
+        git_in(&origin, &["init", "-q"]);
+        git_in(&origin, &["commit", "-q", "--allow-empty", "-m", "main"]);
+        let first = pr_commit(1);
+
+        let url =
+            GitUrl::try_from(Url::parse("https://hub.example/acme/model.git").unwrap()).unwrap();
+        let redirect = format!("url.file://{}.insteadOf", origin.display());
+        temp_env::with_vars(
+            [
+                ("GIT_CONFIG_COUNT", Some("1")),
+                ("GIT_CONFIG_KEY_0", Some(redirect.as_str())),
+                ("GIT_CONFIG_VALUE_0", Some(url.as_str())),
+            ],
+            || -> Result<(), GitError> {
+                let git = Git::default();
+                git.create_fresh_clone(&url, &clone, CloneMode::Bare)?;
+                git_in(&clone, &["cat-file", "-e", &format!("{first}^{{commit}}")]);
+
+                let second = pr_commit(2);
+                git.update_clone(&url, &clone)?;
+                git_in(&clone, &["cat-file", "-e", &format!("{second}^{{commit}}")]);
+                Ok(())
+            },
+        )
+    }
+
     #[test]
     fn test_git_error() {
         let temp_dir = TempDir::new().unwrap();
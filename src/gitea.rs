@@ -1,14 +1,22 @@
-use std::{collections::HashSet, env, str::FromStr, time::Duration};
-
-use anyhow::{anyhow, Result};
+use std::{
+    collections::HashSet,
+    env, fs,
+    path::{Path, PathBuf},
+    str::FromStr,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use anyhow::{anyhow, Context, Result};
 use globset::{Glob, GlobSet, GlobSetBuilder};
 use indicatif::{ProgressBar, ProgressStyle};
 use reqwest::StatusCode;
 use serde::Deserialize;
-use tracing::warn;
+use serde_json::Value;
+use tracing::{debug, warn};
 use url::Url;
 
-use crate::{git_url::GitUrl, validation::GLOBAL_USER_AGENT};
+use crate::{findings_store, git_url::GitUrl, validation::GLOBAL_USER_AGENT};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum RepoType {
@@ -416,6 +424,131 @@ pub fn wiki_url(repo_url: &GitUrl) -> Option<GitUrl> {
     GitUrl::from_str(&url).ok()
 }
 
+/// Use the configured API URL when it serves the repository's host, and the
+/// host's own `/api/v1/` otherwise.
+fn api_base_for_host(api_url: &Url, host: &str) -> Result<Url> {
+    if api_url.host_str().is_some_and(|h| h.eq_ignore_ascii_case(host)) {
+        let mut base = api_url.clone();
+        if !base.path().ends_with('/') {
+            base.set_path(&format!("{}/", base.path()));
+        }
+        return Ok(base);
+    }
+    Ok(Url::parse(&format!("https://{host}/api/v1/"))?)
+}
+
+async fn fetch_paginated_values(
+    client: &reqwest::Client,
+    token: Option<&str>,
+    mut url: Url,
+    extra: &[(&str, &str)],
+) -> Result<Vec<Value>> {
+    let mut page = 1u32;
+    let mut items = Vec::new();
+    loop {
+        {
+            let mut pairs = url.query_pairs_mut();
+            pairs.clear();
+            for (key, value) in extra {
+                pairs.append_pair(key, value);
+            }
+            pairs.append_pair("page", &page.to_string()).append_pair("limit", "50");
+        }
+        let mut req = client.get(url.clone()).header("User-Agent", GLOBAL_USER_AGENT.as_str());
+        if let Some(token) = token {
+            req = req.header("Authorization", format!("token {token}"));
+        }
+        let resp = req.send().await?;
+        if !resp.status().is_success() {
+            debug!("Gitea endpoint {} returned {}", url, resp.status());
+            break;
+        }
+        let batch: Vec<Value> = resp.json().await?;
+        if batch.is_empty() {
+            break;
+        }
+        items.extend(batch);
+        page += 1;
+    }
+    Ok(items)
+}
+
+/// Download issues, pull requests and releases of a Gitea repository so they
+/// can be scanned. The wiki is cloned separately via [`wiki_url`].
+pub async fn fetch_repo_items(
+    repo_url: &GitUrl,
+    api_url: &Url,
+    ignore_certs: bool,
+    output_root: &Path,
+    datastore: &Arc<Mutex<findings_store::FindingsStore>>,
+) -> Result<Vec<PathBuf>> {
+    let (host, owner, repo) = parse_repo(repo_url).context("invalid Gitea repo URL")?;
+    let base = api_base_for_host(api_url, &host)?;
+    let client = reqwest::Client::builder()
+        .timeout(Duration::from_secs(30))
+        .danger_accept_invalid_certs(ignore_certs)
+        .build()?;
+    let token = env::var("KF_GITEA_TOKEN").ok().filter(|t| !t.is_empty());
+    let web_root = format!("https://{host}/{owner}/{repo}");
+
+    let mut dirs = Vec::new();
+
+    // Issues and pull requests
+    let issues_dir = output_root.join("gitea_issues").join(&owner).join(&repo);
+    fs::create_dir_all(&issues_dir)?;
+    let url = base.join(&format!("repos/{owner}/{repo}/issues"))?;
+    let issues = fetch_paginated_values(
+        &client,
+        token.as_deref(),
+        url,
+        &[("state", "all"), ("type", "all")],
+    )
+    .await?;
+    for issue in issues {
+        let number = issue.get("number").and_then(|v| v.as_u64()).unwrap_or(0);
+        let title = issue.get("title").and_then(|v| v.as_str()).unwrap_or("");
+        let body = issue.get("body").and_then(|v| v.as_str()).unwrap_or("");
+        let file_path = issues_dir.join(format!("issue_{number}.md"));
+        fs::write(&file_path, format!("# {title}\n\n{body}"))?;
+        let link = issue
+            .get("html_url")
+            .and_then(|v| v.as_str())
+            .map(str::to_string)
+            .unwrap_or_else(|| format!("{web_root}/issues/{number}"));
+        let mut ds = datastore.lock().unwrap();
+        ds.register_repo_link(file_path, link);
+    }
+    if issues_dir.read_dir().ok().and_then(|mut d| d.next()).is_some() {
+        dirs.push(issues_dir);
+    }
+
+    // Releases
+    let releases_dir = output_root.join("gitea_releases").join(&owner).join(&repo);
+    fs::create_dir_all(&releases_dir)?;
+    let url = base.join(&format!("repos/{owner}/{repo}/releases"))?;
+    let releases = fetch_paginated_values(&client, token.as_deref(), url, &[]).await?;
+    for release in releases {
+        let id = release.get("id").and_then(|v| v.as_u64()).unwrap_or(0);
+        let tag = release.get("tag_name").and_then(|v| v.as_str()).unwrap_or("");
+        let name = release.get("name").and_then(|v| v.as_str()).unwrap_or(tag);
+        let body = release.get("body").and_then(|v| v.as_str()).unwrap_or("");
+        let file_path = releases_dir.join(format!("release_{id}.md"));
+        fs::write(&file_path, format!("# {name}\n\n{body}"))?;
+        let link = release
+            .get("html_url")
+            .and_then(|v| v.as_str())
+            .map(str::to_string)
+            .unwrap_or_else(|| format!("{web_root}/releases/tag/{tag}"));
+        let mut ds = datastore.lock().unwrap();
+        ds.register_repo_link(file_path, link);
+    }
+    if releases_dir.read_dir().ok().and_then(|mut d| d.next()).is_some() {
+        dirs.push(releases_dir);
+    }
+
+    Ok(dirs)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -437,4 +570,17 @@ mod tests {
     fn normalize_repo_identifier_handles_git_suffix() {
         assert_eq!(normalize_repo_identifier("owner/repo.git"), Some("owner/repo".into()));
     }
+
+    #[test]
+    fn api_base_prefers_configured_url_for_matching_host() {
+        let configured = Url::parse("https://git.example.com:3000/gitea/api/v1").unwrap();
+        assert_eq!(
+            api_base_for_host(&configured, "git.example.com").unwrap().as_str(),
+            "https://git.example.com:3000/gitea/api/v1/"
+        );
+        assert_eq!(
+            api_base_for_host(&configured, "codeberg.org").unwrap().as_str(),
+            "https://codeberg.org/api/v1/"
+        );
+    }
 }
@@ -20,7 +20,12 @@ use serde_json::Value;
 use tracing::{info, warn};
 use url::Url;
 
-use crate::{findings_store, git_url::GitUrl, validation::GLOBAL_USER_AGENT};
+use crate::{
+    ci_logs::{self, CiLogLimits},
+    findings_store,
+    git_url::GitUrl,
+    validation::GLOBAL_USER_AGENT,
+};
 use std::str::FromStr;
 
 #[derive(Deserialize)]
@@ -697,6 +702,184 @@ pub async fn fetch_repo_items(
     Ok(dirs)
 }
 
+fn with_github_token(req: reqwest::RequestBuilder) -> reqwest::RequestBuilder {
+    match env::var("KF_GITHUB_TOKEN") {
+        Ok(token) if !token.is_empty() => req.bearer_auth(token),
+        _ => req,
+    }
+}
+
+/// Download GitHub Actions job logs and uploaded artifacts for the most
+/// recent workflow runs of `repo_url`.
+pub async fn fetch_ci_logs(
+    repo_url: &GitUrl,
+    github_api_url: &Url,
+    ignore_certs: bool,
+    output_root: &Path,
+    limits: &CiLogLimits,
+    datastore: &Arc<Mutex<findings_store::FindingsStore>>,
+) -> Result<Vec<PathBuf>> {
+    let (_, owner, repo) = parse_repo(repo_url).context("invalid GitHub repo URL")?;
+    let client = reqwest::Client::builder().danger_accept_invalid_certs(ignore_certs).build()?;
+    let api = normalize_api_base(github_api_url)
+        .join(&format!("repos/{owner}/{repo}/actions"))
+        .context("Failed to build GitHub Actions URL")?;
+    let created = limits.cutoff().format("%Y-%m-%d");
+    let per_page = limits.max_runs.clamp(1, 100);
+
+    let mut runs = Vec::new();
+    let mut page = 1;
+    while runs.len() < limits.max_runs {
+        let url = format!("{api}/runs?created=%3E%3D{created}&per_page={per_page}&page={page}");
+        let req = client.get(&url).header("User-Agent", GLOBAL_USER_AGENT.as_str());
+        let resp = with_github_token(req).send().await?;
+        if !resp.status().is_success() {
+            break;
+        }
+        let body: Value = match resp.json().await {
+            Ok(body) => body,
+            Err(e) => {
+                warn!("Stopping at page {page} of workflow runs for {owner}/{repo}: {e}");
+                break;
+            }
+        };
+        let batch =
+            body.get("workflow_runs").and_then(|v| v.as_array()).cloned().unwrap_or_default();
+        if batch.is_empty() {
+            break;
+        }
+        runs.extend(batch);
+        page += 1;
+    }
+    runs.truncate(limits.max_runs);
+
+    let ci_dir = output_root.join("github_ci").join(&owner).join(&repo);
+    for run in runs {
+        let Some(run_id) = run.get("id").and_then(|v| v.as_u64()) else {
+            continue;
+        };
+        let run_url =
+            run.get("html_url").and_then(|v| v.as_str()).map(str::to_string).unwrap_or_else(|| {
+                format!("https://github.com/{owner}/{repo}/actions/runs/{run_id}")
+            });
+        let run_dir = ci_dir.join(format!("run_{run_id}"));
+
+        // Job logs
+        page = 1;
+        loop {
+            let url = format!("{api}/runs/{run_id}/jobs?per_page=100&page={page}");
+            let req = client.get(&url).header("User-Agent", GLOBAL_USER_AGENT.as_str());
+            let resp = with_github_token(req).send().await?;
+            if !resp.status().is_success() {
+                break;
+            }
+            let body: Value = match resp.json().await {
+                Ok(body) => body,
+                Err(e) => {
+                    warn!("Stopping at page {page} of jobs for run {run_id}: {e}");
+                    break;
+                }
+            };
+            let jobs = body.get("jobs").and_then(|v| v.as_array()).cloned().unwrap_or_default();
+            if jobs.is_empty() {
+                break;
+            }
+            for job in jobs {
+                let Some(job_id) = job.get("id").and_then(|v| v.as_u64()) else {
+                    continue;
+                };
+                let req = client
+                    .get(&format!("{api}/jobs/{job_id}/logs"))
+                    .header("User-Agent", GLOBAL_USER_AGENT.as_str());
+                let what = format!("log of job {job_id} in {owner}/{repo}");
+                // Logs expire or may need a token with `actions:read`.
+                let Ok(log) = ci_logs::download(with_github_token(req), &what).await else {
+                    continue;
+                };
+                let job_url = job
+                    .get("html_url")
+                    .and_then(|v| v.as_str())
+                    .map(str::to_string)
+                    .unwrap_or_else(|| format!("{run_url}/job/{job_id}"));
+                ci_logs::write_log(
+                    &run_dir.join(format!("job_{job_id}.log")),
+                    &log,
+                    job_url,
+                    datastore,
+                )?;
+            }
+            page += 1;
+        }
+
+        // Uploaded artifacts
+        page = 1;
+        loop {
+            let url = format!("{api}/runs/{run_id}/artifacts?per_page=100&page={page}");
+            let req = client.get(&url).header("User-Agent", GLOBAL_USER_AGENT.as_str());
+            let resp = with_github_token(req).send().await?;
+            if !resp.status().is_success() {
+                break;
+            }
+            let body: Value = match resp.json().await {
+                Ok(body) => body,
+                Err(e) => {
+                    warn!("Stopping at page {page} of artifacts for run {run_id}: {e}");
+                    break;
+                }
+            };
+            let artifacts =
+                body.get("artifacts").and_then(|v| v.as_array()).cloned().unwrap_or_default();
+            if artifacts.is_empty() {
+                break;
+            }
+            for artifact in artifacts {
+                let Some(artifact_id) = artifact.get("id").and_then(|v| v.as_u64()) else {
+                    continue;
+                };
+                let expired = artifact.get("expired").and_then(|v| v.as_bool()).unwrap_or(false);
+                let size = artifact.get("size_in_bytes").and_then(|v| v.as_u64()).unwrap_or(0);
+                if expired || size > ci_logs::MAX_ARTIFACT_BYTES {
+                    continue;
+                }
+                let Some(download_url) =
+                    artifact.get("archive_download_url").and_then(|v| v.as_str())
+                else {
+                    continue;
+                };
+                let req = client.get(download_url).header("User-Agent", GLOBAL_USER_AGENT.as_str());
+                let what = format!("artifact {artifact_id} of {owner}/{repo}");
+                let data = match ci_logs::download(with_github_token(req), &what).await {
+                    Ok(data) => data,
+                    // Artifact downloads always require a token.
+                    Err(Some(status)) => {
+                        warn!("Skipping {what}: {status}");
+                        continue;
+                    }
+                    Err(None) => continue,
+                };
+                let artifacts_dir = run_dir.join("artifacts");
+                fs::create_dir_all(&artifacts_dir)?;
+                let archive = artifacts_dir.join(format!("{artifact_id}.zip"));
+                fs::write(&archive, data)?;
+                let artifact_url = format!("{run_url}/artifacts/{artifact_id}");
+                ci_logs::unpack_artifact(
+                    &archive,
+                    &artifacts_dir.join(artifact_id.to_string()),
+                    |_| artifact_url.clone(),
+                    datastore,
+                )?;
+            }
+            page += 1;
+        }
+    }
+
+    if ci_dir.read_dir().ok().and_then(|mut d| d.next()).is_some() {
+        Ok(vec![ci_dir])
+    } else {
+        Ok(Vec::new())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -7,6 +7,7 @@ use std::{
 };
 
 use anyhow::{Context, Result};
+use chrono::SecondsFormat;
 use gitlab::{
     api::{
         groups::projects::GroupProjects,
@@ -25,7 +26,11 @@ use tokio::task;
 use tracing::{info, warn};
 use url::{form_urlencoded, Url};
 
-use crate::{findings_store, git_url::GitUrl};
+use crate::{
+    ci_logs::{self, CiLogLimits},
+    findings_store,
+    git_url::GitUrl,
+};
 use std::str::FromStr;
 
 #[derive(Deserialize)]
@@ -712,6 +717,137 @@ pub async fn fetch_repo_items(
     Ok(dirs)
 }
 
+fn with_gitlab_token(req: reqwest::RequestBuilder) -> reqwest::RequestBuilder {
+    match env::var("KF_GITLAB_TOKEN") {
+        Ok(token) if !token.is_empty() => req.header("PRIVATE-TOKEN", token),
+        _ => req,
+    }
+}
+
+/// Download GitLab CI job logs and artifacts for the most recent pipelines
+/// of `repo_url`. The API is `gitlab_api_url` when it serves the
+/// repository's host, and the host's own `/api/v4/` otherwise.
+pub async fn fetch_ci_logs(
+    repo_url: &GitUrl,
+    gitlab_api_url: &Url,
+    ignore_certs: bool,
+    output_root: &Path,
+    limits: &CiLogLimits,
+    datastore: &Arc<Mutex<findings_store::FindingsStore>>,
+) -> Result<Vec<PathBuf>> {
+    let (host, path) = parse_repo(repo_url).context("invalid GitLab repo URL")?;
+    let encoded = form_urlencoded::byte_serialize(path.as_bytes()).collect::<String>();
+    let client = reqwest::Client::builder().danger_accept_invalid_certs(ignore_certs).build()?;
+    let base = if gitlab_api_url.host_str().is_some_and(|h| h.eq_ignore_ascii_case(&host)) {
+        normalize_api_base(gitlab_api_url)
+    } else {
+        Url::parse(&format!("https://{host}/"))?
+    };
+    let api = base
+        .join(&format!("api/v4/projects/{encoded}"))
+        .context("Failed to build GitLab project URL")?;
+    let updated_after = limits.cutoff().to_rfc3339_opts(SecondsFormat::Secs, true);
+    let per_page = limits.max_runs.clamp(1, 100);
+
+    let mut pipelines = Vec::new();
+    let mut page = 1;
+    while pipelines.len() < limits.max_runs {
+        let url = format!(
+            "{api}/pipelines?updated_after={updated_after}&order_by=id&sort=desc&per_page={per_page}&page={page}"
+        );
+        let resp = with_gitlab_token(client.get(&url)).send().await?;
+        if !resp.status().is_success() {
+            break;
+        }
+        let batch: Vec<Value> = resp.json().await?;
+        if batch.is_empty() {
+            break;
+        }
+        pipelines.extend(batch);
+        page += 1;
+    }
+    pipelines.truncate(limits.max_runs);
+
+    let ci_dir = output_root.join("gitlab_ci").join(path.replace('/', "_"));
+    for pipeline in pipelines {
+        let Some(pipeline_id) = pipeline.get("id").and_then(|v| v.as_u64()) else {
+            continue;
+        };
+        let pipeline_dir = ci_dir.join(format!("pipeline_{pipeline_id}"));
+        page = 1;
+        loop {
+            let url = format!("{api}/pipelines/{pipeline_id}/jobs?per_page=100&page={page}");
+            let resp = with_gitlab_token(client.get(&url)).send().await?;
+            if !resp.status().is_success() {
+                break;
+            }
+            let jobs: Vec<Value> = resp.json().await?;
+            if jobs.is_empty() {
+                break;
+            }
+            for job in jobs {
+                let Some(job_id) = job.get("id").and_then(|v| v.as_u64()) else {
+                    continue;
+                };
+                let job_url = job
+                    .get("web_url")
+                    .and_then(|v| v.as_str())
+                    .map(str::to_string)
+                    .unwrap_or_else(|| format!("https://{host}/{path}/-/jobs/{job_id}"));
+
+                // Job log
+                let req = with_gitlab_token(client.get(&format!("{api}/jobs/{job_id}/trace")));
+                let what = format!("log of job {job_id} in {path}");
+                if let Ok(log) = ci_logs::download(req, &what).await {
+                    ci_logs::write_log(
+                        &pipeline_dir.join(format!("job_{job_id}.log")),
+                        &log,
+                        job_url.clone(),
+                        datastore,
+                    )?;
+                }
+
+                // Job artifacts
+                let Some(size) =
+                    job.get("artifacts_file").and_then(|f| f.get("size")).and_then(|v| v.as_u64())
+                else {
+                    continue;
+                };
+                if size > ci_logs::MAX_ARTIFACT_BYTES {
+                    continue;
+                }
+                let req = with_gitlab_token(client.get(&format!("{api}/jobs/{job_id}/artifacts")));
+                let what = format!("artifacts of job {job_id} in {path}");
+                let data = match ci_logs::download(req, &what).await {
+                    Ok(data) => data,
+                    Err(Some(status)) => {
+                        warn!("Skipping {what}: {status}");
+                        continue;
+                    }
+                    Err(None) => continue,
+                };
+                let artifacts_dir = pipeline_dir.join("artifacts");
+                fs::create_dir_all(&artifacts_dir)?;
+                let archive = artifacts_dir.join(format!("job_{job_id}.zip"));
+                fs::write(&archive, data)?;
+                ci_logs::unpack_artifact(
+                    &archive,
+                    &artifacts_dir.join(format!("job_{job_id}")),
+                    |rel| format!("{job_url}/artifacts/file/{rel}"),
+                    datastore,
+                )?;
+            }
+            page += 1;
+        }
+    }
+
+    if ci_dir.read_dir().ok().and_then(|mut d| d.next()).is_some() {
+        Ok(vec![ci_dir])
+    } else {
+        Ok(Vec::new())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1,13 +1,19 @@
-use std::{collections::HashSet, env, time::Duration};
-
-use anyhow::{anyhow, Result};
+use std::{
+    collections::HashSet,
+    env, fs,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use anyhow::{anyhow, Context, Result};
 use indicatif::{ProgressBar, ProgressStyle};
 use reqwest::{header::LINK, StatusCode, Url};
 use serde::Deserialize;
 use serde_json::Value;
 use tracing::{debug, warn};
 
-use crate::{git_url::GitUrl, validation::GLOBAL_USER_AGENT};
+use crate::{findings_store, git_url::GitUrl, validation::GLOBAL_USER_AGENT};
 
 #[derive(Debug, Clone, Default)]
 pub struct RepoSpecifiers {
@@ -83,6 +89,14 @@ impl ResourceKind {
         }
     }
 
+    fn web_path(self, slug: &str) -> String {
+        match self {
+            ResourceKind::Model => slug.to_string(),
+            ResourceKind::Dataset => format!("datasets/{slug}"),
+            ResourceKind::Space => format!("spaces/{slug}"),
+        }
+    }
+
     fn git_url(self, slug: &str) -> String {
         match self {
             ResourceKind::Model => format!("https://huggingface.co/{slug}.git"),
@@ -584,8 +598,152 @@ pub fn wiki_url(_repo_url: &GitUrl) -> Option<GitUrl> {
     None
 }
 
+/// Whether `host` serves a Hugging Face Hub: `huggingface.co`, `hf.co`, or
+/// the host of a self-hosted hub named in `HF_ENDPOINT`.
+pub fn is_hub_host(host: &str) -> bool {
+    hub_host_matches(host, env::var("HF_ENDPOINT").ok().as_deref())
+}
+
+fn hub_host_matches(host: &str, endpoint: Option<&str>) -> bool {
+    let host = host.to_ascii_lowercase();
+    host.contains("huggingface")
+        || host == "hf.co"
+        || endpoint
+            .and_then(|endpoint| Url::parse(endpoint.trim()).ok())
+            .is_some_and(|url| url.host_str().is_some_and(|h| h.eq_ignore_ascii_case(&host)))
+}
+
+fn parse_repo(repo_url: &GitUrl) -> Option<(Url, ResourceKind, String)> {
+    let url = Url::parse(repo_url.as_str()).ok()?;
+    let segments: Vec<&str> = url.path_segments()?.filter(|s| !s.is_empty()).collect();
+    let kind = match segments.first().copied() {
+        Some("datasets") => ResourceKind::Dataset,
+        Some("spaces") => ResourceKind::Space,
+        _ => ResourceKind::Model,
+    };
+    let slug = parse_slug_segments(kind, &segments)?;
+    let mut web_root = url;
+    web_root.set_path("/");
+    web_root.set_query(None);
+    Some((web_root, kind, slug))
+}
+
+/// Render a discussion or pull request and its comments as Markdown.
+fn render_discussion(details: &Value) -> String {
+    let title = details.get("title").and_then(|v| v.as_str()).unwrap_or("");
+    let mut out = format!("# {title}\n");
+    let events = details.get("events").and_then(|v| v.as_array()).cloned().unwrap_or_default();
+    for event in events {
+        if event.get("type").and_then(|v| v.as_str()) != Some("comment") {
+            continue;
+        }
+        let author = event
+            .get("author")
+            .and_then(|a| a.get("name"))
+            .and_then(|v| v.as_str())
+            .unwrap_or("unknown");
+        let raw = event
+            .get("data")
+            .and_then(|d| d.get("latest"))
+            .and_then(|l| l.get("raw"))
+            .and_then(|v| v.as_str())
+            .unwrap_or("");
+        out.push_str(&format!("\n## {author}\n\n{raw}\n"));
+    }
+    out
+}
+
+/// Download the discussions and pull requests of a Hugging Face repository,
+/// including each pull request's diff. The commits on `refs/pr/*` themselves
+/// come with the clone, whose refspec fetches every ref.
+///
+/// The API is served from the repository's own host; self-hosted hubs are
+/// recognised through `HF_ENDPOINT` (see [`is_hub_host`]).
+pub async fn fetch_repo_items(
+    repo_url: &GitUrl,
+    auth: &AuthConfig,
+    ignore_certs: bool,
+    output_root: &Path,
+    datastore: &Arc<Mutex<findings_store::FindingsStore>>,
+) -> Result<Vec<PathBuf>> {
+    let (web_root, kind, slug) = parse_repo(repo_url).context("invalid Hugging Face repo URL")?;
+    let client = reqwest::Client::builder()
+        .timeout(Duration::from_secs(30))
+        .danger_accept_invalid_certs(ignore_certs)
+        .build()?;
+    let api = web_root.join(&format!("api/{}/{slug}/discussions", kind.api_path()))?;
+    let web = web_root.join(&format!("{}/discussions", kind.web_path(&slug)))?;
+
+    let dir = output_root
+        .join("huggingface_discussions")
+        .join(kind.api_path())
+        .join(slug.replace('/', "_"));
+    fs::create_dir_all(&dir)?;
+
+    // Pages are requested until one is empty or repeats earlier discussions,
+    // since `count` is not always reported.
+    let mut numbers = Vec::new();
+    let mut seen = HashSet::new();
+    let mut page = 0u32;
+    loop {
+        let mut url = api.clone();
+        url.query_pairs_mut().append_pair("p", &page.to_string());
+        let request = client.get(url).header("User-Agent", GLOBAL_USER_AGENT.as_str());
+        let response = auth.apply(request).send().await?;
+        if !response.status().is_success() {
+            warn!("Hugging Face discussions for {slug} returned {}", response.status());
+            break;
+        }
+        let body: Value = response.json().await?;
+        let discussions =
+            body.get("discussions").and_then(|v| v.as_array()).cloned().unwrap_or_default();
+        if discussions.is_empty() {
+            break;
+        }
+        let before = numbers.len();
+        numbers.extend(
+            discussions
+                .iter()
+                .filter_map(|d| d.get("num").and_then(|v| v.as_u64()))
+                .filter(|num| seen.insert(*num)),
+        );
+        if numbers.len() == before {
+            break;
+        }
+        page += 1;
+    }
+
+    for num in numbers {
+        let url = Url::parse(&format!("{api}/{num}"))?;
+        let request = client.get(url).header("User-Agent", GLOBAL_USER_AGENT.as_str());
+        let response = auth.apply(request).send().await?;
+        if !response.status().is_success() {
+            continue;
+        }
+        let details: Value = response.json().await?;
+        let link = format!("{web}/{num}");
+        let file_path = dir.join(format!("discussion_{num}.md"));
+        fs::write(&file_path, render_discussion(&details))?;
+        let mut ds = datastore.lock().unwrap();
+        ds.register_repo_link(file_path, link.clone());
+        if let Some(diff) = details.get("diff").and_then(|v| v.as_str()) {
+            let diff_path = dir.join(format!("pr_{num}.diff"));
+            fs::write(&diff_path, diff)?;
+            ds.register_repo_link(diff_path, format!("{link}/files"));
+        }
+    }
+
+    if dir.read_dir().ok().and_then(|mut d| d.next()).is_some() {
+        Ok(vec![dir])
+    } else {
+        Ok(Vec::new())
+    }
+}
+
 #[cfg(test)]
 mod tests {
+    use std::str::FromStr;
+
     use super::*;
 
     #[test]
@@ -628,6 +786,45 @@ mod tests {
         assert_eq!(url.as_str(), "https://huggingface.co/api/models?cursor=abc");
     }
 
+    #[test]
+    fn hub_hosts_include_configured_endpoint() {
+        assert!(hub_host_matches("huggingface.co", None));
+        assert!(hub_host_matches("hf.co", None));
+        assert!(!hub_host_matches("hub.example.com", None));
+        assert!(hub_host_matches("Hub.Example.com", Some("https://hub.example.com")));
+        assert!(!hub_host_matches("hub.example.com", Some("not a url")));
+    }
+
+    #[test]
+    fn parse_repo_detects_kind_and_host() {
+        let url = GitUrl::from_str("https://hub.example.com/datasets/org/data.git").unwrap();
+        let (root, kind, slug) = parse_repo(&url).expect("dataset repo");
+        assert_eq!(root.as_str(), "https://hub.example.com/");
+        assert_eq!(kind, ResourceKind::Dataset);
+        assert_eq!(slug, "org/data");
+
+        let url = GitUrl::from_str("https://huggingface.co/org/model.git").unwrap();
+        let (_, kind, slug) = parse_repo(&url).expect("model repo");
+        assert_eq!(kind, ResourceKind::Model);
+        assert_eq!(kind.web_path(&slug), "org/model");
+    }
+
+    #[test]
+    fn render_discussion_includes_comments_only() {
+        let details = serde_json::json!({
+            "title": "Add config",
+            "events": [
+                { "type": "status-change", "data": { "status": "open" } },
+                {
+                    "type": "comment",
+                    "author": { "name": "alice" },
+                    "data": { "latest": { "raw": "token: hf_example" } }
+                }
+            ]
+        });
+        assert_eq!(render_discussion(&details), "# Add config\n\n## alice\n\ntoken: hf_example\n");
+    }
+
     #[test]
     fn truncate_for_display_adds_ellipsis() {
         assert_eq!(truncate_for_display("abcdef", 3), "abc…");
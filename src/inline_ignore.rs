@@ -1,11 +1,66 @@
-use crate::location::OffsetSpan;
+use chrono::{Local, NaiveDate};
+use tracing::debug;
+
+use crate::{allowlist::rule_matches, location::OffsetSpan};
 
 /// Configuration for inline ignore directives.
-#[derive(Clone, Debug, Default)]
+#[derive(Clone, Debug)]
 pub struct InlineIgnoreConfig {
     tokens: Vec<Vec<u8>>,
+    today: NaiveDate,
+    keep_suppressed: bool,
 }
 
+impl Default for InlineIgnoreConfig {
+    fn default() -> Self {
+        Self::disabled()
+    }
+}
+
+/// A parsed inline ignore directive, e.g.
+/// `kingfisher:ignore[kingfisher.aws.2] until=2026-12-31 reason="rotated"`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct InlineDirective {
+    /// Rule ids or prefixes the directive applies to; empty means every rule.
+    pub rules: Vec<String>,
+    /// Raw `until=` value; the directive stops applying after this day.
+    pub until: Option<String>,
+    pub reason: Option<String>,
+}
+
+impl InlineDirective {
+    fn applies_to(&self, rule_id: &str, today: NaiveDate) -> bool {
+        if !self.rules.is_empty() && !self.rules.iter().any(|r| rule_matches(r, rule_id)) {
+            return false;
+        }
+        match self.until.as_deref() {
+            None => true,
+            Some(date) => match NaiveDate::parse_from_str(date, "%Y-%m-%d") {
+                Ok(until) if until >= today => true,
+                Ok(_) => {
+                    debug!("Inline ignore directive for {rule_id} expired on {date}");
+                    false
+                }
+                Err(_) => {
+                    debug!("Inline ignore directive has an invalid until date '{date}'");
+                    false
+                }
+            },
+        }
+    }
+
+    /// Text recorded in `Match::suppressed_by` for findings hidden by this directive.
+    pub fn suppression_reason(&self) -> String {
+        match &self.reason {
+            Some(reason) => format!("{INLINE_DIRECTIVE_SUPPRESSION} ({reason})"),
+            None => INLINE_DIRECTIVE_SUPPRESSION.to_string(),
+        }
+    }
+}
+
+/// Prefix of the suppression reason recorded for inline directives.
+pub const INLINE_DIRECTIVE_SUPPRESSION: &str = "inline directive";
+
 impl InlineIgnoreConfig {
     /// Create a new configuration.
     ///
@@ -27,12 +82,24 @@ impl InlineIgnoreConfig {
             tokens.push(lowered);
         }
 
-        Self { tokens }
+        Self { tokens, today: Local::now().date_naive(), keep_suppressed: false }
     }
 
     /// Return a configuration with inline ignores disabled.
     pub fn disabled() -> Self {
-        Self { tokens: Vec::new() }
+        Self { tokens: Vec::new(), today: Local::now().date_naive(), keep_suppressed: false }
+    }
+
+    /// Keep suppressed findings as hidden matches (for `--show-suppressed`)
+    /// instead of dropping them.
+    pub fn with_suppressed_kept(mut self, keep: bool) -> Self {
+        self.keep_suppressed = keep;
+        self
+    }
+
+    /// Whether suppressed findings are kept as hidden matches.
+    pub fn keeps_suppressed(&self) -> bool {
+        self.keep_suppressed
     }
 
     #[inline]
@@ -41,17 +108,28 @@ impl InlineIgnoreConfig {
     }
 
     /// Returns `true` when the provided blob slice contains an inline ignore
-    /// directive that should suppress a finding for the given span.
-    pub fn should_ignore(&self, blob_bytes: &[u8], span: &OffsetSpan) -> bool {
+    /// directive that should suppress a finding of `rule_id` for the given span.
+    pub fn should_ignore(&self, blob_bytes: &[u8], span: &OffsetSpan, rule_id: &str) -> bool {
+        self.directive_for(blob_bytes, span, rule_id).is_some()
+    }
+
+    /// Find the inline ignore directive, if any, that suppresses a finding of
+    /// `rule_id` at the given span.
+    pub fn directive_for(
+        &self,
+        blob_bytes: &[u8],
+        span: &OffsetSpan,
+        rule_id: &str,
+    ) -> Option<InlineDirective> {
         if !self.has_tokens() {
-            return false;
+            return None;
         }
 
         let (start_line_start, start_line_end) = line_bounds(blob_bytes, span.start);
         if start_line_end > start_line_start {
             let start_line = &blob_bytes[start_line_start..start_line_end];
-            if line_has_directive(start_line, &self.tokens) {
-                return true;
+            if let Some(directive) = self.line_directive(start_line, rule_id) {
+                return Some(directive);
             }
         }
 
@@ -67,8 +145,8 @@ impl InlineIgnoreConfig {
             }
 
             let prev_line = &blob_bytes[prev_start..prev_end];
-            if line_has_directive(prev_line, &self.tokens) {
-                return true;
+            if let Some(directive) = self.line_directive(prev_line, rule_id) {
+                return Some(directive);
             }
 
             if !should_skip_for_directive_search(prev_line) {
@@ -89,8 +167,8 @@ impl InlineIgnoreConfig {
             && (closing_line_start != start_line_start || closing_line_end != start_line_end)
         {
             let closing_line = &blob_bytes[closing_line_start..closing_line_end];
-            if line_has_directive(closing_line, &self.tokens) {
-                return true;
+            if let Some(directive) = self.line_directive(closing_line, rule_id) {
+                return Some(directive);
             }
         }
 
@@ -110,8 +188,8 @@ impl InlineIgnoreConfig {
             }
 
             let next_line = &blob_bytes[cursor..next_end];
-            if line_has_directive(next_line, &self.tokens) {
-                return true;
+            if let Some(directive) = self.line_directive(next_line, rule_id) {
+                return Some(directive);
             }
 
             if !should_skip_for_directive_search(next_line) {
@@ -121,8 +199,83 @@ impl InlineIgnoreConfig {
             cursor = next_end;
         }
 
-        false
+        None
+    }
+
+    /// Return the first directive on `line` that applies to `rule_id` today.
+    fn line_directive(&self, line: &[u8], rule_id: &str) -> Option<InlineDirective> {
+        if !line_has_directive(line, &self.tokens) {
+            return None;
+        }
+        parse_directives(line, &self.tokens)
+            .into_iter()
+            .find(|directive| directive.applies_to(rule_id, self.today))
+    }
+}
+
+/// Parse every directive on a line, including the optional `[rule, ...]`
+/// scope and `until=` / `reason=` parameters that follow each token.
+fn parse_directives(line: &[u8], tokens: &[Vec<u8>]) -> Vec<InlineDirective> {
+    let lowercase = line.to_ascii_lowercase();
+    let mut directives = Vec::new();
+    for token in tokens {
+        for pos in memchr::memmem::find_iter(&lowercase, token.as_slice()) {
+            let rest = String::from_utf8_lossy(&line[pos + token.len()..]);
+            if let Some(directive) = parse_directive_params(&rest) {
+                directives.push(directive);
+            }
+        }
+    }
+    directives
+}
+
+/// Parse the text following a directive token. Returns `None` when a rule
+/// scope is opened but never closed, so a malformed directive cannot widen
+/// into a suppression for every rule.
+fn parse_directive_params(rest: &str) -> Option<InlineDirective> {
+    let mut directive = InlineDirective::default();
+    let mut rest = rest;
+
+    if let Some(scope) = rest.strip_prefix('[') {
+        let close = scope.find(']')?;
+        directive.rules = scope[..close]
+            .split(',')
+            .map(str::trim)
+            .filter(|r| !r.is_empty())
+            .map(str::to_string)
+            .collect();
+        rest = &scope[close + 1..];
+    }
+
+    loop {
+        let trimmed = rest.trim_start();
+        let Some((key, value)) = trimmed.split_once('=') else {
+            break;
+        };
+        let key = key.to_ascii_lowercase();
+        if key != "until" && key != "reason" {
+            break;
+        }
+
+        let (value, remainder) = if let Some(quoted) = value.strip_prefix('"') {
+            match quoted.find('"') {
+                Some(end) => (&quoted[..end], &quoted[end + 1..]),
+                None => (quoted, ""),
+            }
+        } else {
+            let end = value.find(char::is_whitespace).unwrap_or(value.len());
+            (&value[..end], &value[end..])
+        };
+
+        if key == "until" {
+            directive.until = Some(value.to_string());
+        } else {
+            directive.reason = Some(value.to_string());
+        }
+        rest = remainder;
     }
+
+    Some(directive)
 }
 
 fn should_skip_for_directive_search(line: &[u8]) -> bool {
@@ -270,10 +423,33 @@ fn line_has_directive(line: &[u8], tokens: &[Vec<u8>]) -> bool {
 #[cfg(test)]
 mod tests {
     use super::{
-        line_bounds, line_has_directive, should_skip_for_directive_search, trim_ascii_whitespace,
-        InlineIgnoreConfig,
+        line_bounds, line_has_directive, parse_directive_params, should_skip_for_directive_search,
+        trim_ascii_whitespace, InlineDirective, InlineIgnoreConfig,
     };
     use crate::location::OffsetSpan;
+    use chrono::{Local, NaiveDate};
+
+    fn span_of(blob: &[u8], matched: &[u8]) -> OffsetSpan {
+        let start = blob
+            .windows(matched.len())
+            .position(|window| window == matched)
+            .expect("match bytes present");
+        OffsetSpan::from_range(start..start + matched.len())
+    }
+
+    fn config_on(year: i32, month: u32, day: u32) -> InlineIgnoreConfig {
+        InlineIgnoreConfig {
+            today: NaiveDate::from_ymd_opt(year, month, day).unwrap(),
+            ..InlineIgnoreConfig::new(&[])
+        }
+    }
+
+    #[test]
+    fn default_config_is_disabled_and_dated_today() {
+        let config = InlineIgnoreConfig::default();
+        assert!(!config.has_tokens());
+        assert_eq!(config.today, Local::now().date_naive());
+    }
 
     #[test]
     fn bounds_cover_expected_ranges() {
@@ -308,7 +484,7 @@ mod tests {
             .expect("match bytes present");
         let span = OffsetSpan::from_range(start..start + matched.len());
         let config = InlineIgnoreConfig::new(&[]);
-        assert!(config.should_ignore(blob, &span));
+        assert!(config.should_ignore(blob, &span, "kingfisher.test"));
     }
 
     #[test]
@@ -321,7 +497,7 @@ mod tests {
             .expect("match bytes present");
         let span = OffsetSpan::from_range(start..start + matched.len());
         let config = InlineIgnoreConfig::new(&[]);
-        assert!(config.should_ignore(blob, &span));
+        assert!(config.should_ignore(blob, &span, "kingfisher.test"));
     }
 
     #[test]
@@ -334,7 +510,7 @@ mod tests {
             .expect("match bytes present");
         let span = OffsetSpan::from_range(start..start + matched.len());
         let config = InlineIgnoreConfig::new(&[]);
-        assert!(config.should_ignore(blob, &span));
+        assert!(config.should_ignore(blob, &span, "kingfisher.test"));
     }
 
     #[test]
@@ -347,7 +523,7 @@ mod tests {
             .expect("match bytes present");
         let span = OffsetSpan::from_range(start..start + matched.len());
         let config = InlineIgnoreConfig::new(&[]);
-        assert!(config.should_ignore(blob, &span));
+        assert!(config.should_ignore(blob, &span, "kingfisher.test"));
     }
 
     #[test]
@@ -361,7 +537,7 @@ mod tests {
             .expect("match bytes present");
         let span = OffsetSpan::from_range(start..start + matched.len());
         let config = InlineIgnoreConfig::new(&[]);
-        assert!(config.should_ignore(blob, &span));
+        assert!(config.should_ignore(blob, &span, "kingfisher.test"));
     }
 
     #[test]
@@ -374,7 +550,7 @@ mod tests {
             .expect("match bytes present");
         let span = OffsetSpan::from_range(start..start + matched.len());
         let config = InlineIgnoreConfig::new(&[]);
-        assert!(config.should_ignore(blob, &span));
+        assert!(config.should_ignore(blob, &span, "kingfisher.test"));
     }
 
     #[test]
@@ -387,7 +563,7 @@ mod tests {
             .expect("match bytes present");
         let span = OffsetSpan::from_range(start..start + matched.len());
         let config = InlineIgnoreConfig::new(&[]);
-        assert!(config.should_ignore(blob, &span));
+        assert!(config.should_ignore(blob, &span, "kingfisher.test"));
     }
 
     #[test]
@@ -418,6 +594,68 @@ mod tests {
             .expect("match bytes present");
         let span = OffsetSpan::from_range(start..start + matched.len());
         let config = InlineIgnoreConfig::disabled();
-        assert!(!config.should_ignore(blob, &span));
+        assert!(!config.should_ignore(blob, &span, "kingfisher.test"));
+    }
+
+    #[test]
+    fn parses_scope_and_parameters() {
+        let directive = parse_directive_params(
+            "[kingfisher.aws.2, kingfisher.gcp] until=2026-12-31 reason=\"rotated key\" -->",
+        )
+        .unwrap();
+        assert_eq!(
+            directive,
+            InlineDirective {
+                rules: vec!["kingfisher.aws.2".to_string(), "kingfisher.gcp".to_string()],
+                until: Some("2026-12-31".to_string()),
+                reason: Some("rotated key".to_string()),
+            }
+        );
+        assert_eq!(parse_directive_params(" */").unwrap(), InlineDirective::default());
+        assert!(parse_directive_params("[kingfisher.aws.2 until=2026-12-31").is_none());
+    }
+
+    #[test]
+    fn scoped_directive_only_ignores_named_rules() {
+        let blob = b"key = 'AKIAEXAMPLE' # kingfisher:ignore[kingfisher.aws.2]";
+        let span = span_of(blob, b"AKIAEXAMPLE");
+        let config = config_on(2026, 1, 1);
+        assert!(config.should_ignore(blob, &span, "kingfisher.aws.2"));
+        assert!(config.should_ignore(blob, &span, "KINGFISHER.AWS.2"));
+        assert!(!config.should_ignore(blob, &span, "kingfisher.aws.1"));
+        assert!(!config.should_ignore(blob, &span, "kingfisher.github.1"));
+
+        let prefix = b"key = 'AKIAEXAMPLE' # kingfisher:ignore[kingfisher.aws]";
+        assert!(config.should_ignore(prefix, &span_of(prefix, b"AKIAEXAMPLE"), "kingfisher.aws.1"));
+    }
+
+    #[test]
+    fn directive_stops_applying_after_until_date() {
+        let blob = b"key = 'value' # kingfisher:ignore until=2026-12-31 reason=\"test fixture\"";
+        let span = span_of(blob, b"value");
+
+        let directive = config_on(2026, 12, 31).directive_for(blob, &span, "kingfisher.test");
+        assert_eq!(
+            directive.map(|d| d.suppression_reason()).as_deref(),
+            Some("inline directive (test fixture)")
+        );
+        assert!(!config_on(2027, 1, 1).should_ignore(blob, &span, "kingfisher.test"));
+
+        let invalid = b"key = 'value' # kingfisher:ignore until=someday";
+        assert!(!config_on(2026, 1, 1).should_ignore(
+            invalid,
+            &span_of(invalid, b"value"),
+            "kingfisher.test"
+        ));
+    }
+
+    #[test]
+    fn non_matching_directive_does_not_stop_search() {
+        let blob = b"# kingfisher:ignore[kingfisher.slack]\nsecret = \"\"\"\nline1\n\"\"\"  # kingfisher:ignore[kingfisher.github]\n";
+        let span = span_of(blob, b"line1\n");
+        let config = config_on(2026, 1, 1);
+        assert!(config.should_ignore(blob, &span, "kingfisher.slack.1"));
+        assert!(config.should_ignore(blob, &span, "kingfisher.github.2"));
+        assert!(!config.should_ignore(blob, &span, "kingfisher.aws.1"));
     }
 }
@@ -1,4 +1,5 @@
 pub mod access_map;
+pub mod allowlist;
 pub mod azure;
 pub mod baseline;
 pub mod binary;
@@ -6,6 +7,7 @@ pub mod bitbucket;
 pub mod blob;
 pub mod bstring_escape;
 pub mod bstring_table;
+pub mod ci_logs;
 pub mod cli;
 pub mod confluence;
 pub mod content_type;
@@ -31,7 +33,10 @@ pub mod location;
 pub mod matcher;
 pub mod origin;
 pub mod parser;
+pub mod project_config;
 pub mod reporter;
+pub mod revalidate;
+pub mod revocation;
 pub mod rule_loader;
 pub mod rule_profiling;
 pub mod rules;
@@ -46,6 +51,7 @@ pub mod update;
 pub mod util;
 pub mod validation;
 pub mod validation_body;
+pub mod validation_cache;
 
 use std::path::{Path, PathBuf};
 
@@ -28,6 +28,7 @@ static GLOBAL: System = System;
 
 use std::{
     io::{IsTerminal, Read, Write},
+    process::ExitCode,
     sync::{Arc, Mutex},
 };
 
@@ -52,11 +53,14 @@ use kingfisher::{
     findings_store::FindingsStore,
     gitea, github, huggingface,
     reporter::{styles::Styles, DetailsReporter},
+    revalidate, revocation,
     rule_loader::RuleLoader,
+    rules::rule::Validation,
     rules_database::RulesDatabase,
     scanner::{load_and_record_rules, run_scan},
     update::check_for_update_async,
-    validation::set_user_agent_suffix,
+    validation::{egress, set_user_agent_suffix},
+    validation_cache,
 };
 use serde_json::json;
 use tempfile::TempDir;
@@ -75,10 +79,16 @@ use crate::cli::commands::{
     gitea::GiteaRepoType,
     gitlab::GitLabRepoType,
     scan::{ListRepositoriesCommand, ScanOperation},
+    validation_cache::ValidationCacheArgs,
+    validation_dependency::ValidationDependencyArgs,
+    validation_dry_run::ValidationDryRunArgs,
+    validation_egress::ValidationEgressArgs,
+    validation_rate::ValidationRateArgs,
+    validation_ssh::ValidationSshArgs,
     view,
 };
 
-fn main() -> anyhow::Result<()> {
+fn main() -> anyhow::Result<ExitCode> {
     color_backtrace::install();
     // Parse command-line arguments
     let CommandLineArgs { command, global_args } = CommandLineArgs::parse_args();
@@ -94,6 +104,9 @@ fn main() -> anyhow::Result<()> {
         Command::Rules(_) => num_cpus::get(), // Default for Rules commands
         Command::AccessMap(_) => 1,
         Command::View(_) => 1,
+        Command::ValidationCache(_) => 1,
+        Command::Validate(validate_args) => validate_args.num_jobs,
+        Command::Revoke(_) => 1,
     };
 
     // Set up the Tokio runtime with the specified number of threads
@@ -183,7 +196,12 @@ pub fn determine_exit_code(datastore: &Arc<Mutex<findings_store::FindingsStore>>
     }
 }
 
-async fn async_main(args: CommandLineArgs) -> Result<()> {
+/// Map a report exit code (0, 200 or 205) onto the process exit status.
+fn report_exit_code(code: i32) -> ExitCode {
+    ExitCode::from(u8::try_from(code).unwrap_or(1))
+}
+
+async fn async_main(args: CommandLineArgs) -> Result<ExitCode> {
     setup_logging(&args.global_args);
     let global_args = args.global_args.clone();
 
@@ -193,10 +211,21 @@ async fn async_main(args: CommandLineArgs) -> Result<()> {
             g.self_update = true;
             g.no_update_check = false;
             let _ = check_for_update_async(&g, None).await;
-            Ok(())
+            Ok(ExitCode::SUCCESS)
+        }
+        Command::View(view_args) => view::run(view_args).await.map(|()| ExitCode::SUCCESS),
+        Command::AccessMap(identity_args) => {
+            access_map::run(identity_args).await.map(|()| ExitCode::SUCCESS)
+        }
+        Command::ValidationCache(cache_args) => {
+            validation_cache::run(cache_args).map(|()| ExitCode::SUCCESS)
+        }
+        Command::Validate(validate_args) => {
+            revalidate::run(validate_args, &global_args).await.map(report_exit_code)
+        }
+        Command::Revoke(revoke_args) => {
+            revocation::run(revoke_args, &global_args).await.map(report_exit_code)
         }
-        Command::View(view_args) => view::run(view_args).await,
-        Command::AccessMap(identity_args) => access_map::run(identity_args).await,
         command => {
             let update_status = check_for_update_async(&global_args, None).await;
             match command {
@@ -380,6 +409,15 @@ async fn async_main(args: CommandLineArgs) -> Result<()> {
                 Command::AccessMap(_) => {
                     anyhow::bail!("AccessMap command should not reach this branch")
                 }
+                Command::ValidationCache(_) => {
+                    anyhow::bail!("ValidationCache command should not reach this branch")
+                }
+                Command::Validate(_) => {
+                    anyhow::bail!("Validate command should not reach this branch")
+                }
+                Command::Revoke(_) => {
+                    anyhow::bail!("Revoke command should not reach this branch")
+                }
                 Command::SelfUpdate => {
                     anyhow::bail!("SelfUpdate command should not reach this branch")
                 }
@@ -387,7 +425,7 @@ async fn async_main(args: CommandLineArgs) -> Result<()> {
             if let Some(message) = &update_status.message {
                 info!("{}", message);
             }
-            Ok(())
+            Ok(ExitCode::SUCCESS)
         }
     }
 }
@@ -473,12 +511,17 @@ fn create_default_scan_args() -> cli::commands::scan::ScanArgs {
 
             // Docker image scanning
             docker_image: Vec::new(),
+            registry_repository: Vec::new(),
+            all_registry_repositories: false,
 
             // git clone / history options
             git_clone: GitCloneMode::Bare,
             git_history: GitHistoryMode::Full,
             commit_metadata: true,
             repo_artifacts: false,
+            ci_logs: false,
+            ci_logs_max_age_days: 30,
+            ci_logs_max_runs: 20,
             scan_nested_repos: true,
             since_commit: None,
             branch: None,
@@ -516,6 +559,17 @@ fn create_default_scan_args() -> cli::commands::scan::ScanArgs {
         no_ignore_if_contains: false,
         validation_timeout: 10,
         validation_retries: 1,
+        config: None,
+        path_overrides: Vec::new(),
+        explicit_args: Default::default(),
+        allowlist: None,
+        show_suppressed: false,
+        validation_cache: ValidationCacheArgs::default(),
+        validation_rate: ValidationRateArgs::default(),
+        validation_egress: ValidationEgressArgs::default(),
+        validation_dry_run: ValidationDryRunArgs::default(),
+        validation_dependency: ValidationDependencyArgs::default(),
+        validation_ssh: ValidationSshArgs::default(),
     }
 }
 /// Run the rules check command
@@ -540,6 +594,16 @@ pub fn run_rules_check(args: &RulesCheckArgs) -> Result<()> {
             warn!("Rule '{}' has a very short pattern", rule.name());
             num_warnings += 1;
         }
+        if let Some(Validation::Http(http)) = &rule_syntax.validation {
+            if http.requests().any(|request| egress::declared_hosts(request).is_empty()) {
+                warn!(
+                    "Rule '{}' builds its validation URL host from a template but declares no \
+                     allowed_hosts; it will never be validated",
+                    rule.name()
+                );
+                num_warnings += 1;
+            }
+        }
         if rule.syntax().examples.is_empty() {
             warn!("Rule '{}' has no examples", rule.name());
             num_warnings += 1;
@@ -614,6 +678,7 @@ pub fn run_rules_check(args: &RulesCheckArgs) -> Result<()> {
         println!("  - Rules with very short names");
         println!("  - Rules with very short patterns");
         println!("  - Rules without examples");
+        println!("  - HTTP validators with a templated host and no allowed_hosts");
     } else {
         println!("\nAll rules passed validation successfully!");
     }
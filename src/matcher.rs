@@ -1,4 +1,5 @@
 use std::{
+    collections::BTreeMap,
     hash::{Hash, Hasher},
     str,
     sync::{Arc, Mutex},
@@ -35,8 +36,11 @@ use crate::{
     scanner_pool::ScannerPool,
     snippet::Base64BString,
     util::intern,
-    validation::{is_parseable_mongodb_uri, is_parseable_mysql_uri, is_parseable_postgres_uri},
-    validation_body::{self, ValidationResponseBody},
+    validation::{
+        is_parseable_memcached_uri, is_parseable_mongodb_uri, is_parseable_mysql_uri,
+        is_parseable_postgres_uri, is_parseable_redis_uri,
+    },
+    validation_body::{self, ValidationIdentity, ValidationResponseBody},
 };
 
 const MAX_CHUNK_SIZE: usize = 1 << 30; // 1 GiB per scan segment
@@ -69,8 +73,10 @@ pub struct OwnedBlobMatch {
     pub validation_response_body: ValidationResponseBody,
     pub validation_response_status: StatusCode,
     pub validation_success: bool,
+    pub validation_identity: ValidationIdentity,
     pub calculated_entropy: f32,
     pub is_base64: bool,
+    pub suppressed_by: Option<String>,
 }
 impl<'a> Matcher<'a> {
     pub fn get_profiling_report(&self) -> Option<Vec<RuleStats>> {
@@ -90,8 +96,10 @@ impl OwnedBlobMatch {
             validation_response_status: StatusCode::from_u16(m.validation_response_status)
                 .unwrap_or(StatusCode::CONTINUE),
             validation_success: m.validation_success,
+            validation_identity: m.validation_identity.clone(),
             calculated_entropy: m.calculated_entropy,
             is_base64: m.is_base64,
+            suppressed_by: m.suppressed_by.clone(),
         }
     }
 
@@ -113,9 +121,11 @@ impl OwnedBlobMatch {
             validation_response_body: blob_match.validation_response_body,
             validation_response_status: blob_match.validation_response_status,
             validation_success: blob_match.validation_success,
+            validation_identity: ValidationIdentity::new(),
             calculated_entropy: blob_match.calculated_entropy,
             finding_fingerprint: 0, //default
             is_base64: blob_match.is_base64,
+            suppressed_by: blob_match.suppressed_by,
         };
 
         // Convert matching_finding to a &str (using lossy conversion if needed)
@@ -163,6 +173,9 @@ pub struct BlobMatch<'a> {
     pub validation_success: bool,
     pub calculated_entropy: f32,
     pub is_base64: bool,
+
+    /// Set when an inline ignore directive suppresses the match
+    pub suppressed_by: Option<String>,
 }
 #[derive(Clone)]
 struct UserData {
@@ -268,6 +281,13 @@ impl<'a> Matcher<'a> {
         })
     }
 
+    /// Keep inline-suppressed matches as hidden matches instead of dropping
+    /// them, so `--show-suppressed` can report them.
+    pub fn keep_inline_suppressions(mut self, keep: bool) -> Self {
+        self.inline_ignore_config = self.inline_ignore_config.with_suppressed_kept(keep);
+        self
+    }
+
     fn scan_bytes_raw(&mut self, input: &[u8], _filename: &str) -> Result<()> {
         // Remember previous peak automatically
         let prev_capacity = self.user_data.raw_matches_scratch.capacity();
@@ -701,10 +721,23 @@ fn filter_match<'b>(
         let matching_input_offset_span = OffsetSpan::from_range(
             (start + matching_input.start())..(start + matching_input.end()),
         );
-        if inline_ignore_config.should_ignore(blob_bytes, &matching_input_offset_span) {
-            debug!("Skipping match due to inline ignore directive");
-            continue;
-        }
+        // With `--show-suppressed` the match is kept (hidden) so the report can
+        // say which directive hid it; otherwise it is dropped here.
+        let suppressed_by = match inline_ignore_config.directive_for(
+            blob_bytes,
+            &matching_input_offset_span,
+            rule.id(),
+        ) {
+            Some(directive) if inline_ignore_config.keeps_suppressed() => {
+                debug!("Suppressing match for rule {} due to inline ignore directive", rule.id());
+                Some(directive.suppression_reason())
+            }
+            Some(_) => {
+                debug!("Skipping match for rule {} due to inline ignore directive", rule.id());
+                continue;
+            }
+            None => None,
+        };
         if let Some(validation) = rule.syntax.validation.as_ref() {
             match validation {
                 Validation::MongoDB => {
@@ -740,6 +773,32 @@ fn filter_match<'b>(
                         continue;
                     }
                 }
+                Validation::Redis => {
+                    let Ok(uri) = std::str::from_utf8(matching_input.as_bytes()) else {
+                        debug!("Skipping match for rule {} due to non-UTF8 Redis URI", rule.id());
+                        continue;
+                    };
+                    if !is_parseable_redis_uri(uri) {
+                        debug!("Skipping match for rule {} due to invalid Redis URI", rule.id());
+                        continue;
+                    }
+                }
+                Validation::Memcached => {
+                    let Ok(uri) = std::str::from_utf8(matching_input.as_bytes()) else {
+                        debug!(
+                            "Skipping match for rule {} due to non-UTF8 Memcached URI",
+                            rule.id()
+                        );
+                        continue;
+                    };
+                    if !is_parseable_memcached_uri(uri) {
+                        debug!(
+                            "Skipping match for rule {} due to invalid Memcached URI",
+                            rule.id()
+                        );
+                        continue;
+                    }
+                }
                 _ => {}
             }
         }
@@ -772,6 +831,7 @@ fn filter_match<'b>(
             validation_success: false,
             calculated_entropy,
             is_base64,
+            suppressed_by,
         });
     }
     if let Some(t) = timer.take() {
@@ -1006,12 +1066,25 @@ pub struct Match {
     /// Validation Success
     pub validation_success: bool,
 
+    /// Identity fields extracted from a successful validation response
+    #[serde(default, skip_serializing_if = "ValidationIdentity::is_empty")]
+    pub validation_identity: ValidationIdentity,
+
+    /// Files outside the match's own blob that supplied `depends_on_rule`
+    /// values, keyed by dependency variable
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub dependency_sources: BTreeMap<String, String>,
+
     /// Validation Success
     pub calculated_entropy: f32,
 
     pub visible: bool,
     #[serde(default)]
     pub is_base64: bool,
+
+    /// Why the match was hidden, when a suppression mechanism hid it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub suppressed_by: Option<String>,
 }
 impl Match {
     #[inline]
@@ -1053,7 +1126,8 @@ impl Match {
         // matching_snippet
         Match {
             rule: owned_blob_match.rule.clone(),
-            visible: owned_blob_match.rule.visible().to_owned(),
+            visible: owned_blob_match.rule.visible().to_owned()
+                && owned_blob_match.suppressed_by.is_none(),
             location: Location::with_source_span(offset_span, Some(source_span.clone())),
             groups: owned_blob_match.captures.clone(),
             blob_id: owned_blob_match.blob_id,
@@ -1061,8 +1135,11 @@ impl Match {
             validation_response_body: owned_blob_match.validation_response_body.clone(),
             validation_response_status: owned_blob_match.validation_response_status.as_u16(),
             validation_success: owned_blob_match.validation_success,
+            validation_identity: owned_blob_match.validation_identity.clone(),
+            dependency_sources: BTreeMap::new(),
             calculated_entropy: owned_blob_match.calculated_entropy,
             is_base64: owned_blob_match.is_base64,
+            suppressed_by: owned_blob_match.suppressed_by.clone(),
         }
     }
 
@@ -1208,6 +1285,8 @@ mod test {
                 references: vec![],
                 validation: None::<Validation>,          // no HTTP validation needed
                 depends_on_rule: vec![],
+                extract: Default::default(),
+                revocation: None,
                 pattern_requirements: None,
             });
 
@@ -1260,6 +1339,7 @@ mod test {
             negative_examples: vec![],
             references: vec![],
             validation: Some(Validation::Http(HttpValidation {
+                steps: vec![],
                 request: HttpRequest {
                     method: "GET".to_string(),
                     url: "https://example.com".to_string(),
@@ -1268,6 +1348,7 @@ mod test {
                     response_matcher: Some(vec![]),
                     multipart: None,
                     response_is_html: false,
+                    allowed_hosts: vec![],
                 },
                 multipart: None,
             })),
@@ -1281,6 +1362,8 @@ mod test {
                     variable: "domain".to_string(),
                 }),
             ],
+            extract: Default::default(),
+            revocation: None,
             pattern_requirements: None,
         })];
         let rules_db = RulesDatabase::from_rules(rules)?;
@@ -1323,6 +1406,8 @@ mod test {
             references: vec![],
             validation: None,
             depends_on_rule: vec![],
+            extract: Default::default(),
+            revocation: None,
             pattern_requirements: Some(PatternRequirements {
                 min_digits: None,
                 min_uppercase: None,
@@ -1386,6 +1471,8 @@ mod test {
             references: vec![],
             validation: None,
             depends_on_rule: vec![],
+            extract: Default::default(),
+            revocation: None,
             pattern_requirements: Some(PatternRequirements {
                 min_digits: None,
                 min_uppercase: None,
@@ -1509,6 +1596,8 @@ mod test {
             references: vec![],
             validation: None::<Validation>,
             depends_on_rule: vec![],
+            extract: Default::default(),
+            revocation: None,
             pattern_requirements: None,
         });
 
@@ -1548,6 +1637,8 @@ mod test {
             references: vec![],
             validation: None::<Validation>,
             depends_on_rule: vec![],
+            extract: Default::default(),
+            revocation: None,
             pattern_requirements: None,
         });
         let rules_db = RulesDatabase::from_rules(vec![rule])?;
@@ -1567,6 +1658,47 @@ mod test {
         Ok(())
     }
 
+    #[test]
+    fn inline_suppressed_match_is_kept_hidden_on_request() -> Result<()> {
+        let rule = Rule::new(RuleSyntax {
+            id: "inline.keep".into(),
+            name: "inline keep".into(),
+            pattern: "secret_token".into(),
+            confidence: crate::rules::rule::Confidence::Low,
+            min_entropy: 0.0,
+            visible: true,
+            examples: vec![],
+            negative_examples: vec![],
+            references: vec![],
+            validation: None::<Validation>,
+            depends_on_rule: vec![],
+            extract: Default::default(),
+            revocation: None,
+            pattern_requirements: None,
+        });
+        let rules_db = RulesDatabase::from_rules(vec![rule])?;
+        let seen = BlobIdMap::new();
+        let scanner_pool = Arc::new(ScannerPool::new(Arc::new(rules_db.vsdb.clone())));
+        let mut matcher =
+            Matcher::new(&rules_db, scanner_pool, &seen, None, false, None, &[], false, true)?
+                .keep_inline_suppressions(true);
+
+        let blob = Blob::from_bytes(
+            b"let key = \"secret_token\" # kingfisher:ignore reason=\"fixture\"".to_vec(),
+        );
+        let origin = OriginSet::from(Origin::from_file(PathBuf::from("inline.txt")));
+
+        match matcher.scan_blob(&blob, &origin, None, false, false, false)? {
+            ScanResult::New(matches) => {
+                assert_eq!(matches.len(), 1);
+                assert_eq!(matches[0].suppressed_by.as_deref(), Some("inline directive (fixture)"));
+            }
+            _ => panic!("unexpected scan result"),
+        }
+
+        Ok(())
+    }
+
     #[test]
     fn inline_comment_after_multiline_secret_skips_match() -> Result<()> {
         let rule = Rule::new(RuleSyntax {
@@ -1581,6 +1713,8 @@ mod test {
             references: vec![],
             validation: None::<Validation>,
             depends_on_rule: vec![],
+            extract: Default::default(),
+            revocation: None,
             pattern_requirements: None,
         });
         let rules_db = RulesDatabase::from_rules(vec![rule])?;
@@ -1622,6 +1756,8 @@ line2
             references: vec![],
             validation: None::<Validation>,
             depends_on_rule: vec![],
+            extract: Default::default(),
+            revocation: None,
             pattern_requirements: None,
         });
         let rules_db = RulesDatabase::from_rules(vec![rule])?;
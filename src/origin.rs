@@ -120,6 +120,28 @@ impl Origin {
             Self::Extended(e) => e.path().map(PathBuf::from),
         }
     }
+
+    /// Get the path for the blob relative to whichever of `roots` contains it,
+    /// using forward slashes. Git blobs are already relative to their repository.
+    pub fn relative_path(&self, roots: &[PathBuf]) -> Option<String> {
+        if let Self::GitRepo(e) = self {
+            return e.first_commit.as_ref().map(|c| c.blob_path.replace('\\', "/"));
+        }
+        let full = self.full_path()?;
+        let rel = roots
+            .iter()
+            .find_map(|root| {
+                // A root that is the file itself leaves nothing to strip; keep its name.
+                let stripped = full.strip_prefix(root).ok()?;
+                if stripped.as_os_str().is_empty() {
+                    full.file_name().map(Path::new)
+                } else {
+                    Some(stripped)
+                }
+            })
+            .unwrap_or(full.as_path());
+        Some(rel.to_string_lossy().replace('\\', "/"))
+    }
 }
 impl std::fmt::Display for Origin {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
@@ -0,0 +1,549 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use anyhow::{bail, Context, Result};
+use clap::ValueEnum;
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use serde::Deserialize;
+use tracing::debug;
+
+use crate::{
+    cli::commands::{
+        output::ReportOutputFormat,
+        scan::{ConfidenceLevel, ScanArgs},
+        validation_dependency::DependencyScope,
+    },
+    findings_store::FindingsStore,
+    matcher::Match,
+    origin::Origin,
+    rules::rule::Confidence,
+};
+
+/// File names probed, in order, at the root of each scanned path.
+pub const CONFIG_FILE_NAMES: &[&str] = &[".kingfisher.toml", ".kingfisher.yml", ".kingfisher.yaml"];
+
+/// Project-level scan policy loaded from `.kingfisher.toml` or `.kingfisher.yml`.
+///
+/// Keys mirror the long flag names of `kingfisher scan`.
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+pub struct ProjectConfig {
+    // RuleSpecifierArgs
+    pub rules_path: Vec<PathBuf>,
+    pub rule: Vec<String>,
+    pub load_builtins: Option<bool>,
+
+    // ScanArgs
+    pub confidence: Option<String>,
+    pub min_entropy: Option<f32>,
+    pub no_validate: Option<bool>,
+    pub validation_timeout: Option<u64>,
+    pub validation_retries: Option<u32>,
+    pub validation_rps: Option<f64>,
+    pub validation_budget: Option<u64>,
+    pub validation_allow_host: Vec<String>,
+    pub validation_deny_host: Vec<String>,
+    pub validation_side_effect_free: Option<bool>,
+    pub dependency_scope: Option<String>,
+    pub only_valid: Option<bool>,
+    pub no_dedup: Option<bool>,
+    pub redact: Option<bool>,
+    pub no_base64: Option<bool>,
+    pub baseline_file: Option<PathBuf>,
+    pub allowlist: Option<PathBuf>,
+    pub skip_regex: Vec<String>,
+    pub skip_word: Vec<String>,
+    pub skip_aws_account: Vec<String>,
+    pub ignore_comment: Vec<String>,
+    pub no_ignore: Option<bool>,
+
+    // ContentFilteringArgs
+    pub exclude: Vec<String>,
+    pub max_file_size: Option<f64>,
+    pub no_extract_archives: Option<bool>,
+    pub extraction_depth: Option<u8>,
+    pub no_binary: Option<bool>,
+
+    // OutputArgs
+    pub format: Option<String>,
+    pub output: Option<PathBuf>,
+
+    /// Settings that only apply beneath a given subdirectory
+    pub overrides: Vec<PathOverride>,
+}
+
+/// A per-subdirectory override block.
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+pub struct PathOverride {
+    /// Directory (or glob) relative to the config file's directory
+    pub path: String,
+    /// Additional exclude globs, relative to `path`
+    pub exclude: Vec<String>,
+    /// Minimum confidence for findings under `path`
+    pub confidence: Option<String>,
+    /// Rule ids (or id prefixes) to suppress under `path`
+    pub skip_rule: Vec<String>,
+    /// Skipwords that suppress findings under `path` (case-insensitive)
+    pub skip_word: Vec<String>,
+    /// Directory of the config file the override came from
+    #[serde(skip)]
+    pub base_dir: PathBuf,
+}
+
+/// Locate the project config: an explicit `--config` path, or the first
+/// well-known file name found at the root of a scanned path.
+pub fn discover(explicit: Option<&Path>, roots: &[PathBuf]) -> Result<Option<PathBuf>> {
+    if let Some(path) = explicit {
+        if !path.is_file() {
+            bail!("Config file does not exist: {}", path.display());
+        }
+        return Ok(Some(path.to_path_buf()));
+    }
+
+    for root in roots {
+        let dir = if root.is_dir() {
+            root.as_path()
+        } else {
+            match root.parent() {
+                Some(parent) if root.is_file() => parent,
+                _ => continue,
+            }
+        };
+        for name in CONFIG_FILE_NAMES {
+            let candidate = dir.join(name);
+            if candidate.is_file() {
+                return Ok(Some(candidate));
+            }
+        }
+    }
+    Ok(None)
+}
+
+pub fn load(path: &Path) -> Result<ProjectConfig> {
+    let data = fs::read_to_string(path)
+        .with_context(|| format!("Failed to read config file {}", path.display()))?;
+    let is_toml =
+        path.extension().and_then(|e| e.to_str()).is_some_and(|e| e.eq_ignore_ascii_case("toml"));
+    let config = if is_toml {
+        toml::from_str(&data)
+            .with_context(|| format!("Failed to parse TOML config {}", path.display()))?
+    } else {
+        serde_yaml::from_str(&data)
+            .with_context(|| format!("Failed to parse YAML config {}", path.display()))?
+    };
+    Ok(config)
+}
+
+/// Discover and load the project config for `args`, folding it into the
+/// parsed CLI arguments.
+pub fn apply_project_config(args: &mut ScanArgs) -> Result<()> {
+    let roots: Vec<PathBuf> = args
+        .input_specifier_args
+        .path_inputs
+        .iter()
+        .filter(|p| p.as_path() != Path::new("-"))
+        .cloned()
+        .collect();
+    let Some(path) = discover(args.config.as_deref(), &roots)? else {
+        return Ok(());
+    };
+    debug!("Loading project config from {}", path.display());
+    let config = load(&path)?;
+    let base_dir = path.parent().map(Path::to_path_buf).unwrap_or_default();
+    merge_into(config, &base_dir, args)
+}
+
+/// Merge `config` into `args`. Repeatable flags are extended, while single-valued
+/// flags are only taken from the file when they were not given on the command line.
+/// Relative `rules-path`, `baseline-file`, `allowlist` and `output` entries, and override
+/// paths, resolve against `base_dir`.
+pub fn merge_into(config: ProjectConfig, base_dir: &Path, args: &mut ScanArgs) -> Result<()> {
+    let resolve = |p: PathBuf| if p.is_relative() { base_dir.join(p) } else { p };
+    let explicit = args.explicit_args.clone();
+    let from_config = |id: &str| !explicit.contains(id);
+
+    // Rules
+    extend_unique(&mut args.rules.rules_path, config.rules_path.into_iter().map(resolve).collect());
+    if !config.rule.is_empty() && from_config("rule") {
+        args.rules.rule = config.rule;
+    }
+    if let Some(load_builtins) = config.load_builtins {
+        if from_config("load_builtins") {
+            args.rules.load_builtins = load_builtins;
+        }
+    }
+
+    // Scan behaviour
+    if let Some(confidence) = config.confidence {
+        let confidence = parse_confidence(&confidence)?;
+        if from_config("confidence") {
+            args.confidence = confidence;
+        }
+    }
+    if args.min_entropy.is_none() {
+        args.min_entropy = config.min_entropy;
+    }
+    if let Some(timeout) = config.validation_timeout {
+        if !(1..=60).contains(&timeout) {
+            bail!("validation-timeout must be between 1 and 60 seconds, got {timeout}");
+        }
+        if from_config("validation_timeout") {
+            args.validation_timeout = timeout;
+        }
+    }
+    if let Some(retries) = config.validation_retries {
+        if retries > 5 {
+            bail!("validation-retries must be between 0 and 5, got {retries}");
+        }
+        if from_config("validation_retries") {
+            args.validation_retries = retries;
+        }
+    }
+    if let Some(rps) = config.validation_rps {
+        if !rps.is_finite() || rps < 0.0 {
+            bail!("validation-rps must be a non-negative number, got {rps}");
+        }
+        if from_config("requests_per_second") {
+            args.validation_rate.requests_per_second = rps;
+        }
+    }
+    if args.validation_rate.budget.is_none() {
+        args.validation_rate.budget = config.validation_budget;
+    }
+    extend_unique(&mut args.validation_egress.allow_hosts, config.validation_allow_host);
+    extend_unique(&mut args.validation_egress.deny_hosts, config.validation_deny_host);
+    merge_flag(
+        &mut args.validation_egress.side_effect_free,
+        config.validation_side_effect_free,
+        from_config("side_effect_free"),
+    );
+    if let Some(scope) = config.dependency_scope {
+        let scope = DependencyScope::from_str(&scope, true)
+            .map_err(|e| anyhow::anyhow!("Invalid dependency-scope '{scope}' in config: {e}"))?;
+        if from_config("scope") {
+            args.validation_dependency.scope = scope;
+        }
+    }
+    merge_flag(&mut args.no_validate, config.no_validate, from_config("no_validate"));
+    merge_flag(&mut args.only_valid, config.only_valid, from_config("only_valid"));
+    merge_flag(&mut args.no_dedup, config.no_dedup, from_config("no_dedup"));
+    merge_flag(&mut args.redact, config.redact, from_config("redact"));
+    merge_flag(&mut args.no_base64, config.no_base64, from_config("no_base64"));
+    merge_flag(&mut args.no_inline_ignore, config.no_ignore, from_config("no_inline_ignore"));
+    if args.baseline_file.is_none() {
+        args.baseline_file = config.baseline_file.map(resolve);
+    }
+    if args.allowlist.is_none() {
+        args.allowlist = config.allowlist.map(resolve);
+    }
+    extend_unique(&mut args.skip_regex, config.skip_regex);
+    extend_unique(&mut args.skip_word, config.skip_word);
+    extend_unique(&mut args.skip_aws_account, config.skip_aws_account);
+    extend_unique(&mut args.extra_ignore_comments, config.ignore_comment);
+
+    // Content filtering
+    let filtering = &mut args.content_filtering_args;
+    extend_unique(&mut filtering.exclude, config.exclude);
+    if let Some(max_size) = config.max_file_size {
+        if from_config("max_file_size_mb") {
+            filtering.max_file_size_mb = max_size;
+        }
+    }
+    if let Some(depth) = config.extraction_depth {
+        if !(1..=25).contains(&depth) {
+            bail!("extraction-depth must be between 1 and 25, got {depth}");
+        }
+        if from_config("extraction_depth") {
+            filtering.extraction_depth = depth;
+        }
+    }
+    merge_flag(
+        &mut filtering.no_extract_archives,
+        config.no_extract_archives,
+        from_config("no_extract_archives"),
+    );
+    merge_flag(&mut filtering.no_binary, config.no_binary, from_config("no_binary"));
+
+    // Output
+    if let Some(format) = config.format {
+        let format = ReportOutputFormat::from_str(&format, true)
+            .map_err(|e| anyhow::anyhow!("Invalid format '{format}' in config: {e}"))?;
+        if from_config("format") {
+            args.output_args.format = format;
+        }
+    }
+    if args.output_args.output.is_none() {
+        args.output_args.output = config.output.map(resolve);
+    }
+
+    // Per-subdirectory overrides
+    for ov in &config.overrides {
+        if ov.path.trim().is_empty() {
+            bail!("Every [[overrides]] entry in the config file needs a `path`");
+        }
+        if let Some(confidence) = &ov.confidence {
+            parse_confidence(confidence)?;
+        }
+        let anchor = override_anchor(base_dir, &ov.path);
+        for pattern in &ov.exclude {
+            let pattern = format!("{anchor}/{}", pattern.trim_start_matches('/'));
+            filtering.exclude.push(format!("{pattern}/**"));
+            filtering.exclude.push(pattern);
+        }
+    }
+    args.path_overrides.extend(
+        config
+            .overrides
+            .into_iter()
+            .map(|ov| PathOverride { base_dir: base_dir.to_path_buf(), ..ov }),
+    );
+
+    Ok(())
+}
+
+/// Take a boolean flag from the config, in either direction, unless it was
+/// given on the command line.
+fn merge_flag(flag: &mut bool, value: Option<bool>, from_config: bool) {
+    if let (Some(value), true) = (value, from_config) {
+        *flag = value;
+    }
+}
+
+/// Glob prefix for an override `path`, anchored to the config file's directory
+/// so that it does not match same-named directories elsewhere in the tree.
+fn override_anchor(base_dir: &Path, path: &str) -> String {
+    let base = path.trim_matches('/');
+    let dir = base_dir.to_string_lossy().replace('\\', "/");
+    let dir = dir.trim_end_matches('/');
+    if dir.is_empty() {
+        base.to_string()
+    } else {
+        format!("{}/{base}", globset::escape(dir))
+    }
+}
+
+fn extend_unique<T: PartialEq>(target: &mut Vec<T>, values: Vec<T>) {
+    for value in values {
+        if !target.contains(&value) {
+            target.push(value);
+        }
+    }
+}
+
+fn parse_confidence(value: &str) -> Result<ConfidenceLevel> {
+    ConfidenceLevel::from_str(value, true)
+        .map_err(|e| anyhow::anyhow!("Invalid confidence '{value}' in config: {e}"))
+}
+
+struct CompiledOverride {
+    base_dir: PathBuf,
+    paths: GlobSet,
+    min_confidence: Option<Confidence>,
+    skip_rules: Vec<String>,
+    skip_words: Vec<String>,
+}
+
+impl CompiledOverride {
+    fn new(ov: &PathOverride) -> Result<Self> {
+        let base = ov.path.trim_matches('/');
+        let mut builder = GlobSetBuilder::new();
+        builder.add(Glob::new(base)?);
+        builder.add(Glob::new(&format!("{base}/**"))?);
+        let min_confidence = match &ov.confidence {
+            Some(value) => Some(Confidence::from(parse_confidence(value)?)),
+            None => None,
+        };
+        Ok(Self {
+            base_dir: ov.base_dir.clone(),
+            paths: builder.build()?,
+            min_confidence,
+            skip_rules: ov.skip_rule.iter().map(|r| r.to_lowercase()).collect(),
+            skip_words: ov.skip_word.iter().map(|w| w.to_lowercase()).collect(),
+        })
+    }
+
+    /// Path of `origin` relative to the config directory, the same base the
+    /// override's excludes are anchored to. Blobs from repositories outside
+    /// that directory fall back to their path within the repository.
+    fn relative_path(&self, origin: &Origin, roots: &[PathBuf]) -> Option<String> {
+        let full = origin.full_path()?;
+        match full.strip_prefix(&self.base_dir) {
+            Ok(rel) => Some(rel.to_string_lossy().replace('\\', "/")),
+            Err(_) if matches!(origin, Origin::GitRepo(_)) => origin.relative_path(roots),
+            Err(_) => None,
+        }
+    }
+
+    fn suppresses(&self, m: &Match) -> bool {
+        if self.min_confidence.is_some_and(|min| m.rule.confidence() < min) {
+            return true;
+        }
+        let rule_id = m.rule.id().to_lowercase();
+        if self.skip_rules.iter().any(|r| rule_id.starts_with(r.as_str())) {
+            return true;
+        }
+        !self.skip_words.is_empty()
+            && m.groups.captures.iter().any(|c| {
+                let value = c.raw_value().to_lowercase();
+                self.skip_words.iter().any(|w| value.contains(w.as_str()))
+            })
+    }
+}
+
+/// Hide findings suppressed by per-subdirectory `overrides` blocks.
+pub fn apply_path_overrides(
+    store: &mut FindingsStore,
+    overrides: &[PathOverride],
+    roots: &[PathBuf],
+) -> Result<()> {
+    if overrides.is_empty() {
+        return Ok(());
+    }
+    let compiled = overrides.iter().map(CompiledOverride::new).collect::<Result<Vec<_>>>()?;
+
+    for arc_msg in store.get_matches_mut() {
+        let (origin, _blob, m) = Arc::make_mut(arc_msg);
+        if !m.visible {
+            continue;
+        }
+        let hit = compiled.iter().find_map(|ov| {
+            let rel = origin.iter().find_map(|o| ov.relative_path(o, roots))?;
+            (ov.paths.is_match(&rel) && ov.suppresses(m)).then_some(rel)
+        });
+        if let Some(rel) = hit {
+            debug!("Skipping {} ({}) due to config override", rel, m.rule.id());
+            m.visible = false;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn discover_prefers_explicit_path() -> Result<()> {
+        let dir = tempdir()?;
+        fs::write(dir.path().join(".kingfisher.toml"), "")?;
+        let explicit = dir.path().join("custom.yml");
+        fs::write(&explicit, "")?;
+
+        let found = discover(Some(&explicit), &[dir.path().to_path_buf()])?;
+        assert_eq!(found, Some(explicit));
+        Ok(())
+    }
+
+    #[test]
+    fn discover_finds_file_at_scan_root() -> Result<()> {
+        let dir = tempdir()?;
+        assert_eq!(discover(None, &[dir.path().to_path_buf()])?, None);
+
+        fs::write(dir.path().join(".kingfisher.yml"), "")?;
+        let found = discover(None, &[dir.path().to_path_buf()])?;
+        assert_eq!(found, Some(dir.path().join(".kingfisher.yml")));
+        Ok(())
+    }
+
+    #[test]
+    fn discover_rejects_missing_explicit_path() {
+        let dir = tempdir().unwrap();
+        let missing = dir.path().join("nope.toml");
+        assert!(discover(Some(&missing), &[]).is_err());
+    }
+
+    #[test]
+    fn load_parses_toml_and_yaml() -> Result<()> {
+        let dir = tempdir()?;
+        let toml_path = dir.path().join(".kingfisher.toml");
+        fs::write(
+            &toml_path,
+            r#"
+confidence = "high"
+skip-word = ["example"]
+
+[[overrides]]
+path = "vendor"
+skip-rule = ["kingfisher.aws"]
+"#,
+        )?;
+        let config = load(&toml_path)?;
+        assert_eq!(config.confidence.as_deref(), Some("high"));
+        assert_eq!(config.skip_word, vec!["example"]);
+        assert_eq!(config.overrides.len(), 1);
+        assert_eq!(config.overrides[0].skip_rule, vec!["kingfisher.aws"]);
+
+        let yaml_path = dir.path().join(".kingfisher.yml");
+        fs::write(&yaml_path, "exclude:\n  - \"*.lock\"\nvalidation-timeout: 20\n")?;
+        let config = load(&yaml_path)?;
+        assert_eq!(config.exclude, vec!["*.lock"]);
+        assert_eq!(config.validation_timeout, Some(20));
+        Ok(())
+    }
+
+    #[test]
+    fn load_rejects_unknown_keys() -> Result<()> {
+        let dir = tempdir()?;
+        let path = dir.path().join(".kingfisher.toml");
+        fs::write(&path, "skip-words = [\"typo\"]\n")?;
+        assert!(load(&path).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn config_flags_can_turn_options_off() {
+        let mut flag = true;
+        merge_flag(&mut flag, Some(false), true);
+        assert!(!flag);
+
+        let mut flag = true;
+        merge_flag(&mut flag, Some(false), false);
+        assert!(flag, "a flag given on the command line wins");
+    }
+
+    #[test]
+    fn override_excludes_are_anchored_to_the_config_directory() -> Result<()> {
+        let anchor = override_anchor(Path::new("/repo"), "/vendor/");
+        assert_eq!(anchor, "/repo/vendor");
+
+        let mut builder = GlobSetBuilder::new();
+        builder.add(Glob::new(&format!("{anchor}/fixtures"))?);
+        let globs = builder.build()?;
+        assert!(globs.is_match("/repo/vendor/fixtures"));
+        assert!(!globs.is_match("/repo/src/vendor/fixtures"));
+        assert_eq!(override_anchor(Path::new("/a[1]"), "x"), "/a[[]1[]]/x");
+        Ok(())
+    }
+
+    #[test]
+    fn override_globs_cover_subtree() -> Result<()> {
+        let ov = CompiledOverride::new(&PathOverride {
+            path: "vendor/".to_string(),
+            ..Default::default()
+        })?;
+        assert!(ov.paths.is_match("vendor"));
+        assert!(ov.paths.is_match("vendor/lib/creds.txt"));
+        assert!(!ov.paths.is_match("src/vendor.rs"));
+        Ok(())
+    }
+
+    #[test]
+    fn override_paths_are_relative_to_the_config_directory() -> Result<()> {
+        let ov = CompiledOverride::new(&PathOverride {
+            path: "vendor".to_string(),
+            base_dir: PathBuf::from("/scan/app"),
+            ..Default::default()
+        })?;
+        let roots = [PathBuf::from("/scan")];
+        let inside = Origin::from_file(PathBuf::from("/scan/app/vendor/creds.txt"));
+        assert_eq!(ov.relative_path(&inside, &roots).as_deref(), Some("vendor/creds.txt"));
+        let outside = Origin::from_file(PathBuf::from("/scan/vendor/creds.txt"));
+        assert_eq!(ov.relative_path(&outside, &roots), None);
+        Ok(())
+    }
+}
@@ -8,11 +8,13 @@ use anyhow::Result;
 use http::StatusCode;
 use percent_encoding::{utf8_percent_encode, AsciiSet, CONTROLS};
 use schemars::JsonSchema;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use url::Url;
 
 use crate::{
-    access_map::{AccessSummary, AccessTokenDetails, ProviderMetadata, ResourceExposure},
+    access_map::{
+        AccessMapResult, AccessSummary, AccessTokenDetails, ProviderMetadata, ResourceExposure,
+    },
     blob::BlobMetadata,
     bstring_escape::Escaped,
     cli,
@@ -21,7 +23,8 @@ use crate::{
     matcher::{compute_finding_fingerprint, Match},
     origin::{Origin, OriginSet},
     rules::rule::Confidence,
-    validation_body::{self, ValidationResponseBody},
+    util::redaction_enabled,
+    validation_body::{self, ValidationIdentity, ValidationResponseBody},
 };
 mod bson_format;
 mod json_format;
@@ -310,6 +313,12 @@ impl DetailsReporter {
 
     fn docker_display_path(&self, path: &std::path::Path) -> Option<String> {
         let ds = self.datastore.lock().ok()?;
+        // Files unpacked from a layer are shown at their path in the image.
+        for (dir, layer) in ds.docker_layers().iter() {
+            if let Ok(rel) = path.strip_prefix(dir) {
+                return Some(format!("{} | {}", layer.image, rel.display()));
+            }
+        }
         for (dir, image) in ds.docker_images().iter() {
             if path.starts_with(dir) {
                 let rel = path.strip_prefix(dir).ok()?;
@@ -323,6 +332,38 @@ impl DetailsReporter {
         None
     }
 
+    /// Image and layer attribution for a file saved from a Docker image.
+    fn docker_metadata(&self, path: &std::path::Path) -> Option<DockerMetadata> {
+        let ds = self.datastore.lock().ok()?;
+        for (dir, layer) in ds.docker_layers().iter() {
+            if let Ok(rel) = path.strip_prefix(dir) {
+                let rel = rel.to_string_lossy().replace('\\', "/");
+                // Files extracted from an archive in the layer (`lib.tar!inner`)
+                // survive only as long as the archive itself does.
+                let outer = rel.split_once('!').map_or(rel.as_str(), |(outer, _)| outer);
+                let hidden = layer.hidden.contains(&rel) || layer.hidden.contains(outer);
+                return Some(DockerMetadata {
+                    image: layer.image.clone(),
+                    layer_index: Some(layer.index),
+                    layer_digest: Some(layer.digest.clone()),
+                    created_by: layer.created_by.clone(),
+                    present_in_final_image: Some(!hidden),
+                    tags: layer.tags.clone(),
+                });
+            }
+        }
+        ds.docker_images().iter().find(|(dir, _)| path.starts_with(dir)).map(|(_, image)| {
+            DockerMetadata {
+                image: image.clone(),
+                layer_index: None,
+                layer_digest: None,
+                created_by: None,
+                present_in_final_image: None,
+                tags: Vec::new(),
+            }
+        })
+    }
+
     fn process_matches(&self, only_valid: bool, filter_visible: bool) -> Result<Vec<ReportMatch>> {
         let datastore = self.datastore.lock().unwrap();
         Ok(datastore
@@ -404,7 +445,14 @@ impl DetailsReporter {
     }
 
     fn matches_for_output(&self, args: &cli::commands::scan::ScanArgs) -> Result<Vec<ReportMatch>> {
-        let mut matches = self.get_filtered_matches()?;
+        let mut matches = if args.show_suppressed {
+            self.get_unfiltered_matches(None)?
+                .into_iter()
+                .filter(|rm| rm.visible || rm.m.suppressed_by.is_some())
+                .collect()
+        } else {
+            self.get_filtered_matches()?
+        };
         if !args.no_dedup {
             matches = self.deduplicate_matches(matches, args.no_dedup);
         }
@@ -476,19 +524,28 @@ impl DetailsReporter {
         };
         // --- END FIX ---
 
-        let validation_status = if rm.validation_success {
-            "Active Credential".to_string()
-        } else if rm.validation_response_status == StatusCode::CONTINUE.as_u16() {
-            "Not Attempted".to_string()
+        // The snippet is escaped for display; keep the exact values so
+        // `kingfisher validate` can re-run the validator with them.
+        let captures = if redaction_enabled() {
+            Vec::new()
         } else {
-            "Inactive Credential".to_string()
+            rm.m.groups
+                .captures
+                .iter()
+                .map(|c| ReportCapture {
+                    name: c.name.map(str::to_string),
+                    match_number: c.match_number,
+                    value: c.raw_value().to_string(),
+                })
+                .collect()
         };
 
-        const MAX_RESPONSE_LENGTH: usize = 512;
-        let validation_body = validation_body::as_str(&rm.validation_response_body);
-        let truncated_body: String = validation_body.chars().take(MAX_RESPONSE_LENGTH).collect();
-        let ellipsis = if validation_body.len() > MAX_RESPONSE_LENGTH { "..." } else { "" };
-        let response_body = format!("{}{}", truncated_body, ellipsis);
+        let validation = ValidationInfo::from_result(
+            rm.validation_success,
+            rm.validation_response_status,
+            &rm.validation_response_body,
+            &rm.m.validation_identity,
+        );
 
         let git_metadata_val = rm
             .origin
@@ -502,6 +559,11 @@ impl DetailsReporter {
             })
             .next();
 
+        let docker_metadata = rm.origin.iter().find_map(|origin| match origin {
+            Origin::File(e) => self.docker_metadata(&e.path),
+            _ => None,
+        });
+
         let file_path = rm
             .origin
             .iter()
@@ -524,10 +586,12 @@ impl DetailsReporter {
             },
             finding: FindingRecordData {
                 snippet,
+                captures,
                 fingerprint: rm.m.finding_fingerprint.to_string(),
                 confidence: rm.m.rule.confidence().to_string(),
                 entropy: format!("{:.2}", rm.m.calculated_entropy),
-                validation: ValidationInfo { status: validation_status, response: response_body },
+                validation,
+                dependency_sources: rm.m.dependency_sources.clone(),
                 language: rm
                     .blob_metadata
                     .language
@@ -539,6 +603,8 @@ impl DetailsReporter {
                 path: file_path,
                 encoding: if rm.m.is_base64 { Some("base64".to_string()) } else { None },
                 git_metadata: git_metadata_val,
+                docker_metadata,
+                suppressed_by: rm.m.suppressed_by.clone(),
             },
         }
     }
@@ -628,42 +694,7 @@ impl DetailsReporter {
             return None;
         }
 
-        let mut entries = Vec::new();
-        for result in raw_results {
-            let account = summarize_account(&result.identity);
-            let mut grouped: BTreeMap<Vec<String>, Vec<String>> = BTreeMap::new();
-
-            if result.resources.is_empty() {
-                grouped.insert(Vec::new(), vec![result.identity.id.clone()]);
-            } else {
-                for resource in &result.resources {
-                    let resource_name = format_resource(resource);
-                    let permissions = normalize_permissions(&result.cloud, &resource.permissions);
-                    grouped.entry(permissions).or_default().push(resource_name);
-                }
-            }
-
-            let mut groups: Vec<AccessMapResourceGroup> = grouped
-                .into_iter()
-                .map(|(permissions, mut resources)| {
-                    resources.sort();
-                    AccessMapResourceGroup { resources, permissions }
-                })
-                .collect();
-
-            groups.sort_by(|a, b| a.resources.cmp(&b.resources));
-
-            entries.push(AccessMapEntry {
-                provider: result.cloud.clone(),
-                account: account.clone(),
-                groups,
-                token_details: result.token_details.clone(),
-                provider_metadata: result.provider_metadata.clone(),
-                fingerprint: result.fingerprint.clone(),
-            });
-        }
-
-        Some(entries)
+        Some(access_map_entries(raw_results))
     }
 
     fn style_finding_heading<D>(&self, val: D) -> StyledObject<D> {
@@ -697,6 +728,46 @@ impl DetailsReporter {
     }
 }
 
+/// Group access-map results by permission set for report output.
+pub(crate) fn access_map_entries(results: &[AccessMapResult]) -> Vec<AccessMapEntry> {
+    let mut entries = Vec::new();
+    for result in results {
+        let account = summarize_account(&result.identity);
+        let mut grouped: BTreeMap<Vec<String>, Vec<String>> = BTreeMap::new();
+
+        if result.resources.is_empty() {
+            grouped.insert(Vec::new(), vec![result.identity.id.clone()]);
+        } else {
+            for resource in &result.resources {
+                let resource_name = format_resource(resource);
+                let permissions = normalize_permissions(&result.cloud, &resource.permissions);
+                grouped.entry(permissions).or_default().push(resource_name);
+            }
+        }
+
+        let mut groups: Vec<AccessMapResourceGroup> = grouped
+            .into_iter()
+            .map(|(permissions, mut resources)| {
+                resources.sort();
+                AccessMapResourceGroup { resources, permissions }
+            })
+            .collect();
+
+        groups.sort_by(|a, b| a.resources.cmp(&b.resources));
+
+        entries.push(AccessMapEntry {
+            provider: result.cloud.clone(),
+            account: account.clone(),
+            groups,
+            token_details: result.token_details.clone(),
+            provider_metadata: result.provider_metadata.clone(),
+            fingerprint: result.fingerprint.clone(),
+        });
+    }
+
+    entries
+}
+
 fn normalize_permissions(cloud: &str, permissions: &[String]) -> Vec<String> {
     if cloud.eq_ignore_ascii_case("aws") {
         return Vec::new();
@@ -805,7 +876,7 @@ pub struct ReportMatch {
     pub validation_success: bool,
 }
 
-#[derive(Serialize, JsonSchema, Clone, Debug)]
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Debug)]
 pub struct FindingReporterRecord {
     pub rule: RuleMetadata,
     pub finding: FindingRecordData,
@@ -839,25 +910,65 @@ pub struct ReportEnvelope {
     pub access_map: Option<Vec<AccessMapEntry>>,
 }
 
-#[derive(Serialize, JsonSchema, Clone, Debug)]
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Debug)]
 pub struct RuleMetadata {
     pub name: String,
     pub id: String,
 }
 
-#[derive(Serialize, JsonSchema, Clone, Debug)]
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Debug)]
 pub struct ValidationInfo {
     pub status: String,
     pub response: String,
+    /// Identity fields the rule's `extract` block pulled from the response.
+    #[serde(default, skip_serializing_if = "ValidationIdentity::is_empty")]
+    pub identity: ValidationIdentity,
 }
 
-#[derive(Serialize, JsonSchema, Clone, Debug)]
+impl ValidationInfo {
+    /// Summarize a validation outcome the way it appears in reports, truncating
+    /// long response bodies.
+    pub fn from_result(
+        success: bool,
+        status: u16,
+        body: &ValidationResponseBody,
+        identity: &ValidationIdentity,
+    ) -> Self {
+        let status = if success {
+            "Active Credential".to_string()
+        } else if status == StatusCode::CONTINUE.as_u16() {
+            "Not Attempted".to_string()
+        } else {
+            "Inactive Credential".to_string()
+        };
+
+        const MAX_RESPONSE_LENGTH: usize = 512;
+        let body = validation_body::as_str(body);
+        let truncated_body: String = body.chars().take(MAX_RESPONSE_LENGTH).collect();
+        let ellipsis = if body.len() > MAX_RESPONSE_LENGTH { "..." } else { "" };
+        ValidationInfo {
+            status,
+            response: format!("{}{}", truncated_body, ellipsis),
+            identity: identity.clone(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Debug)]
 pub struct FindingRecordData {
     pub snippet: String,
+    /// The unescaped capture values, used to re-validate the finding. Omitted
+    /// when secrets are redacted.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub captures: Vec<ReportCapture>,
     pub fingerprint: String,
     pub confidence: String,
     pub entropy: String,
     pub validation: ValidationInfo,
+    /// Files other than `path` that supplied the values this finding's rule
+    /// depends on, keyed by dependency variable
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub dependency_sources: BTreeMap<String, String>,
     pub language: String,
     pub line: u32,
     pub column_start: u32,
@@ -867,6 +978,40 @@ pub struct FindingRecordData {
     pub encoding: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub git_metadata: Option<serde_json::Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub docker_metadata: Option<DockerMetadata>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub suppressed_by: Option<String>,
+}
+
+/// A capture group value exactly as the rule matched it.
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Debug, PartialEq, Eq)]
+pub struct ReportCapture {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    pub match_number: i32,
+    pub value: String,
+}
+
+/// Where in a Docker image a finding was made. The layer fields are absent
+/// for the image config and history.
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Debug, PartialEq, Eq)]
+pub struct DockerMetadata {
+    pub image: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub layer_index: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub layer_digest: Option<String>,
+    /// The Dockerfile instruction that created the layer
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub created_by: Option<String>,
+    /// Whether the file is still in the final filesystem, i.e. not deleted
+    /// (whited out) or replaced by a later layer
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub present_in_final_image: Option<bool>,
+    /// Every tag containing the layer, for registry scans
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
 }
 
 #[cfg(test)]
@@ -877,6 +1022,12 @@ mod tests {
         cli::commands::inputs::{ContentFilteringArgs, InputSpecifierArgs},
         cli::commands::output::OutputArgs,
         cli::commands::scan::{ConfidenceLevel, ScanArgs},
+        cli::commands::validation_cache::ValidationCacheArgs,
+        cli::commands::validation_dependency::ValidationDependencyArgs,
+        cli::commands::validation_dry_run::ValidationDryRunArgs,
+        cli::commands::validation_egress::ValidationEgressArgs,
+        cli::commands::validation_rate::ValidationRateArgs,
+        cli::commands::validation_ssh::ValidationSshArgs,
         cli::commands::{
             azure::AzureRepoType,
             bitbucket::{BitbucketAuthArgs, BitbucketRepoType},
@@ -890,6 +1041,7 @@ mod tests {
         matcher::{SerializableCapture, SerializableCaptures},
         origin::{Origin, OriginSet},
         rules::rule::{Confidence, Rule, RuleSyntax},
+        scanner::DockerLayer,
     };
     use gix::{date::Time, ObjectId};
     use smallvec::SmallVec;
@@ -961,10 +1113,15 @@ mod tests {
                 gcs_prefix: None,
                 gcs_service_account: None,
                 docker_image: Vec::new(),
+                registry_repository: Vec::new(),
+                all_registry_repositories: false,
                 git_clone: GitCloneMode::Bare,
                 git_history: GitHistoryMode::Full,
                 commit_metadata: true,
                 repo_artifacts: false,
+                ci_logs: false,
+                ci_logs_max_age_days: 30,
+                ci_logs_max_runs: 20,
                 scan_nested_repos: true,
                 since_commit: None,
                 branch: None,
@@ -1002,6 +1159,17 @@ mod tests {
             no_ignore_if_contains: false,
             validation_timeout: 10,
             validation_retries: 1,
+            config: None,
+            path_overrides: Vec::new(),
+            explicit_args: Default::default(),
+            allowlist: None,
+            show_suppressed: false,
+            validation_cache: ValidationCacheArgs::default(),
+            validation_rate: ValidationRateArgs::default(),
+            validation_egress: ValidationEgressArgs::default(),
+            validation_dry_run: ValidationDryRunArgs::default(),
+            validation_dependency: ValidationDependencyArgs::default(),
+            validation_ssh: ValidationSshArgs::default(),
         }
     }
 
@@ -1035,6 +1203,8 @@ mod tests {
             references: vec![],
             validation: None,
             depends_on_rule: vec![],
+            extract: Default::default(),
+            revocation: None,
             pattern_requirements: None,
         }));
 
@@ -1065,9 +1235,12 @@ mod tests {
                 validation_response_body: validation_body_stored.clone(),
                 validation_response_status: validation_status,
                 validation_success,
+                validation_identity: Default::default(),
+                dependency_sources: Default::default(),
                 calculated_entropy: 5.29,
                 visible: true,
                 is_base64: false,
+                suppressed_by: None,
             },
             comment: None,
             match_confidence: Confidence::Medium,
@@ -1127,6 +1300,67 @@ mod tests {
         );
     }
 
+    #[test]
+    fn docker_layer_findings_carry_image_attribution() {
+        let temp = tempdir().unwrap();
+        let datastore =
+            Arc::new(Mutex::new(findings_store::FindingsStore::new(temp.path().to_path_buf())));
+        let image_dir = temp.path().join("docker_example");
+        let layer_dir = image_dir.join("layers").join("001-bbbb");
+        {
+            let mut ds = datastore.lock().unwrap();
+            ds.register_docker_image(image_dir.clone(), "example:1".into());
+            ds.register_docker_layer(
+                layer_dir.clone(),
+                DockerLayer {
+                    image: "example:1".into(),
+                    index: 1,
+                    digest: "sha256:bbbb".into(),
+                    created_by: Some("COPY .env /app/.env".into()),
+                    hidden: ["app/.env".to_string(), "app/lib.tar".to_string()]
+                        .into_iter()
+                        .collect(),
+                    tags: Vec::new(),
+                },
+            );
+        }
+        let reporter = DetailsReporter { datastore, styles: Styles::new(false), only_valid: false };
+        let scan_args = sample_scan_args();
+        let (mut report_match, _) = sample_report_match("", StatusCode::CONTINUE.as_u16(), false);
+
+        report_match.origin =
+            OriginSet::new(Origin::from_file(layer_dir.join("app").join(".env")), vec![]);
+        let record = reporter.build_finding_record(&report_match, &scan_args);
+        assert_eq!(record.finding.path, "example:1 | app/.env");
+        let docker = record.finding.docker_metadata.unwrap();
+        assert_eq!(docker.layer_index, Some(1));
+        assert_eq!(docker.layer_digest.as_deref(), Some("sha256:bbbb"));
+        assert_eq!(docker.created_by.as_deref(), Some("COPY .env /app/.env"));
+        assert_eq!(docker.present_in_final_image, Some(false));
+
+        // A file inside an archive the final image no longer contains.
+        report_match.origin = OriginSet::new(
+            Origin::from_file(layer_dir.join("app").join("lib.tar!config/.env")),
+            vec![],
+        );
+        let record = reporter.build_finding_record(&report_match, &scan_args);
+        let docker = record.finding.docker_metadata.unwrap();
+        assert_eq!(docker.present_in_final_image, Some(false));
+        report_match.origin = OriginSet::new(
+            Origin::from_file(layer_dir.join("app").join("kept.tar!config/.env")),
+            vec![],
+        );
+        let record = reporter.build_finding_record(&report_match, &scan_args);
+        assert_eq!(record.finding.docker_metadata.unwrap().present_in_final_image, Some(true));
+
+        report_match.origin =
+            OriginSet::new(Origin::from_file(image_dir.join("image-config.json")), vec![]);
+        let record = reporter.build_finding_record(&report_match, &scan_args);
+        assert_eq!(record.finding.path, "example:1 | image-config.json");
+        let docker = record.finding.docker_metadata.unwrap();
+        assert_eq!((docker.image.as_str(), docker.layer_index), ("example:1", None));
+    }
+
     use super::build_git_urls;
 
     #[test]
@@ -40,6 +40,12 @@ mod tests {
     use crate::cli::commands::github::GitCloneMode;
     use crate::cli::commands::github::GitHistoryMode;
     use crate::cli::commands::rules::RuleSpecifierArgs;
+    use crate::cli::commands::validation_cache::ValidationCacheArgs;
+    use crate::cli::commands::validation_dependency::ValidationDependencyArgs;
+    use crate::cli::commands::validation_dry_run::ValidationDryRunArgs;
+    use crate::cli::commands::validation_egress::ValidationEgressArgs;
+    use crate::cli::commands::validation_rate::ValidationRateArgs;
+    use crate::cli::commands::validation_ssh::ValidationSshArgs;
     use crate::matcher::{SerializableCapture, SerializableCaptures};
     use crate::rules::rule::{Confidence, Rule, RuleSyntax};
     use crate::util::intern;
@@ -157,11 +163,16 @@ mod tests {
                 gcs_service_account: None,
 
                 docker_image: Vec::new(),
+                registry_repository: Vec::new(),
+                all_registry_repositories: false,
                 // clone / history options
                 git_clone: GitCloneMode::Bare,
                 git_history: GitHistoryMode::Full,
                 commit_metadata: true,
                 repo_artifacts: false,
+                ci_logs: false,
+                ci_logs_max_age_days: 30,
+                ci_logs_max_runs: 20,
                 scan_nested_repos: true,
                 since_commit: None,
                 branch: None,
@@ -197,6 +208,17 @@ mod tests {
             no_ignore_if_contains: false,
             validation_timeout: 10,
             validation_retries: 1,
+            config: None,
+            path_overrides: Vec::new(),
+            explicit_args: Default::default(),
+            allowlist: None,
+            show_suppressed: false,
+            validation_cache: ValidationCacheArgs::default(),
+            validation_rate: ValidationRateArgs::default(),
+            validation_egress: ValidationEgressArgs::default(),
+            validation_dry_run: ValidationDryRunArgs::default(),
+            validation_dependency: ValidationDependencyArgs::default(),
+            validation_ssh: ValidationSshArgs::default(),
         }
     }
 
@@ -213,6 +235,8 @@ mod tests {
             references: vec![],
             validation: None,
             depends_on_rule: vec![],
+            extract: Default::default(),
+            revocation: None,
             pattern_requirements: None,
         };
         let rule = Arc::new(Rule::new(syntax));
@@ -239,9 +263,12 @@ mod tests {
             validation_response_body: validation_body::from_string("validation response"),
             validation_response_status: 200,
             validation_success,
+            validation_identity: Default::default(),
+            dependency_sources: Default::default(),
             calculated_entropy: 4.5,
             visible: true,
             is_base64: false,
+            suppressed_by: None,
         }
     }
 
@@ -7,10 +7,19 @@ use super::*;
 impl DetailsReporter {
     pub fn pretty_format<W: std::io::Write>(
         &self,
-        mut writer: W,
+        writer: W,
         args: &cli::commands::scan::ScanArgs,
     ) -> Result<()> {
         let envelope = self.build_report_envelope(args)?;
+        self.pretty_format_envelope(writer, envelope)
+    }
+
+    /// Write already-built finding records in the pretty format.
+    pub fn pretty_format_envelope<W: std::io::Write>(
+        &self,
+        mut writer: W,
+        envelope: ReportEnvelope,
+    ) -> Result<()> {
         let num_findings = envelope.findings.len();
         for (index, record) in envelope.findings.iter().enumerate() {
             self.write_finding_record(&mut writer, record, index + 1, num_findings)?;
@@ -154,12 +163,50 @@ impl<'a> Display for PrettyFindingRecord<'a> {
         if !finding.validation.response.is_empty() {
             writeln!(f, " |__Response....: {}", style_fn(&finding.validation.response))?;
         }
+        if !finding.validation.identity.is_empty() {
+            let identity = finding
+                .validation
+                .identity
+                .iter()
+                .map(|(name, value)| format!("{name}={value}"))
+                .collect::<Vec<_>>()
+                .join(", ");
+            writeln!(f, " |__Identity....: {}", identity)?;
+        }
+        if !finding.dependency_sources.is_empty() {
+            let sources = finding
+                .dependency_sources
+                .iter()
+                .map(|(variable, path)| format!("{variable} from {path}"))
+                .collect::<Vec<_>>()
+                .join(", ");
+            writeln!(f, " |__Dependencies: {}", sources)?;
+        }
         writeln!(f, " |Language......: {}", finding.language)?;
         writeln!(f, " |Line Num......: {}", finding.line)?;
         writeln!(f, " |Path..........: {}", style_fn(&finding.path))?;
+        if let Some(reason) = &finding.suppressed_by {
+            writeln!(f, " |Suppressed....: {}", reason)?;
+        }
         if let Some(git) = &finding.git_metadata {
             reporter.write_git_metadata_value(f, git)?;
         }
+        if let Some(docker) = &finding.docker_metadata {
+            writeln!(f, " |Image.........: {}", reporter.style_metadata(&docker.image))?;
+            if let (Some(index), Some(digest)) = (docker.layer_index, &docker.layer_digest) {
+                writeln!(f, " |__Layer.......: {} ({})", index, digest)?;
+            }
+            if let Some(created_by) = &docker.created_by {
+                writeln!(f, " |__Instruction.: {}", created_by)?;
+            }
+            if let Some(present) = docker.present_in_final_image {
+                let state = if present { "present" } else { "removed by a later layer" };
+                writeln!(f, " |__Final Image.: {}", state)?;
+            }
+            if !docker.tags.is_empty() {
+                writeln!(f, " |__Tags........: {}", docker.tags.join(", "))?;
+            }
+        }
         Ok(())
     }
 }
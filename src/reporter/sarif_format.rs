@@ -34,9 +34,24 @@ impl DetailsReporter {
         let mut props = BTreeMap::new();
         props.insert("validation_status".to_string(), serde_json::json!(finding.validation.status));
         props.insert("entropy".to_string(), serde_json::json!(finding.entropy));
+        if !finding.validation.identity.is_empty() {
+            props.insert(
+                "validation_identity".to_string(),
+                serde_json::json!(finding.validation.identity),
+            );
+        }
+        if !finding.dependency_sources.is_empty() {
+            props.insert(
+                "dependency_sources".to_string(),
+                serde_json::json!(finding.dependency_sources),
+            );
+        }
         if let Some(git) = &finding.git_metadata {
             props.insert("git_metadata".to_string(), git.clone());
         }
+        if let Some(docker) = &finding.docker_metadata {
+            props.insert("docker_metadata".to_string(), serde_json::json!(docker));
+        }
         let properties =
             sarif::PropertyBagBuilder::default().additional_properties(props).build()?;
 
@@ -165,13 +180,16 @@ mod tests {
             rule: RuleMetadata { name: "test-rule".to_string(), id: "rule-1".to_string() },
             finding: FindingRecordData {
                 snippet: "secret".to_string(),
+                captures: Vec::new(),
                 fingerprint: "fingerprint".to_string(),
                 confidence: confidence.to_string(),
                 entropy: "0.0".to_string(),
                 validation: ValidationInfo {
                     status: "unknown".to_string(),
                     response: "n/a".to_string(),
+                    identity: Default::default(),
                 },
+                dependency_sources: Default::default(),
                 language: "Rust".to_string(),
                 line: 1,
                 column_start: 1,
@@ -179,6 +197,8 @@ mod tests {
                 path: "src/lib.rs".to_string(),
                 encoding: None,
                 git_metadata: None,
+                docker_metadata: None,
+                suppressed_by: None,
             },
         }
     }
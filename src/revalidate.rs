@@ -0,0 +1,780 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fs,
+    io::{Read, Write},
+    path::PathBuf,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use anyhow::{bail, Context, Result};
+use crossbeam_skiplist::SkipMap;
+use futures::{stream, StreamExt};
+use http::StatusCode;
+use indicatif::{ProgressBar, ProgressStyle};
+use rustc_hash::FxHashMap;
+use serde::Serialize;
+use serde_json::Value;
+use smallvec::SmallVec;
+use tracing::{info, warn};
+
+use crate::{
+    access_map,
+    blob::BlobId,
+    bstring_escape::Escaped,
+    cli::{commands::validate::ValidateArgs, global::GlobalArgs},
+    entropy::calculate_shannon_entropy,
+    findings_store::FindingsStore,
+    inline_ignore::INLINE_DIRECTIVE_SUPPRESSION,
+    liquid_filters::register_all,
+    location::OffsetSpan,
+    matcher::{
+        compute_finding_fingerprint, OwnedBlobMatch, SerializableCapture, SerializableCaptures,
+    },
+    reporter::{
+        access_map_entries, styles::Styles, DetailsReporter, FindingRecordData,
+        FindingReporterRecord, ReportEnvelope, RuleMetadata, ValidationInfo,
+    },
+    rule_loader::RuleLoader,
+    rules::rule::{Confidence, PatternRequirementContext, PatternValidationResult, Rule},
+    rules_database::RulesDatabase,
+    scanner::{maybe_record_access_map, AccessMapCollector},
+    util::intern,
+    validation::{
+        collect_variables_and_dependencies, dry_run, egress, jwt, rate_limit, set_persistent_cache,
+        ssh, validate_single_match, CachedResponse,
+    },
+    validation_body,
+    validation_cache::ValidationCache,
+};
+
+/// How the input report was laid out, so the updated report can be written
+/// back in the same shape.
+#[derive(Debug)]
+enum ReportLayout {
+    /// A `--format json` envelope; `findings` is replaced on output.
+    Json(Value),
+    /// `--format jsonl`; `None` marks the position of the next finding and
+    /// other lines (such as the access map) are passed through untouched.
+    Jsonl(Vec<Option<String>>),
+}
+
+#[derive(Debug)]
+pub(crate) struct ParsedReport {
+    pub(crate) findings: Vec<FindingReporterRecord>,
+    layout: ReportLayout,
+}
+
+/// A finding whose validation status differs from the input report.
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct ValidationChange {
+    pub rule_id: String,
+    pub path: String,
+    pub line: u32,
+    pub fingerprint: String,
+    pub previous_status: String,
+    pub current_status: String,
+}
+
+fn is_finding_record(value: &Value) -> bool {
+    value.get("rule").is_some() && value.get("finding").is_some()
+}
+
+pub(crate) fn parse_report(text: &str) -> Result<ParsedReport> {
+    if let Ok(mut envelope @ Value::Object(_)) = serde_json::from_str::<Value>(text) {
+        if let Some(findings) = envelope.get_mut("findings").map(Value::take) {
+            let findings = serde_json::from_value(findings).context("parse report findings")?;
+            return Ok(ParsedReport { findings, layout: ReportLayout::Json(envelope) });
+        }
+    }
+
+    let mut findings = Vec::new();
+    let mut lines = Vec::new();
+    for (idx, line) in text.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let value: Value = serde_json::from_str(line)
+            .with_context(|| format!("line {} is not valid JSON", idx + 1))?;
+        if is_finding_record(&value) {
+            findings.push(
+                serde_json::from_value(value)
+                    .with_context(|| format!("parse finding on line {}", idx + 1))?,
+            );
+            lines.push(None);
+        } else {
+            lines.push(Some(line.to_string()));
+        }
+    }
+    if findings.is_empty() && !lines.is_empty() {
+        bail!("Report does not contain any Kingfisher findings");
+    }
+    Ok(ParsedReport { findings, layout: ReportLayout::Jsonl(lines) })
+}
+
+fn render_report(report: &ParsedReport) -> Result<String> {
+    match &report.layout {
+        ReportLayout::Json(envelope) => {
+            let mut envelope = envelope.clone();
+            envelope["findings"] = serde_json::to_value(&report.findings)?;
+            Ok(format!("{}\n", serde_json::to_string_pretty(&envelope)?))
+        }
+        ReportLayout::Jsonl(lines) => {
+            let mut out = String::new();
+            let mut findings = report.findings.iter();
+            for line in lines {
+                match line {
+                    Some(other) => out.push_str(other),
+                    None => {
+                        let record = findings.next().expect("one finding per placeholder line");
+                        out.push_str(&serde_json::to_string(record)?);
+                    }
+                }
+                out.push('\n');
+            }
+            Ok(out)
+        }
+    }
+}
+
+/// Rebuild the captures a finding was validated with. Reports record the raw
+/// captures; older reports only carry the escaped snippet, which is used as the
+/// primary secret, named after the rule's first capture group.
+fn captures_for(record: &FindingReporterRecord, rule: &Rule) -> SerializableCaptures {
+    if record.finding.captures.is_empty() {
+        return primary_capture(rule, &record.finding.snippet);
+    }
+    let captures = record
+        .finding
+        .captures
+        .iter()
+        .map(|c| {
+            let value = intern(&c.value);
+            SerializableCapture {
+                name: c.name.as_deref().map(intern),
+                match_number: c.match_number,
+                start: 0,
+                end: value.len(),
+                value,
+            }
+        })
+        .collect();
+    SerializableCaptures { captures }
+}
+
+/// Whether the report holds the exact secret of `record`: its raw captures, or
+/// a snippet that display escaping left untouched.
+pub(crate) fn secret_recoverable(record: &FindingReporterRecord) -> bool {
+    let snippet = &record.finding.snippet;
+    !record.finding.captures.is_empty()
+        || (!snippet.starts_with("[REDACTED:")
+            && !snippet.contains("\\u{")
+            && !snippet.contains(char::REPLACEMENT_CHARACTER)
+            && Escaped(snippet.as_bytes()).to_string() == *snippet)
+}
+
+fn primary_capture(rule: &Rule, secret: &str) -> SerializableCaptures {
+    let name = rule
+        .syntax()
+        .as_regex()
+        .ok()
+        .and_then(|re| re.capture_names().nth(1).flatten().map(intern));
+    let value = intern(secret);
+    let mut captures = SmallVec::<[SerializableCapture; 2]>::new();
+    captures.push(SerializableCapture {
+        name,
+        match_number: if name.is_some() { 1 } else { 0 },
+        start: 0,
+        end: value.len(),
+        value,
+    });
+    SerializableCaptures { captures }
+}
+
+pub(crate) fn owned_match_for(record: &FindingReporterRecord, rule: Arc<Rule>) -> OwnedBlobMatch {
+    // Reports do not record byte offsets; line numbers keep dependent values
+    // from the same file ordered closely enough to pair them up.
+    let line = record.finding.line as usize;
+    OwnedBlobMatch {
+        captures: captures_for(record, &rule),
+        rule,
+        blob_id: BlobId::default(),
+        finding_fingerprint: record.finding.fingerprint.parse().unwrap_or_default(),
+        matching_input_offset_span: OffsetSpan::from_range(line..line),
+        validation_response_body: validation_body::from_string(String::new()),
+        validation_response_status: StatusCode::CONTINUE,
+        validation_success: false,
+        validation_identity: Default::default(),
+        calculated_entropy: record.finding.entropy.parse().unwrap_or_default(),
+        is_base64: record.finding.encoding.as_deref() == Some("base64"),
+        suppressed_by: record.finding.suppressed_by.clone(),
+    }
+}
+
+pub(crate) type Dependencies =
+    (FxHashMap<String, Vec<(String, OffsetSpan)>>, FxHashMap<String, Vec<String>>);
+
+/// Collect the dependency values for the findings at `indices`, all found in
+/// `path`. Dependent rules (e.g. an AWS secret key needing its access key id)
+/// draw their values from other findings in the same file, or from the files
+/// the scan recorded as the source of a dependency.
+pub(crate) fn dependencies_for(
+    findings: &[FindingReporterRecord],
+    rules_db: &RulesDatabase,
+    path: &str,
+    indices: &[usize],
+) -> Dependencies {
+    let sources: BTreeSet<&String> =
+        indices.iter().flat_map(|&idx| findings[idx].finding.dependency_sources.values()).collect();
+    let all_in_path: Vec<OwnedBlobMatch> = findings
+        .iter()
+        .filter_map(|r| rules_db.get_rule_by_text_id(&r.rule.id).map(|rule| (r, rule)))
+        .filter(|(r, rule)| {
+            r.finding.path == path
+                || (sources.contains(&r.finding.path) && rule.syntax().depends_on_rule.is_empty())
+        })
+        .filter(|(r, _)| secret_recoverable(r))
+        .map(|(r, rule)| owned_match_for(r, rule))
+        .collect();
+    collect_variables_and_dependencies(&all_in_path)
+}
+
+fn is_inline_suppressed(record: &FindingReporterRecord) -> bool {
+    record
+        .finding
+        .suppressed_by
+        .as_deref()
+        .is_some_and(|s| s.starts_with(INLINE_DIRECTIVE_SUPPRESSION))
+}
+
+/// Offer the secret of every finding in the report as a candidate HMAC key,
+/// as the scan did, so HS* tokens can be verified again.
+fn remember_hmac_candidates(findings: &[FindingReporterRecord]) {
+    for record in findings.iter().filter(|r| secret_recoverable(r) && !is_inline_suppressed(r)) {
+        let source = || format!("{} in {}", record.rule.id, record.finding.path);
+        if record.finding.captures.is_empty() {
+            jwt::remember_hmac_secret(&record.finding.snippet, source);
+        } else {
+            for capture in &record.finding.captures {
+                jwt::remember_hmac_secret(&capture.value, source);
+            }
+        }
+    }
+}
+
+/// Re-validate every finding whose rule is loaded and has a validator, updating
+/// the records in place and returning the findings whose status changed.
+async fn revalidate_findings(
+    findings: &mut [FindingReporterRecord],
+    rules_db: &RulesDatabase,
+    args: &ValidateArgs,
+    global_args: &GlobalArgs,
+) -> Result<Vec<ValidationChange>> {
+    let mut by_path: BTreeMap<String, Vec<usize>> = BTreeMap::new();
+    let (mut unknown_rule, mut redacted, mut unrecoverable, mut no_validator) =
+        (0usize, 0usize, 0usize, 0usize);
+    for (idx, record) in findings.iter().enumerate() {
+        let Some(rule) = rules_db.get_rule_by_text_id(&record.rule.id) else {
+            unknown_rule += 1;
+            continue;
+        };
+        if record.finding.snippet.starts_with("[REDACTED:") {
+            redacted += 1;
+        } else if !secret_recoverable(record) {
+            unrecoverable += 1;
+        } else if rule.syntax().validation.is_none() {
+            no_validator += 1;
+        } else {
+            by_path.entry(record.finding.path.clone()).or_default().push(idx);
+        }
+    }
+    if unknown_rule > 0 {
+        warn!("Skipped {unknown_rule} findings whose rule is not loaded; pass --rules-path for custom rules");
+    }
+    if redacted > 0 {
+        warn!("Skipped {redacted} findings with redacted secrets; re-validation needs an unredacted report");
+    }
+    if unrecoverable > 0 {
+        warn!(
+            "Cannot re-validate {unrecoverable} findings: the report predates raw captures and \
+             their snippets were escaped; re-run the scan to refresh the report"
+        );
+    }
+    if no_validator > 0 {
+        info!("Skipped {no_validator} findings whose rule has no validator");
+    }
+
+    remember_hmac_candidates(findings);
+
+    let mut jobs: Vec<(usize, OwnedBlobMatch, Arc<Dependencies>)> = Vec::new();
+    for (path, indices) in &by_path {
+        let deps = Arc::new(dependencies_for(findings, rules_db, path, indices));
+        for &idx in indices {
+            let rule = rules_db.get_rule_by_text_id(&findings[idx].rule.id).expect("rule resolved");
+            jobs.push((idx, owned_match_for(&findings[idx], rule), Arc::clone(&deps)));
+        }
+    }
+
+    let parser = register_all(liquid::ParserBuilder::with_stdlib()).build()?;
+    let client = reqwest::Client::builder()
+        .danger_accept_invalid_certs(global_args.ignore_certs)
+        .timeout(Duration::from_secs(30))
+        .redirect(egress::redirect_policy())
+        .build()?;
+    let cache: Arc<SkipMap<String, CachedResponse>> = Arc::new(SkipMap::new());
+    let timeout = Duration::from_secs(args.validation_timeout);
+
+    let pb = if global_args.use_progress() {
+        ProgressBar::new(jobs.len() as u64)
+    } else {
+        ProgressBar::hidden()
+    };
+    pb.set_style(
+        ProgressStyle::with_template(
+            "{spinner:.green} {msg} [{bar:40.green/blue}] {pos}/{len} [{elapsed_precise}]",
+        )?
+        .progress_chars("=>-"),
+    );
+    pb.set_message("Re-validating findings…");
+
+    let results: Vec<(usize, OwnedBlobMatch)> = stream::iter(jobs)
+        .map(|(idx, mut om, deps)| {
+            let (parser, client, cache, pb) = (&parser, &client, &cache, &pb);
+            async move {
+                validate_single_match(
+                    &mut om,
+                    parser,
+                    client,
+                    &deps.0,
+                    &deps.1,
+                    cache,
+                    timeout,
+                    args.validation_retries,
+                )
+                .await;
+                pb.inc(1);
+                (idx, om)
+            }
+        })
+        .buffer_unordered(args.num_jobs.max(1))
+        .collect()
+        .await;
+    pb.finish_and_clear();
+
+    let mut changes = Vec::new();
+    for (idx, om) in results {
+        let record = &mut findings[idx];
+        let updated = ValidationInfo::from_result(
+            om.validation_success,
+            om.validation_response_status.as_u16(),
+            &om.validation_response_body,
+            &om.validation_identity,
+        );
+        if updated.status != record.finding.validation.status {
+            changes.push(ValidationChange {
+                rule_id: record.rule.id.clone(),
+                path: record.finding.path.clone(),
+                line: record.finding.line,
+                fingerprint: record.finding.fingerprint.clone(),
+                previous_status: record.finding.validation.status.clone(),
+                current_status: updated.status.clone(),
+            });
+        }
+        record.finding.validation = updated;
+    }
+    changes.sort_by(|a, b| (&a.path, a.line, &a.rule_id).cmp(&(&b.path, b.line, &b.rule_id)));
+    Ok(changes)
+}
+
+/// Exit code mirroring `kingfisher scan`: 205 when an active credential
+/// remains, 200 for other findings and 0 when there is nothing to report.
+fn exit_code(findings: &[FindingReporterRecord]) -> i32 {
+    let reported: Vec<_> = findings.iter().filter(|r| !is_inline_suppressed(r)).collect();
+    if reported.iter().any(|r| r.finding.validation.status == "Active Credential") {
+        205
+    } else if reported.is_empty() {
+        0
+    } else {
+        200
+    }
+}
+
+fn install_persistent_cache(args: &ValidateArgs) -> Result<Option<Arc<ValidationCache>>> {
+    let cache = if args.validation_dry_run.enabled {
+        None
+    } else {
+        ValidationCache::from_args(&args.validation_cache)?.map(Arc::new)
+    };
+    set_persistent_cache(cache.clone());
+    Ok(cache)
+}
+
+/// Pick the rule a secret from stdin is validated against. An exact id wins
+/// over prefix matches; otherwise the selectors must resolve to a single rule.
+fn select_rule<'a>(rules: &[&'a Rule], selectors: &[String]) -> Result<&'a Rule> {
+    if let Some(rule) = rules.iter().find(|r| selectors.iter().any(|s| s == r.id())) {
+        return Ok(rule);
+    }
+    match rules {
+        [rule] => Ok(rule),
+        [] => bail!("No rule matches --rule {}", selectors.join(", ")),
+        _ => bail!(
+            "--rule {} selects {} rules; validating a secret from stdin needs a single rule id such as {}",
+            selectors.join(", "),
+            rules.len(),
+            rules[0].id()
+        ),
+    }
+}
+
+/// Capture the secret with the rule's own pattern so named groups and the
+/// checksum see the same values as during a scan. Returns the captures and,
+/// when the rule's pattern requirements reject the secret, why.
+fn capture_secret(rule: &Rule, secret: &str) -> Result<(SerializableCaptures, Option<String>)> {
+    let re = rule.syntax().as_regex()?;
+    let bytes = secret.as_bytes();
+    let Some(captures) = re.captures(bytes) else {
+        warn!(
+            "Secret does not match the pattern of rule {}; validating it as the rule's primary capture",
+            rule.id()
+        );
+        let outcome = rule.pattern_requirements().map(|reqs| reqs.validate(bytes, None, false));
+        return Ok((primary_capture(rule, secret), outcome.and_then(requirement_failure)));
+    };
+
+    let serialized = SerializableCaptures::from_captures(&captures, bytes, &re);
+    let outcome = rule.pattern_requirements().map(|reqs| {
+        let full_match = captures.get(0).expect("group 0 always participates").as_bytes();
+        // Same choice of bytes as the matcher: the full match when the pattern
+        // has named or multiple groups, otherwise the single captured secret.
+        let use_full_match = re.capture_names().any(|n| n.is_some()) || captures.len() > 2;
+        let checked = if use_full_match {
+            full_match
+        } else {
+            captures.get(1).map_or(full_match, |c| c.as_bytes())
+        };
+        let context = PatternRequirementContext { regex: &re, captures: &captures, full_match };
+        reqs.validate(checked, Some(context), false)
+    });
+    Ok((serialized, outcome.and_then(requirement_failure)))
+}
+
+fn requirement_failure(outcome: PatternValidationResult) -> Option<String> {
+    match outcome {
+        PatternValidationResult::Passed | PatternValidationResult::IgnoredBySubstring { .. } => {
+            None
+        }
+        PatternValidationResult::Failed => {
+            Some("Secret does not meet the rule's pattern requirements".to_string())
+        }
+        PatternValidationResult::FailedChecksum { .. } => {
+            Some("Secret failed the rule's checksum check".to_string())
+        }
+    }
+}
+
+/// Validate a single secret read from stdin and print the result in the
+/// pretty report format.
+async fn validate_secret(args: &ValidateArgs, global_args: &GlobalArgs) -> Result<i32> {
+    if args.diff_output.is_some() {
+        bail!("--diff-output only applies when re-validating a report");
+    }
+    let mut input = String::new();
+    std::io::stdin().read_to_string(&mut input).context("Failed to read secret from stdin")?;
+    let secret = input.trim();
+    if secret.is_empty() {
+        bail!("No secret provided on stdin");
+    }
+
+    let loaded = RuleLoader::from_rule_specifiers(&args.rules)
+        .load_with_confidence(Confidence::Low)
+        .context("Failed to load rules")?;
+    let rules = loaded.resolve_enabled_rules().context("Failed to resolve rules")?;
+    let rule = Arc::new(select_rule(&rules, &args.rules.rule)?.clone());
+
+    let (captures, rejected) = capture_secret(&rule, secret)?;
+    let primary = captures.captures.first().map_or(secret, |c| c.raw_value());
+
+    let mut dep_vars: FxHashMap<String, Vec<(String, OffsetSpan)>> = FxHashMap::default();
+    for (name, value) in &args.vars {
+        dep_vars
+            .entry(name.clone())
+            .or_default()
+            .push((value.clone(), OffsetSpan::from_range(0..0)));
+    }
+    let mut missing: FxHashMap<String, Vec<String>> = FxHashMap::default();
+    for dependency in rule.syntax().depends_on_rule.iter().flatten() {
+        let variable = dependency.variable.to_uppercase();
+        if !dep_vars.contains_key(&variable) {
+            warn!(
+                "Rule {} needs a value from {}; pass it with --var {variable}=<value>",
+                rule.id(),
+                dependency.rule_id
+            );
+            missing.entry(rule.id().to_string()).or_default().push(dependency.rule_id.clone());
+        }
+    }
+
+    let mut om = OwnedBlobMatch {
+        captures,
+        rule: Arc::clone(&rule),
+        blob_id: BlobId::default(),
+        finding_fingerprint: compute_finding_fingerprint(primary, "stdin", 0, secret.len() as u64),
+        matching_input_offset_span: OffsetSpan::from_range(0..secret.len()),
+        validation_response_body: validation_body::from_string(String::new()),
+        validation_response_status: StatusCode::CONTINUE,
+        validation_success: false,
+        validation_identity: Default::default(),
+        calculated_entropy: calculate_shannon_entropy(primary.as_bytes()),
+        is_base64: false,
+        suppressed_by: None,
+    };
+
+    if let Some(reason) = rejected {
+        om.validation_response_body = validation_body::from_string(reason);
+    } else if rule.syntax().validation.is_none() {
+        info!("Rule {} has no validator", rule.id());
+    } else {
+        let parser = register_all(liquid::ParserBuilder::with_stdlib()).build()?;
+        let client = reqwest::Client::builder()
+            .danger_accept_invalid_certs(global_args.ignore_certs)
+            .timeout(Duration::from_secs(30))
+            .redirect(egress::redirect_policy())
+            .build()?;
+        let cache: Arc<SkipMap<String, CachedResponse>> = Arc::new(SkipMap::new());
+        let persistent = install_persistent_cache(args)?;
+        validate_single_match(
+            &mut om,
+            &parser,
+            &client,
+            &dep_vars,
+            &missing,
+            &cache,
+            Duration::from_secs(args.validation_timeout),
+            args.validation_retries,
+        )
+        .await;
+        if let Some(persistent) = &persistent {
+            persistent.save_or_warn();
+        }
+        dry_run::finish()?;
+    }
+
+    let access_map = if args.access_map {
+        let collector = AccessMapCollector::default();
+        maybe_record_access_map(&om, Some(&collector));
+        let results = access_map::map_requests(collector.into_requests()).await;
+        (!results.is_empty()).then(|| access_map_entries(&results))
+    } else {
+        None
+    };
+
+    let record = FindingReporterRecord {
+        rule: RuleMetadata { name: rule.name().to_string(), id: rule.id().to_string() },
+        finding: FindingRecordData {
+            snippet: Escaped(primary.as_bytes()).to_string(),
+            captures: Vec::new(),
+            fingerprint: om.finding_fingerprint.to_string(),
+            confidence: rule.confidence().to_string(),
+            entropy: format!("{:.2}", om.calculated_entropy),
+            validation: ValidationInfo::from_result(
+                om.validation_success,
+                om.validation_response_status.as_u16(),
+                &om.validation_response_body,
+                &om.validation_identity,
+            ),
+            dependency_sources: Default::default(),
+            language: "Unknown".to_string(),
+            line: 1,
+            column_start: 0,
+            column_end: secret.len() as u32,
+            path: "-".to_string(),
+            encoding: None,
+            git_metadata: None,
+            docker_metadata: None,
+            suppressed_by: None,
+        },
+    };
+    let code = exit_code(std::slice::from_ref(&record));
+
+    let use_color = global_args.use_color(std::io::stdout()) && args.output.is_none();
+    let reporter = DetailsReporter {
+        datastore: Arc::new(Mutex::new(FindingsStore::new(PathBuf::new()))),
+        styles: Styles::new(use_color),
+        only_valid: false,
+    };
+    let envelope = ReportEnvelope { findings: vec![record], access_map };
+    match &args.output {
+        Some(path) => {
+            let file = fs::File::create(path)
+                .with_context(|| format!("Failed to write result to {}", path.display()))?;
+            reporter.pretty_format_envelope(file, envelope)?;
+        }
+        None => reporter.pretty_format_envelope(std::io::stdout().lock(), envelope)?,
+    }
+
+    Ok(code)
+}
+
+/// Run the `kingfisher validate` subcommand and return the process exit code.
+pub async fn run(args: ValidateArgs, global_args: &GlobalArgs) -> Result<i32> {
+    rate_limit::configure((&args.validation_rate).into());
+    egress::configure((&args.validation_egress).into());
+    dry_run::configure(&args.validation_dry_run);
+    ssh::configure(&args.validation_ssh);
+    if args.report.as_os_str() == "-" {
+        return validate_secret(&args, global_args).await;
+    }
+    let text = fs::read_to_string(&args.report)
+        .with_context(|| format!("Failed to read report at {}", args.report.display()))?;
+    let mut report = parse_report(&text)
+        .with_context(|| format!("Failed to parse report at {}", args.report.display()))?;
+
+    let loaded = RuleLoader::from_rule_specifiers(&args.rules)
+        .load_with_confidence(Confidence::Low)
+        .context("Failed to load rules")?;
+    let rules = loaded.resolve_enabled_rules().context("Failed to resolve rules")?;
+    let rules_db = RulesDatabase::from_rules(rules.into_iter().cloned().collect())
+        .context("Failed to compile rules")?;
+
+    let cache = install_persistent_cache(&args)?;
+    let changes = revalidate_findings(&mut report.findings, &rules_db, &args, global_args).await?;
+    if let Some(cache) = &cache {
+        cache.save_or_warn();
+    }
+    dry_run::finish()?;
+
+    let rendered = render_report(&report)?;
+    match &args.output {
+        Some(path) => fs::write(path, rendered)
+            .with_context(|| format!("Failed to write report to {}", path.display()))?,
+        None => std::io::stdout().write_all(rendered.as_bytes())?,
+    }
+
+    info!("{} of {} findings changed validation status", changes.len(), report.findings.len());
+    for change in &changes {
+        info!(
+            "{}:{} [{}] {} -> {}",
+            change.path, change.line, change.rule_id, change.previous_status, change.current_status
+        );
+    }
+    if let Some(path) = &args.diff_output {
+        fs::write(path, serde_json::to_string_pretty(&changes)?)
+            .with_context(|| format!("Failed to write diff to {}", path.display()))?;
+    }
+
+    Ok(exit_code(&report.findings))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{reporter::ReportCapture, rules::rule::RuleSyntax};
+
+    const RECORD: &str = r#"{"rule":{"name":"GitHub Personal Access Token","id":"kingfisher.github.1"},"finding":{"snippet":"ghp_example","fingerprint":"42","confidence":"medium","entropy":"4.20","validation":{"status":"Active Credential","response":"ok"},"language":"Unknown","line":3,"column_start":1,"column_end":12,"path":"config.py"}}"#;
+
+    #[test]
+    fn json_envelope_round_trips() -> Result<()> {
+        let text = format!(r#"{{"findings":[{RECORD}],"access_map":[]}}"#);
+        let mut report = parse_report(&text)?;
+        assert_eq!(report.findings.len(), 1);
+        report.findings[0].finding.validation.status = "Inactive Credential".into();
+
+        let rendered: Value = serde_json::from_str(&render_report(&report)?)?;
+        assert_eq!(
+            rendered["findings"][0]["finding"]["validation"]["status"],
+            "Inactive Credential"
+        );
+        assert!(rendered["access_map"].is_array());
+        Ok(())
+    }
+
+    #[test]
+    fn jsonl_keeps_non_finding_lines_in_place() -> Result<()> {
+        let text = format!("{RECORD}\n{{\"access_map\":[]}}\n{RECORD}\n");
+        let report = parse_report(&text)?;
+        assert_eq!(report.findings.len(), 2);
+
+        let rendered = render_report(&report)?;
+        let lines: Vec<&str> = rendered.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[1], "{\"access_map\":[]}");
+        assert!(lines[2].contains("kingfisher.github.1"));
+        Ok(())
+    }
+
+    #[test]
+    fn rejects_non_kingfisher_json() {
+        assert!(parse_report("{\"hello\":\"world\"}\n").is_err());
+        assert!(parse_report("not json").is_err());
+    }
+
+    #[test]
+    fn exit_code_follows_scan_conventions() -> Result<()> {
+        let mut report = parse_report(RECORD)?;
+        assert_eq!(exit_code(&report.findings), 205);
+        report.findings[0].finding.validation.status = "Inactive Credential".into();
+        assert_eq!(exit_code(&report.findings), 200);
+        report.findings[0].finding.suppressed_by = Some("inline directive".into());
+        assert_eq!(exit_code(&report.findings), 0);
+        Ok(())
+    }
+
+    #[test]
+    fn escaped_snippets_are_not_used_as_secrets() -> Result<()> {
+        let mut record = parse_report(RECORD)?.findings.remove(0);
+        assert!(secret_recoverable(&record));
+
+        record.finding.snippet = "ghp_ex\\u{7}ample".into();
+        assert!(!secret_recoverable(&record));
+
+        record.finding.captures = vec![ReportCapture {
+            name: Some("TOKEN".into()),
+            match_number: 1,
+            value: "ghp_ex\u{7}ample".into(),
+        }];
+        assert!(secret_recoverable(&record));
+        Ok(())
+    }
+
+    #[test]
+    fn raw_captures_take_precedence_over_the_snippet() -> Result<()> {
+        let rule = Rule::new(RuleSyntax {
+            id: "kingfisher.github.1".into(),
+            name: "GitHub Personal Access Token".into(),
+            pattern: "(?P<TOKEN>ghp_[a-z]+)".into(),
+            confidence: Confidence::Medium,
+            min_entropy: 0.0,
+            visible: true,
+            examples: vec![],
+            negative_examples: vec![],
+            references: vec![],
+            validation: None,
+            depends_on_rule: vec![],
+            extract: Default::default(),
+            revocation: None,
+            pattern_requirements: None,
+        });
+        let mut record = parse_report(RECORD)?.findings.remove(0);
+        record.finding.snippet = "ghp_ex\\u{1b}ample".into();
+        record.finding.captures = vec![
+            ReportCapture {
+                name: Some("TOKEN".into()),
+                match_number: 1,
+                value: "ghp_ex\u{1b}ample".into(),
+            },
+            ReportCapture {
+                name: Some("HOST".into()),
+                match_number: 2,
+                value: "example.com".into(),
+            },
+        ];
+
+        let captures = captures_for(&record, &rule).captures;
+        assert_eq!(captures.len(), 2);
+        assert_eq!(captures[0].name, Some("TOKEN"));
+        assert_eq!(captures[0].raw_value(), "ghp_ex\u{1b}ample");
+        assert_eq!(captures[1].raw_value(), "example.com");
+        Ok(())
+    }
+}
@@ -0,0 +1,267 @@
+//! `kingfisher revoke`: revoke confirmed secrets from a saved report through
+//! their rule's `revocation` block, after confirmation, recording every attempt
+//! in an append-only audit log.
+
+use std::{
+    fs::{self, OpenOptions},
+    io::{BufRead, IsTerminal, Write},
+    time::Duration,
+};
+
+use anyhow::{bail, Context, Result};
+use chrono::Utc;
+use http::StatusCode;
+use reqwest::Client;
+use serde::Serialize;
+use tracing::{info, warn};
+
+use crate::{
+    cli::{commands::revoke::RevokeArgs, global::GlobalArgs},
+    liquid_filters::register_all,
+    matcher::OwnedBlobMatch,
+    reporter::FindingReporterRecord,
+    revalidate::{
+        dependencies_for, owned_match_for, parse_report, secret_recoverable, Dependencies,
+    },
+    rule_loader::RuleLoader,
+    rules::rule::{Confidence, Revocation},
+    rules_database::RulesDatabase,
+    validation::{aws, captured_values_for, egress, send_http_sequence, utils},
+};
+
+/// The outcome of one revocation attempt.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RevocationOutcome {
+    pub revoked: bool,
+    pub status: StatusCode,
+    pub response: String,
+}
+
+impl RevocationOutcome {
+    fn failed(status: StatusCode, response: impl Into<String>) -> Self {
+        Self { revoked: false, status, response: response.into() }
+    }
+}
+
+/// One line of the audit log. The secret itself is never written.
+#[derive(Serialize, Debug)]
+struct AuditEntry<'a> {
+    timestamp: String,
+    rule_id: &'a str,
+    fingerprint: &'a str,
+    path: &'a str,
+    line: u32,
+    revoked: bool,
+    status: u16,
+    response: &'a str,
+}
+
+/// Revoke the secret of `om` through its rule's `revocation` block, using
+/// `deps` for the values the rule takes from `depends_on_rule` dependencies.
+pub async fn revoke_match(
+    om: &OwnedBlobMatch,
+    deps: &Dependencies,
+    parser: &liquid::Parser,
+    client: &Client,
+    timeout: Duration,
+) -> RevocationOutcome {
+    let rule = om.rule.syntax();
+    let Some(revocation) = &rule.revocation else {
+        return RevocationOutcome::failed(
+            StatusCode::NOT_IMPLEMENTED,
+            format!("Rule {} does not define a revocation", rule.id),
+        );
+    };
+    if let Some(missing) = deps.1.get(&rule.id).filter(|m| !m.is_empty()) {
+        return RevocationOutcome::failed(
+            StatusCode::PRECONDITION_REQUIRED,
+            format!("Revocation skipped - missing dependent rules: {}", missing.join(", ")),
+        );
+    }
+
+    let captured_values = captured_values_for(om, &deps.0);
+    match revocation {
+        // Revocation requests are not retried: a lost response must not turn
+        // into a second, possibly different, state change.
+        Revocation::Http(http) => {
+            let (revoked, status, response) = egress::with_redirect_hosts(
+                egress::http_hosts(http),
+                send_http_sequence(
+                    http,
+                    &rule.id,
+                    &rule.name,
+                    &captured_values,
+                    parser,
+                    client,
+                    timeout,
+                    0,
+                ),
+            )
+            .await;
+            RevocationOutcome { revoked, status, response }
+        }
+        Revocation::AWS => {
+            let secret = captured_values
+                .iter()
+                .find(|(n, ..)| n == "TOKEN")
+                .map(|(_, v, ..)| v.clone())
+                .unwrap_or_default();
+            let akid = utils::find_closest_variable(&captured_values, &secret, "TOKEN", "AKID")
+                .unwrap_or_default();
+            if akid.is_empty() || secret.is_empty() {
+                return RevocationOutcome::failed(
+                    StatusCode::BAD_REQUEST,
+                    "Missing AWS access-key ID or secret.",
+                );
+            }
+            match aws::deactivate_aws_access_key(&akid, &secret).await {
+                Ok((true, response)) => {
+                    RevocationOutcome { revoked: true, status: StatusCode::OK, response }
+                }
+                Ok((false, response)) => RevocationOutcome::failed(StatusCode::FORBIDDEN, response),
+                Err(e) => RevocationOutcome::failed(StatusCode::BAD_GATEWAY, format!("{e:#}")),
+            }
+        }
+    }
+}
+
+/// List what is about to be revoked and, unless `--yes` was given, ask on the
+/// terminal. Returns whether to go ahead.
+fn confirm(args: &RevokeArgs, targets: &[&FindingReporterRecord]) -> Result<bool> {
+    eprintln!("About to revoke {} credential(s):", targets.len());
+    for record in targets {
+        eprintln!(
+            "  [{}] {}:{} (fingerprint {})",
+            record.rule.id, record.finding.path, record.finding.line, record.finding.fingerprint
+        );
+    }
+    if args.yes {
+        return Ok(true);
+    }
+    if !std::io::stdin().is_terminal() {
+        bail!("Refusing to revoke without confirmation; pass --yes to revoke non-interactively");
+    }
+    eprint!("Revoke these credentials? This cannot be undone. [y/N] ");
+    std::io::stderr().flush()?;
+    let mut answer = String::new();
+    std::io::stdin().lock().read_line(&mut answer)?;
+    Ok(matches!(answer.trim().to_ascii_lowercase().as_str(), "y" | "yes"))
+}
+
+/// Run the `kingfisher revoke` subcommand and return the process exit code:
+/// 0 when every selected secret was revoked, 1 otherwise.
+pub async fn run(args: RevokeArgs, global_args: &GlobalArgs) -> Result<i32> {
+    let text = fs::read_to_string(&args.report)
+        .with_context(|| format!("Failed to read report at {}", args.report.display()))?;
+    let report = parse_report(&text)
+        .with_context(|| format!("Failed to parse report at {}", args.report.display()))?;
+    let findings = &report.findings;
+
+    let loaded = RuleLoader::from_rule_specifiers(&args.rules)
+        .load_with_confidence(Confidence::Low)
+        .context("Failed to load rules")?;
+    let rules = loaded.resolve_enabled_rules().context("Failed to resolve rules")?;
+    let rules_db = RulesDatabase::from_rules(rules.into_iter().cloned().collect())
+        .context("Failed to compile rules")?;
+
+    let mut targets: Vec<usize> = Vec::new();
+    for fingerprint in &args.fingerprints {
+        let Some(idx) = findings.iter().position(|r| &r.finding.fingerprint == fingerprint) else {
+            bail!("No finding with fingerprint {fingerprint} in {}", args.report.display());
+        };
+        let record = &findings[idx];
+        let Some(rule) = rules_db.get_rule_by_text_id(&record.rule.id) else {
+            bail!("Rule {} is not loaded; pass --rules-path for custom rules", record.rule.id);
+        };
+        if rule.syntax().revocation.is_none() {
+            bail!("Rule {} does not define how to revoke its secrets", record.rule.id);
+        }
+        if record.finding.snippet.starts_with("[REDACTED:") {
+            bail!("Finding {fingerprint} is redacted; revocation needs an unredacted report");
+        }
+        if !secret_recoverable(record) {
+            bail!(
+                "Finding {fingerprint}'s secret cannot be recovered from its escaped snippet; \
+                 re-run the scan to refresh the report"
+            );
+        }
+        if record.finding.validation.status != "Active Credential" {
+            warn!(
+                "Skipping finding {fingerprint}: it was not reported as an active credential; \
+                 re-validate it with `kingfisher validate` first"
+            );
+            continue;
+        }
+        if !targets.contains(&idx) {
+            targets.push(idx);
+        }
+    }
+    if targets.is_empty() {
+        info!("Nothing to revoke");
+        return Ok(0);
+    }
+    let selected: Vec<&FindingReporterRecord> = targets.iter().map(|&i| &findings[i]).collect();
+    if !confirm(&args, &selected)? {
+        info!("Revocation cancelled");
+        return Ok(1);
+    }
+
+    let parser = register_all(liquid::ParserBuilder::with_stdlib()).build()?;
+    let client = reqwest::Client::builder()
+        .danger_accept_invalid_certs(global_args.ignore_certs)
+        .timeout(Duration::from_secs(30))
+        .redirect(egress::redirect_policy())
+        .build()?;
+    let timeout = Duration::from_secs(args.revocation_timeout);
+    let mut audit = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&args.audit_log)
+        .with_context(|| format!("Failed to open audit log {}", args.audit_log.display()))?;
+
+    let mut failed = 0usize;
+    for idx in targets {
+        let record = &findings[idx];
+        let rule = rules_db.get_rule_by_text_id(&record.rule.id).expect("rule resolved");
+        let deps = dependencies_for(findings, &rules_db, &record.finding.path, &[idx]);
+        let om = owned_match_for(record, rule);
+        let outcome = revoke_match(&om, &deps, &parser, &client, timeout).await;
+
+        let entry = AuditEntry {
+            timestamp: Utc::now().to_rfc3339(),
+            rule_id: &record.rule.id,
+            fingerprint: &record.finding.fingerprint,
+            path: &record.finding.path,
+            line: record.finding.line,
+            revoked: outcome.revoked,
+            status: outcome.status.as_u16(),
+            response: &outcome.response,
+        };
+        writeln!(audit, "{}", serde_json::to_string(&entry)?)
+            .with_context(|| format!("Failed to write audit log {}", args.audit_log.display()))?;
+
+        if outcome.revoked {
+            println!(
+                "Revoked [{}] {}:{} (fingerprint {})",
+                record.rule.id,
+                record.finding.path,
+                record.finding.line,
+                record.finding.fingerprint
+            );
+        } else {
+            failed += 1;
+            println!(
+                "Failed to revoke [{}] {}:{} (fingerprint {}): {} {}",
+                record.rule.id,
+                record.finding.path,
+                record.finding.line,
+                record.finding.fingerprint,
+                outcome.status.as_u16(),
+                outcome.response
+            );
+        }
+    }
+    info!("Audit log written to {}", args.audit_log.display());
+
+    Ok(if failed == 0 { 0 } else { 1 })
+}
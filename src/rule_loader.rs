@@ -67,7 +67,11 @@ impl RuleLoader {
     }
 
     pub fn load(&self, args: &cli::commands::scan::ScanArgs) -> Result<LoadedRules> {
-        let confidence = Confidence::from(args.confidence);
+        self.load_with_confidence(Confidence::from(args.confidence))
+    }
+
+    /// Load rules at or above `confidence` without needing a full set of scan arguments.
+    pub fn load_with_confidence(&self, confidence: Confidence) -> Result<LoadedRules> {
         let mut id_to_rule: BTreeMap<String, Rule> = BTreeMap::new();
 
         if self.load_builtins {
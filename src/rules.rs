@@ -7,7 +7,9 @@ use tracing::{debug, debug_span, error};
 pub mod rule;
 use std::{collections::BTreeMap, fs::File, io::BufReader, path::Path};
 
-use rule::{Confidence, RuleSyntax, Validation};
+use rule::{
+    Confidence, Extractor, HttpValidation, ResponseMatcher, Revocation, RuleSyntax, Validation,
+};
 use serde::de::DeserializeOwned;
 
 #[derive(Debug, Error)]
@@ -29,6 +31,103 @@ pub enum RulesError {
 
     #[error("HTTP validation for rule `{rule_id}` in file {path} missing response_matcher")]
     MissingResponseMatcher { path: String, rule_id: String },
+
+    #[error(
+        "HTTP validation step {step} for rule `{rule_id}` in file {path} is invalid: {reason}"
+    )]
+    InvalidHttpStep { path: String, rule_id: String, step: usize, reason: String },
+
+    #[error("Identity extraction for rule `{rule_id}` in file {path} is invalid: {reason}")]
+    InvalidExtract { path: String, rule_id: String, reason: String },
+
+    #[error("JsonMatch for rule `{rule_id}` in file {path} is invalid: {reason}")]
+    InvalidJsonMatch { path: String, rule_id: String, reason: String },
+
+    #[error("Revocation for rule `{rule_id}` in file {path} is invalid: {reason}")]
+    InvalidRevocation { path: String, rule_id: String, reason: String },
+}
+
+/// Describe why `extractor` could never match, if it is malformed.
+fn extractor_error(name: &str, extractor: &Extractor) -> Option<String> {
+    match extractor {
+        Extractor::Regex(pattern) => {
+            regex::Regex::new(pattern).err().map(|e| format!("invalid regex for `{name}`: {e}"))
+        }
+        Extractor::JsonPointer(pointer) if !pointer.is_empty() && !pointer.starts_with('/') => {
+            Some(format!("JSON pointer for `{name}` must start with `/`"))
+        }
+        _ => None,
+    }
+}
+
+/// Reject multi-step HTTP validations that could never run: a step with a
+/// multipart body, or an extractor with an invalid regex or JSON pointer.
+fn check_http_steps(path: &Path, rule_id: &str, http_val: &HttpValidation) -> Result<()> {
+    for (index, step) in http_val.steps.iter().enumerate() {
+        let reason = if step.request.multipart.is_some() {
+            Some("multipart bodies are only supported on the final request".to_string())
+        } else {
+            step.extract.iter().find_map(|(name, extractor)| extractor_error(name, extractor))
+        };
+        if let Some(reason) = reason {
+            bail!(RulesError::InvalidHttpStep {
+                path: path.display().to_string(),
+                rule_id: rule_id.to_string(),
+                step: index + 1,
+                reason,
+            });
+        }
+    }
+    Ok(())
+}
+
+/// Reject `JsonMatch` response matchers with a malformed path, regex or bound.
+fn check_json_matchers(path: &Path, rule_id: &str, http_val: &HttpValidation) -> Result<()> {
+    let matchers =
+        http_val.requests().flat_map(|request| request.response_matcher.iter().flatten());
+    for matcher in matchers {
+        if let ResponseMatcher::JsonMatch(json_matcher) = matcher {
+            if let Err(reason) = crate::validation::json_match::check(json_matcher) {
+                bail!(RulesError::InvalidJsonMatch {
+                    path: path.display().to_string(),
+                    rule_id: rule_id.to_string(),
+                    reason,
+                });
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Reject a rule-level `extract` block containing a malformed extractor.
+fn check_extract(path: &Path, rule_syntax: &RuleSyntax) -> Result<()> {
+    if let Some(reason) =
+        rule_syntax.extract.iter().find_map(|(name, extractor)| extractor_error(name, extractor))
+    {
+        bail!(RulesError::InvalidExtract {
+            path: path.display().to_string(),
+            rule_id: rule_syntax.id.clone(),
+            reason,
+        });
+    }
+    Ok(())
+}
+
+/// Reject an HTTP `revocation` block that could never be sent: a multipart
+/// body, or a malformed step extractor or `JsonMatch`.
+fn check_revocation(path: &Path, rule_syntax: &RuleSyntax) -> Result<()> {
+    let Some(Revocation::Http(http_rev)) = &rule_syntax.revocation else {
+        return Ok(());
+    };
+    if http_rev.multipart.is_some() || http_rev.request.multipart.is_some() {
+        bail!(RulesError::InvalidRevocation {
+            path: path.display().to_string(),
+            rule_id: rule_syntax.id.clone(),
+            reason: "multipart bodies are not supported".to_string(),
+        });
+    }
+    check_http_steps(path, &rule_syntax.id, http_rev)?;
+    check_json_matchers(path, &rule_syntax.id, http_rev)
 }
 
 #[derive(Clone, Default)]
@@ -74,7 +173,11 @@ impl Rules {
                                     rule_id: rule_syntax.id.clone(),
                                 });
                             }
+                            check_http_steps(path, &rule_syntax.id, http_val)?;
+                            check_json_matchers(path, &rule_syntax.id, http_val)?;
                         }
+                        check_extract(path, &rule_syntax)?;
+                        check_revocation(path, &rule_syntax)?;
                         rules.rules.insert(rule_syntax.id.clone(), rule_syntax);
                     }
                 }
@@ -146,7 +249,11 @@ impl Rules {
                                 rule_id: rule_syntax.id.clone(),
                             });
                         }
+                        check_http_steps(path, &rule_syntax.id, http_val)?;
+                        check_json_matchers(path, &rule_syntax.id, http_val)?;
                     }
+                    check_extract(path, &rule_syntax)?;
+                    check_revocation(path, &rule_syntax)?;
                     rules.rules.insert(rule_syntax.id.clone(), rule_syntax);
                 }
                 debug!("Loaded {} rules from {}", rules.num_rules(), path.display());
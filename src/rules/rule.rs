@@ -49,11 +49,26 @@ pub enum Validation {
     MySQL,
     Postgres,
     Jdbc,
+    Redis,
+    Memcached,
+    Smtp,
+    Ldap,
+    Ssh,
     JWT,
     Raw(String),
     Http(HttpValidation),
 }
 
+/// How `kingfisher revoke` revokes a confirmed secret: HTTP request(s)
+/// templated exactly like an `Http` validation, or deactivating an AWS access
+/// key through IAM.
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash, Clone)]
+#[serde(tag = "type", content = "content")]
+pub enum Revocation {
+    AWS,
+    Http(HttpValidation),
+}
+
 /// Specifies that a rule depends on a variable from another rule.
 #[derive(Debug, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash, Clone)]
 pub struct DependsOnRule {
@@ -290,12 +305,50 @@ pub enum PatternValidationResult {
 
 /// Configuration for HTTP validation. This contains a request configuration
 /// and an optional multipart configuration.
+///
+/// `steps` are sent in order before `request` and may extract values from
+/// their responses into Liquid variables for the requests that follow (e.g. an
+/// OAuth token exchange before an identity call). `request` is always the final
+/// step, and its `response_matcher` decides validity.
 #[derive(Debug, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash, Clone)]
 pub struct HttpValidation {
+    #[serde(default)]
+    pub steps: Vec<HttpStep>,
     pub request: HttpRequest,
     pub multipart: Option<MultipartConfig>,
 }
 
+impl HttpValidation {
+    /// Every request this validation may send, in order.
+    pub fn requests(&self) -> impl Iterator<Item = &HttpRequest> {
+        self.steps.iter().map(|step| &step.request).chain(std::iter::once(&self.request))
+    }
+}
+
+/// A preliminary request in a multi-step HTTP validation.
+///
+/// The step fails validation when its `response_matcher` does not match, or,
+/// without a matcher, when the response status is not 2xx.
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash, Clone)]
+pub struct HttpStep {
+    pub request: HttpRequest,
+    /// Variables to set from this step's response, keyed by Liquid variable name.
+    #[serde(default)]
+    pub extract: BTreeMap<String, Extractor>,
+}
+
+/// Where a multi-step validation takes a value from in a step's response.
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash, Clone)]
+#[serde(rename_all = "snake_case")]
+pub enum Extractor {
+    /// JSON pointer (RFC 6901) into the response body, e.g. `/access_token`.
+    JsonPointer(String),
+    /// Response header name.
+    Header(String),
+    /// Regex applied to the response body; the first capture group is used when present.
+    Regex(String),
+}
+
 /// Configuration for an HTTP request used for validation.
 #[derive(Debug, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash, Clone)]
 pub struct HttpRequest {
@@ -312,6 +365,10 @@ pub struct HttpRequest {
     // allow HTML only when explicitly set true
     #[serde(default = "default_false")]
     pub response_is_html: bool,
+    /// Hosts the request may be sent to (`api.example.com`, `*.example.com` or `*`).
+    /// Required when the URL's host is templated.
+    #[serde(default)]
+    pub allowed_hosts: Vec<String>,
 }
 
 /// Configuration for multipart HTTP requests.
@@ -339,6 +396,58 @@ pub struct ReportResponseData {
     report_response: bool,
 }
 
+/// Checks a value selected from a JSON response body.
+///
+/// `path` is a JSON pointer (`/ok`) or a JSONPath expression (`$.ok`,
+/// `$.data[*].id`). The matcher passes when any selected value satisfies every
+/// condition given; with no condition it only requires the path to exist.
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash, Clone)]
+pub struct JsonMatcher {
+    pub r#type: String, // "JsonMatch"
+    pub path: String,
+    /// The selected value must equal this JSON value.
+    #[serde(default)]
+    pub equals: Option<JsonLiteral>,
+    /// The selected value (JSON text for non-strings) must match this regex.
+    #[serde(default)]
+    pub regex: Option<String>,
+    /// Inclusive lower bound for a numeric value.
+    #[serde(default)]
+    pub min: Option<JsonLiteral>,
+    /// Inclusive upper bound for a numeric value.
+    #[serde(default)]
+    pub max: Option<JsonLiteral>,
+    /// `false` passes only when the path selects nothing.
+    #[serde(default)]
+    pub exists: Option<bool>,
+    #[serde(default = "default_false")]
+    pub negative: bool, // true = "fail if the value *does* match"
+}
+
+/// A JSON value from a rule file. Ordered and hashed by its JSON text so the
+/// matchers that hold it stay usable as map keys.
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone)]
+#[serde(transparent)]
+pub struct JsonLiteral(pub serde_json::Value);
+
+impl PartialOrd for JsonLiteral {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for JsonLiteral {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0.to_string().cmp(&other.0.to_string())
+    }
+}
+
+impl Hash for JsonLiteral {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.0.to_string().hash(state);
+    }
+}
+
 /// Describes how to match HTTP responses.
 /// This is an untagged enum to allow for different matching strategies.
 #[derive(Debug, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash, Clone)]
@@ -367,6 +476,8 @@ pub enum ResponseMatcher {
         #[serde(default = "default_false")]
         match_all_values: bool,
     },
+    // Must stay ahead of `JsonValid`, which would otherwise accept any map with a `type`.
+    JsonMatch(JsonMatcher),
     JsonValid {
         // "JsonValid"
         r#type: String,
@@ -522,6 +633,13 @@ pub struct RuleSyntax {
     /// Optional character type requirements for matched secrets.
     #[serde(default)]
     pub pattern_requirements: Option<PatternRequirements>,
+    /// Identity fields (account id, username, scopes, ...) to pull out of a
+    /// successful validation response, keyed by the name they are reported under.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub extract: BTreeMap<String, Extractor>,
+    /// Optional way to revoke a confirmed secret (`kingfisher revoke`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub revocation: Option<Revocation>,
 }
 
 lazy_static! {
@@ -576,6 +694,8 @@ impl RuleSyntax {
     ///     visible: true,
     ///     validation: None,
     ///     depends_on_rule: vec![],
+    ///     extract: Default::default(),
+    ///     revocation: None,
     ///     pattern_requirements: None,
     /// };
     /// assert_eq!(r.as_anchored_regex().unwrap().as_str(), r"hello\s*world$");
@@ -1,22 +1,402 @@
 use std::env;
 use std::fs::File;
-use std::io::{Read, Write};
+use std::io::{BufRead, BufReader, Read, Write};
 use std::path::{Path, PathBuf};
 use std::process::{Command, Stdio};
 use std::time::Duration;
 
-use anyhow::{anyhow, Context, Result};
+use anyhow::{anyhow, bail, Context, Result};
 use base64::Engine;
+use flate2::read::GzDecoder;
 use indicatif::{ProgressBar, ProgressStyle};
 use oci_client::client::{linux_amd64_resolver, Client, ClientConfig};
 use oci_client::secrets::RegistryAuth;
 use oci_client::Reference;
+use rustc_hash::FxHashSet;
 use serde_json::Value;
 use sha2::{Digest, Sha256};
+use tar::Archive;
 use tracing::debug;
-use walkdir::WalkDir;
 
-use crate::decompress::decompress_file;
+use crate::decompress::is_safe_extract_path;
+
+/// The image config, pretty-printed so findings get useful line numbers.
+pub const IMAGE_CONFIG_FILE: &str = "image-config.json";
+/// The image history rendered as Dockerfile instructions, one per line.
+pub const IMAGE_HISTORY_FILE: &str = "image-history.Dockerfile";
+
+/// `scan docker oci-archive:<path>` reads a `docker save` or OCI layout tarball.
+pub const OCI_ARCHIVE_PREFIX: &str = "oci-archive:";
+/// `scan docker oci-dir:<path>` reads an unpacked OCI image layout directory.
+pub const OCI_DIR_PREFIX: &str = "oci-dir:";
+
+/// Nesting limit for OCI indexes that point at further indexes.
+const MAX_INDEX_DEPTH: usize = 4;
+
+const OPAQUE_WHITEOUT: &str = ".wh..wh..opq";
+const WHITEOUT_PREFIX: &str = ".wh.";
+
+/// One filesystem layer of a saved image, unpacked into its own directory.
+/// A layer shared by several registry tags describes the first tag it was
+/// pulled from.
+#[derive(Debug, Clone)]
+pub struct DockerLayer {
+    pub image: String,
+    /// Position in the image's layer list, base layer first
+    pub index: usize,
+    pub digest: String,
+    /// The Dockerfile instruction that created the layer, from the image history
+    pub created_by: Option<String>,
+    /// Files of this layer (relative to its root) that a later layer deletes
+    /// or replaces, so they are absent from the final filesystem
+    pub hidden: FxHashSet<String>,
+    /// Every image reference sharing this layer, for registry scans that
+    /// unpack each layer once across tags
+    pub tags: Vec<String>,
+}
+
+/// An image saved for scanning: its directory and the layers unpacked in it.
+pub struct SavedImage {
+    pub dir: PathBuf,
+    pub image: String,
+    pub layers: Vec<(PathBuf, DockerLayer)>,
+}
+
+/// What unpacking a layer tarball added and removed.
+#[derive(Debug, Default)]
+pub(super) struct LayerContents {
+    files: Vec<String>,
+    /// Paths removed from lower layers by `.wh.<name>` entries
+    deleted: Vec<String>,
+    /// Directories whose lower-layer contents are hidden by `.wh..wh..opq`
+    opaque: Vec<String>,
+}
+
+struct UnpackedLayer {
+    dir: PathBuf,
+    digest: String,
+    contents: LayerContents,
+}
+
+/// Unpack a layer tarball, gzip-compressed or not, into `dest`. Whiteout
+/// entries are recorded instead of being written out.
+pub(super) fn unpack_layer<R: BufRead>(mut reader: R, dest: &Path) -> Result<LayerContents> {
+    let gzipped = reader.fill_buf()?.starts_with(&[0x1f, 0x8b]);
+    if gzipped {
+        unpack_tar(GzDecoder::new(reader), dest)
+    } else {
+        unpack_tar(reader, dest)
+    }
+}
+
+fn unpack_tar(reader: impl Read, dest: &Path) -> Result<LayerContents> {
+    std::fs::create_dir_all(dest)?;
+    let mut archive = Archive::new(reader);
+    let mut contents = LayerContents::default();
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+        let path = entry.path()?.to_string_lossy().to_string();
+        let path = path.trim_start_matches("./").trim_start_matches('/').to_string();
+        let (parent, name) = match path.rsplit_once('/') {
+            Some((parent, name)) => (parent, name),
+            None => ("", path.as_str()),
+        };
+        if name == OPAQUE_WHITEOUT {
+            if !parent.is_empty() {
+                contents.opaque.push(parent.to_string());
+            }
+            continue;
+        }
+        if let Some(deleted) = name.strip_prefix(WHITEOUT_PREFIX) {
+            let deleted =
+                if parent.is_empty() { deleted.to_string() } else { format!("{parent}/{deleted}") };
+            contents.deleted.push(deleted);
+            continue;
+        }
+        if !entry.header().entry_type().is_file() || path.is_empty() {
+            continue;
+        }
+        if !is_safe_extract_path(Path::new(&path)) {
+            tracing::warn!("unsafe layer path: {path}");
+            continue;
+        }
+        let out_path = dest.join(&path);
+        if let Some(parent) = out_path.parent() {
+            if let Err(e) = std::fs::create_dir_all(parent) {
+                debug!("failed to create directory {}: {}", parent.display(), e);
+                continue;
+            }
+        }
+        let copied =
+            File::create(&out_path).and_then(|mut out| std::io::copy(&mut entry, &mut out));
+        if let Err(e) = copied {
+            debug!("failed to extract {}: {}", out_path.display(), e);
+            continue;
+        }
+        contents.files.push(path);
+    }
+    Ok(contents)
+}
+
+/// `path` and each of its parent directories.
+fn self_and_ancestors(path: &str) -> impl Iterator<Item = &str> {
+    std::iter::once(path).chain(path.match_indices('/').map(move |(i, _)| &path[..i]))
+}
+
+/// For each layer, the files it adds that a later layer deletes, hides behind
+/// an opaque directory, or replaces.
+pub(super) fn hidden_files(layers: &[&LayerContents]) -> Vec<FxHashSet<String>> {
+    let mut later_files: FxHashSet<&str> = FxHashSet::default();
+    let mut later_removed: FxHashSet<&str> = FxHashSet::default();
+    let mut hidden = vec![FxHashSet::default(); layers.len()];
+    for (index, layer) in layers.iter().enumerate().rev() {
+        hidden[index] = layer
+            .files
+            .iter()
+            .filter(|file| {
+                later_files.contains(file.as_str())
+                    || self_and_ancestors(file).any(|p| later_removed.contains(p))
+            })
+            .cloned()
+            .collect();
+        later_files.extend(layer.files.iter().map(String::as_str));
+        later_removed.extend(layer.deleted.iter().chain(&layer.opaque).map(String::as_str));
+    }
+    hidden
+}
+
+/// Render a history `created_by` entry as the Dockerfile instruction that
+/// produced it.
+fn dockerfile_instruction(created_by: &str) -> String {
+    let instruction = created_by.trim();
+    let instruction =
+        instruction.strip_suffix("# buildkit").map(str::trim_end).unwrap_or(instruction);
+    if let Some(rest) = instruction.strip_prefix("/bin/sh -c #(nop)") {
+        rest.trim().to_string()
+    } else if let Some(rest) = instruction.strip_prefix("/bin/sh -c ") {
+        format!("RUN {}", rest.trim())
+    } else {
+        instruction.to_string()
+    }
+}
+
+/// Write the image config and its history into `dir` so they are scanned like
+/// any other file. Returns, for each layer, the instruction that created it.
+pub(super) fn write_image_config(config: &[u8], dir: &Path) -> Result<Vec<Option<String>>> {
+    std::fs::create_dir_all(dir)?;
+    let parsed: Value = serde_json::from_slice(config).unwrap_or(Value::Null);
+    if parsed.is_null() {
+        std::fs::write(dir.join(IMAGE_CONFIG_FILE), config)?;
+    } else {
+        std::fs::write(dir.join(IMAGE_CONFIG_FILE), serde_json::to_vec_pretty(&parsed)?)?;
+    }
+
+    let history = parsed.get("history").and_then(Value::as_array).cloned().unwrap_or_default();
+    let instructions: Vec<String> = history
+        .iter()
+        .filter_map(|entry| entry.get("created_by").and_then(Value::as_str))
+        .map(dockerfile_instruction)
+        .collect();
+    if !instructions.is_empty() {
+        std::fs::write(dir.join(IMAGE_HISTORY_FILE), instructions.join("\n") + "\n")?;
+    }
+
+    // Entries marked `empty_layer` (ENV, LABEL, ...) produce no layer.
+    Ok(history
+        .iter()
+        .filter(|entry| !entry.get("empty_layer").and_then(Value::as_bool).unwrap_or(false))
+        .map(|entry| entry.get("created_by").and_then(Value::as_str).map(dockerfile_instruction))
+        .collect())
+}
+
+/// Write the image config next to the layers and attribute each layer to its
+/// history entry.
+fn finish_image(
+    image: &str,
+    config: &[u8],
+    out_dir: &Path,
+    layers: Vec<UnpackedLayer>,
+) -> Result<Vec<(PathBuf, DockerLayer)>> {
+    let layer_instructions = write_image_config(config, out_dir)?;
+    let hidden = hidden_files(&layers.iter().map(|l| &l.contents).collect::<Vec<_>>());
+
+    Ok(layers
+        .into_iter()
+        .zip(hidden)
+        .enumerate()
+        .map(|(index, (layer, hidden))| {
+            let info = DockerLayer {
+                image: image.to_string(),
+                index,
+                digest: layer.digest,
+                created_by: layer_instructions.get(index).cloned().flatten(),
+                hidden,
+                tags: Vec::new(),
+            };
+            (layer.dir, info)
+        })
+        .collect())
+}
+
+pub(super) fn progress_bar(use_progress: bool) -> ProgressBar {
+    if use_progress {
+        let style =
+            ProgressStyle::with_template("{spinner} {msg} {pos}/{len}").expect("progress template");
+        let pb = ProgressBar::new(0).with_style(style);
+        pb.enable_steady_tick(Duration::from_millis(100));
+        pb
+    } else {
+        ProgressBar::hidden()
+    }
+}
+
+/// Unpack an image tarball (`docker save` output or an OCI layout archive).
+/// The archive is unpacked in a staging directory that is removed afterwards,
+/// so only the unpacked layers and config get scanned.
+fn unpack_image_archive(
+    image: &str,
+    archive: &Path,
+    out_dir: &Path,
+    pb: &ProgressBar,
+) -> Result<Vec<(PathBuf, DockerLayer)>> {
+    let staging = out_dir.join("image-archive");
+    let file = File::open(archive)
+        .with_context(|| format!("opening image archive {}", archive.display()))?;
+    // An image archive is a plain or gzip-compressed tarball, just like a layer.
+    unpack_layer(BufReader::new(file), &staging)
+        .with_context(|| format!("unpacking image archive {}", archive.display()))?;
+    let layers = unpack_image_layout(image, &staging, out_dir, pb);
+    std::fs::remove_dir_all(&staging)?;
+    layers
+}
+
+/// Unpack the image stored in `layout`: a `docker save` directory
+/// (`manifest.json`) or an OCI image layout (`index.json` and `blobs/`).
+fn unpack_image_layout(
+    image: &str,
+    layout: &Path,
+    out_dir: &Path,
+    pb: &ProgressBar,
+) -> Result<Vec<(PathBuf, DockerLayer)>> {
+    let (config, layer_blobs) = if layout.join("manifest.json").is_file() {
+        docker_save_manifest(layout)?
+    } else if layout.join("index.json").is_file() {
+        oci_layout_manifest(layout)?
+    } else {
+        bail!("{} is neither a docker save nor an OCI image layout", layout.display());
+    };
+
+    pb.set_length(layer_blobs.len() as u64);
+    pb.set_message("extracting layers");
+    let mut layers = Vec::with_capacity(layer_blobs.len());
+    for (index, (digest, path)) in layer_blobs.into_iter().enumerate() {
+        let dir = layer_dir(out_dir, index, &digest);
+        let file = File::open(&path).with_context(|| format!("opening layer {digest}"))?;
+        let contents = unpack_layer(BufReader::new(file), &dir)
+            .with_context(|| format!("unpacking layer {digest}"))?;
+        layers.push(UnpackedLayer { dir, digest, contents });
+        pb.inc(1);
+    }
+    finish_image(image, &config, out_dir, layers)
+}
+
+type LayoutImage = (Vec<u8>, Vec<(String, PathBuf)>);
+
+fn read_json(path: &Path) -> Result<Value> {
+    serde_json::from_slice(&std::fs::read(path)?)
+        .with_context(|| format!("parsing {}", path.display()))
+}
+
+/// The config and the layer blobs (digest, path) listed by `manifest.json`,
+/// both for the legacy `docker save` layout and the OCI one written by newer
+/// Docker releases.
+fn docker_save_manifest(layout: &Path) -> Result<LayoutImage> {
+    let manifest = read_json(&layout.join("manifest.json"))?;
+    let entry = manifest.get(0).ok_or_else(|| anyhow!("empty docker save manifest"))?;
+    let config_path =
+        entry.get("Config").and_then(Value::as_str).ok_or_else(|| anyhow!("no image config"))?;
+    let config = std::fs::read(layout_file(layout, config_path)?)?;
+
+    let mut layers = Vec::new();
+    let paths = entry.get("Layers").and_then(Value::as_array).into_iter().flatten();
+    for rel in paths.filter_map(Value::as_str) {
+        let path = layout_file(layout, rel)?;
+        let mut hasher = Sha256::new();
+        std::io::copy(&mut File::open(&path)?, &mut hasher)?;
+        layers.push((format!("sha256:{:x}", hasher.finalize()), path));
+    }
+    Ok((config, layers))
+}
+
+/// The config and the layer blobs (digest, path) of the image in an OCI
+/// image layout. Multi-platform indexes resolve to linux/amd64, like pulls.
+fn oci_layout_manifest(layout: &Path) -> Result<LayoutImage> {
+    let mut manifest = read_json(&layout.join("index.json"))?;
+    // The layout index may point at a further (multi-platform) image index.
+    for _ in 0..MAX_INDEX_DEPTH {
+        let Some(manifests) = manifest.get("manifests").and_then(Value::as_array) else {
+            break;
+        };
+        let descriptor = manifests
+            .iter()
+            .find(|m| {
+                m.pointer("/platform/os").and_then(Value::as_str) == Some("linux")
+                    && m.pointer("/platform/architecture").and_then(Value::as_str) == Some("amd64")
+            })
+            .or_else(|| manifests.first())
+            .ok_or_else(|| anyhow!("OCI index lists no manifests"))?;
+        let digest = descriptor
+            .get("digest")
+            .and_then(Value::as_str)
+            .ok_or_else(|| anyhow!("OCI index entry has no digest"))?;
+        manifest = read_json(&blob_path(layout, digest)?)?;
+    }
+
+    let config_digest = manifest
+        .pointer("/config/digest")
+        .and_then(Value::as_str)
+        .ok_or_else(|| anyhow!("OCI manifest has no config"))?;
+    let config = std::fs::read(blob_path(layout, config_digest)?)?;
+
+    let mut layers = Vec::new();
+    for layer in manifest.get("layers").and_then(Value::as_array).into_iter().flatten() {
+        let digest = layer
+            .get("digest")
+            .and_then(Value::as_str)
+            .ok_or_else(|| anyhow!("OCI layer has no digest"))?;
+        if layer.get("mediaType").and_then(Value::as_str).is_some_and(|t| t.ends_with("+zstd")) {
+            bail!("zstd-compressed layer {digest} is not supported");
+        }
+        layers.push((digest.to_string(), blob_path(layout, digest)?));
+    }
+    Ok((config, layers))
+}
+
+/// `blobs/<algorithm>/<hex>` for a content digest, refusing anything that is
+/// not a plain digest.
+fn blob_path(layout: &Path, digest: &str) -> Result<PathBuf> {
+    let plain = |s: &str| !s.is_empty() && s.chars().all(|c| c.is_ascii_alphanumeric());
+    match digest.split_once(':') {
+        Some((algorithm, hex)) if plain(algorithm) && plain(hex) => {
+            Ok(layout.join("blobs").join(algorithm).join(hex))
+        }
+        _ => bail!("invalid blob digest {digest}"),
+    }
+}
+
+/// A path from `manifest.json`, which must stay inside the layout.
+fn layout_file(layout: &Path, rel: &str) -> Result<PathBuf> {
+    let path = Path::new(rel);
+    if path.is_absolute() || !is_safe_extract_path(path) {
+        bail!("unsafe path {rel} in manifest.json");
+    }
+    Ok(layout.join(path))
+}
+
+fn layer_dir(out_dir: &Path, index: usize, digest: &str) -> PathBuf {
+    let hex = digest.rsplit(':').next().unwrap_or(digest);
+    out_dir.join("layers").join(format!("{index:03}-{}", &hex[..hex.len().min(12)]))
+}
 
 fn helper_get_creds(helper: &str, registry: &str) -> Option<(String, String)> {
     fn run(bin: &str, registry: &str) -> Option<(String, String)> {
@@ -55,7 +435,7 @@ fn helper_get_creds(helper: &str, registry: &str) -> Option<(String, String)> {
 
 /// Turn `registry.example.com/foo/bar:latest` into something like
 /// `registry.example.com_foo_bar_latest_4d3c9e83`
-fn image_dir_name(reference: &str) -> String {
+pub(super) fn image_dir_name(reference: &str) -> String {
     // keep it readable
     let mut name = reference.replace(['/', ':'], "_");
 
@@ -107,7 +487,7 @@ fn creds_from_docker_config(registry: &str) -> Option<(String, String)> {
     None
 }
 
-fn registry_auth(reference: &Reference) -> RegistryAuth {
+pub(super) fn registry_auth(reference: &Reference) -> RegistryAuth {
     if let Ok(token) = env::var("KF_DOCKER_TOKEN") {
         if let Some((user, pass)) = token.split_once(':') {
             return RegistryAuth::Basic(user.to_string(), pass.to_string());
@@ -129,7 +509,12 @@ impl Docker {
         Docker
     }
 
-    fn try_save_local_image(&self, image: &str, out_dir: &Path, use_progress: bool) -> Result<()> {
+    fn try_save_local_image(
+        &self,
+        image: &str,
+        out_dir: &Path,
+        use_progress: bool,
+    ) -> Result<Vec<(PathBuf, DockerLayer)>> {
         let docker = Command::new("docker")
             .args(["image", "inspect", image])
             .stdout(std::process::Stdio::null())
@@ -140,15 +525,7 @@ impl Docker {
             return Err(anyhow!("image not local"));
         }
 
-        let pb = if use_progress {
-            let style = ProgressStyle::with_template("{spinner} {msg} {pos}/{len}")
-                .expect("progress template");
-            let pb = ProgressBar::new(0).with_style(style);
-            pb.enable_steady_tick(Duration::from_millis(100));
-            pb
-        } else {
-            ProgressBar::hidden()
-        };
+        let pb = progress_bar(use_progress);
         pb.set_message(format!("saving local {image}"));
 
         std::fs::create_dir_all(out_dir)?;
@@ -162,34 +539,12 @@ impl Docker {
             return Err(anyhow!("failed to save local image"));
         }
 
-        pb.set_message("extracting layers");
-        decompress_file(&tar_path, Some(out_dir))?;
-
-        let mut layer_paths = Vec::new();
-        for entry in WalkDir::new(out_dir) {
-            let entry = entry?;
-            if entry.file_name() == "layer.tar" {
-                layer_paths.push(entry.path().to_path_buf());
-            }
-        }
-
-        pb.set_length(layer_paths.len() as u64);
-        for p in layer_paths {
-            let mut file = File::open(&p)?;
-            let mut hasher = Sha256::new();
-            std::io::copy(&mut file, &mut hasher)?;
-            let digest = format!("{:x}", hasher.finalize());
-
-            let new_path = out_dir.join(format!("layer_{digest}.tar"));
-            std::fs::rename(&p, &new_path)?;
-            // extract layer contents so inner filenames appear in scan results
-            decompress_file(&new_path, Some(out_dir))?;
-            std::fs::remove_file(&new_path)?;
-            pb.inc(1);
-        }
+        let layers = unpack_image_archive(image, &tar_path, out_dir, &pb);
+        std::fs::remove_file(&tar_path)?;
+        let layers = layers?;
 
         pb.finish_with_message(format!("saved {image}"));
-        Ok(())
+        Ok(layers)
     }
 
     pub async fn save_image_to_dir(
@@ -197,23 +552,32 @@ impl Docker {
         image: &str,
         out_dir: &Path,
         use_progress: bool,
-    ) -> Result<()> {
-        if self.try_save_local_image(image, out_dir, use_progress).is_ok() {
-            return Ok(());
+    ) -> Result<Vec<(PathBuf, DockerLayer)>> {
+        // Images on disk are read directly, without a Docker daemon or registry.
+        if let Some(archive) = image.strip_prefix(OCI_ARCHIVE_PREFIX) {
+            let pb = progress_bar(use_progress);
+            pb.set_message(format!("reading {archive}"));
+            let layers = unpack_image_archive(image, Path::new(archive), out_dir, &pb)?;
+            pb.finish_with_message(format!("read {image}"));
+            return Ok(layers);
+        }
+        if let Some(layout) = image.strip_prefix(OCI_DIR_PREFIX) {
+            let pb = progress_bar(use_progress);
+            pb.set_message(format!("reading {layout}"));
+            std::fs::create_dir_all(out_dir)?;
+            let layers = unpack_image_layout(image, Path::new(layout), out_dir, &pb)?;
+            pb.finish_with_message(format!("read {image}"));
+            return Ok(layers);
+        }
+
+        if let Ok(layers) = self.try_save_local_image(image, out_dir, use_progress) {
+            return Ok(layers);
         }
         let reference: Reference =
             image.parse().with_context(|| format!("invalid image reference {image}"))?;
         debug!("Pulling {image}");
-        let pb = if use_progress {
-            let style = ProgressStyle::with_template("{spinner} {msg} {pos}/{len}")
-                .expect("progress template");
-            let pb = ProgressBar::new(0).with_style(style);
-            pb.enable_steady_tick(Duration::from_millis(100));
-            pb.set_message(format!("pulling {image}"));
-            pb
-        } else {
-            ProgressBar::hidden()
-        };
+        let pb = progress_bar(use_progress);
+        pb.set_message(format!("pulling {image}"));
         let client = Client::new(ClientConfig {
             platform_resolver: Some(Box::new(linux_amd64_resolver)),
             ..Default::default()
@@ -231,25 +595,17 @@ impl Docker {
         pb.set_message("extracting layers");
 
         std::fs::create_dir_all(out_dir)?;
-        for layer in pulled.layers.into_iter() {
-            let ext = match layer.media_type.as_str() {
-                oci_client::manifest::IMAGE_LAYER_GZIP_MEDIA_TYPE
-                | oci_client::manifest::IMAGE_DOCKER_LAYER_GZIP_MEDIA_TYPE => "tar.gz",
-                oci_client::manifest::IMAGE_LAYER_MEDIA_TYPE
-                | oci_client::manifest::IMAGE_DOCKER_LAYER_TAR_MEDIA_TYPE => "tar",
-                _ => "bin",
-            };
+        let mut layers = Vec::with_capacity(pulled.layers.len());
+        for (index, layer) in pulled.layers.iter().enumerate() {
             let digest = layer.sha256_digest();
-            let file_name = format!("layer_{}.{}", digest.replace(':', "_"), ext);
-            let tmp_path = out_dir.join(file_name);
-            let mut tmp = std::fs::File::create(&tmp_path)?;
-            tmp.write_all(&layer.data)?;
-            decompress_file(&tmp_path, Some(out_dir))?;
-            std::fs::remove_file(&tmp_path)?;
+            let dir = layer_dir(out_dir, index, &digest);
+            let contents = unpack_layer(&layer.data[..], &dir)?;
+            layers.push(UnpackedLayer { dir, digest, contents });
             pb.inc(1);
         }
+        let layers = finish_image(image, &pulled.config.data, out_dir, layers)?;
         pb.finish_with_message(format!("saved {image}"));
-        Ok(())
+        Ok(layers)
     }
 }
 
@@ -257,21 +613,21 @@ pub async fn save_docker_images(
     images: &[String],
     clone_root: &Path,
     use_progress: bool,
-) -> Result<Vec<(PathBuf, String)>> {
+) -> Result<Vec<SavedImage>> {
     let docker = Docker::new();
-    let mut dirs = Vec::new();
+    let mut saved = Vec::new();
 
     for image in images {
         let dir_name = image_dir_name(image);
         let out_dir = clone_root.join(format!("docker_{dir_name}"));
-        docker
+        let layers = docker
             .save_image_to_dir(image, &out_dir, use_progress)
             .await
             .with_context(|| format!("saving image {image}"))?;
-        dirs.push((out_dir, image.clone()));
+        saved.push(SavedImage { dir: out_dir, image: image.clone(), layers });
     }
 
-    Ok(dirs)
+    Ok(saved)
 }
 
 #[cfg(test)]
@@ -282,4 +638,176 @@ mod tests {
     fn docker_struct_new() {
         let _ = Docker::new();
     }
+
+    fn layer_tar(entries: &[(&str, &str)]) -> Vec<u8> {
+        let mut builder = tar::Builder::new(Vec::new());
+        for (path, body) in entries {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(body.len() as u64);
+            header.set_mode(0o644);
+            header.set_cksum();
+            builder.append_data(&mut header, path, body.as_bytes()).unwrap();
+        }
+        builder.into_inner().unwrap()
+    }
+
+    #[test]
+    fn renders_history_as_dockerfile_instructions() {
+        assert_eq!(
+            dockerfile_instruction("/bin/sh -c #(nop)  ENV AWS_SECRET_ACCESS_KEY=abc"),
+            "ENV AWS_SECRET_ACCESS_KEY=abc"
+        );
+        assert_eq!(
+            dockerfile_instruction("/bin/sh -c apt-get update && rm -rf /var/lib/apt"),
+            "RUN apt-get update && rm -rf /var/lib/apt"
+        );
+        assert_eq!(
+            dockerfile_instruction("COPY app.env /app/.env # buildkit"),
+            "COPY app.env /app/.env"
+        );
+    }
+
+    #[test]
+    fn attributes_layers_and_tracks_whiteouts() {
+        let out = tempfile::tempdir().unwrap();
+        let base = layer_tar(&[
+            ("etc/app.conf", "password=one"),
+            ("app/.env", "TOKEN=two"),
+            ("cache/tmp/key", "three"),
+            ("opt/keep", "four"),
+        ]);
+        let top = layer_tar(&[
+            ("./etc/app.conf", "password=replaced"),
+            ("app/.wh..env", ""),
+            ("cache/.wh..wh..opq", ""),
+        ]);
+
+        let mut layers = Vec::new();
+        for (index, (digest, tarball)) in
+            [("sha256:aaaa", base), ("sha256:bbbb", top)].into_iter().enumerate()
+        {
+            let dir = layer_dir(out.path(), index, digest);
+            let contents = unpack_layer(&tarball[..], &dir).unwrap();
+            layers.push(UnpackedLayer { dir, digest: digest.to_string(), contents });
+        }
+        let config = serde_json::json!({
+            "config": { "Env": ["PATH=/usr/bin"] },
+            "history": [
+                { "created_by": "/bin/sh -c #(nop) ADD file:123 in / " },
+                { "created_by": "/bin/sh -c #(nop)  ENV API_KEY=xyz", "empty_layer": true },
+                { "created_by": "/bin/sh -c rm /app/.env" },
+            ],
+        });
+        let config = serde_json::to_vec(&config).unwrap();
+        let described = finish_image("example:1", &config, out.path(), layers).unwrap();
+
+        let (base_dir, base) = &described[0];
+        assert!(base_dir.join("app/.env").is_file());
+        assert_eq!(base.created_by.as_deref(), Some("ADD file:123 in /"));
+        let mut hidden: Vec<_> = base.hidden.iter().map(String::as_str).collect();
+        hidden.sort_unstable();
+        assert_eq!(hidden, ["app/.env", "cache/tmp/key", "etc/app.conf"]);
+
+        let (top_dir, top) = &described[1];
+        assert_eq!((top.index, top.digest.as_str()), (1, "sha256:bbbb"));
+        assert_eq!(top.created_by.as_deref(), Some("RUN rm /app/.env"));
+        assert!(top.hidden.is_empty());
+        assert!(!top_dir.join("app/.wh..env").exists());
+
+        let history = std::fs::read_to_string(out.path().join(IMAGE_HISTORY_FILE)).unwrap();
+        assert!(history.contains("ENV API_KEY=xyz\n"));
+        assert!(out.path().join(IMAGE_CONFIG_FILE).is_file());
+    }
+
+    fn write_blob(layout: &Path, bytes: &[u8]) -> String {
+        let hex = format!("{:x}", Sha256::digest(bytes));
+        std::fs::create_dir_all(layout.join("blobs/sha256")).unwrap();
+        std::fs::write(layout.join("blobs/sha256").join(&hex), bytes).unwrap();
+        format!("sha256:{hex}")
+    }
+
+    /// An OCI image layout whose index points at a multi-platform index.
+    fn oci_layout(layout: &Path) -> String {
+        let layer = layer_tar(&[("app/.env", "AWS_SECRET_ACCESS_KEY=abc")]);
+        let layer_digest = write_blob(layout, &layer);
+        let config = serde_json::json!({
+            "history": [{ "created_by": "COPY .env /app/.env # buildkit" }],
+        });
+        let config_digest = write_blob(layout, &serde_json::to_vec(&config).unwrap());
+        let manifest = serde_json::json!({
+            "schemaVersion": 2,
+            "config": { "digest": config_digest },
+            "layers": [{ "mediaType": "application/vnd.oci.image.layer.v1.tar", "digest": layer_digest }],
+        });
+        let manifest_digest = write_blob(layout, &serde_json::to_vec(&manifest).unwrap());
+        let platforms = serde_json::json!({
+            "manifests": [
+                { "digest": "sha256:0000", "platform": { "os": "linux", "architecture": "arm64" } },
+                { "digest": manifest_digest, "platform": { "os": "linux", "architecture": "amd64" } },
+            ],
+        });
+        let platforms_digest = write_blob(layout, &serde_json::to_vec(&platforms).unwrap());
+        let index = serde_json::json!({ "manifests": [{ "digest": platforms_digest }] });
+        std::fs::write(layout.join("index.json"), serde_json::to_vec(&index).unwrap()).unwrap();
+        std::fs::write(layout.join("oci-layout"), r#"{"imageLayoutVersion":"1.0.0"}"#).unwrap();
+        layer_digest
+    }
+
+    #[tokio::test]
+    async fn reads_oci_layout_directories_and_archives() {
+        let layout = tempfile::tempdir().unwrap();
+        let layer_digest = oci_layout(layout.path());
+
+        let out = tempfile::tempdir().unwrap();
+        let image = format!("oci-dir:{}", layout.path().display());
+        let layers =
+            Docker::new().save_image_to_dir(&image, &out.path().join("dir"), false).await.unwrap();
+        assert_eq!(layers.len(), 1);
+        let (dir, layer) = &layers[0];
+        assert_eq!(layer.digest, layer_digest);
+        assert_eq!(layer.created_by.as_deref(), Some("COPY .env /app/.env"));
+        assert!(dir.join("app/.env").is_file());
+
+        let mut archive = tar::Builder::new(Vec::new());
+        for entry in walkdir::WalkDir::new(layout.path()) {
+            let entry = entry.unwrap();
+            if entry.file_type().is_file() {
+                let name = entry.path().strip_prefix(layout.path()).unwrap();
+                archive.append_path_with_name(entry.path(), name).unwrap();
+            }
+        }
+        let archive_path = out.path().join("image.tar");
+        std::fs::write(&archive_path, archive.into_inner().unwrap()).unwrap();
+        let image = format!("oci-archive:{}", archive_path.display());
+        let archive_out = out.path().join("archive");
+        let layers = Docker::new().save_image_to_dir(&image, &archive_out, false).await.unwrap();
+        assert_eq!(layers[0].1.digest, layer_digest);
+        assert!(layers[0].0.join("app/.env").is_file());
+        assert!(archive_out.join(IMAGE_CONFIG_FILE).is_file());
+        assert!(!archive_out.join("image-archive").exists());
+    }
+
+    #[test]
+    fn reads_docker_save_layouts() {
+        let layout = tempfile::tempdir().unwrap();
+        let layer = layer_tar(&[("etc/secret", "token")]);
+        std::fs::create_dir_all(layout.path().join("abc")).unwrap();
+        std::fs::write(layout.path().join("abc/layer.tar"), &layer).unwrap();
+        std::fs::write(layout.path().join("cfg.json"), r#"{"history":[]}"#).unwrap();
+        let manifest = r#"[{"Config":"cfg.json","RepoTags":["app:1"],"Layers":["abc/layer.tar"]}]"#;
+        std::fs::write(layout.path().join("manifest.json"), manifest).unwrap();
+
+        let out = tempfile::tempdir().unwrap();
+        let layers =
+            unpack_image_layout("app:1", layout.path(), out.path(), &ProgressBar::hidden())
+                .unwrap();
+        assert_eq!(layers[0].1.digest, format!("sha256:{:x}", Sha256::digest(&layer)));
+        assert!(layers[0].0.join("etc/secret").is_file());
+
+        let escaping = r#"[{"Config":"../cfg.json","Layers":[]}]"#;
+        std::fs::write(layout.path().join("manifest.json"), escaping).unwrap();
+        assert!(unpack_image_layout("app:1", layout.path(), out.path(), &ProgressBar::hidden())
+            .is_err());
+        assert!(blob_path(layout.path(), "sha256:../../etc").is_err());
+    }
 }
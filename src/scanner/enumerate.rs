@@ -174,7 +174,8 @@ pub fn enumerate_filesystem_inputs(
         &args.extra_ignore_comments,
         args.no_inline_ignore,
         !args.no_ignore_if_contains,
-    )?;
+    )?
+    .keep_inline_suppressions(args.show_suppressed);
     let blob_processor_init_time = Mutex::new(t1.elapsed());
     let make_blob_processor = || -> BlobProcessor {
         let t1 = Instant::now();
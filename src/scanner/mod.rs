@@ -1,16 +1,19 @@
 //! Public façade for the scanner subsystem.
 pub(crate) use docker::save_docker_images;
+pub use docker::{DockerLayer, IMAGE_CONFIG_FILE, IMAGE_HISTORY_FILE};
 pub(crate) use enumerate::enumerate_filesystem_inputs;
+pub(crate) use registry::save_registry_repositories;
 pub(crate) use repos::{
     clone_or_update_git_repos_streaming, enumerate_azure_repos, enumerate_bitbucket_repos,
     enumerate_github_repos, enumerate_huggingface_repos,
 };
 pub use runner::{load_and_record_rules, run_async_scan, run_scan};
-pub(crate) use validation::{run_secret_validation, AccessMapCollector};
+pub(crate) use validation::{maybe_record_access_map, run_secret_validation, AccessMapCollector};
 
 mod docker;
 mod enumerate;
 mod processing;
+mod registry;
 mod repos;
 mod runner;
 mod summary;
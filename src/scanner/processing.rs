@@ -9,6 +9,7 @@ use crate::{
     matcher::{Match, Matcher, OwnedBlobMatch, ScanResult},
     origin::{Origin, OriginSet},
     scanner::repos::DatastoreMessage,
+    validation::ssh,
     Path,
 };
 
@@ -31,9 +32,11 @@ impl<'a> BlobProcessor<'a> {
     ) -> Result<Option<DatastoreMessage>> {
         let _span = debug_span!("matcher", temp_id = blob.temp_id()).entered();
         let t1 = Instant::now();
-        let language_hint = origin
-            .iter()
-            .find_map(|p| p.blob_path())
+        let blob_path = origin.iter().find_map(|p| p.blob_path());
+        if let Some(path) = blob_path {
+            ssh::record_public_keys(path, blob.bytes());
+        }
+        let language_hint = blob_path
             .and_then(|path| ContentInspector::default().guess_language(path, blob.bytes()));
         let res =
             self.matcher.scan_blob(&blob, &origin, language_hint, redact, no_dedup, no_base64)?;
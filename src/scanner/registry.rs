@@ -0,0 +1,457 @@
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::future::Future;
+use std::net::IpAddr;
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, Context, Result};
+use oci_client::client::{linux_amd64_resolver, Client, ClientConfig, ClientProtocol};
+use oci_client::secrets::RegistryAuth;
+use oci_client::Reference;
+use reqwest::header::{LINK, WWW_AUTHENTICATE};
+use reqwest::StatusCode;
+use rustc_hash::FxHashMap;
+use serde_json::Value;
+use tracing::{debug, warn};
+
+use super::docker::{
+    hidden_files, image_dir_name, progress_bar, registry_auth, unpack_layer, write_image_config,
+    DockerLayer, LayerContents,
+};
+
+const TAG_PAGE_SIZE: usize = 100;
+const CATALOG_PAGE_SIZE: usize = 100;
+
+/// Every tag of the scanned registry repositories, unpacked under one
+/// directory with each distinct layer unpacked once.
+pub struct SavedRegistry {
+    pub dir: PathBuf,
+    /// Per-tag directories holding the image config and history
+    pub images: Vec<(PathBuf, String)>,
+    pub layers: Vec<(PathBuf, DockerLayer)>,
+}
+
+struct SeenLayer {
+    dir: PathBuf,
+    contents: LayerContents,
+    info: DockerLayer,
+}
+
+/// Loopback registries are spoken to over plain HTTP, as Docker does by default.
+fn is_loopback_registry(registry: &str) -> bool {
+    let host = match registry.strip_prefix('[') {
+        Some(rest) => rest.split(']').next().unwrap_or(rest),
+        None => registry.split(':').next().unwrap_or(registry),
+    };
+    host.eq_ignore_ascii_case("localhost")
+        || host.parse::<IpAddr>().is_ok_and(|ip| ip.is_loopback())
+}
+
+fn registry_client(registry: &str, ignore_certs: bool) -> Client {
+    let protocol = if is_loopback_registry(registry) {
+        ClientProtocol::HttpsExcept(vec![registry.to_string()])
+    } else {
+        ClientProtocol::Https
+    };
+    Client::new(ClientConfig {
+        protocol,
+        platform_resolver: Some(Box::new(linux_amd64_resolver)),
+        accept_invalid_certificates: ignore_certs,
+        ..Default::default()
+    })
+}
+
+/// All tags of `repository`, following `last` pagination.
+async fn list_tags(
+    client: &Client,
+    repository: &Reference,
+    auth: &RegistryAuth,
+) -> Result<Vec<String>> {
+    collect_tag_pages(|last| async move {
+        let page = client.list_tags(repository, auth, Some(TAG_PAGE_SIZE), last.as_deref()).await?;
+        Ok(page.tags)
+    })
+    .await
+}
+
+/// Request pages of `TAG_PAGE_SIZE` tags, each after the last tag seen, until
+/// a page is short or adds no new tag. Registries that ignore `n` or `last`
+/// return a different page size or repeat themselves, and still terminate.
+async fn collect_tag_pages<F, Fut>(mut fetch: F) -> Result<Vec<String>>
+where
+    F: FnMut(Option<String>) -> Fut,
+    Fut: Future<Output = Result<Vec<String>>>,
+{
+    let mut seen = HashSet::new();
+    let mut tags: Vec<String> = Vec::new();
+    loop {
+        let page = fetch(tags.last().cloned()).await?;
+        let (page_len, before) = (page.len(), tags.len());
+        tags.extend(page.into_iter().filter(|tag| seen.insert(tag.clone())));
+        if tags.len() == before || page_len != TAG_PAGE_SIZE {
+            return Ok(tags);
+        }
+    }
+}
+
+/// `<url>; rel="next"` from a `Link` header.
+fn next_link(header: &str) -> Option<String> {
+    header.split(',').find_map(|link| {
+        let (target, params) = link.split_once(';')?;
+        params
+            .contains("rel=\"next\"")
+            .then(|| target.trim().trim_start_matches('<').trim_end_matches('>').to_string())
+    })
+}
+
+/// `next` resolved against `base`, or `None` when it points at another origin,
+/// which must never receive the registry credentials.
+fn same_origin_url(base: &str, next: &str) -> Option<String> {
+    let base = url::Url::parse(base).ok()?;
+    let next = base.join(next).ok()?;
+    (next.origin() == base.origin()).then(|| next.to_string())
+}
+
+/// The parameters of a `Bearer realm="...",service="...",scope="..."` challenge.
+fn parse_bearer_challenge(header: &str) -> Option<BTreeMap<String, String>> {
+    let params = header.trim().strip_prefix("Bearer ")?;
+    let mut parsed = BTreeMap::new();
+    let mut rest = params.trim();
+    while let Some((key, after)) = rest.split_once('=') {
+        let key = key.trim().trim_start_matches(',').trim().to_string();
+        let after = after.trim_start();
+        let (value, remainder) = match after.strip_prefix('"') {
+            Some(quoted) => quoted.split_once('"').unwrap_or((quoted, "")),
+            None => after.split_once(',').unwrap_or((after, "")),
+        };
+        parsed.insert(key, value.to_string());
+        rest = remainder;
+    }
+    parsed.contains_key("realm").then_some(parsed)
+}
+
+fn with_credentials(
+    request: reqwest::RequestBuilder,
+    auth: &RegistryAuth,
+) -> reqwest::RequestBuilder {
+    match auth {
+        RegistryAuth::Basic(user, pass) => request.basic_auth(user, Some(pass)),
+        RegistryAuth::Bearer(token) => request.bearer_auth(token),
+        _ => request,
+    }
+}
+
+/// GET `url`, answering a Bearer challenge from a token-authenticated registry
+/// with a token for the scope it names.
+async fn get_authorized(
+    http: &reqwest::Client,
+    url: &str,
+    auth: &RegistryAuth,
+    token: &mut Option<String>,
+) -> Result<reqwest::Response> {
+    if let Some(token) = token.as_deref() {
+        return Ok(http.get(url).bearer_auth(token).send().await?);
+    }
+    let response = with_credentials(http.get(url), auth).send().await?;
+    if response.status() != StatusCode::UNAUTHORIZED {
+        return Ok(response);
+    }
+    let Some(mut challenge) = response
+        .headers()
+        .get(WWW_AUTHENTICATE)
+        .and_then(|v| v.to_str().ok())
+        .and_then(parse_bearer_challenge)
+    else {
+        return Ok(response);
+    };
+    let realm = challenge.remove("realm").unwrap_or_default();
+    let body: Value = with_credentials(http.get(&realm).query(&challenge), auth)
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+    let issued = body
+        .get("token")
+        .or_else(|| body.get("access_token"))
+        .and_then(Value::as_str)
+        .ok_or_else(|| anyhow!("token service {realm} returned no token"))?;
+    *token = Some(issued.to_string());
+    Ok(http.get(url).bearer_auth(issued).send().await?)
+}
+
+/// Repository names from the registry's `/v2/_catalog`, following `Link`
+/// pagination.
+async fn list_catalog(
+    registry: &str,
+    auth: &RegistryAuth,
+    ignore_certs: bool,
+) -> Result<Vec<String>> {
+    let scheme = if is_loopback_registry(registry) { "http" } else { "https" };
+    let base = format!("{scheme}://{registry}");
+    let http = reqwest::Client::builder().danger_accept_invalid_certs(ignore_certs).build()?;
+    let mut url = format!("{base}/v2/_catalog?n={CATALOG_PAGE_SIZE}");
+    let mut token = None;
+    let mut repositories = Vec::new();
+    loop {
+        let response = get_authorized(&http, &url, auth, &mut token).await?.error_for_status()?;
+        let next = response.headers().get(LINK).and_then(|v| v.to_str().ok()).and_then(next_link);
+        let page: Value = response.json().await?;
+        repositories.extend(
+            page.get("repositories")
+                .and_then(Value::as_array)
+                .into_iter()
+                .flatten()
+                .filter_map(Value::as_str)
+                .map(str::to_string),
+        );
+        let Some(next) = next else {
+            return Ok(repositories);
+        };
+        match same_origin_url(&base, &next) {
+            Some(next) => url = next,
+            None => {
+                warn!("not following catalog link to another origin: {next}");
+                return Ok(repositories);
+            }
+        }
+    }
+}
+
+/// Resolve `targets` to repositories: each target itself, or with
+/// `all_repositories` every repository in the catalog of the target host.
+async fn resolve_repositories(
+    targets: &[String],
+    all_repositories: bool,
+    ignore_certs: bool,
+) -> Result<Vec<Reference>> {
+    let mut repositories = Vec::new();
+    for target in targets {
+        let target = target.trim_end_matches('/');
+        if all_repositories {
+            let host = target.split('/').next().unwrap_or(target);
+            // Resolve credentials as for any image on this registry.
+            let probe: Reference = format!("{host}/catalog").parse()?;
+            let names = list_catalog(host, &registry_auth(&probe), ignore_certs)
+                .await
+                .with_context(|| format!("listing the catalog of {host}"))?;
+            for name in names {
+                repositories.push(format!("{host}/{name}").parse()?);
+            }
+        } else {
+            repositories.push(
+                target.parse().with_context(|| format!("invalid registry repository {target}"))?,
+            );
+        }
+    }
+    Ok(repositories)
+}
+
+/// Scan every tag of the given registry repositories. Layers shared between
+/// tags are downloaded and unpacked once; each records every tag it is in,
+/// while its image, index and instruction come from the first tag pulled. A
+/// tag whose manifest or layers cannot be pulled or unpacked is skipped with a
+/// warning.
+pub async fn save_registry_repositories(
+    targets: &[String],
+    all_repositories: bool,
+    clone_root: &Path,
+    use_progress: bool,
+    ignore_certs: bool,
+) -> Result<SavedRegistry> {
+    let out_dir = clone_root.join("registry");
+    let mut images = Vec::new();
+    let mut seen: FxHashMap<String, SeenLayer> = FxHashMap::default();
+
+    for repository in resolve_repositories(targets, all_repositories, ignore_certs).await? {
+        let registry = repository.registry().to_string();
+        let client = registry_client(&registry, ignore_certs);
+        let auth = registry_auth(&repository);
+        let tags = list_tags(&client, &repository, &auth)
+            .await
+            .with_context(|| format!("listing tags of {repository}"))?;
+        debug!("{} tags in {}", tags.len(), repository);
+
+        let pb = progress_bar(use_progress);
+        pb.set_length(tags.len() as u64);
+        'tags: for tag in tags {
+            let reference = Reference::with_tag(
+                registry.clone(),
+                repository.repository().to_string(),
+                tag.clone(),
+            );
+            let image = reference.whole();
+            pb.set_message(format!("scanning {image}"));
+            let (manifest, _digest, config) =
+                match client.pull_manifest_and_config(&reference, &auth).await {
+                    Ok(pulled) => pulled,
+                    Err(e) => {
+                        warn!("skipping {image}: {e}");
+                        pb.inc(1);
+                        continue;
+                    }
+                };
+
+            let image_dir = out_dir.join("images").join(image_dir_name(&image));
+            let instructions = write_image_config(config.as_bytes(), &image_dir)?;
+
+            let mut digests = Vec::with_capacity(manifest.layers.len());
+            let mut unpacked = Vec::new();
+            for (index, descriptor) in manifest.layers.iter().enumerate() {
+                if descriptor.media_type.ends_with("+zstd") {
+                    warn!("skipping zstd-compressed layer {} of {image}", descriptor.digest);
+                    continue;
+                }
+                digests.push(descriptor.digest.clone());
+                if seen.contains_key(&descriptor.digest) {
+                    continue;
+                }
+                let hex = descriptor.digest.rsplit(':').next().unwrap_or(&descriptor.digest);
+                let dir = out_dir.join("layers").join(&hex[..hex.len().min(12)]);
+                let mut data = Vec::new();
+                let pulled = match client.pull_blob(&reference, descriptor, &mut data).await {
+                    Ok(()) => unpack_layer(&data[..], &dir)
+                        .with_context(|| format!("unpacking layer {}", descriptor.digest)),
+                    Err(e) => Err(anyhow!("pulling layer {} failed: {e}", descriptor.digest)),
+                };
+                let contents = match pulled {
+                    Ok(contents) => contents,
+                    Err(e) => {
+                        warn!("skipping {image}: {e:#}");
+                        // Drop what this tag alone unpacked, so nothing is
+                        // scanned without its image metadata.
+                        let _ = fs::remove_dir_all(&dir);
+                        for digest in unpacked {
+                            if let Some(layer) = seen.remove(&digest) {
+                                let _ = fs::remove_dir_all(layer.dir);
+                            }
+                        }
+                        let _ = fs::remove_dir_all(&image_dir);
+                        pb.inc(1);
+                        continue 'tags;
+                    }
+                };
+                let info = DockerLayer {
+                    image: image.clone(),
+                    index,
+                    digest: descriptor.digest.clone(),
+                    created_by: instructions.get(index).cloned().flatten(),
+                    hidden: Default::default(),
+                    tags: Vec::new(),
+                };
+                seen.insert(descriptor.digest.clone(), SeenLayer { dir, contents, info });
+                unpacked.push(descriptor.digest.clone());
+            }
+            images.push((image_dir, image.clone()));
+
+            // A shared layer's file counts as present when any tag still has it.
+            let hidden =
+                hidden_files(&digests.iter().map(|d| &seen[d].contents).collect::<Vec<_>>());
+            for (digest, hidden) in digests.iter().zip(hidden) {
+                let layer = &mut seen.get_mut(digest).expect("layer was unpacked").info;
+                if layer.tags.is_empty() {
+                    layer.hidden = hidden;
+                } else {
+                    layer.hidden.retain(|file| hidden.contains(file));
+                }
+                if !layer.tags.contains(&image) {
+                    layer.tags.push(image.clone());
+                }
+            }
+            pb.inc(1);
+        }
+        pb.finish_with_message(format!("scanned {repository}"));
+    }
+
+    let layers = seen.into_values().map(|layer| (layer.dir, layer.info)).collect();
+    Ok(SavedRegistry { dir: out_dir, images, layers })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn loopback_registries_use_plain_http() {
+        for registry in ["localhost:5000", "127.0.0.1:5000", "[::1]:5000", "localhost"] {
+            assert!(is_loopback_registry(registry), "{registry}");
+        }
+        for registry in ["ghcr.io", "registry.example.com:5000", "10.0.0.5:5000"] {
+            assert!(!is_loopback_registry(registry), "{registry}");
+        }
+    }
+
+    #[test]
+    fn parses_registry_auth_headers() {
+        let challenge = parse_bearer_challenge(
+            r#"Bearer realm="https://auth.docker.io/token",service="registry.docker.io",scope="registry:catalog:*""#,
+        )
+        .unwrap();
+        assert_eq!(challenge["realm"], "https://auth.docker.io/token");
+        assert_eq!(challenge["service"], "registry.docker.io");
+        assert_eq!(challenge["scope"], "registry:catalog:*");
+        assert!(parse_bearer_challenge(r#"Basic realm="registry""#).is_none());
+
+        assert_eq!(
+            next_link(r#"</v2/_catalog?last=app&n=100>; rel="next""#).as_deref(),
+            Some("/v2/_catalog?last=app&n=100")
+        );
+        assert_eq!(next_link(r#"</v2/_catalog?n=100>; rel="prev""#), None);
+    }
+
+    #[test]
+    fn catalog_links_stay_on_the_registry_origin() {
+        let base = "https://registry.example.com:5000";
+        assert_eq!(
+            same_origin_url(base, "/v2/_catalog?last=app&n=100").as_deref(),
+            Some("https://registry.example.com:5000/v2/_catalog?last=app&n=100")
+        );
+        assert_eq!(
+            same_origin_url(base, "https://registry.example.com:5000/v2/_catalog?last=b")
+                .as_deref(),
+            Some("https://registry.example.com:5000/v2/_catalog?last=b")
+        );
+        for other in [
+            "https://attacker.example/v2/_catalog",
+            "http://registry.example.com:5000/v2/_catalog",
+            "https://registry.example.com/v2/_catalog",
+            "//attacker.example/v2/_catalog",
+        ] {
+            assert_eq!(same_origin_url(base, other), None, "{other}");
+        }
+    }
+
+    fn tag_names(range: std::ops::Range<usize>) -> Vec<String> {
+        range.map(|i| format!("v{i:04}")).collect()
+    }
+
+    #[tokio::test]
+    async fn tag_pagination_follows_last() -> Result<()> {
+        let all = tag_names(0..250);
+        let tags = collect_tag_pages(|last| {
+            let start = last.map_or(0, |l| all.iter().position(|t| *t == l).unwrap() + 1);
+            let page = all[start..].iter().take(TAG_PAGE_SIZE).cloned().collect();
+            async move { Ok(page) }
+        })
+        .await?;
+        assert_eq!(tags, all);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn tag_pagination_stops_when_registry_ignores_last() -> Result<()> {
+        // Exactly one full page, returned again on every request.
+        let tags = collect_tag_pages(|_| async { Ok(tag_names(0..TAG_PAGE_SIZE)) }).await?;
+        assert_eq!(tags.len(), TAG_PAGE_SIZE);
+
+        // Everything at once, ignoring `n`.
+        let mut requests = 0;
+        let tags = collect_tag_pages(|_| {
+            requests += 1;
+            async { Ok(tag_names(0..300)) }
+        })
+        .await?;
+        assert_eq!((tags.len(), requests), (300, 1));
+        Ok(())
+    }
+}
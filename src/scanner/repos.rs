@@ -15,6 +15,7 @@ use crate::blob::BlobIdMap;
 use crate::{
     azure, bitbucket,
     blob::BlobMetadata,
+    ci_logs,
     cli::{
         commands::{github::GitCloneMode, github::GitHistoryMode, scan},
         global,
@@ -42,6 +43,17 @@ fn repo_host_contains(repo_url: &GitUrl, needle: &str) -> bool {
         .unwrap_or(false)
 }
 
+/// Whether the configured `api_url` is on the same host as `repo_url`, as for
+/// self-hosted GitHub Enterprise or GitLab instances.
+fn repo_host_served_by(repo_url: &GitUrl, api_url: &Url) -> bool {
+    let repo_host =
+        Url::parse(repo_url.as_str()).ok().and_then(|u| u.host_str().map(str::to_string));
+    match (repo_host, api_url.host_str()) {
+        (Some(repo_host), Some(api_host)) => repo_host.eq_ignore_ascii_case(api_host),
+        _ => false,
+    }
+}
+
 fn apply_repo_clone_limit(
     repo_urls: &mut Vec<GitUrl>,
     limit: Option<usize>,
@@ -742,6 +754,7 @@ pub async fn fetch_git_host_artifacts(
     bitbucket_api_url: &Url,
     bitbucket_auth: &bitbucket::AuthConfig,
     bitbucket_host: Option<String>,
+    gitea_api_url: &Url,
     global_args: &global::GlobalArgs,
     datastore: &Arc<Mutex<findings_store::FindingsStore>>,
 ) -> Result<Vec<PathBuf>> {
@@ -802,6 +815,75 @@ pub async fn fetch_git_host_artifacts(
                 )
                 .await?,
             );
+        } else if host.contains("gitea")
+            || gitea_api_url.host_str().is_some_and(|expected| expected.eq_ignore_ascii_case(&host))
+        {
+            dirs.extend(
+                gitea::fetch_repo_items(
+                    repo_url,
+                    gitea_api_url,
+                    global_args.ignore_certs,
+                    &output_root,
+                    datastore,
+                )
+                .await?,
+            );
+        } else if huggingface::is_hub_host(&host) {
+            dirs.extend(
+                huggingface::fetch_repo_items(
+                    repo_url,
+                    &huggingface::AuthConfig::from_env(),
+                    global_args.ignore_certs,
+                    &output_root,
+                    datastore,
+                )
+                .await?,
+            );
+        }
+    }
+    Ok(dirs)
+}
+
+pub async fn fetch_ci_logs(
+    repo_urls: &[GitUrl],
+    github_api_url: &Url,
+    gitlab_api_url: &Url,
+    limits: &ci_logs::CiLogLimits,
+    global_args: &global::GlobalArgs,
+    datastore: &Arc<Mutex<findings_store::FindingsStore>>,
+) -> Result<Vec<PathBuf>> {
+    let output_root = {
+        let ds = datastore.lock().unwrap();
+        ds.clone_root()
+    };
+    let mut dirs = Vec::new();
+    for repo_url in repo_urls.iter().filter(|url| !url.as_str().ends_with(".wiki.git")) {
+        if repo_host_contains(repo_url, "github") || repo_host_served_by(repo_url, github_api_url) {
+            dirs.extend(
+                github::fetch_ci_logs(
+                    repo_url,
+                    github_api_url,
+                    global_args.ignore_certs,
+                    &output_root,
+                    limits,
+                    datastore,
+                )
+                .await?,
+            );
+        } else if repo_host_contains(repo_url, "gitlab")
+            || repo_host_served_by(repo_url, gitlab_api_url)
+        {
+            dirs.extend(
+                gitlab::fetch_ci_logs(
+                    repo_url,
+                    gitlab_api_url,
+                    global_args.ignore_certs,
+                    &output_root,
+                    limits,
+                    datastore,
+                )
+                .await?,
+            );
         }
     }
     Ok(dirs)
@@ -835,7 +917,8 @@ pub async fn fetch_s3_objects(
         &args.extra_ignore_comments,
         args.no_inline_ignore,
         !args.no_ignore_if_contains,
-    )?;
+    )?
+    .keep_inline_suppressions(args.show_suppressed);
     let mut processor = BlobProcessor { matcher };
 
     let progress = if progress_enabled {
@@ -917,7 +1000,8 @@ pub async fn fetch_gcs_objects(
         &args.extra_ignore_comments,
         args.no_inline_ignore,
         !args.no_ignore_if_contains,
-    )?;
+    )?
+    .keep_inline_suppressions(args.show_suppressed);
     let mut processor = BlobProcessor { matcher };
 
     let progress = if progress_enabled {
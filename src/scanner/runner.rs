@@ -16,7 +16,10 @@ use tokio::time::{Duration, Instant};
 use tracing::{debug, error, error_span, info, trace};
 
 use crate::{
-    access_map, azure, bitbucket,
+    access_map,
+    allowlist::Allowlist,
+    azure, bitbucket,
+    ci_logs::CiLogLimits,
     cli::{commands::scan, global},
     findings_store,
     findings_store::{FindingsStore, FindingsStoreMessage},
@@ -33,15 +36,16 @@ use crate::{
         clone_or_update_git_repos_streaming, enumerate_azure_repos, enumerate_bitbucket_repos,
         enumerate_filesystem_inputs, enumerate_github_repos, enumerate_huggingface_repos,
         repos::{
-            enumerate_gitea_repos, enumerate_gitlab_repos, fetch_confluence_pages,
+            enumerate_gitea_repos, enumerate_gitlab_repos, fetch_ci_logs, fetch_confluence_pages,
             fetch_gcs_objects, fetch_git_host_artifacts, fetch_jira_issues, fetch_s3_objects,
             fetch_slack_messages,
         },
-        run_secret_validation, save_docker_images,
+        run_secret_validation, save_docker_images, save_registry_repositories,
         summary::{compute_scan_totals, print_scan_summary},
         AccessMapCollector,
     },
     util::set_redaction_enabled,
+    validation_cache::ValidationCache,
 };
 
 pub async fn run_scan(
@@ -167,12 +171,30 @@ pub async fn run_async_scan(
             &args.input_specifier_args.bitbucket_api_url,
             &bitbucket_auth,
             bitbucket_host.clone(),
+            &args.input_specifier_args.gitea_api_url,
             global_args,
             &datastore,
         )
         .await?;
         input_roots.extend(repo_artifact_dirs);
     }
+    // Fetch CI job logs and artifacts if enabled
+    if args.input_specifier_args.ci_logs {
+        let limits = CiLogLimits {
+            max_age_days: args.input_specifier_args.ci_logs_max_age_days,
+            max_runs: args.input_specifier_args.ci_logs_max_runs,
+        };
+        let ci_log_dirs = fetch_ci_logs(
+            &repo_urls,
+            &args.input_specifier_args.github_api_url,
+            &args.input_specifier_args.gitlab_api_url,
+            &limits,
+            global_args,
+            &datastore,
+        )
+        .await?;
+        input_roots.extend(ci_log_dirs);
+    }
     // Fetch Jira issues if requested
     let jira_dirs = fetch_jira_issues(args, global_args, &datastore).await?;
     input_roots.extend(jira_dirs);
@@ -197,13 +219,42 @@ pub async fn run_async_scan(
             progress_enabled,
         )
         .await?;
-        for (dir, img) in docker_dirs {
+        for saved in docker_dirs {
             {
                 let mut ds = datastore.lock().unwrap();
-                ds.register_docker_image(dir.clone(), img);
+                ds.register_docker_image(saved.dir.clone(), saved.image);
+                for (layer_dir, layer) in saved.layers {
+                    ds.register_docker_layer(layer_dir, layer);
+                }
+            }
+            input_roots.push(saved.dir);
+        }
+    }
+
+    // Scan every tag of registry repositories if specified
+    if !args.input_specifier_args.registry_repository.is_empty() {
+        let clone_root = {
+            let ds = datastore.lock().unwrap();
+            ds.clone_root()
+        };
+        let saved = save_registry_repositories(
+            &args.input_specifier_args.registry_repository,
+            args.input_specifier_args.all_registry_repositories,
+            &clone_root,
+            progress_enabled,
+            global_args.ignore_certs,
+        )
+        .await?;
+        {
+            let mut ds = datastore.lock().unwrap();
+            for (dir, image) in saved.images {
+                ds.register_docker_image(dir, image);
+            }
+            for (layer_dir, layer) in saved.layers {
+                ds.register_docker_layer(layer_dir, layer);
             }
-            input_roots.push(dir);
         }
+        input_roots.push(saved.dir);
     }
 
     let shared_profiler = Arc::new(ConcurrentRuleProfiler::new());
@@ -245,6 +296,13 @@ pub async fn run_async_scan(
             .unwrap_or_else(|| std::path::PathBuf::from("baseline-file.yaml")),
     );
 
+    let allowlist = match args.allowlist.as_deref() {
+        Some(path) => {
+            Some(Arc::new(Allowlist::load(path, args.input_specifier_args.branch.as_deref())?))
+        }
+        None => None,
+    };
+
     let mut skip_aws_accounts = args.skip_aws_account.clone();
 
     let mut access_map_collector =
@@ -267,6 +325,18 @@ pub async fn run_async_scan(
     }
 
     crate::validation::set_skip_aws_account_ids(skip_aws_accounts);
+    crate::validation::rate_limit::configure((&args.validation_rate).into());
+    crate::validation::egress::configure((&args.validation_egress).into());
+    crate::validation::dry_run::configure(&args.validation_dry_run);
+    crate::validation::dependency_scope::configure(&args.validation_dependency, &input_roots);
+    crate::validation::ssh::configure(&args.validation_ssh);
+
+    let validation_cache = if args.no_validate || args.validation_dry_run.enabled {
+        None
+    } else {
+        ValidationCache::from_args(&args.validation_cache)?.map(Arc::new)
+    };
+    crate::validation::set_persistent_cache(validation_cache.clone());
 
     let repo_roots = expand_repo_roots(&input_roots)?;
     let git_repo_count =
@@ -280,6 +350,7 @@ pub async fn run_async_scan(
             reqwest::Client::builder()
                 .danger_accept_invalid_certs(global_args.ignore_certs)
                 .timeout(Duration::from_secs(30))
+                .redirect(crate::validation::egress::redirect_policy())
                 .build()?,
             Arc::new(SkipMap::new()),
         )))
@@ -339,6 +410,20 @@ pub async fn run_async_scan(
             ds.replace_matches(deduped_arcs);
         }
 
+        if !args.path_overrides.is_empty() {
+            let mut ds = datastore.lock().unwrap();
+            crate::project_config::apply_path_overrides(
+                &mut ds,
+                &args.path_overrides,
+                &input_roots,
+            )?;
+        }
+
+        if let Some(allowlist) = &allowlist {
+            let mut ds = datastore.lock().unwrap();
+            allowlist.apply(&mut ds, &input_roots);
+        }
+
         if args.baseline_file.is_some() || args.manage_baseline {
             let mut ds = datastore.lock().unwrap();
             crate::baseline::apply_baseline(
@@ -371,6 +456,9 @@ pub async fn run_async_scan(
 
         crate::reporter::run(global_args, Arc::clone(&datastore), args)
             .context("Failed to run report command")?;
+        if let Some(allowlist) = &allowlist {
+            allowlist.report_usage();
+        }
         print_scan_summary(
             start_time,
             scan_started_at,
@@ -385,6 +473,10 @@ pub async fn run_async_scan(
             None,
         );
         maybe_hint_access_map(&datastore, args);
+        if let Some(cache) = &validation_cache {
+            cache.save_or_warn();
+        }
+        crate::validation::dry_run::finish()?;
         return Ok(());
     }
 
@@ -422,6 +514,16 @@ pub async fn run_async_scan(
 
     deduplicate_new_matches(&datastore, 0)?;
 
+    if !args.path_overrides.is_empty() {
+        let mut ds = datastore.lock().unwrap();
+        crate::project_config::apply_path_overrides(&mut ds, &args.path_overrides, &repo_roots)?;
+    }
+
+    if let Some(allowlist) = &allowlist {
+        let mut ds = datastore.lock().unwrap();
+        allowlist.apply(&mut ds, &repo_roots);
+    }
+
     if args.baseline_file.is_some() || args.manage_baseline {
         let mut ds = datastore.lock().unwrap();
         crate::baseline::apply_baseline(
@@ -470,6 +572,7 @@ pub async fn run_async_scan(
                 let repo_rules = repo_rules.clone();
                 let base_clone_root = base_clone_root.clone();
                 let baseline_path = Arc::clone(&baseline_path);
+                let allowlist = allowlist.clone();
                 let shared_profiler = Arc::clone(&shared_profiler);
                 let args = args.clone();
                 let root = root.clone();
@@ -504,6 +607,20 @@ pub async fn run_async_scan(
                         )
                         .and_then(|_| deduplicate_new_matches(&repo_datastore, 0))?;
 
+                        if !args.path_overrides.is_empty() {
+                            let mut ds = repo_datastore.lock().unwrap();
+                            crate::project_config::apply_path_overrides(
+                                &mut ds,
+                                &args.path_overrides,
+                                &[root.clone()],
+                            )?;
+                        }
+
+                        if let Some(allowlist) = &allowlist {
+                            let mut ds = repo_datastore.lock().unwrap();
+                            allowlist.apply(&mut ds, &[root.clone()]);
+                        }
+
                         if args.baseline_file.is_some() || args.manage_baseline {
                             let mut ds = repo_datastore.lock().unwrap();
                             crate::baseline::apply_baseline(
@@ -585,6 +702,20 @@ pub async fn run_async_scan(
     if !ran_repo_scan.load(Ordering::Relaxed) {
         deduplicate_new_matches(&datastore, 0)?;
 
+        if !args.path_overrides.is_empty() {
+            let mut ds = datastore.lock().unwrap();
+            crate::project_config::apply_path_overrides(
+                &mut ds,
+                &args.path_overrides,
+                &repo_roots,
+            )?;
+        }
+
+        if let Some(allowlist) = &allowlist {
+            let mut ds = datastore.lock().unwrap();
+            allowlist.apply(&mut ds, &repo_roots);
+        }
+
         if args.baseline_file.is_some() || args.manage_baseline {
             let mut ds = datastore.lock().unwrap();
             crate::baseline::apply_baseline(
@@ -628,6 +759,10 @@ pub async fn run_async_scan(
         None
     };
 
+    if let Some(allowlist) = &allowlist {
+        allowlist.report_usage();
+    }
+
     print_scan_summary(
         start_time,
         scan_started_at,
@@ -647,6 +782,10 @@ pub async fn run_async_scan(
     } else {
         maybe_hint_access_map(&datastore, args);
     }
+    if let Some(cache) = &validation_cache {
+        cache.save_or_warn();
+    }
+    crate::validation::dry_run::finish()?;
     Ok(())
 }
 
@@ -1,4 +1,6 @@
 use std::{
+    collections::{BTreeMap, BTreeSet},
+    path::Path,
     sync::{
         atomic::{AtomicUsize, Ordering},
         Arc, Mutex,
@@ -13,18 +15,22 @@ use futures::{stream, StreamExt};
 use indicatif::{ProgressBar, ProgressStyle};
 use liquid::Parser;
 use reqwest::{Client, StatusCode};
-use rustc_hash::FxHashMap;
+use rustc_hash::{FxHashMap, FxHashSet};
 use tokio::{sync::Notify, time::timeout};
+use tracing::debug;
 
 use crate::{
     access_map::AccessMapRequest,
     blob::BlobId,
+    cli::commands::validation_dependency::DependencyScope,
     findings_store::{FindingsStore, FindingsStoreMessage},
+    inline_ignore::INLINE_DIRECTIVE_SUPPRESSION,
     location::OffsetSpan,
     matcher::{Match, OwnedBlobMatch},
     rules::rule::Validation,
     validation::{
-        collect_variables_and_dependencies, utils, validate_single_match, CachedResponse,
+        collect_variables_and_dependencies, dependency_scope, jwt, utils, validate_single_match,
+        CachedResponse,
     },
     validation_body,
 };
@@ -103,6 +109,12 @@ impl AccessMapCollector {
     }
 }
 
+fn is_inline_suppressed(m: &Match) -> bool {
+    m.suppressed_by
+        .as_deref()
+        .is_some_and(|reason| reason.starts_with(INLINE_DIRECTIVE_SUPPRESSION))
+}
+
 #[allow(clippy::too_many_arguments)]
 pub async fn run_secret_validation(
     datastore: Arc<Mutex<FindingsStore>>,
@@ -126,6 +138,9 @@ pub async fn run_secret_validation(
         let ds = datastore.lock().unwrap();
         let rules = ds.get_rules()?;
         let mut map: FxHashMap<BlobId, Vec<Arc<FindingsStoreMessage>>> = FxHashMap::default();
+        // HS* tokens are checked against every secret in the scan, not just
+        // the ones in this chunk.
+        remember_hmac_candidates(ds.get_matches());
         let matches = if let Some(r) = range.clone() {
             ds.get_matches()[r].to_vec()
         } else {
@@ -138,10 +153,24 @@ pub async fn run_secret_validation(
         (rules, map)
     };
 
+    // Dependencies a blob lacks may come from other blobs in the same scope.
+    let (scope, max_candidates) = dependency_scope::settings();
+    let scope_index = build_scope_index(scope, &all_matches_by_blob);
+
+    // Result accumulator
+    let mut updated_arcs: Vec<Arc<FindingsStoreMessage>> = Vec::new();
+
     // ── 3. Partition blobs ──────────────────────────────────────────────────
+    // Matches silenced by an inline directive are carried through unvalidated.
     let mut simple_matches = Vec::new();
     let mut dependent_blobs = FxHashMap::default(); // blob_id -- Vec<Arc<…>>
     for (blob_id, matches) in all_matches_by_blob {
+        let (suppressed, matches): (Vec<_>, Vec<_>) =
+            matches.into_iter().partition(|m| is_inline_suppressed(&m.2));
+        updated_arcs.extend(suppressed);
+        if matches.is_empty() {
+            continue;
+        }
         if matches.iter().any(|m| !m.2.rule.syntax().depends_on_rule.is_empty()) {
             dependent_blobs.insert(blob_id, matches);
         } else {
@@ -149,9 +178,6 @@ pub async fn run_secret_validation(
         }
     }
 
-    // Result accumulator
-    let mut updated_arcs: Vec<Arc<FindingsStoreMessage>> = Vec::new();
-
     // ── Phase 1: simple, global de-dupe ──────────────────────────────────────
     if !simple_matches.is_empty() {
         let mut groups: FxHashMap<String, Vec<Arc<FindingsStoreMessage>>> = FxHashMap::default();
@@ -212,6 +238,7 @@ pub async fn run_secret_validation(
                             body: validation_body::from_string(String::new()),
                             status: StatusCode::ACCEPTED,
                             is_valid: false,
+                            identity: Default::default(),
                             timestamp: Instant::now(),
                         });
                     }
@@ -243,6 +270,7 @@ pub async fn run_secret_validation(
                     body: om.validation_response_body.clone(),
                     status: om.validation_response_status,
                     is_valid: om.validation_success,
+                    identity: om.validation_identity.clone(),
                     timestamp: Instant::now(),
                 };
                 val_res.insert(key, cr);
@@ -266,6 +294,7 @@ pub async fn run_secret_validation(
                         validation_success: cr.is_valid,
                         validation_response_status: cr.status.as_u16(),
                         validation_response_body: cr.body.clone(),
+                        validation_identity: cr.identity.clone(),
                         ..old_match.clone()
                     },
                 )));
@@ -295,6 +324,7 @@ pub async fn run_secret_validation(
 
         let val_cache = Arc::new(DashMap::<String, CachedResponse>::new());
         let in_flight = Arc::new(DashMap::<String, ()>::new());
+        let scope_index = &scope_index;
 
         for chunk in blob_ids.chunks(chunk_size) {
             let tasks: Vec<_> = chunk
@@ -323,7 +353,16 @@ pub async fn run_secret_validation(
                             })
                             .collect::<Vec<_>>();
 
-                        let (dep_vars, missing_deps) = collect_variables_and_dependencies(&owned);
+                        let (mut dep_vars, mut missing_deps) =
+                            collect_variables_and_dependencies(&owned);
+                        let sources = resolve_scoped_dependencies(
+                            &matches_for_blob,
+                            scope_index,
+                            scope,
+                            max_candidates,
+                            &mut dep_vars,
+                            &mut missing_deps,
+                        );
 
                         let mut by_key: FxHashMap<String, Vec<OwnedBlobMatch>> =
                             FxHashMap::default();
@@ -368,6 +407,7 @@ pub async fn run_secret_validation(
                                             rep.validation_response_body.clone();
                                         d.validation_response_status =
                                             rep.validation_response_status;
+                                        d.validation_identity = rep.validation_identity.clone();
                                     }
                                     let mut out = vec![rep];
                                     out.extend(dups);
@@ -378,15 +418,15 @@ pub async fn run_secret_validation(
                             .collect()
                             .await;
 
-                        validated.into_iter().flatten().collect::<Vec<_>>()
+                        (validated.into_iter().flatten().collect::<Vec<_>>(), sources)
                     }
                 })
                 .collect();
 
-            let validated_blobs: Vec<Vec<OwnedBlobMatch>> =
+            let validated_blobs: Vec<(Vec<OwnedBlobMatch>, BTreeMap<String, String>)> =
                 stream::iter(tasks).buffer_unordered(concurrency).collect().await;
 
-            for blob_vec in validated_blobs {
+            for (blob_vec, sources) in validated_blobs {
                 if blob_vec.is_empty() {
                     continue;
                 }
@@ -408,6 +448,8 @@ pub async fn run_secret_validation(
                             validation_success: om.validation_success,
                             validation_response_body: om.validation_response_body.clone(),
                             validation_response_status: om.validation_response_status.as_u16(),
+                            validation_identity: om.validation_identity.clone(),
+                            dependency_sources: dependency_sources_for(&orig.2, &sources),
                             ..orig.2.clone()
                         },
                     )));
@@ -427,6 +469,151 @@ pub async fn run_secret_validation(
     Ok(())
 }
 
+/// Offer every captured secret as a candidate HMAC key for JWT verification.
+fn remember_hmac_candidates(matches: &[Arc<FindingsStoreMessage>]) {
+    for msg in matches.iter().filter(|m| !is_inline_suppressed(&m.2)) {
+        for capture in msg.2.groups.captures.iter() {
+            jwt::remember_hmac_secret(capture.raw_value(), || match dependency_scope::source_path(
+                msg.0.first(),
+            ) {
+                Some(path) => format!("{} in {path}", msg.2.rule.id()),
+                None => msg.2.rule.id().to_string(),
+            });
+        }
+    }
+}
+
+/// Matches grouped by dependency scope key, then by rule id.
+type ScopeIndex = FxHashMap<String, FxHashMap<String, Vec<Arc<FindingsStoreMessage>>>>;
+
+/// The dependency scope keys of the blob `matches` were found in.
+fn blob_scope_keys(
+    scope: DependencyScope,
+    matches: &[Arc<FindingsStoreMessage>],
+) -> BTreeSet<String> {
+    let roots = dependency_scope::input_roots();
+    matches
+        .first()
+        .map(|first| {
+            first.0.iter().filter_map(|o| dependency_scope::scope_key(scope, o, roots)).collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Index the matches of every blob under the dependency scope its origins
+/// belong to and by rule, so a dependency is looked up directly. Empty for the
+/// blob scope.
+fn build_scope_index(
+    scope: DependencyScope,
+    matches_by_blob: &FxHashMap<BlobId, Vec<Arc<FindingsStoreMessage>>>,
+) -> ScopeIndex {
+    let mut index = ScopeIndex::default();
+    if scope == DependencyScope::Blob {
+        return index;
+    }
+    for matches in matches_by_blob.values() {
+        for key in blob_scope_keys(scope, matches) {
+            let by_rule = index.entry(key).or_default();
+            for m in matches.iter().filter(|m| !is_inline_suppressed(&m.2)) {
+                by_rule.entry(m.2.rule.id().to_string()).or_default().push(Arc::clone(m));
+            }
+        }
+    }
+    index
+}
+
+/// Fill in the dependencies a blob's own matches cannot satisfy from other
+/// blobs sharing one of its dependency scopes, removing them from
+/// `missing_deps`. Returns the file that supplied each dependency variable.
+fn resolve_scoped_dependencies(
+    blob_matches: &[Arc<FindingsStoreMessage>],
+    scope_index: &ScopeIndex,
+    scope: DependencyScope,
+    max_candidates: usize,
+    dep_vars: &mut FxHashMap<String, Vec<(String, OffsetSpan)>>,
+    missing_deps: &mut FxHashMap<String, Vec<String>>,
+) -> BTreeMap<String, String> {
+    let mut sources = BTreeMap::new();
+    let Some(first) = blob_matches.first() else {
+        return sources;
+    };
+    if missing_deps.is_empty() || scope_index.is_empty() {
+        return sources;
+    }
+    let blob_id = first.2.blob_id;
+    let near = dependency_scope::source_path(first.0.first()).unwrap_or_default();
+    let keys = blob_scope_keys(scope, blob_matches);
+
+    let mut resolved: FxHashSet<String> = FxHashSet::default();
+    for msg in blob_matches {
+        let Some(missing) = missing_deps.get(msg.2.rule.id()) else {
+            continue;
+        };
+        for dep in msg.2.rule.syntax().depends_on_rule.iter().flatten() {
+            if !missing.contains(&dep.rule_id) || resolved.contains(&dep.rule_id) {
+                continue;
+            }
+            let candidates: Vec<(&str, String)> = keys
+                .iter()
+                .filter_map(|key| scope_index.get(key)?.get(&dep.rule_id))
+                .flatten()
+                .filter(|c| c.2.blob_id != blob_id)
+                .filter_map(|c| {
+                    let capture =
+                        c.2.groups.captures.get(1).or_else(|| c.2.groups.captures.get(0))?;
+                    Some((capture.raw_value(), dependency_scope::source_path(c.0.first())?))
+                })
+                .collect();
+            let chosen = dependency_scope::choose(
+                Path::new(&near),
+                candidates.iter().map(|(value, path)| (*value, path.as_str())),
+                max_candidates,
+            );
+            let Some((value, path)) = chosen else {
+                debug!(
+                    "No unambiguous `{}` value for {} among {} candidates in scope",
+                    dep.rule_id,
+                    msg.2.rule.id(),
+                    candidates.len()
+                );
+                continue;
+            };
+            // Offsets in another file mean nothing here; the value is the only
+            // candidate for its variable, so it is paired regardless.
+            let variable = dep.variable.to_uppercase();
+            dep_vars
+                .entry(variable.clone())
+                .or_default()
+                .push((value, OffsetSpan::from_range(0..0)));
+            sources.insert(variable, path);
+            resolved.insert(dep.rule_id.clone());
+        }
+    }
+
+    missing_deps.retain(|_, deps| {
+        deps.retain(|d| !resolved.contains(d));
+        !deps.is_empty()
+    });
+    sources
+}
+
+/// The entries of `sources` for the variables the match's rule depends on.
+fn dependency_sources_for(
+    m: &Match,
+    sources: &BTreeMap<String, String>,
+) -> BTreeMap<String, String> {
+    m.rule
+        .syntax()
+        .depends_on_rule
+        .iter()
+        .flatten()
+        .filter_map(|dep| {
+            let variable = dep.variable.to_uppercase();
+            sources.get(&variable).map(|path| (variable, path.clone()))
+        })
+        .collect()
+}
+
 // ---------------------------------------------------
 // The core validation logic, used in an async pipeline
 // ---------------------------------------------------
@@ -461,6 +648,7 @@ async fn validate_single(
         om.validation_success = cached.is_valid;
         om.validation_response_body = cached.body.clone();
         om.validation_response_status = cached.status;
+        om.validation_identity = cached.identity.clone();
         if om.validation_success {
             success_count.fetch_add(1, Ordering::Relaxed);
         } else if om.validation_response_status != http::StatusCode::CONTINUE {
@@ -482,6 +670,7 @@ async fn validate_single(
             om.validation_success = cached.is_valid;
             om.validation_response_body = cached.body.clone();
             om.validation_response_status = cached.status;
+            om.validation_identity = cached.identity.clone();
             if om.validation_success {
                 success_count.fetch_add(1, Ordering::Relaxed);
             } else if om.validation_response_status != http::StatusCode::CONTINUE {
@@ -522,6 +711,7 @@ async fn validate_single(
                     is_valid: om.validation_success,
                     status: om.validation_response_status,
                     body: om.validation_response_body.clone(),
+                    identity: om.validation_identity.clone(),
                     timestamp: Instant::now(),
                 },
             );
@@ -562,7 +752,7 @@ fn build_cache_key(
     format!("{}|{}|{}", om.rule.name(), capture0, dep_vars_str)
 }
 
-fn maybe_record_access_map(om: &OwnedBlobMatch, collector: Option<&AccessMapCollector>) {
+pub(crate) fn maybe_record_access_map(om: &OwnedBlobMatch, collector: Option<&AccessMapCollector>) {
     let is_gitlab_rule = om.rule.id().starts_with("kingfisher.gitlab.");
     let validation_ok =
         om.validation_success || (is_gitlab_rule && om.validation_response_status.is_success());
@@ -2,7 +2,7 @@ use std::{
     collections::BTreeMap,
     fs,
     hash::{Hash, Hasher},
-    sync::Arc,
+    sync::{Arc, RwLock},
     time::{Duration, Instant},
 };
 
@@ -21,20 +21,32 @@ use tracing::debug;
 use crate::{
     location::OffsetSpan,
     matcher::{OwnedBlobMatch, SerializableCaptures},
-    rules::rule::Validation,
-    validation_body::{self, ValidationResponseBody},
+    rules::rule::{HttpRequest, HttpStep, HttpValidation, Validation},
+    validation_body::{self, ValidationIdentity, ValidationResponseBody},
+    validation_cache::{ResultClass, ValidationCache},
 };
 
-mod aws;
+pub(crate) mod aws;
 mod azure;
 mod coinbase;
+pub mod dependency_scope;
+pub mod dry_run;
+pub mod egress;
 pub mod gcp;
 mod httpvalidation;
 mod jdbc;
-mod jwt;
+pub mod json_match;
+pub mod jwt;
+mod ldap;
+mod memcached;
 mod mongodb;
 mod mysql;
 mod postgres;
+pub mod rate_limit;
+mod redis;
+mod smtp;
+pub mod ssh;
+pub use ldap::validate_ldap;
 pub use mysql::validate_mysql;
 pub use postgres::validate_postgres;
 pub mod utils;
@@ -105,6 +117,17 @@ fn secret_fingerprint(m: &OwnedBlobMatch) -> u64 {
 
 static VALIDATION_CACHE: OnceCell<DashMap<u64, CachedResponse>> = OnceCell::new();
 static IN_FLIGHT: OnceCell<DashMap<u64, Arc<Notify>>> = OnceCell::new();
+static PERSISTENT_CACHE: RwLock<Option<Arc<ValidationCache>>> = RwLock::new(None);
+
+/// Serve and record validation results through an on-disk cache, replacing any
+/// cache installed by an earlier run; `None` stops using one.
+pub fn set_persistent_cache(cache: Option<Arc<ValidationCache>>) {
+    *PERSISTENT_CACHE.write().unwrap_or_else(|e| e.into_inner()) = cache;
+}
+
+fn persistent_cache() -> Option<Arc<ValidationCache>> {
+    PERSISTENT_CACHE.read().unwrap_or_else(|e| e.into_inner()).clone()
+}
 
 /// Call this once near program start (e.g. in `main()`)
 pub fn init_validation_caches() {
@@ -136,17 +159,34 @@ pub fn is_parseable_mysql_uri(uri: &str) -> bool {
     mysql::parse_mysql_url(uri).is_ok()
 }
 
+/// Returns `true` if the provided string can be parsed as a Redis connection URI.
+pub fn is_parseable_redis_uri(uri: &str) -> bool {
+    redis::parse_redis_url(uri).is_ok()
+}
+
+/// Returns `true` if the provided string can be parsed as a Memcached connection URI.
+pub fn is_parseable_memcached_uri(uri: &str) -> bool {
+    memcached::parse_memcached_url(uri).is_ok()
+}
+
 #[derive(Clone)]
 pub struct CachedResponse {
     pub body: ValidationResponseBody,
     pub status: StatusCode,
     pub is_valid: bool,
+    pub identity: ValidationIdentity,
     pub timestamp: Instant,
 }
 
 impl CachedResponse {
     pub fn new(body: ValidationResponseBody, status: StatusCode, is_valid: bool) -> Self {
-        Self { body, status, is_valid, timestamp: Instant::now() }
+        Self {
+            body,
+            status,
+            is_valid,
+            identity: ValidationIdentity::new(),
+            timestamp: Instant::now(),
+        }
     }
 
     pub fn is_still_valid(&self, cache_duration: Duration) -> bool {
@@ -193,35 +233,250 @@ pub fn collect_variables_and_dependencies(
     (variable_map, missing_deps)
 }
 
-/// Render a template and parse the resulting string as a URL.
+/// Append the values `m`'s rule takes from its `depends_on_rule` dependencies
+/// to its captured values.
+fn add_dependency_values(
+    m: &OwnedBlobMatch,
+    dependent_variables: &FxHashMap<String, Vec<(String, OffsetSpan)>>,
+    captured_values: &mut Vec<(String, String, usize, usize)>,
+) {
+    for dep in m.rule.syntax().depends_on_rule.iter().flatten() {
+        if let Some(vals) = dependent_variables.get(&dep.variable.to_uppercase()) {
+            for (val, span) in vals {
+                // Skip adding captured values for TOKEN dependencies
+                if dep.variable.eq_ignore_ascii_case("TOKEN") {
+                    continue;
+                }
+                captured_values.push((
+                    dep.variable.to_uppercase(),
+                    val.clone(),
+                    span.start,
+                    span.end,
+                ));
+            }
+        }
+    }
+}
+
+/// The captured values of `m` together with its dependency values, as seen by
+/// validator templates.
+pub(crate) fn captured_values_for(
+    m: &OwnedBlobMatch,
+    dependent_variables: &FxHashMap<String, Vec<(String, OffsetSpan)>>,
+) -> Vec<(String, String, usize, usize)> {
+    let mut captured_values = utils::process_captures(&m.captures);
+    add_dependency_values(m, dependent_variables, &mut captured_values);
+    captured_values
+}
+
+/// Send an HTTP request sequence outside of validation, such as a rule's
+/// `revocation` block, with the same templating, egress policy and retries as
+/// an HTTP validator. Without a `response_matcher`, any 2xx final response
+/// succeeds. Returns whether it did, with the final status and body.
+#[allow(clippy::too_many_arguments)]
+pub(crate) async fn send_http_sequence(
+    http: &HttpValidation,
+    rule_id: &str,
+    rule_name: &str,
+    captured_values: &[(String, String, usize, usize)],
+    parser: &liquid::Parser,
+    client: &Client,
+    timeout: Duration,
+    max_retries: u32,
+) -> (bool, StatusCode, String) {
+    let mut globals = Object::new();
+    populate_globals_from_captures(&mut globals, captured_values);
+    if let Err((status, e)) = run_http_steps(
+        &http.steps,
+        rule_id,
+        rule_name,
+        captured_values,
+        parser,
+        &mut globals,
+        client,
+        timeout,
+        max_retries,
+    )
+    .await
+    {
+        return (false, status, e);
+    }
+    let url = match render_and_parse_url(parser, &globals, rule_name, &http.request).await {
+        Ok(url) => url,
+        Err((status, e)) => return (false, status, e),
+    };
+    let builder = match httpvalidation::build_request_builder(
+        client,
+        &http.request.method,
+        &url,
+        &http.request.headers,
+        &http.request.body,
+        timeout,
+        parser,
+        &globals,
+    ) {
+        Ok(builder) => builder,
+        Err(e) => return (false, StatusCode::BAD_REQUEST, e),
+    };
+    let resp = match httpvalidation::retry_request(
+        builder,
+        max_retries,
+        Duration::from_millis(500),
+        Duration::from_secs(2),
+    )
+    .await
+    {
+        Ok(resp) => resp,
+        Err(e) => return (false, StatusCode::BAD_GATEWAY, format!("HTTP error: {e:?}")),
+    };
+    let status = resp.status();
+    let headers = resp.headers().clone();
+    let mut body = match resp.text().await {
+        Ok(body) => body,
+        Err(e) => return (false, StatusCode::BAD_GATEWAY, format!("Error reading response: {e}")),
+    };
+    let passed = match http.request.response_matcher.as_deref() {
+        Some(matchers) if !matchers.is_empty() => httpvalidation::validate_response(
+            matchers,
+            &body,
+            &status,
+            &headers,
+            http.request.response_is_html,
+        ),
+        _ => status.is_success(),
+    };
+    truncate_to_char_boundary(&mut body, MAX_VALIDATION_BODY_LEN);
+    (passed, status, body)
+}
+
+/// Render a request's URL template and parse the result, refusing hosts the
+/// rule or the egress policy does not allow. Refusals map to `CONTINUE` so the
+/// finding is reported as not attempted rather than invalid.
 async fn render_and_parse_url(
     parser: &liquid::Parser,
     globals: &liquid::Object,
     rule_name: &str,
-    template_url: &str,
-) -> Result<Url, String> {
+    request: &HttpRequest,
+) -> Result<Url, (StatusCode, String)> {
     let rendered_url_str =
-        render_template(parser, globals, rule_name, template_url).await.map_err(|e| {
+        render_template(parser, globals, rule_name, &request.url).await.map_err(|e| {
             let error_msg = format!("Error rendering URL template: <{}> {}", rule_name, e);
             debug!("{}", error_msg);
-            error_msg
+            (StatusCode::BAD_REQUEST, error_msg)
         })?;
 
     let url = Url::parse(&rendered_url_str).map_err(|e| {
         let error_msg = format!("Error parsing rendered URL: {}", e);
         debug!("{}", error_msg);
-        error_msg
+        (StatusCode::BAD_REQUEST, error_msg)
     })?;
 
-    // Check if the URL is resolvable.
-    utils::check_url_resolvable(&url).await.map_err(|e| {
-        let error_msg = format!("URL <{}> resolution failed: {}", &url, e);
-        error_msg
+    // Enforce the egress policy before the host is resolved.
+    egress::check_request_url(request, &url).map_err(|e| {
+        debug!("<{}> {}", rule_name, e);
+        (StatusCode::CONTINUE, format!("Validation skipped - {}", e))
     })?;
 
+    // Check if the URL is resolvable. A dry run does not touch the network at all.
+    if !dry_run::enabled() {
+        utils::check_url_resolvable(&url).await.map_err(|e| {
+            let error_msg = format!("URL <{}> resolution failed: {}", &url, e);
+            (StatusCode::BAD_REQUEST, error_msg)
+        })?;
+    }
+
     Ok(url)
 }
 
+/// Send the preliminary `steps` of a multi-step HTTP validation in order,
+/// adding the values each step extracts to `globals` for the requests after it.
+/// On failure, returns the status and body to report for the match.
+#[allow(clippy::too_many_arguments)]
+async fn run_http_steps(
+    steps: &[HttpStep],
+    rule_id: &str,
+    rule_name: &str,
+    captured_values: &[(String, String, usize, usize)],
+    parser: &liquid::Parser,
+    globals: &mut Object,
+    client: &Client,
+    timeout: Duration,
+    max_retries: u32,
+) -> Result<(), (StatusCode, String)> {
+    for (index, step) in steps.iter().enumerate() {
+        let step_no = index + 1;
+        let url = render_and_parse_url(parser, globals, rule_name, &step.request).await?;
+        let builder = httpvalidation::build_request_builder(
+            client,
+            &step.request.method,
+            &url,
+            &step.request.headers,
+            &step.request.body,
+            timeout,
+            parser,
+            globals,
+        )
+        .map_err(|e| (StatusCode::BAD_REQUEST, format!("Step {step_no}: {e}")))?;
+
+        if dry_run::enabled() {
+            let secrets = captured_values.iter().map(|(n, v, ..)| (n.as_str(), v.as_str()));
+            dry_run::record(rule_id, rule_name, builder, Vec::new(), secrets)
+                .map_err(|e| (StatusCode::BAD_REQUEST, format!("{e:#}")))?;
+            // Nothing was sent, so later steps see a placeholder for each extracted value.
+            for name in step.extract.keys() {
+                globals.insert(name.clone().into(), Value::scalar(format!("[EXTRACTED:{name}]")));
+            }
+            continue;
+        }
+
+        let resp = httpvalidation::retry_request(
+            builder,
+            max_retries,
+            Duration::from_millis(500),
+            Duration::from_secs(2),
+        )
+        .await
+        .map_err(|e| {
+            if e.is::<rate_limit::BudgetExhausted>() || e.is::<egress::EgressDenied>() {
+                (StatusCode::CONTINUE, format!("Validation skipped - {e}"))
+            } else {
+                (StatusCode::BAD_GATEWAY, format!("Step {step_no}: HTTP error: {e:?}"))
+            }
+        })?;
+        let status = resp.status();
+        let headers = resp.headers().clone();
+        let mut body = resp.text().await.map_err(|e| {
+            (StatusCode::BAD_GATEWAY, format!("Step {step_no}: error reading response: {e}"))
+        })?;
+
+        let passed = match step.request.response_matcher.as_deref() {
+            Some(matchers) if !matchers.is_empty() => httpvalidation::validate_response(
+                matchers,
+                &body,
+                &status,
+                &headers,
+                step.request.response_is_html,
+            ),
+            _ => status.is_success(),
+        };
+        if !passed {
+            truncate_to_char_boundary(&mut body, MAX_VALIDATION_BODY_LEN);
+            return Err((status, format!("Step {step_no} failed: {body}")));
+        }
+        for (name, extractor) in &step.extract {
+            let value =
+                httpvalidation::extract_value(extractor, &body, &headers).ok_or_else(|| {
+                    (
+                        status,
+                        format!("Step {step_no}: could not extract `{name}` from the response"),
+                    )
+                })?;
+            globals.insert(name.clone().into(), Value::scalar(value));
+        }
+    }
+    Ok(())
+}
+
 /// Render a template string using Liquid.
 async fn render_template(
     parser: &liquid::Parser,
@@ -256,7 +511,12 @@ pub async fn validate_single_match(
     validation_timeout: Duration,
     validation_retries: u32,
 ) {
-    let timeout_result = time::timeout(validation_timeout, async {
+    let rule_id = m.rule.syntax().id.clone();
+    let redirect_hosts = match &m.rule.syntax().validation {
+        Some(Validation::Http(http)) => egress::http_hosts(http),
+        _ => Vec::new(),
+    };
+    let validation = rate_limit::with_rule(&rule_id, async {
         timed_validate_single_match(
             m,
             parser,
@@ -268,8 +528,11 @@ pub async fn validate_single_match(
             validation_retries,
         )
         .await
-    })
-    .await;
+    });
+    // Redirects may only lead to hosts the rule's requests declare.
+    let timeout_result =
+        time::timeout(validation_timeout, egress::with_redirect_hosts(redirect_hosts, validation))
+            .await;
 
     if timeout_result.is_err() {
         m.validation_success = false;
@@ -304,6 +567,7 @@ async fn timed_validate_single_match<'a>(
             m.validation_success = entry.is_valid;
             m.validation_response_body = entry.body.clone();
             m.validation_response_status = entry.status;
+            m.validation_identity = entry.identity.clone();
             return;
         }
     }
@@ -313,9 +577,17 @@ async fn timed_validate_single_match<'a>(
             m.validation_success = entry.is_valid;
             m.validation_response_body = entry.body.clone();
             m.validation_response_status = entry.status;
+            m.validation_identity = entry.identity.clone();
         }
         return;
     }
+    if let Some(entry) = persistent_cache().and_then(|cache| cache.get(fp)) {
+        m.validation_success = entry.class == ResultClass::Valid;
+        m.validation_response_status = entry.status_code();
+        m.validation_response_body = validation_body::from_string(entry.body);
+        m.validation_identity = entry.identity;
+        return;
+    }
     let notify = Arc::new(Notify::new());
     IN_FLIGHT.get().unwrap().insert(fp, notify.clone());
 
@@ -327,9 +599,20 @@ async fn timed_validate_single_match<'a>(
                 body: m.validation_response_body.clone(),
                 status: m.validation_response_status,
                 is_valid: m.validation_success,
+                identity: m.validation_identity.clone(),
                 timestamp: Instant::now(),
             },
         );
+        if let Some(cache) = persistent_cache() {
+            cache.record_with_identity(
+                fp,
+                &m.rule.syntax().id,
+                m.validation_success,
+                m.validation_response_status,
+                validation_body::as_str(&m.validation_response_body),
+                &m.validation_identity,
+            );
+        }
         IN_FLIGHT.get().unwrap().remove(&fp);
         notify.notify_waiters();
     };
@@ -348,6 +631,34 @@ async fn timed_validate_single_match<'a>(
             return;
         }
     }
+    if m.rule.syntax().validation.is_some() && rate_limit::limiter().budget_exhausted() {
+        m.validation_success = false;
+        m.validation_response_body = validation_body::from_string(
+            "Validation skipped - request budget exhausted".to_string(),
+        );
+        m.validation_response_status = StatusCode::CONTINUE;
+        commit_and_return(m);
+        return;
+    }
+    if let Some(validation) = &m.rule.syntax().validation {
+        if let Err(reason) = egress::policy().check_validation(validation) {
+            m.validation_success = false;
+            m.validation_response_body =
+                validation_body::from_string(format!("Validation skipped - {}", reason));
+            m.validation_response_status = StatusCode::CONTINUE;
+            commit_and_return(m);
+            return;
+        }
+        if dry_run::enabled() && !matches!(validation, Validation::Http(_)) {
+            m.validation_success = false;
+            m.validation_response_body = validation_body::from_string(
+                "Validation skipped - dry run only exports HTTP validators".to_string(),
+            );
+            m.validation_response_status = StatusCode::CONTINUE;
+            commit_and_return(m);
+            return;
+        }
+    }
 
     // 3. capture processing
     let match_re_result = m.rule.syntax().as_anchored_regex();
@@ -363,22 +674,7 @@ async fn timed_validate_single_match<'a>(
         }
     };
 
-    for dep in m.rule.syntax().depends_on_rule.iter().flatten() {
-        if let Some(vals) = dependent_variables.get(&dep.variable.to_uppercase()) {
-            for (val, span) in vals {
-                // Skip adding captured values for TOKEN dependencies
-                if dep.variable.eq_ignore_ascii_case("TOKEN") {
-                    continue;
-                }
-                captured_values.push((
-                    dep.variable.to_uppercase(),
-                    val.clone(),
-                    span.start,
-                    span.end,
-                ));
-            }
-        }
-    }
+    add_dependency_values(m, dependent_variables, &mut captured_values);
 
     let mut globals = Object::new();
     populate_globals_from_captures(&mut globals, &captured_values);
@@ -394,20 +690,40 @@ async fn timed_validate_single_match<'a>(
             let request_timeout = validation_timeout;
             let multipart_timeout = validation_timeout;
             let max_retries: u32 = validation_retries;
+            // multi-step: earlier requests supply variables for the final one
+            if let Err((status, e)) = run_http_steps(
+                &http_validation.steps,
+                &rule_syntax.id,
+                &rule_syntax.name,
+                &captured_values,
+                parser,
+                &mut globals,
+                client,
+                request_timeout,
+                max_retries,
+            )
+            .await
+            {
+                m.validation_success = false;
+                m.validation_response_body = validation_body::from_string(e);
+                m.validation_response_status = status;
+                commit_and_return(m);
+                return;
+            }
             // render URL
             let url = match render_and_parse_url(
                 parser,
                 &globals,
                 &rule_syntax.name,
-                &http_validation.request.url,
+                &http_validation.request,
             )
             .await
             {
                 Ok(u) => u,
-                Err(e) => {
+                Err((status, e)) => {
                     m.validation_success = false;
                     m.validation_response_body = validation_body::from_string(e);
-                    m.validation_response_status = StatusCode::BAD_REQUEST;
+                    m.validation_response_status = status;
                     commit_and_return(m);
                     return;
                 }
@@ -434,6 +750,40 @@ async fn timed_validate_single_match<'a>(
                 }
             };
 
+            // dry run: export the rendered request instead of sending it
+            if dry_run::enabled() {
+                let mut parts = Vec::new();
+                for part in http_validation.request.multipart.iter().flat_map(|mp| &mp.parts) {
+                    parts.push(dry_run::FormPart {
+                        name: part.name.clone(),
+                        is_file: part.part_type == "file",
+                        value: render_template(parser, &globals, &rule_syntax.name, &part.content)
+                            .await
+                            .unwrap_or_default(),
+                        content_type: part.content_type.clone(),
+                    });
+                }
+                let secrets = captured_values.iter().map(|(n, v, ..)| (n.as_str(), v.as_str()));
+                let (status, body) = match dry_run::record(
+                    &rule_syntax.id,
+                    &rule_syntax.name,
+                    request_builder,
+                    parts,
+                    secrets,
+                ) {
+                    Ok(()) => (
+                        StatusCode::CONTINUE,
+                        "Validation skipped - dry run; request exported".to_string(),
+                    ),
+                    Err(e) => (StatusCode::BAD_REQUEST, format!("{e:#}")),
+                };
+                m.validation_success = false;
+                m.validation_response_body = validation_body::from_string(body);
+                m.validation_response_status = status;
+                commit_and_return(m);
+                return;
+            }
+
             let is_multipart = http_validation.request.multipart.is_some();
             let mut cache_key = String::new();
 
@@ -464,6 +814,7 @@ async fn timed_validate_single_match<'a>(
                         m.validation_success = c.is_valid;
                         m.validation_response_body = c.body.clone();
                         m.validation_response_status = c.status;
+                        m.validation_identity = c.identity.clone();
                         commit_and_return(m);
                         return;
                     }
@@ -585,6 +936,9 @@ async fn timed_validate_single_match<'a>(
                             return;
                         }
                     };
+                    // Identity fields may sit past the stored body limit, so read them first.
+                    let identity =
+                        httpvalidation::extract_identity(&rule_syntax.extract, &body, &headers);
                     truncate_to_char_boundary(&mut body, MAX_VALIDATION_BODY_LEN);
 
                     m.validation_response_status = status;
@@ -603,6 +957,9 @@ async fn timed_validate_single_match<'a>(
                         &headers,
                         http_validation.request.response_is_html,
                     );
+                    if m.validation_success {
+                        m.validation_identity = identity;
+                    }
 
                     if !is_multipart && !cache_key.is_empty() {
                         cache.insert(
@@ -611,11 +968,20 @@ async fn timed_validate_single_match<'a>(
                                 body: body_opt,
                                 status,
                                 is_valid: m.validation_success,
+                                identity: m.validation_identity.clone(),
                                 timestamp: Instant::now(),
                             },
                         );
                     }
                 }
+                Err(e)
+                    if e.is::<rate_limit::BudgetExhausted>() || e.is::<egress::EgressDenied>() =>
+                {
+                    m.validation_success = false;
+                    m.validation_response_body =
+                        validation_body::from_string(format!("Validation skipped - {e}"));
+                    m.validation_response_status = StatusCode::CONTINUE;
+                }
                 Err(e) => {
                     m.validation_success = false;
                     m.validation_response_body =
@@ -642,6 +1008,15 @@ async fn timed_validate_single_match<'a>(
                 return;
             }
 
+            if let Err(e) = egress::check_connection_string(&uri) {
+                m.validation_success = false;
+                m.validation_response_body =
+                    validation_body::from_string(format!("Validation skipped - {}", e));
+                m.validation_response_status = StatusCode::CONTINUE;
+                commit_and_return(m);
+                return;
+            }
+
             let cache_key = mongodb::generate_mongodb_cache_key(&uri);
             if let Some(cached) = cache.get(&cache_key) {
                 let c = cached.value();
@@ -687,6 +1062,15 @@ async fn timed_validate_single_match<'a>(
                 return;
             }
 
+            if let Err(e) = egress::check_connection_string(&mysql_url) {
+                m.validation_success = false;
+                m.validation_response_body =
+                    validation_body::from_string(format!("Validation skipped - {}", e));
+                m.validation_response_status = StatusCode::CONTINUE;
+                commit_and_return(m);
+                return;
+            }
+
             let cache_key = mysql::generate_mysql_cache_key(&mysql_url);
             if let Some(cached) = cache.get(&cache_key) {
                 let c = cached.value();
@@ -724,6 +1108,145 @@ async fn timed_validate_single_match<'a>(
                     body: m.validation_response_body.clone(),
                     status: m.validation_response_status,
                     is_valid: m.validation_success,
+                    identity: ValidationIdentity::new(),
+                    timestamp: Instant::now(),
+                },
+            );
+        }
+
+        // ---------------------------------------------------- Redis validator
+        Some(Validation::Redis) => {
+            let redis_url = globals
+                .get("TOKEN")
+                .and_then(|v| v.as_scalar())
+                .map(|s| s.into_owned().to_kstr().to_string())
+                .unwrap_or_default();
+
+            if redis_url.is_empty() {
+                m.validation_success = false;
+                m.validation_response_body =
+                    validation_body::from_string("Redis URL not found.".to_string());
+                m.validation_response_status = StatusCode::BAD_REQUEST;
+                commit_and_return(m);
+                return;
+            }
+
+            if let Err(e) = egress::check_connection_string(&redis_url) {
+                m.validation_success = false;
+                m.validation_response_body =
+                    validation_body::from_string(format!("Validation skipped - {}", e));
+                m.validation_response_status = StatusCode::CONTINUE;
+                commit_and_return(m);
+                return;
+            }
+
+            let cache_key = redis::generate_redis_cache_key(&redis_url);
+            if let Some(cached) = cache.get(&cache_key) {
+                let c = cached.value();
+                if c.timestamp.elapsed() < Duration::from_secs(VALIDATION_CACHE_SECONDS) {
+                    m.validation_success = c.is_valid;
+                    m.validation_response_body = c.body.clone();
+                    m.validation_response_status = c.status;
+                    commit_and_return(m);
+                    return;
+                }
+            }
+
+            match redis::validate_redis(&redis_url).await {
+                Ok((ok, meta)) => {
+                    m.validation_success = ok;
+                    m.validation_response_body = validation_body::from_string(if ok {
+                        format!("Redis connection is valid. Metadata: {:?}", meta)
+                    } else {
+                        "Redis connection failed.".to_string()
+                    });
+                    m.validation_response_status =
+                        if ok { StatusCode::OK } else { StatusCode::UNAUTHORIZED };
+                }
+                Err(e) => {
+                    m.validation_success = false;
+                    m.validation_response_body =
+                        validation_body::from_string(format!("Redis error: {}", e));
+                    m.validation_response_status = StatusCode::BAD_GATEWAY;
+                }
+            }
+
+            cache.insert(
+                cache_key,
+                CachedResponse {
+                    body: m.validation_response_body.clone(),
+                    status: m.validation_response_status,
+                    is_valid: m.validation_success,
+                    identity: ValidationIdentity::new(),
+                    timestamp: Instant::now(),
+                },
+            );
+        }
+
+        // ---------------------------------------------------- Memcached validator
+        Some(Validation::Memcached) => {
+            let memcached_url = globals
+                .get("TOKEN")
+                .and_then(|v| v.as_scalar())
+                .map(|s| s.into_owned().to_kstr().to_string())
+                .unwrap_or_default();
+
+            if memcached_url.is_empty() {
+                m.validation_success = false;
+                m.validation_response_body =
+                    validation_body::from_string("Memcached URL not found.".to_string());
+                m.validation_response_status = StatusCode::BAD_REQUEST;
+                commit_and_return(m);
+                return;
+            }
+
+            if let Err(e) = egress::check_connection_string(&memcached_url) {
+                m.validation_success = false;
+                m.validation_response_body =
+                    validation_body::from_string(format!("Validation skipped - {}", e));
+                m.validation_response_status = StatusCode::CONTINUE;
+                commit_and_return(m);
+                return;
+            }
+
+            let cache_key = memcached::generate_memcached_cache_key(&memcached_url);
+            if let Some(cached) = cache.get(&cache_key) {
+                let c = cached.value();
+                if c.timestamp.elapsed() < Duration::from_secs(VALIDATION_CACHE_SECONDS) {
+                    m.validation_success = c.is_valid;
+                    m.validation_response_body = c.body.clone();
+                    m.validation_response_status = c.status;
+                    commit_and_return(m);
+                    return;
+                }
+            }
+
+            match memcached::validate_memcached(&memcached_url).await {
+                Ok((ok, meta)) => {
+                    m.validation_success = ok;
+                    m.validation_response_body = validation_body::from_string(if ok {
+                        format!("Memcached credentials are valid. Metadata: {:?}", meta)
+                    } else {
+                        "Memcached authentication failed.".to_string()
+                    });
+                    m.validation_response_status =
+                        if ok { StatusCode::OK } else { StatusCode::UNAUTHORIZED };
+                }
+                Err(e) => {
+                    m.validation_success = false;
+                    m.validation_response_body =
+                        validation_body::from_string(format!("Memcached error: {}", e));
+                    m.validation_response_status = StatusCode::BAD_GATEWAY;
+                }
+            }
+
+            cache.insert(
+                cache_key,
+                CachedResponse {
+                    body: m.validation_response_body.clone(),
+                    status: m.validation_response_status,
+                    is_valid: m.validation_success,
+                    identity: ValidationIdentity::new(),
                     timestamp: Instant::now(),
                 },
             );
@@ -787,6 +1310,7 @@ async fn timed_validate_single_match<'a>(
                     body: m.validation_response_body.clone(),
                     status: m.validation_response_status,
                     is_valid: m.validation_success,
+                    identity: ValidationIdentity::new(),
                     timestamp: Instant::now(),
                 },
             );
@@ -809,6 +1333,15 @@ async fn timed_validate_single_match<'a>(
                 return;
             }
 
+            if let Err(e) = egress::check_connection_string(&jdbc_conn) {
+                m.validation_success = false;
+                m.validation_response_body =
+                    validation_body::from_string(format!("Validation skipped - {}", e));
+                m.validation_response_status = StatusCode::CONTINUE;
+                commit_and_return(m);
+                return;
+            }
+
             let cache_key = jdbc::generate_jdbc_cache_key(&jdbc_conn);
             if let Some(cached) = cache.get(&cache_key) {
                 let c = cached.value();
@@ -841,6 +1374,7 @@ async fn timed_validate_single_match<'a>(
                     body: m.validation_response_body.clone(),
                     status: m.validation_response_status,
                     is_valid: m.validation_success,
+                    identity: ValidationIdentity::new(),
                     timestamp: Instant::now(),
                 },
             );
@@ -863,6 +1397,15 @@ async fn timed_validate_single_match<'a>(
                 return;
             }
 
+            if let Err(e) = egress::check_connection_string(&pg_url) {
+                m.validation_success = false;
+                m.validation_response_body =
+                    validation_body::from_string(format!("Validation skipped - {}", e));
+                m.validation_response_status = StatusCode::CONTINUE;
+                commit_and_return(m);
+                return;
+            }
+
             let cache_key = postgres::generate_postgres_cache_key(&pg_url);
             if let Some(cached) = cache.get(&cache_key) {
                 let c = cached.value();
@@ -899,6 +1442,250 @@ async fn timed_validate_single_match<'a>(
                     body: m.validation_response_body.clone(),
                     status: m.validation_response_status,
                     is_valid: m.validation_success,
+                    identity: ValidationIdentity::new(),
+                    timestamp: Instant::now(),
+                },
+            );
+        }
+        // ---------------------------------------------------- SMTP validator
+        Some(Validation::Smtp) => {
+            let scalar = |name: &str| {
+                globals
+                    .get(name)
+                    .and_then(|v| v.as_scalar())
+                    .map(|s| s.into_owned().to_kstr().to_string())
+            };
+            let target = match smtp::resolve_target(
+                &scalar("TOKEN").unwrap_or_default(),
+                scalar("HOST").as_deref(),
+                scalar("PORT").as_deref(),
+                scalar("USERNAME").or_else(|| scalar("USER")).as_deref(),
+            ) {
+                Ok(target) => target,
+                Err(e) => {
+                    m.validation_success = false;
+                    m.validation_response_body = validation_body::from_string(e.to_string());
+                    m.validation_response_status = StatusCode::BAD_REQUEST;
+                    commit_and_return(m);
+                    return;
+                }
+            };
+
+            if let Err(e) = egress::policy().check_host(&target.host) {
+                m.validation_success = false;
+                m.validation_response_body =
+                    validation_body::from_string(format!("Validation skipped - {}", e));
+                m.validation_response_status = StatusCode::CONTINUE;
+                commit_and_return(m);
+                return;
+            }
+
+            let cache_key = smtp::generate_smtp_cache_key(&target);
+            if let Some(cached) = cache.get(&cache_key) {
+                let c = cached.value();
+                if c.timestamp.elapsed() < Duration::from_secs(VALIDATION_CACHE_SECONDS) {
+                    m.validation_success = c.is_valid;
+                    m.validation_response_body = c.body.clone();
+                    m.validation_response_status = c.status;
+                    commit_and_return(m);
+                    return;
+                }
+            }
+
+            // Mail servers lock accounts after repeated failures; pace logins
+            // like any other validation traffic.
+            if let Err(e) = rate_limit::acquire(&target.host).await {
+                m.validation_success = false;
+                m.validation_response_body =
+                    validation_body::from_string(format!("Validation skipped - {}", e));
+                m.validation_response_status = StatusCode::CONTINUE;
+                commit_and_return(m);
+                return;
+            }
+
+            match smtp::validate_smtp(&target).await {
+                Ok((ok, capabilities)) => {
+                    m.validation_success = ok;
+                    m.validation_response_body = validation_body::from_string(if ok {
+                        format!("SMTP authentication succeeded. Capabilities: {:?}", capabilities)
+                    } else {
+                        format!("SMTP authentication rejected. Capabilities: {:?}", capabilities)
+                    });
+                    m.validation_response_status =
+                        if ok { StatusCode::OK } else { StatusCode::UNAUTHORIZED };
+                }
+                Err(e) => {
+                    m.validation_success = false;
+                    m.validation_response_body =
+                        validation_body::from_string(format!("SMTP error: {}", e));
+                    m.validation_response_status = StatusCode::BAD_GATEWAY;
+                }
+            }
+            cache.insert(
+                cache_key,
+                CachedResponse {
+                    body: m.validation_response_body.clone(),
+                    status: m.validation_response_status,
+                    is_valid: m.validation_success,
+                    identity: ValidationIdentity::new(),
+                    timestamp: Instant::now(),
+                },
+            );
+        }
+        // ---------------------------------------------------- LDAP validator
+        Some(Validation::Ldap) => {
+            let password = captured_values
+                .iter()
+                .find(|(n, ..)| n == "TOKEN")
+                .map(|(_, v, ..)| v.clone())
+                .unwrap_or_default();
+            let ldap_url =
+                utils::find_closest_variable(&captured_values, &password, "TOKEN", "LDAP_URL")
+                    .unwrap_or_default();
+            let bind_dn =
+                utils::find_closest_variable(&captured_values, &password, "TOKEN", "BIND_DN")
+                    .unwrap_or_default();
+
+            if password.is_empty() || ldap_url.is_empty() || bind_dn.is_empty() {
+                m.validation_success = false;
+                m.validation_response_body = validation_body::from_string(
+                    "Missing LDAP URL, bind DN or password.".to_string(),
+                );
+                m.validation_response_status = StatusCode::BAD_REQUEST;
+                commit_and_return(m);
+                return;
+            }
+
+            let host = match ldap::parse_ldap_url(&ldap_url) {
+                Ok(target) => target.host,
+                Err(e) => {
+                    m.validation_success = false;
+                    m.validation_response_body = validation_body::from_string(e.to_string());
+                    m.validation_response_status = StatusCode::BAD_REQUEST;
+                    commit_and_return(m);
+                    return;
+                }
+            };
+            if let Err(e) = egress::policy().check_host(&host) {
+                m.validation_success = false;
+                m.validation_response_body =
+                    validation_body::from_string(format!("Validation skipped - {}", e));
+                m.validation_response_status = StatusCode::CONTINUE;
+                commit_and_return(m);
+                return;
+            }
+
+            let cache_key = ldap::generate_ldap_cache_key(&ldap_url, &bind_dn, &password);
+            if let Some(cached) = cache.get(&cache_key) {
+                let c = cached.value();
+                if c.timestamp.elapsed() < Duration::from_secs(VALIDATION_CACHE_SECONDS) {
+                    m.validation_success = c.is_valid;
+                    m.validation_response_body = c.body.clone();
+                    m.validation_response_status = c.status;
+                    commit_and_return(m);
+                    return;
+                }
+            }
+
+            // Directory servers lock accounts after repeated failed binds.
+            if let Err(e) = rate_limit::acquire(&host).await {
+                m.validation_success = false;
+                m.validation_response_body =
+                    validation_body::from_string(format!("Validation skipped - {}", e));
+                m.validation_response_status = StatusCode::CONTINUE;
+                commit_and_return(m);
+                return;
+            }
+
+            match ldap::validate_ldap(&ldap_url, &bind_dn, &password).await {
+                Ok((ok, meta)) => {
+                    m.validation_success = ok;
+                    m.validation_response_body = validation_body::from_string(if ok {
+                        format!("LDAP bind succeeded. Metadata: {:?}", meta)
+                    } else {
+                        format!("LDAP bind rejected. Metadata: {:?}", meta)
+                    });
+                    m.validation_response_status =
+                        if ok { StatusCode::OK } else { StatusCode::UNAUTHORIZED };
+                }
+                Err(e) => {
+                    m.validation_success = false;
+                    m.validation_response_body =
+                        validation_body::from_string(format!("LDAP error: {}", e));
+                    m.validation_response_status = StatusCode::BAD_GATEWAY;
+                }
+            }
+            cache.insert(
+                cache_key,
+                CachedResponse {
+                    body: m.validation_response_body.clone(),
+                    status: m.validation_response_status,
+                    is_valid: m.validation_success,
+                    identity: ValidationIdentity::new(),
+                    timestamp: Instant::now(),
+                },
+            );
+        }
+        // ----------------------------------------------------- SSH key validator
+        Some(Validation::Ssh) => {
+            let key_text = globals
+                .get("TOKEN")
+                .and_then(|v| v.as_scalar())
+                .map(|s| s.into_owned().to_kstr().to_string())
+                .unwrap_or_default();
+
+            if key_text.is_empty() {
+                m.validation_success = false;
+                m.validation_response_body =
+                    validation_body::from_string("SSH private key not found.".to_string());
+                m.validation_response_status = StatusCode::BAD_REQUEST;
+                commit_and_return(m);
+                return;
+            }
+
+            let cache_key = ssh::generate_ssh_cache_key(&key_text);
+            if let Some(cached) = cache.get(&cache_key) {
+                let c = cached.value();
+                if c.timestamp.elapsed() < Duration::from_secs(VALIDATION_CACHE_SECONDS) {
+                    m.validation_success = c.is_valid;
+                    m.validation_response_body = c.body.clone();
+                    m.validation_response_status = c.status;
+                    commit_and_return(m);
+                    return;
+                }
+            }
+
+            // A key that was only analysed (no `--ssh-validate-host`), or that
+            // could not be parsed (e.g. a PGP key block), is "Not Attempted"
+            // rather than inactive.
+            match ssh::validate_ssh_key(&key_text, ssh::hosts()).await {
+                Ok((outcome, meta)) => {
+                    m.validation_success = outcome == Some(true);
+                    m.validation_response_body = validation_body::from_string(match outcome {
+                        Some(true) => format!("SSH key accepted. Metadata: {:?}", meta),
+                        Some(false) => format!("SSH key rejected. Metadata: {:?}", meta),
+                        None => format!("SSH key analysed. Metadata: {:?}", meta),
+                    });
+                    m.validation_response_status = match outcome {
+                        Some(true) => StatusCode::OK,
+                        Some(false) => StatusCode::UNAUTHORIZED,
+                        None => StatusCode::CONTINUE,
+                    };
+                }
+                Err(e) => {
+                    m.validation_success = false;
+                    m.validation_response_body =
+                        validation_body::from_string(format!("SSH key analysis failed - {}", e));
+                    m.validation_response_status = StatusCode::CONTINUE;
+                }
+            }
+            cache.insert(
+                cache_key,
+                CachedResponse {
+                    body: m.validation_response_body.clone(),
+                    status: m.validation_response_status,
+                    is_valid: m.validation_success,
+                    identity: ValidationIdentity::new(),
                     timestamp: Instant::now(),
                 },
             );
@@ -920,6 +1707,7 @@ async fn timed_validate_single_match<'a>(
                 return;
             }
 
+            m.validation_identity = jwt::describe_jwt(&token);
             match jwt::validate_jwt(&token).await {
                 Ok((ok, msg)) => {
                     m.validation_success = ok;
@@ -980,6 +1768,7 @@ async fn timed_validate_single_match<'a>(
                         body: m.validation_response_body.clone(),
                         status: m.validation_response_status,
                         is_valid: m.validation_success,
+                        identity: ValidationIdentity::new(),
                         timestamp: Instant::now(),
                     },
                 );
@@ -1021,10 +1810,19 @@ async fn timed_validate_single_match<'a>(
                             body: m.validation_response_body.clone(),
                             status: m.validation_response_status,
                             is_valid: m.validation_success,
+                            identity: ValidationIdentity::new(),
                             timestamp: Instant::now(),
                         },
                     );
                 }
+                Err(e)
+                    if e.is::<rate_limit::BudgetExhausted>() || e.is::<egress::EgressDenied>() =>
+                {
+                    m.validation_success = false;
+                    m.validation_response_body =
+                        validation_body::from_string(format!("Validation skipped - {e}"));
+                    m.validation_response_status = StatusCode::CONTINUE;
+                }
                 Err(e) => {
                     m.validation_success = false;
                     m.validation_response_body = validation_body::from_string(format!(
@@ -1100,6 +1898,7 @@ async fn timed_validate_single_match<'a>(
                     body: m.validation_response_body.clone(),
                     status: m.validation_response_status,
                     is_valid: m.validation_success,
+                    identity: ValidationIdentity::new(),
                     timestamp: Instant::now(),
                 },
             );
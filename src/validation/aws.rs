@@ -34,7 +34,10 @@ use tokio::{
     time::{sleep, timeout},
 };
 
-use crate::validation::GLOBAL_USER_AGENT;
+use crate::validation::{rate_limit, GLOBAL_USER_AGENT};
+
+const STS_HOST: &str = "sts.amazonaws.com";
+const IAM_HOST: &str = "iam.amazonaws.com";
 
 static AWS_VALIDATION_SEMAPHORE: OnceCell<Semaphore> = OnceCell::new();
 const BUILTIN_SKIP_ACCOUNT_IDS: &[&str] = &[
@@ -233,6 +236,7 @@ pub async fn validate_aws_credentials(
     const ATTEMPT_TIMEOUT: Duration = Duration::from_secs(5);
 
     for attempt in 1..=MAX_ATTEMPTS {
+        rate_limit::acquire(STS_HOST).await?;
         let result = timeout(ATTEMPT_TIMEOUT, sts_client.get_caller_identity().send()).await;
         match result {
             Ok(Ok(identity)) => {
@@ -241,6 +245,12 @@ pub async fn validate_aws_credentials(
             }
             Ok(Err(e)) => {
                 if is_throttling_or_transient(&e) {
+                    if let SdkError::ServiceError(ctx) = &e {
+                        let status: StatusCode = ctx.raw().status().into();
+                        if status == StatusCode::TOO_MANY_REQUESTS {
+                            rate_limit::limiter().pause(STS_HOST, Duration::from_secs(1));
+                        }
+                    }
                     if attempt == MAX_ATTEMPTS {
                         return Err(anyhow!("AWS validation failed: {}", e));
                     }
@@ -261,6 +271,35 @@ pub async fn validate_aws_credentials(
     Err(anyhow!("AWS validation failed"))
 }
 
+/// Deactivate an access key using the key itself. Without a user name, IAM
+/// `UpdateAccessKey` acts on the calling user, so no other credentials are
+/// needed. Returns whether the key was deactivated, with the reason if IAM
+/// refused; a request that never got an answer from IAM is an error.
+pub async fn deactivate_aws_access_key(
+    aws_access_key_id: &str,
+    aws_secret_access_key: &str,
+) -> Result<(bool, String)> {
+    let credentials =
+        Credentials::new(aws_access_key_id, aws_secret_access_key, None, None, "static");
+    let config = build_base_config(credentials).await;
+    let iam = aws_sdk_iam::Client::new(&config);
+
+    rate_limit::acquire(IAM_HOST).await?;
+    match iam
+        .update_access_key()
+        .access_key_id(aws_access_key_id)
+        .status(aws_sdk_iam::types::StatusType::Inactive)
+        .send()
+        .await
+    {
+        Ok(_) => Ok((true, format!("Access key {aws_access_key_id} deactivated"))),
+        Err(e @ SdkError::ServiceError(_)) => {
+            Ok((false, aws_sdk_iam::error::DisplayErrorContext(&e).to_string()))
+        }
+        Err(e) => Err(anyhow!("{}", aws_sdk_iam::error::DisplayErrorContext(&e))),
+    }
+}
+
 /// Converts an AWS Key ID to an AWS Account Number.
 /// It assumes that the Key ID has a specific format and extracts the account
 /// number encoded within it. Reference: https://medium.com/@TalBeerySec/a-short-note-on-aws-key-id-f88cc4317489
@@ -11,7 +11,9 @@ use serde_json::Value as JsonValue;
 use sha2::Sha256;
 
 use crate::{
-    validation::{Cache, CachedResponse, ValidationResponseBody, VALIDATION_CACHE_SECONDS},
+    validation::{
+        rate_limit, Cache, CachedResponse, ValidationResponseBody, VALIDATION_CACHE_SECONDS,
+    },
     validation_body,
 };
 
@@ -82,7 +84,7 @@ pub async fn validate_azure_storage_credentials(
     );
 
     let client = Client::builder().build()?;
-    let resp = client.get(&url).headers(hdrs).send().await?;
+    let resp = rate_limit::send(client.get(&url).headers(hdrs)).await?;
 
     /* ── capture status before `.text()` consumes resp ────────── */
     let status = resp.status();
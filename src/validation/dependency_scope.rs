@@ -0,0 +1,187 @@
+//! `--dependency-scope`: when the file a dependent rule matched in lacks a
+//! value the rule depends on, look for it in other files of the same
+//! directory, commit or repository.
+
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+};
+
+use once_cell::sync::OnceCell;
+
+use crate::{
+    cli::commands::validation_dependency::{DependencyScope, ValidationDependencyArgs},
+    origin::Origin,
+};
+
+/// Default for `--dependency-max-candidates`.
+pub const DEFAULT_MAX_CANDIDATES: usize = 8;
+
+static SETTINGS: OnceCell<(DependencyScope, usize)> = OnceCell::new();
+static INPUT_ROOTS: OnceCell<Vec<PathBuf>> = OnceCell::new();
+
+/// Install the dependency scope for this process, along with the scanned
+/// input roots that delimit the repository scope of plain files. Only the
+/// first call has an effect.
+pub fn configure(args: &ValidationDependencyArgs, input_roots: &[PathBuf]) {
+    let _ = SETTINGS.set((args.scope, args.max_candidates));
+    let _ = INPUT_ROOTS.set(input_roots.to_vec());
+}
+
+/// The configured scope and candidate limit.
+pub fn settings() -> (DependencyScope, usize) {
+    SETTINGS.get().copied().unwrap_or((DependencyScope::Blob, DEFAULT_MAX_CANDIDATES))
+}
+
+/// The input roots passed to [`configure`].
+pub fn input_roots() -> &'static [PathBuf] {
+    INPUT_ROOTS.get().map(Vec::as_slice).unwrap_or_default()
+}
+
+/// The path reported as the source of a dependency value: the file path for
+/// plain files, the path within the repository for Git blobs.
+pub fn source_path(origin: &Origin) -> Option<String> {
+    match origin {
+        Origin::File(e) => Some(e.path.display().to_string()),
+        Origin::GitRepo(e) => e.first_commit.as_ref().map(|c| c.blob_path.clone()),
+        Origin::Extended(e) => e.path().map(|p| p.display().to_string()),
+    }
+}
+
+fn directory_key(origin: &Origin) -> Option<String> {
+    let repo = match origin {
+        Origin::GitRepo(e) => e.repo_path.display().to_string(),
+        _ => String::new(),
+    };
+    let path = source_path(origin)?;
+    let parent = Path::new(&path).parent().map(|p| p.display().to_string()).unwrap_or_default();
+    Some(format!("dir|{repo}|{parent}"))
+}
+
+/// The innermost of `roots` containing the file at `path`.
+fn root_key(path: &str, roots: &[PathBuf]) -> Option<String> {
+    let path = Path::new(path);
+    roots
+        .iter()
+        .filter(|root| path.starts_with(root))
+        .max_by_key(|root| root.components().count())
+        .map(|root| format!("root|{}", root.display()))
+}
+
+/// Key of the `scope` that `origin` belongs to; blobs sharing a key may supply
+/// dependency values to each other. `None` for the blob scope, or when the
+/// origin carries no path to group by.
+///
+/// Outside Git, the repository scope is the input root (see [`input_roots`])
+/// a file was found under; files under no root fall back to their directory.
+pub fn scope_key(scope: DependencyScope, origin: &Origin, roots: &[PathBuf]) -> Option<String> {
+    match (scope, origin) {
+        (DependencyScope::Blob, _) => None,
+        (DependencyScope::Commit, Origin::GitRepo(e)) => e
+            .first_commit
+            .as_ref()
+            .map(|c| format!("commit|{}|{}", e.repo_path.display(), c.commit_metadata.commit_id)),
+        (DependencyScope::Repository, Origin::GitRepo(e)) => {
+            Some(format!("repo|{}", e.repo_path.display()))
+        }
+        (DependencyScope::Repository, _) => source_path(origin)
+            .and_then(|path| root_key(&path, roots))
+            .or_else(|| directory_key(origin)),
+        (DependencyScope::Directory | DependencyScope::Commit, _) => directory_key(origin),
+    }
+}
+
+/// Number of directory steps between the files at `a` and `b`.
+fn distance(a: &Path, b: &Path) -> usize {
+    let dirs =
+        |p: &Path| p.parent().map(|d| d.components().collect::<Vec<_>>()).unwrap_or_default();
+    let (a, b) = (dirs(a), dirs(b));
+    let common = a.iter().zip(&b).take_while(|(x, y)| x == y).count();
+    a.len() + b.len() - 2 * common
+}
+
+/// Pick the value for one dependency from `(value, source path)` candidates:
+/// the one found nearest to `near`, ties going to the lowest path. Returns
+/// `None` when there is no candidate, or more than `max_candidates` distinct
+/// values, since pairing would then be guesswork.
+pub fn choose<'a>(
+    near: &Path,
+    candidates: impl IntoIterator<Item = (&'a str, &'a str)>,
+    max_candidates: usize,
+) -> Option<(String, String)> {
+    let mut ranked: BTreeMap<&str, (usize, &str)> = BTreeMap::new();
+    for (value, path) in candidates {
+        let rank = (distance(near, Path::new(path)), path);
+        ranked
+            .entry(value)
+            .and_modify(|best| {
+                if rank < *best {
+                    *best = rank;
+                }
+            })
+            .or_insert(rank);
+    }
+    if ranked.len() > max_candidates {
+        return None;
+    }
+    ranked
+        .into_iter()
+        .min_by(|(_, a), (_, b)| a.cmp(b))
+        .map(|(value, (_, path))| (value.to_string(), path.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+
+    #[test]
+    fn scope_keys_group_plain_files() {
+        let env = Origin::from_file(PathBuf::from("work/app/config/prod.env"));
+        let yaml = Origin::from_file(PathBuf::from("work/app/config/prod.yaml"));
+        let other = Origin::from_file(PathBuf::from("work/app/deploy/app.yaml"));
+        let lib = Origin::from_file(PathBuf::from("work/lib/.env"));
+        let roots = [PathBuf::from("work"), PathBuf::from("work/app")];
+
+        assert_eq!(scope_key(DependencyScope::Blob, &env, &roots), None);
+        let dir = |o: &Origin| scope_key(DependencyScope::Directory, o, &roots);
+        assert_eq!(dir(&env), dir(&yaml));
+        assert_ne!(dir(&env), dir(&other));
+        // Without a commit, the commit scope falls back to the directory.
+        assert_eq!(scope_key(DependencyScope::Commit, &env, &roots), dir(&env));
+        // Plain files share the innermost input root they were found under.
+        let repo = |o: &Origin| scope_key(DependencyScope::Repository, o, &roots);
+        assert_eq!(repo(&env), repo(&other));
+        assert_eq!(repo(&env), Some("root|work/app".to_string()));
+        assert_eq!(repo(&lib), Some("root|work".to_string()));
+        // Outside every root they only share their directory.
+        assert_eq!(scope_key(DependencyScope::Repository, &env, &[]), dir(&env));
+
+        let git = Origin::from_git_repo(Arc::new(PathBuf::from("/src/app")));
+        assert_eq!(repo(&git), Some("repo|/src/app".to_string()));
+        assert_eq!(dir(&git), None);
+    }
+
+    #[test]
+    fn choose_prefers_nearest_file_within_limit() {
+        let near = Path::new("config/prod/app.env");
+        let candidates = [
+            ("AKIA_ROOT", "settings.yaml"),
+            ("AKIA_SIBLING", "config/prod/app.yaml"),
+            ("AKIA_COUSIN", "config/staging/app.yaml"),
+        ];
+
+        assert_eq!(
+            choose(near, candidates, 8),
+            Some(("AKIA_SIBLING".to_string(), "config/prod/app.yaml".to_string()))
+        );
+        assert_eq!(choose(near, candidates, 2), None);
+        assert_eq!(choose(near, Vec::new(), 8), None);
+        // The same value seen twice counts once, from its nearest file.
+        assert_eq!(
+            choose(near, [("AKIA", "a/b.env"), ("AKIA", "config/prod/.env")], 1),
+            Some(("AKIA".to_string(), "config/prod/.env".to_string()))
+        );
+    }
+}
@@ -0,0 +1,487 @@
+//! `--validation-dry-run`: render HTTP validation requests through the normal
+//! Liquid pipeline and export them as curl commands or a HAR file instead of
+//! sending them.
+
+use std::{borrow::Cow, fs, io::Write, path::PathBuf, sync::Mutex};
+
+use anyhow::{Context, Result};
+use base64::{
+    engine::general_purpose::{STANDARD, URL_SAFE},
+    Engine,
+};
+use chrono::Utc;
+use once_cell::sync::OnceCell;
+use percent_encoding::{utf8_percent_encode, AsciiSet, CONTROLS, NON_ALPHANUMERIC};
+use reqwest::RequestBuilder;
+use serde_json::{json, Value};
+use tracing::info;
+use url::form_urlencoded;
+
+use crate::cli::commands::validation_dry_run::{DryRunFormat, ValidationDryRunArgs};
+
+/// Captured values shorter than this are not redacted; masking one or two
+/// characters would garble the request without protecting anything.
+const MIN_REDACTED_LEN: usize = 4;
+
+/// Headers whose credential is masked even when it was derived from the secret
+/// (e.g. a base64 `Basic` value) rather than containing it verbatim.
+const CREDENTIAL_HEADERS: &[&str] = &["authorization", "proxy-authorization", "cookie"];
+
+/// Characters the `url` crate percent-encodes in a path.
+const PATH: &AsciiSet = &CONTROLS
+    .add(b' ')
+    .add(b'"')
+    .add(b'#')
+    .add(b'<')
+    .add(b'>')
+    .add(b'`')
+    .add(b'?')
+    .add(b'{')
+    .add(b'}');
+
+/// Everything but RFC 3986 unreserved characters, as Liquid's `url_encode`
+/// and most clients encode a single component.
+const COMPONENT: &AsciiSet = &NON_ALPHANUMERIC.remove(b'-').remove(b'.').remove(b'_').remove(b'~');
+
+static DRY_RUN: OnceCell<DryRun> = OnceCell::new();
+
+struct DryRun {
+    format: DryRunFormat,
+    output: Option<PathBuf>,
+    requests: Mutex<Vec<ExportedRequest>>,
+}
+
+/// A rendered multipart form field.
+#[derive(Debug, Clone)]
+pub struct FormPart {
+    pub name: String,
+    /// `true` when `value` is the path of a file to upload.
+    pub is_file: bool,
+    pub value: String,
+    pub content_type: Option<String>,
+}
+
+/// A rendered, redacted validation request.
+#[derive(Debug, Clone)]
+pub struct ExportedRequest {
+    pub rule_id: String,
+    pub rule_name: String,
+    pub method: String,
+    pub url: String,
+    pub headers: Vec<(String, String)>,
+    pub body: Option<String>,
+    pub parts: Vec<FormPart>,
+}
+
+/// Enable dry-run mode for this process when `--validation-dry-run` was given.
+pub fn configure(args: &ValidationDryRunArgs) {
+    if args.enabled {
+        let _ = DRY_RUN.set(DryRun {
+            format: args.format,
+            output: args.output.clone(),
+            requests: Mutex::new(Vec::new()),
+        });
+    }
+}
+
+pub fn enabled() -> bool {
+    DRY_RUN.get().is_some()
+}
+
+/// Record the request `builder` would send, replacing every captured value in
+/// `secrets` with `[REDACTED:<NAME>]`.
+pub fn record<'a>(
+    rule_id: &str,
+    rule_name: &str,
+    builder: RequestBuilder,
+    parts: Vec<FormPart>,
+    secrets: impl IntoIterator<Item = (&'a str, &'a str)>,
+) -> Result<()> {
+    let Some(dry_run) = DRY_RUN.get() else {
+        return Ok(());
+    };
+    let request = builder.build().context("Failed to build validation request")?;
+    let exported = ExportedRequest {
+        rule_id: rule_id.to_string(),
+        rule_name: rule_name.to_string(),
+        method: request.method().to_string(),
+        url: request.url().to_string(),
+        headers: request
+            .headers()
+            .iter()
+            .map(|(name, value)| {
+                (name.as_str().to_string(), String::from_utf8_lossy(value.as_bytes()).into_owned())
+            })
+            .collect(),
+        body: request
+            .body()
+            .and_then(|b| b.as_bytes())
+            .map(|bytes| String::from_utf8_lossy(bytes).into_owned()),
+        parts,
+    };
+    let redacted = redact(exported, &Redactor::new(secrets));
+    dry_run.requests.lock().unwrap_or_else(|p| p.into_inner()).push(redacted);
+    Ok(())
+}
+
+/// Write the recorded requests to the configured output. A no-op unless dry-run
+/// mode is enabled.
+pub fn finish() -> Result<()> {
+    let Some(dry_run) = DRY_RUN.get() else {
+        return Ok(());
+    };
+    let mut requests =
+        std::mem::take(&mut *dry_run.requests.lock().unwrap_or_else(|p| p.into_inner()));
+    requests.sort_by(|a, b| (&a.rule_id, &a.url).cmp(&(&b.rule_id, &b.url)));
+
+    let rendered = match dry_run.format {
+        DryRunFormat::Curl => render_curl(&requests),
+        DryRunFormat::Har => serde_json::to_string_pretty(&render_har(&requests))? + "\n",
+    };
+    match &dry_run.output {
+        Some(path) => {
+            fs::write(path, rendered).with_context(|| {
+                format!("Failed to write validation requests to {}", path.display())
+            })?;
+            info!("Exported {} validation requests to {}", requests.len(), path.display());
+        }
+        None => std::io::stderr().write_all(rendered.as_bytes())?,
+    }
+    Ok(())
+}
+
+struct Redactor<'a> {
+    /// `(value, placeholder)` pairs, longest value first so that a secret
+    /// containing another captured value is masked as a whole. Each value is
+    /// also listed in the encoded forms from [`encoded_forms`].
+    secrets: Vec<(Cow<'a, str>, String)>,
+}
+
+impl<'a> Redactor<'a> {
+    fn new(secrets: impl IntoIterator<Item = (&'a str, &'a str)>) -> Self {
+        let mut secrets: Vec<_> = secrets
+            .into_iter()
+            .filter(|(_, value)| value.len() >= MIN_REDACTED_LEN)
+            .flat_map(|(name, value)| {
+                let placeholder = format!("[REDACTED:{}]", name.to_uppercase());
+                std::iter::once(Cow::Borrowed(value))
+                    .chain(encoded_forms(value).into_iter().map(Cow::Owned))
+                    .filter(|form| form.len() >= MIN_REDACTED_LEN)
+                    .map(move |form| (form, placeholder.clone()))
+            })
+            .collect();
+        secrets.sort_by(|a, b| b.0.len().cmp(&a.0.len()).then_with(|| a.0.cmp(&b.0)));
+        secrets.dedup_by(|a, b| a.0 == b.0);
+        Self { secrets }
+    }
+
+    fn apply(&self, text: &str) -> String {
+        self.secrets.iter().fold(text.to_string(), |acc, (value, placeholder)| {
+            acc.replace(value.as_ref(), placeholder)
+        })
+    }
+}
+
+/// Forms in which a captured value can reach a request: form- and
+/// percent-encoded, JSON-escaped, and base64-encoded inside a longer value
+/// such as a `user:password` pair. For base64 only the characters that depend
+/// on the value alone are kept, at each of the three possible byte offsets.
+fn encoded_forms(value: &str) -> Vec<String> {
+    let mut forms = vec![
+        form_urlencoded::byte_serialize(value.as_bytes()).collect(),
+        utf8_percent_encode(value, PATH).to_string(),
+        utf8_percent_encode(value, COMPONENT).to_string(),
+    ];
+    if let Ok(json) = serde_json::to_string(value) {
+        forms.push(json[1..json.len() - 1].to_string());
+    }
+    for engine in [STANDARD, URL_SAFE] {
+        for offset in 0..3 {
+            let mut bytes = vec![0u8; offset];
+            bytes.extend_from_slice(value.as_bytes());
+            let encoded = engine.encode(&bytes);
+            let start = (offset * 8).div_ceil(6);
+            let end = bytes.len() * 8 / 6;
+            forms.push(encoded[start..end].to_string());
+        }
+    }
+    forms
+}
+
+/// `true` when `token` is exactly one redaction placeholder.
+fn is_placeholder(token: &str) -> bool {
+    token.starts_with("[REDACTED:") && token.ends_with(']') && token.matches('[').count() == 1
+}
+
+fn redact(request: ExportedRequest, redactor: &Redactor) -> ExportedRequest {
+    let headers = request
+        .headers
+        .into_iter()
+        .map(|(name, value)| {
+            let mut value = redactor.apply(&value);
+            // A credential derived from the secret (e.g. `Basic` base64) is
+            // masked whole, including any part only partially redacted.
+            let derived = match value.split_once(' ') {
+                Some((_, credential)) if name != "cookie" => !is_placeholder(credential),
+                _ => !value.contains("[REDACTED:"),
+            };
+            if CREDENTIAL_HEADERS.contains(&name.as_str()) && derived {
+                value = match value.split_once(' ') {
+                    Some((scheme, _)) if name != "cookie" => format!("{scheme} [REDACTED]"),
+                    _ => "[REDACTED]".to_string(),
+                };
+            }
+            (name, value)
+        })
+        .collect();
+    ExportedRequest {
+        url: redactor.apply(&request.url),
+        headers,
+        body: request.body.as_deref().map(|b| redactor.apply(b)),
+        parts: request
+            .parts
+            .into_iter()
+            .map(|part| FormPart { value: redactor.apply(&part.value), ..part })
+            .collect(),
+        ..request
+    }
+}
+
+fn shell_quote(s: &str) -> String {
+    format!("'{}'", s.replace('\'', r"'\''"))
+}
+
+fn render_curl(requests: &[ExportedRequest]) -> String {
+    let mut out = String::new();
+    for request in requests {
+        out.push_str(&format!("# {} ({})\n", request.rule_name, request.rule_id));
+        let mut args = vec![format!("curl -X {} {}", request.method, shell_quote(&request.url))];
+        for (name, value) in &request.headers {
+            args.push(format!("-H {}", shell_quote(&format!("{name}: {value}"))));
+        }
+        if let Some(body) = &request.body {
+            args.push(format!("--data-raw {}", shell_quote(body)));
+        }
+        for part in &request.parts {
+            let mut field = if part.is_file {
+                format!("{}=@{}", part.name, part.value)
+            } else {
+                format!("{}={}", part.name, part.value)
+            };
+            if let Some(content_type) = &part.content_type {
+                field.push_str(&format!(";type={content_type}"));
+            }
+            let flag = if part.is_file { "-F" } else { "--form-string" };
+            args.push(format!("{flag} {}", shell_quote(&field)));
+        }
+        out.push_str(&args.join(" \\\n  "));
+        out.push_str("\n\n");
+    }
+    out
+}
+
+fn render_har(requests: &[ExportedRequest]) -> Value {
+    let started = Utc::now().to_rfc3339();
+    let entries: Vec<Value> = requests
+        .iter()
+        .map(|request| {
+            let headers: Vec<Value> = request
+                .headers
+                .iter()
+                .map(|(name, value)| json!({ "name": name, "value": value }))
+                .collect();
+            let query: Vec<Value> = reqwest::Url::parse(&request.url)
+                .map(|url| {
+                    url.query_pairs()
+                        .map(|(name, value)| json!({ "name": name, "value": value }))
+                        .collect()
+                })
+                .unwrap_or_default();
+            let content_type = request
+                .headers
+                .iter()
+                .find(|(name, _)| name == "content-type")
+                .map(|(_, value)| value.as_str());
+            let mut har_request = json!({
+                "method": request.method,
+                "url": request.url,
+                "httpVersion": "HTTP/1.1",
+                "cookies": [],
+                "headers": headers,
+                "queryString": query,
+                "headersSize": -1,
+                "bodySize": request.body.as_ref().map_or(0, |b| b.len() as i64),
+            });
+            if !request.parts.is_empty() {
+                let params: Vec<Value> = request
+                    .parts
+                    .iter()
+                    .map(|part| {
+                        let mut param = json!({ "name": part.name });
+                        if part.is_file {
+                            param["fileName"] = json!(part.value);
+                        } else {
+                            param["value"] = json!(part.value);
+                        }
+                        if let Some(content_type) = &part.content_type {
+                            param["contentType"] = json!(content_type);
+                        }
+                        param
+                    })
+                    .collect();
+                har_request["postData"] =
+                    json!({ "mimeType": "multipart/form-data", "params": params });
+            } else if let Some(body) = &request.body {
+                har_request["postData"] = json!({
+                    "mimeType": content_type.unwrap_or("application/octet-stream"),
+                    "text": body,
+                });
+            }
+            json!({
+                "startedDateTime": started,
+                "time": 0,
+                "comment": format!("{} ({})", request.rule_name, request.rule_id),
+                "request": har_request,
+                "response": {
+                    "status": 0,
+                    "statusText": "",
+                    "httpVersion": "",
+                    "cookies": [],
+                    "headers": [],
+                    "content": { "size": 0, "mimeType": "" },
+                    "redirectURL": "",
+                    "headersSize": -1,
+                    "bodySize": -1,
+                },
+                "cache": {},
+                "timings": { "send": 0, "wait": 0, "receive": 0 },
+            })
+        })
+        .collect();
+    json!({
+        "log": {
+            "version": "1.2",
+            "creator": { "name": "kingfisher", "version": env!("CARGO_PKG_VERSION") },
+            "entries": entries,
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request() -> ExportedRequest {
+        ExportedRequest {
+            rule_id: "kingfisher.example.1".into(),
+            rule_name: "Example Token".into(),
+            method: "POST".into(),
+            url: "https://api.example.com/v1/check?key=sk_live_abcdef123456".into(),
+            headers: vec![
+                ("x-api-key".into(), "sk_live_abcdef123456".into()),
+                ("authorization".into(), "Basic dXNlcjpza19saXZlX2FiY2RlZjEyMzQ1Ng==".into()),
+                ("content-type".into(), "application/json".into()),
+            ],
+            body: Some(r#"{"account":"acct_42","token":"sk_live_abcdef123456"}"#.into()),
+            parts: vec![],
+        }
+    }
+
+    #[test]
+    fn redacts_captured_values_and_derived_credentials() {
+        let redactor =
+            Redactor::new([("TOKEN", "sk_live_abcdef123456"), ("id", "acct_42"), ("x", "ab")]);
+        assert_eq!(redactor.apply("a=b c+d/e"), "a=b c+d/e");
+        assert_eq!(
+            Redactor::new([("TOKEN", "a b/c+d")]).apply("?k=a+b%2Fc%2Bd&raw=a b/c+d"),
+            "?k=[REDACTED:TOKEN]&raw=[REDACTED:TOKEN]"
+        );
+        let redacted = redact(request(), &redactor);
+
+        assert_eq!(redacted.url, "https://api.example.com/v1/check?key=[REDACTED:TOKEN]");
+        assert_eq!(redacted.headers[0].1, "[REDACTED:TOKEN]");
+        assert_eq!(redacted.headers[1].1, "Basic [REDACTED]");
+        assert_eq!(redacted.headers[2].1, "application/json");
+        assert_eq!(
+            redacted.body.as_deref(),
+            Some(r#"{"account":"[REDACTED:ID]","token":"[REDACTED:TOKEN]"}"#)
+        );
+    }
+
+    #[test]
+    fn redacts_json_escaped_values() {
+        let redactor = Redactor::new([("TOKEN", "p\"w\\d/1234")]);
+        assert_eq!(
+            redactor.apply(r#"{"password":"p\"w\\d/1234"}"#),
+            r#"{"password":"[REDACTED:TOKEN]"}"#
+        );
+    }
+
+    #[test]
+    fn redacts_base64_encoded_values_in_the_body() {
+        let redactor = Redactor::new([("TOKEN", "sk_live_abcdef123456")]);
+        for user in ["u", "us", "usr"] {
+            let basic = STANDARD.encode(format!("{user}:sk_live_abcdef123456"));
+            let body = redactor.apply(&format!(r#"{{"auth":"Basic {basic}"}}"#));
+            assert!(body.contains("[REDACTED:TOKEN]"), "{body}");
+            assert!(!body.contains(&basic[basic.len() - 12..basic.len() - 4]), "{body}");
+        }
+    }
+
+    #[test]
+    fn redacts_percent_encoded_path_segments() {
+        let redactor = Redactor::new([("TOKEN", "my secret key")]);
+        assert_eq!(
+            redactor.apply("https://api.example.com/keys/my%20secret%20key/check"),
+            "https://api.example.com/keys/[REDACTED:TOKEN]/check"
+        );
+        let redactor = Redactor::new([("TOKEN", "a/b:c@d")]);
+        assert_eq!(
+            redactor.apply("https://api.example.com/keys/a%2Fb%3Ac%40d"),
+            "https://api.example.com/keys/[REDACTED:TOKEN]"
+        );
+    }
+
+    #[test]
+    fn partially_redacted_credentials_are_masked_whole() {
+        let redactor = Redactor::new([("TOKEN", "sk_live_abcdef123456")]);
+        let mut req = request();
+        req.headers = vec![
+            ("authorization".into(), "Basic dXNlcjpza19saXZlX2FiY2RlZjEyMzQ1Ng==".into()),
+            ("proxy-authorization".into(), "Bearer sk_live_abcdef123456".into()),
+        ];
+        let redacted = redact(req, &redactor);
+        assert_eq!(redacted.headers[0].1, "Basic [REDACTED]");
+        assert_eq!(redacted.headers[1].1, "Bearer [REDACTED:TOKEN]");
+    }
+
+    #[test]
+    fn curl_output_is_shell_quoted() {
+        let mut req = request();
+        req.body = Some("it's".into());
+        let curl = render_curl(&[req]);
+        assert!(curl.starts_with("# Example Token (kingfisher.example.1)\n"));
+        assert!(curl.contains("curl -X POST 'https://api.example.com/v1/check?key="));
+        assert!(curl.contains(" \\\n  -H 'x-api-key: "));
+        assert!(curl.contains(r"--data-raw 'it'\''s'"));
+    }
+
+    #[test]
+    fn har_output_describes_requests() {
+        let mut req = request();
+        req.parts = vec![FormPart {
+            name: "file".into(),
+            is_file: true,
+            value: "/tmp/a.txt".into(),
+            content_type: Some("text/plain".into()),
+        }];
+        let har = render_har(&[request(), req]);
+        let entries = har["log"]["entries"].as_array().unwrap();
+        assert_eq!(har["log"]["version"], "1.2");
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0]["request"]["method"], "POST");
+        assert_eq!(entries[0]["request"]["queryString"][0]["name"], "key");
+        assert_eq!(entries[0]["request"]["postData"]["mimeType"], "application/json");
+        assert_eq!(entries[1]["request"]["postData"]["params"][0]["fileName"], "/tmp/a.txt");
+    }
+}
@@ -0,0 +1,403 @@
+//! Egress policy for validation traffic.
+//!
+//! A rule's HTTP validator may only contact the hosts it declares in
+//! `allowed_hosts` (or, when it declares none, the literal host of its URL
+//! template). On top of that, `--validation-allow-host` / `--validation-deny-host`
+//! restrict every outbound validation connection, and side-effect-free mode
+//! refuses validators that could change state on the remote service.
+//! Redirects are held to the same rules on every hop.
+
+use std::{future::Future, sync::Arc};
+
+use anyhow::Result;
+use once_cell::sync::OnceCell;
+use reqwest::{redirect, Url};
+
+use crate::{
+    cli::commands::validation_egress::ValidationEgressArgs,
+    rules::rule::{HttpRequest, HttpValidation, Validation},
+};
+
+/// `allowed_hosts` entry that lets a rule contact any host.
+pub const ANY_HOST: &str = "*";
+
+/// Longest redirect chain followed, as with reqwest's default policy.
+const MAX_REDIRECTS: usize = 10;
+
+static POLICY: OnceCell<EgressPolicy> = OnceCell::new();
+
+tokio::task_local! {
+    static REDIRECT_HOSTS: Arc<[String]>;
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct EgressPolicy {
+    /// When non-empty, only these hosts may be contacted.
+    pub allow_hosts: Vec<String>,
+    /// Hosts that may never be contacted; takes precedence over `allow_hosts`.
+    pub deny_hosts: Vec<String>,
+    /// Only run validators that cannot change state on the remote service.
+    pub side_effect_free: bool,
+}
+
+impl From<&ValidationEgressArgs> for EgressPolicy {
+    fn from(args: &ValidationEgressArgs) -> Self {
+        Self {
+            allow_hosts: args.allow_hosts.clone(),
+            deny_hosts: args.deny_hosts.clone(),
+            side_effect_free: args.side_effect_free,
+        }
+    }
+}
+
+/// A validation connection refused by the egress policy.
+#[derive(Debug, Clone, thiserror::Error)]
+#[error("egress to {host} blocked: {reason}")]
+pub struct EgressDenied {
+    pub host: String,
+    pub reason: String,
+}
+
+impl EgressDenied {
+    fn new(host: &str, reason: impl Into<String>) -> Self {
+        Self { host: host.to_string(), reason: reason.into() }
+    }
+}
+
+impl EgressPolicy {
+    /// Apply the global allow and deny lists to `host`.
+    pub fn check_host(&self, host: &str) -> Result<(), EgressDenied> {
+        if let Some(pattern) = self.deny_hosts.iter().find(|p| host_matches(p, host)) {
+            return Err(EgressDenied::new(host, format!("matches denied host `{pattern}`")));
+        }
+        if !self.allow_hosts.is_empty() && !self.allow_hosts.iter().any(|p| host_matches(p, host)) {
+            return Err(EgressDenied::new(host, "not in the allowed host list"));
+        }
+        Ok(())
+    }
+
+    /// Refuse validator kinds that are disabled by side-effect-free mode.
+    pub fn check_validation(&self, validation: &Validation) -> Result<(), String> {
+        if self.side_effect_free && !is_side_effect_free(validation) {
+            let unsafe_method = match validation {
+                Validation::Http(http) => http
+                    .requests()
+                    .find(|request| !is_safe_request(request))
+                    .map(|request| request.method.to_ascii_uppercase()),
+                _ => None,
+            };
+            return Err(match unsafe_method {
+                Some(method) => format!("{method} request is not side-effect-free"),
+                None => format!("{validation:?} validator is not side-effect-free"),
+            });
+        }
+        Ok(())
+    }
+}
+
+/// Install the process-wide policy. Only the first call has an effect.
+pub fn configure(policy: EgressPolicy) {
+    let _ = POLICY.set(policy);
+}
+
+pub fn policy() -> &'static EgressPolicy {
+    POLICY.get_or_init(EgressPolicy::default)
+}
+
+/// Match `host` against `api.example.com`, `*.example.com` (any subdomain) or `*`.
+pub fn host_matches(pattern: &str, host: &str) -> bool {
+    let pattern = pattern.trim().trim_end_matches('.').to_ascii_lowercase();
+    let host = host.trim_end_matches('.').to_ascii_lowercase();
+    if pattern == ANY_HOST {
+        return true;
+    }
+    match pattern.strip_prefix("*.") {
+        Some(suffix) => {
+            host.strip_suffix(suffix).is_some_and(|label| label.len() > 1 && label.ends_with('.'))
+        }
+        None => host == pattern,
+    }
+}
+
+/// Validators that only read: identity lookups, listings and `SELECT 1`-style
+/// logins. HTTP validators qualify only when every step uses a safe method
+/// without a multipart body.
+pub fn is_side_effect_free(validation: &Validation) -> bool {
+    match validation {
+        Validation::Http(http) => http.multipart.is_none() && http.requests().all(is_safe_request),
+        Validation::AWS
+        | Validation::AzureStorage
+        | Validation::Coinbase
+        | Validation::GCP
+        | Validation::MongoDB
+        | Validation::MySQL
+        | Validation::Postgres
+        | Validation::Jdbc
+        | Validation::Redis
+        | Validation::Memcached
+        | Validation::Smtp
+        | Validation::Ldap
+        | Validation::Ssh
+        | Validation::JWT => true,
+        Validation::Raw(_) => false,
+    }
+}
+
+fn is_safe_request(request: &HttpRequest) -> bool {
+    request.multipart.is_none()
+        && matches!(request.method.to_ascii_uppercase().as_str(), "GET" | "HEAD" | "OPTIONS")
+}
+
+/// Host of a URL template when the scheme and authority contain no Liquid markup.
+pub fn static_template_host(template: &str) -> Option<String> {
+    let (scheme, rest) = template.trim().split_once("://")?;
+    if scheme.is_empty()
+        || !scheme.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'))
+    {
+        return None;
+    }
+    let authority = rest.split(['/', '?', '#']).next()?;
+    if authority.contains("{{") || authority.contains("{%") {
+        return None;
+    }
+    let url = Url::parse(&format!("{scheme}://{authority}/")).ok()?;
+    url.host_str().map(str::to_ascii_lowercase)
+}
+
+/// Hosts an HTTP request may be sent to: its `allowed_hosts`, or the literal
+/// host of its URL template. Empty when the host is templated and undeclared.
+pub fn declared_hosts(request: &HttpRequest) -> Vec<String> {
+    if !request.allowed_hosts.is_empty() {
+        return request.allowed_hosts.clone();
+    }
+    static_template_host(&request.url).into_iter().collect()
+}
+
+/// Check a rendered validation URL against the rule's declared hosts and the
+/// global policy. Runs before DNS resolution so a refused host is never looked up.
+pub fn check_request_url(request: &HttpRequest, url: &Url) -> Result<(), EgressDenied> {
+    let host = url.host_str().unwrap_or_default();
+    let declared = declared_hosts(request);
+    if declared.is_empty() {
+        return Err(EgressDenied::new(
+            host,
+            "the rule's URL host is templated and the rule declares no allowed_hosts",
+        ));
+    }
+    if !declared.iter().any(|p| host_matches(p, host)) {
+        return Err(EgressDenied::new(host, "not in the rule's allowed_hosts"));
+    }
+    policy().check_host(host)
+}
+
+/// Every host an HTTP validator or revocation may contact.
+pub fn http_hosts(http: &HttpValidation) -> Vec<String> {
+    http.requests().flat_map(declared_hosts).collect()
+}
+
+/// Run `fut` with `hosts` as the hosts its requests may be redirected to.
+pub async fn with_redirect_hosts<F: Future>(hosts: Vec<String>, fut: F) -> F::Output {
+    REDIRECT_HOSTS.scope(hosts.into(), fut).await
+}
+
+/// Redirect policy for validation clients. Every hop must pass the global
+/// policy and land on a host set with [`with_redirect_hosts`], or, when none
+/// were set, on the host of the original request.
+pub fn redirect_policy() -> redirect::Policy {
+    redirect::Policy::custom(|attempt| {
+        if attempt.previous().len() > MAX_REDIRECTS {
+            return attempt.error("too many redirects");
+        }
+        let host = attempt.url().host_str().unwrap_or_default().to_ascii_lowercase();
+        match check_redirect(&host, attempt.previous().first()) {
+            Ok(()) => attempt.follow(),
+            Err(denied) => attempt.error(denied),
+        }
+    })
+}
+
+fn check_redirect(host: &str, original: Option<&Url>) -> Result<(), EgressDenied> {
+    let declared = REDIRECT_HOSTS.try_with(Arc::clone).ok().filter(|hosts| !hosts.is_empty());
+    let allowed = match declared {
+        Some(hosts) => hosts.iter().any(|p| host_matches(p, host)),
+        None => original.and_then(Url::host_str).is_some_and(|h| h.eq_ignore_ascii_case(host)),
+    };
+    if !allowed {
+        return Err(EgressDenied::new(host, "redirect target is not an allowed host"));
+    }
+    policy().check_host(host)
+}
+
+/// Turn a request error caused by a refused redirect back into the
+/// [`EgressDenied`] behind it, so callers treat it like any other refusal.
+pub fn surface_redirect_denial(err: reqwest::Error) -> anyhow::Error {
+    let mut source = std::error::Error::source(&err);
+    while let Some(e) = source {
+        if let Some(denied) = e.downcast_ref::<EgressDenied>() {
+            return denied.clone().into();
+        }
+        source = e.source();
+    }
+    err.into()
+}
+
+/// Apply the global policy to a database connection string such as
+/// `postgres://…`, `mongodb+srv://…` or `jdbc:mysql://…`.
+pub fn check_connection_string(uri: &str) -> Result<(), EgressDenied> {
+    let uri = uri.trim();
+    let uri = uri.strip_prefix("jdbc:").unwrap_or(uri);
+    let host = Url::parse(uri).ok().and_then(|u| u.host_str().map(str::to_ascii_lowercase));
+    match host {
+        Some(host) => policy().check_host(&host),
+        None if policy().allow_hosts.is_empty() => Ok(()),
+        None => Err(EgressDenied::new("<unknown>", "host could not be determined")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use wiremock::{
+        matchers::{method, path},
+        Mock, MockServer, ResponseTemplate,
+    };
+
+    use super::*;
+
+    fn request(url: &str, allowed_hosts: &[&str]) -> HttpRequest {
+        HttpRequest {
+            method: "GET".to_string(),
+            url: url.to_string(),
+            headers: BTreeMap::new(),
+            body: None,
+            response_matcher: None,
+            multipart: None,
+            response_is_html: false,
+            allowed_hosts: allowed_hosts.iter().map(|h| h.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn wildcard_hosts_match_subdomains_only() {
+        assert!(host_matches("*.algolia.net", "abc-dsn.algolia.net"));
+        assert!(host_matches("*.datadoghq.com", "api.us3.datadoghq.com"));
+        assert!(!host_matches("*.algolia.net", "algolia.net"));
+        assert!(!host_matches("*.algolia.net", "evilalgolia.net"));
+        assert!(host_matches("API.Example.com", "api.example.com."));
+        assert!(host_matches("*", "anything.test"));
+    }
+
+    #[test]
+    fn static_host_is_read_from_template() {
+        assert_eq!(
+            static_template_host("https://api.github.com/user?x={{ TOKEN }}").as_deref(),
+            Some("api.github.com")
+        );
+        assert_eq!(static_template_host("https://{{ DOMAIN }}/api"), None);
+        assert_eq!(static_template_host("https://api.example.com@{{ HOST }}/"), None);
+        assert_eq!(static_template_host("{{ TOKEN }}"), None);
+    }
+
+    #[test]
+    fn templated_host_needs_declaration() {
+        let url = Url::parse("https://tenant.okta.com/api/v1/users/me").unwrap();
+        assert!(check_request_url(&request("https://{{ DOMAIN }}/api", &[]), &url).is_err());
+        assert!(
+            check_request_url(&request("https://{{ DOMAIN }}/api", &["*.okta.com"]), &url).is_ok()
+        );
+
+        let exfil = Url::parse("https://secret.attacker.test/").unwrap();
+        let req = request("https://{{ TOKEN }}.okta.com/", &["*.okta.com"]);
+        assert!(check_request_url(&req, &exfil).is_err());
+
+        let fixed = request("https://api.github.com/user", &[]);
+        let github = Url::parse("https://api.github.com/user").unwrap();
+        assert!(check_request_url(&fixed, &github).is_ok());
+        assert!(check_request_url(&fixed, &url).is_err());
+    }
+
+    #[test]
+    fn deny_list_wins_over_allow_list() {
+        let policy = EgressPolicy {
+            allow_hosts: vec!["*.example.com".into()],
+            deny_hosts: vec!["internal.example.com".into()],
+            side_effect_free: false,
+        };
+        assert!(policy.check_host("api.example.com").is_ok());
+        assert!(policy.check_host("internal.example.com").is_err());
+        assert!(policy.check_host("api.other.test").is_err());
+    }
+
+    #[test]
+    fn side_effect_free_mode_rejects_writes() {
+        let policy = EgressPolicy { side_effect_free: true, ..EgressPolicy::default() };
+        let get = Validation::Http(HttpValidation {
+            steps: vec![],
+            request: request("https://api.example.com/", &[]),
+            multipart: None,
+        });
+        let mut post_request = request("https://hooks.slack.com/services/x", &[]);
+        post_request.method = "POST".to_string();
+        let post = Validation::Http(HttpValidation {
+            steps: vec![],
+            request: post_request,
+            multipart: None,
+        });
+
+        assert!(policy.check_validation(&get).is_ok());
+        assert!(policy.check_validation(&Validation::AWS).is_ok());
+        assert_eq!(
+            policy.check_validation(&post).unwrap_err(),
+            "POST request is not side-effect-free"
+        );
+        assert!(EgressPolicy::default().check_validation(&post).is_ok());
+    }
+
+    #[tokio::test]
+    async fn redirects_must_stay_on_allowed_hosts() {
+        let target = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/leak"))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(0)
+            .mount(&target)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/ok"))
+            .respond_with(ResponseTemplate::new(200))
+            .mount(&target)
+            .await;
+
+        // The origin is allowed as 127.0.0.1; `localhost` reaches the same
+        // machine under a host name the rule does not declare.
+        let origin = MockServer::start().await;
+        let denied = format!("http://localhost:{}/leak", target.address().port());
+        Mock::given(path("/away"))
+            .respond_with(ResponseTemplate::new(302).insert_header("Location", denied.as_str()))
+            .mount(&origin)
+            .await;
+        let allowed = format!("{}/ok", target.uri());
+        Mock::given(path("/stay"))
+            .respond_with(ResponseTemplate::new(302).insert_header("Location", allowed.as_str()))
+            .mount(&origin)
+            .await;
+
+        let client = reqwest::Client::builder().redirect(redirect_policy()).build().unwrap();
+        let hosts = vec!["127.0.0.1".to_string()];
+        let err =
+            with_redirect_hosts(hosts.clone(), client.get(format!("{}/away", origin.uri())).send())
+                .await
+                .unwrap_err();
+        assert!(surface_redirect_denial(err).is::<EgressDenied>());
+
+        let resp = with_redirect_hosts(hosts, client.get(format!("{}/stay", origin.uri())).send())
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), reqwest::StatusCode::OK);
+
+        // Without declared hosts only the original host is followed.
+        let err = client.get(format!("{}/away", origin.uri())).send().await.unwrap_err();
+        assert!(surface_redirect_denial(err).is::<EgressDenied>());
+    }
+}
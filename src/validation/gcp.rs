@@ -1,6 +1,6 @@
 use std::sync::Arc;
 
-use crate::validation::GLOBAL_USER_AGENT;
+use crate::validation::{rate_limit, GLOBAL_USER_AGENT};
 use anyhow::{anyhow, Result};
 use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine as _};
 use chrono::{Duration as ChronoDuration, Utc};
@@ -60,16 +60,12 @@ impl GcpValidator {
         }
 
         let jwt = self.create_jwt(&client_email, &private_key, &token_uri)?;
-        let response = self
-            .client
-            .post(&token_uri)
-            .form(&[
-                ("grant_type", "urn:ietf:params:oauth:grant-type:jwt-bearer"),
-                ("assertion", &jwt),
-            ])
-            .send()
-            .await?
-            .error_for_status()?;
+        let response = rate_limit::send(self.client.post(&token_uri).form(&[
+            ("grant_type", "urn:ietf:params:oauth:grant-type:jwt-bearer"),
+            ("assertion", &jwt),
+        ]))
+        .await?
+        .error_for_status()?;
 
         let json: JsonValue = response.json().await?;
         let access_token = json["access_token"]
@@ -1,6 +1,11 @@
 use std::{collections::BTreeMap, future::Future, str::FromStr, time::Duration};
 
-use crate::validation::GLOBAL_USER_AGENT;
+use crate::validation::{
+    egress::EgressDenied,
+    json_match,
+    rate_limit::{self, BudgetExhausted},
+    GLOBAL_USER_AGENT,
+};
 use anyhow::{anyhow, Error, Result};
 use http::StatusCode;
 use liquid::Object;
@@ -15,7 +20,10 @@ use sha1::{Digest, Sha1};
 use tokio::time::sleep;
 use tracing::debug;
 
-use crate::rules::rule::ResponseMatcher;
+use crate::{
+    rules::rule::{Extractor, ResponseMatcher},
+    validation_body::ValidationIdentity,
+};
 
 /// Build a deterministic cache key from the immutable parts of an HTTP request.
 ///
@@ -180,10 +188,12 @@ pub fn process_headers(
     Ok(headers_map)
 }
 
-/// Exponential‐backoff retry helper that always returns `Result<T, anyhow::Error>`
+/// Exponential‐backoff retry helper that always returns `Result<T, anyhow::Error>`.
+/// When `retry_after` yields a delay for a result, it replaces the computed backoff.
 async fn retry_with_backoff<F, Fut, T>(
     mut operation: F,
     is_retryable: impl Fn(&Result<T, Error>, usize) -> bool,
+    retry_after: impl Fn(&Result<T, Error>) -> Option<Duration>,
     max_retries: usize,
     backoff_min: Duration,
     backoff_max: Duration,
@@ -201,14 +211,40 @@ where
         }
         retries += 1;
         if retries > max_retries {
-            break;
+            return result;
         }
-        let backoff = backoff_min.saturating_mul(2u32.pow(retries as u32)).min(backoff_max);
+        let backoff = retry_after(&result).unwrap_or_else(|| {
+            backoff_min.saturating_mul(2u32.pow(retries as u32)).min(backoff_max)
+        });
         sleep(backoff).await;
     }
     Err(anyhow!("Max retries reached"))
 }
 
+/// Transient HTTP statuses and network errors are worth another attempt; an
+/// exhausted request budget is not.
+fn is_transient(res: &Result<Response, Error>) -> bool {
+    match res {
+        Ok(resp) => matches!(
+            resp.status(),
+            StatusCode::TOO_MANY_REQUESTS
+                | StatusCode::BAD_GATEWAY
+                | StatusCode::SERVICE_UNAVAILABLE
+                | StatusCode::GATEWAY_TIMEOUT
+        ),
+        Err(e) => !e.is::<BudgetExhausted>() && !e.is::<EgressDenied>(),
+    }
+}
+
+/// A `Retry-After` hint has already paused the host in the shared limiter, so
+/// the retry waits there instead of sleeping here as well.
+fn retry_after_hint(res: &Result<Response, Error>) -> Option<Duration> {
+    res.as_ref()
+        .ok()
+        .and_then(|resp| rate_limit::retry_after(resp.headers()))
+        .map(|_| Duration::ZERO)
+}
+
 pub async fn retry_multipart_request<F, Fut>(
     mut build_request: F,
     max_retries: usize,
@@ -225,24 +261,12 @@ where
             let fut = build_request();
             async move {
                 let rb = fut.await;
-                rb.send().await.map_err(Error::from)
+                rate_limit::send(rb).await
             }
         },
         // 2) same retry logic
-        |res: &Result<_, Error>, _attempt| match res {
-            Ok(resp)
-                if matches!(
-                    resp.status(),
-                    StatusCode::BAD_GATEWAY
-                        | StatusCode::SERVICE_UNAVAILABLE
-                        | StatusCode::GATEWAY_TIMEOUT
-                ) =>
-            {
-                true
-            }
-            Err(_) => true,
-            _ => false,
-        },
+        |res: &Result<_, Error>, _attempt| is_transient(res),
+        retry_after_hint,
         max_retries,
         backoff_min,
         backoff_max,
@@ -261,23 +285,11 @@ pub async fn retry_request(
         move || {
             let rb =
                 request_builder.try_clone().expect("retry_request: failed to clone RequestBuilder");
-            async move { rb.send().await.map_err(Error::from) }
+            async move { rate_limit::send(rb).await }
         },
         // 2) is_retryable: transient HTTP status or network error
-        |res: &Result<_, Error>, _attempt| match res {
-            Ok(resp)
-                if matches!(
-                    resp.status(),
-                    StatusCode::BAD_GATEWAY
-                        | StatusCode::SERVICE_UNAVAILABLE
-                        | StatusCode::GATEWAY_TIMEOUT
-                ) =>
-            {
-                true
-            }
-            Err(_) => true,
-            _ => false,
-        },
+        |res: &Result<_, Error>, _attempt| is_transient(res),
+        retry_after_hint,
         max_retries as usize,
         backoff_min,
         backoff_max,
@@ -285,6 +297,45 @@ pub async fn retry_request(
     .await
 }
 
+/// Pull the value `extractor` selects out of a multi-step validation response.
+/// JSON strings are returned without quotes; other JSON values as JSON text.
+pub fn extract_value(extractor: &Extractor, body: &str, headers: &HeaderMap) -> Option<String> {
+    match extractor {
+        Extractor::JsonPointer(pointer) => {
+            let json: serde_json::Value = serde_json::from_str(body).ok()?;
+            match json.pointer(pointer)? {
+                serde_json::Value::String(s) => Some(s.clone()),
+                serde_json::Value::Null => None,
+                other => Some(other.to_string()),
+            }
+        }
+        Extractor::Header(name) => {
+            headers.get(name.as_str())?.to_str().ok().map(|v| v.trim().to_string())
+        }
+        Extractor::Regex(pattern) => {
+            let caps = regex::Regex::new(pattern).ok()?.captures(body)?;
+            caps.get(1).or_else(|| caps.get(0)).map(|m| m.as_str().to_string())
+        }
+    }
+}
+
+/// Collect a rule's identity fields from a successful validation response,
+/// skipping any the response does not contain.
+pub fn extract_identity(
+    extract: &BTreeMap<String, Extractor>,
+    body: &str,
+    headers: &HeaderMap,
+) -> ValidationIdentity {
+    extract
+        .iter()
+        .filter_map(|(name, extractor)| {
+            extract_value(extractor, body, headers)
+                .filter(|value| !value.is_empty())
+                .map(|value| (name.clone(), value))
+        })
+        .collect()
+}
+
 /// Return `true` when the body is very likely HTML.
 fn body_looks_like_html(body: &str, headers: &HeaderMap) -> bool {
     // ---- 1. header heuristic ---------------------------------------------
@@ -396,6 +447,17 @@ pub fn validate_response(
         })
         .all(|b| b);
 
+    let json_match_ok = matchers
+        .iter()
+        .filter_map(|m| {
+            if let ResponseMatcher::JsonMatch(matcher) = m {
+                Some(json_match::matches(matcher, body))
+            } else {
+                None
+            }
+        })
+        .all(|b| b);
+
     let xml_ok = matchers
         .iter()
         .filter_map(|m| {
@@ -418,7 +480,7 @@ pub fn validate_response(
     // );
     // // ──────────────────────────────────────────────────────────────
 
-    let all_ok = word_ok && status_ok && header_ok && json_ok && xml_ok && html_ok;
+    let all_ok = word_ok && status_ok && header_ok && json_ok && json_match_ok && xml_ok && html_ok;
     all_ok
 }
 
@@ -497,6 +559,32 @@ mod tests {
         .await;
         assert!(response.is_ok());
     }
+
+    #[tokio::test]
+    async fn test_retry_request_honours_retry_after() {
+        init();
+        let mock_server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/limited"))
+            .respond_with(ResponseTemplate::new(429).insert_header("Retry-After", "0"))
+            .up_to_n_times(1)
+            .with_priority(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/limited"))
+            .respond_with(ResponseTemplate::new(200))
+            .mount(&mock_server)
+            .await;
+        let client = Client::new();
+        let request_builder = client.get(format!("{}/limited", mock_server.uri()));
+        // The 429 would normally back off for 10s; `Retry-After: 0` retries at once.
+        let response =
+            retry_request(request_builder, 1, Duration::from_secs(10), Duration::from_secs(10))
+                .await
+                .expect("request succeeds after retry");
+        assert_eq!(response.status(), StatusCode::OK);
+    }
     #[test]
     fn test_validate_response() {
         // --- arrange ----------------------------------------------------------
@@ -548,6 +636,30 @@ mod tests {
         assert!(ok, "Slack webhook response should be considered ACTIVE");
     }
 
+    #[test]
+    fn test_validate_response_json_match_reads_error_envelope() {
+        let matchers: Vec<ResponseMatcher> = serde_yaml::from_str(
+            r#"
+- type: StatusMatch
+  status: [200]
+- type: JsonMatch
+  path: $.ok
+  equals: true
+- type: JsonMatch
+  path: /error
+  exists: false
+"#,
+        )
+        .expect("matchers parse");
+        assert!(matches!(matchers[1], ResponseMatcher::JsonMatch(_)));
+
+        let headers = HeaderMap::new();
+        let active = r#"{"ok":true,"user":"bot"}"#;
+        let revoked = r#"{"ok":false,"error":"invalid_auth"}"#;
+        assert!(validate_response(&matchers, active, &StatusCode::OK, &headers, false));
+        assert!(!validate_response(&matchers, revoked, &StatusCode::OK, &headers, false));
+    }
+
     #[test]
     fn test_body_looks_like_html_trims_whitespace() {
         let mut headers = HeaderMap::new();
@@ -605,4 +717,51 @@ mod tests {
             "Should correctly identify HTML even with multi-byte characters at boundary"
         );
     }
+
+    #[test]
+    fn extract_value_reads_json_headers_and_regex() {
+        let body = r#"{"access_token":"at-123","expires_in":3600,"user":{"id":7}}"#;
+        let mut headers = HeaderMap::new();
+        headers.insert("x-session", HeaderValue::from_static(" s-42 "));
+
+        let extract = |e: Extractor| extract_value(&e, body, &headers);
+        assert_eq!(
+            extract(Extractor::JsonPointer("/access_token".into())).as_deref(),
+            Some("at-123")
+        );
+        assert_eq!(extract(Extractor::JsonPointer("/expires_in".into())).as_deref(), Some("3600"));
+        assert_eq!(extract(Extractor::JsonPointer("/user".into())).as_deref(), Some(r#"{"id":7}"#));
+        assert_eq!(extract(Extractor::JsonPointer("/missing".into())), None);
+        assert_eq!(extract(Extractor::Header("X-Session".into())).as_deref(), Some("s-42"));
+        assert_eq!(
+            extract(Extractor::Regex(r#""access_token":"([^"]+)""#.into())).as_deref(),
+            Some("at-123")
+        );
+        assert_eq!(extract(Extractor::Regex(r"at-\d+".into())).as_deref(), Some("at-123"));
+        assert_eq!(extract(Extractor::Regex(r"nope(\d)".into())), None);
+    }
+
+    #[test]
+    fn extract_identity_skips_missing_fields() {
+        let body = r#"{"login":"octocat","id":583231,"email":null}"#;
+        let mut headers = HeaderMap::new();
+        headers.insert("x-oauth-scopes", HeaderValue::from_static("repo, read:org"));
+
+        let extract = BTreeMap::from([
+            ("username".to_string(), Extractor::JsonPointer("/login".into())),
+            ("account_id".to_string(), Extractor::JsonPointer("/id".into())),
+            ("email".to_string(), Extractor::JsonPointer("/email".into())),
+            ("org".to_string(), Extractor::JsonPointer("/company".into())),
+            ("scopes".to_string(), Extractor::Header("X-OAuth-Scopes".into())),
+        ]);
+        let identity = extract_identity(&extract, body, &headers);
+        assert_eq!(
+            identity,
+            ValidationIdentity::from([
+                ("account_id".to_string(), "583231".to_string()),
+                ("scopes".to_string(), "repo, read:org".to_string()),
+                ("username".to_string(), "octocat".to_string()),
+            ])
+        );
+    }
 }
@@ -0,0 +1,240 @@
+//! Evaluation of `JsonMatch` response matchers.
+//!
+//! Paths are either JSON pointers (`/data/0/id`) or a JSONPath subset: `$`
+//! followed by `.name`, `['name']`, `[index]`, `.*` or `[*]`.
+
+use regex::Regex;
+use serde_json::Value;
+
+use crate::rules::rule::{JsonLiteral, JsonMatcher};
+
+#[derive(Debug, PartialEq, Eq)]
+enum Segment {
+    Key(String),
+    Index(usize),
+    Wildcard,
+}
+
+/// Parse a JSON pointer or JSONPath expression into selection steps.
+fn parse_path(path: &str) -> Result<Vec<Segment>, String> {
+    if path.is_empty() {
+        return Ok(Vec::new());
+    }
+    if let Some(pointer) = path.strip_prefix('/') {
+        return Ok(pointer
+            .split('/')
+            .map(|token| Segment::Key(token.replace("~1", "/").replace("~0", "~")))
+            .collect());
+    }
+    let Some(mut rest) = path.strip_prefix('$') else {
+        return Err(format!("path `{path}` must be a JSON pointer (`/a/b`) or JSONPath (`$.a.b`)"));
+    };
+
+    let mut segments = Vec::new();
+    while !rest.is_empty() {
+        if let Some(after) = rest.strip_prefix(".*") {
+            segments.push(Segment::Wildcard);
+            rest = after;
+        } else if let Some(after) = rest.strip_prefix('.') {
+            let end = after.find(['.', '[']).unwrap_or(after.len());
+            if end == 0 {
+                return Err(format!("empty member name in `{path}`"));
+            }
+            segments.push(Segment::Key(after[..end].to_string()));
+            rest = &after[end..];
+        } else if let Some(after) = rest.strip_prefix('[') {
+            let end = after.find(']').ok_or_else(|| format!("unclosed `[` in `{path}`"))?;
+            let inner = after[..end].trim();
+            segments.push(if inner == "*" {
+                Segment::Wildcard
+            } else if let Some(name) = quoted(inner) {
+                Segment::Key(name.to_string())
+            } else {
+                Segment::Index(
+                    inner.parse().map_err(|_| format!("invalid index `{inner}` in `{path}`"))?,
+                )
+            });
+            rest = &after[end + 1..];
+        } else {
+            return Err(format!("unexpected `{rest}` in `{path}`"));
+        }
+    }
+    Ok(segments)
+}
+
+fn quoted(s: &str) -> Option<&str> {
+    s.strip_prefix('\'')
+        .and_then(|s| s.strip_suffix('\''))
+        .or_else(|| s.strip_prefix('"').and_then(|s| s.strip_suffix('"')))
+}
+
+fn select<'a>(value: &'a Value, segments: &[Segment]) -> Vec<&'a Value> {
+    let Some((first, rest)) = segments.split_first() else {
+        return vec![value];
+    };
+    let children: Vec<&Value> = match (first, value) {
+        (Segment::Key(key), Value::Object(map)) => map.get(key).into_iter().collect(),
+        // JSON pointer tokens address array elements by number.
+        (Segment::Key(key), Value::Array(items)) => {
+            key.parse::<usize>().ok().and_then(|i| items.get(i)).into_iter().collect()
+        }
+        (Segment::Index(i), Value::Array(items)) => items.get(*i).into_iter().collect(),
+        (Segment::Wildcard, Value::Array(items)) => items.iter().collect(),
+        (Segment::Wildcard, Value::Object(map)) => map.values().collect(),
+        _ => Vec::new(),
+    };
+    children.into_iter().flat_map(|child| select(child, rest)).collect()
+}
+
+fn as_number(literal: &Option<JsonLiteral>) -> Option<f64> {
+    literal.as_ref().and_then(|l| l.0.as_f64())
+}
+
+/// Reject a matcher that could never be evaluated.
+pub fn check(matcher: &JsonMatcher) -> Result<(), String> {
+    parse_path(&matcher.path)?;
+    if let Some(pattern) = &matcher.regex {
+        Regex::new(pattern).map_err(|e| format!("invalid regex `{pattern}`: {e}"))?;
+    }
+    for (name, bound) in [("min", &matcher.min), ("max", &matcher.max)] {
+        if bound.is_some() && as_number(bound).is_none() {
+            return Err(format!("`{name}` must be a number"));
+        }
+    }
+    Ok(())
+}
+
+fn value_matches(matcher: &JsonMatcher, regex: Option<&Regex>, value: &Value) -> bool {
+    if let Some(JsonLiteral(expected)) = &matcher.equals {
+        let equal = match (expected.as_f64(), value.as_f64()) {
+            (Some(a), Some(b)) => a == b,
+            _ => expected == value,
+        };
+        if !equal {
+            return false;
+        }
+    }
+    if let Some(re) = regex {
+        let text = match value {
+            Value::String(s) => s.clone(),
+            other => other.to_string(),
+        };
+        if !re.is_match(&text) {
+            return false;
+        }
+    }
+    if matcher.min.is_some() || matcher.max.is_some() {
+        let Some(n) = value.as_f64() else {
+            return false;
+        };
+        if as_number(&matcher.min).is_some_and(|min| n < min)
+            || as_number(&matcher.max).is_some_and(|max| n > max)
+        {
+            return false;
+        }
+    }
+    true
+}
+
+/// Evaluate `matcher` against a response body. A body that is not JSON never
+/// matches, whatever `negative` says.
+pub fn matches(matcher: &JsonMatcher, body: &str) -> bool {
+    let Ok(json) = serde_json::from_str::<Value>(body) else {
+        return false;
+    };
+    let Ok(segments) = parse_path(&matcher.path) else {
+        return false;
+    };
+    let regex = match matcher.regex.as_deref().map(Regex::new).transpose() {
+        Ok(regex) => regex,
+        Err(_) => return false,
+    };
+
+    let selected = select(&json, &segments);
+    let raw = if matcher.exists == Some(false) {
+        selected.is_empty()
+    } else {
+        selected.iter().any(|value| value_matches(matcher, regex.as_ref(), value))
+    };
+    raw != matcher.negative
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn matcher(path: &str) -> JsonMatcher {
+        JsonMatcher {
+            r#type: "JsonMatch".to_string(),
+            path: path.to_string(),
+            equals: None,
+            regex: None,
+            min: None,
+            max: None,
+            exists: None,
+            negative: false,
+        }
+    }
+
+    #[test]
+    fn parses_pointers_and_jsonpath() {
+        assert_eq!(
+            parse_path("/a~1b/0").unwrap(),
+            vec![Segment::Key("a/b".into()), Segment::Key("0".into())]
+        );
+        assert_eq!(
+            parse_path("$.data[*]['user id'][2]").unwrap(),
+            vec![
+                Segment::Key("data".into()),
+                Segment::Wildcard,
+                Segment::Key("user id".into()),
+                Segment::Index(2),
+            ]
+        );
+        assert!(parse_path("data.ok").is_err());
+        assert!(parse_path("$.data[x]").is_err());
+        assert!(parse_path("$.data[0").is_err());
+    }
+
+    #[test]
+    fn evaluates_conditions() {
+        let body = r#"{"ok":true,"user":"U123","credits":12,"items":[{"id":"a"},{"id":"b"}]}"#;
+
+        let ok = JsonMatcher { equals: Some(JsonLiteral(json!(true))), ..matcher("$.ok") };
+        assert!(matches(&ok, body));
+        assert!(!matches(&ok, r#"{"ok":false,"error":"invalid_auth"}"#));
+        assert!(!matches(&ok, "not json"));
+
+        let user = JsonMatcher { regex: Some("^U[0-9]+$".into()), ..matcher("/user") };
+        assert!(matches(&user, body));
+
+        let credits = JsonMatcher {
+            min: Some(JsonLiteral(json!(1))),
+            max: Some(JsonLiteral(json!(10))),
+            ..matcher("$.credits")
+        };
+        assert!(!matches(&credits, body));
+
+        let any_item =
+            JsonMatcher { equals: Some(JsonLiteral(json!("b"))), ..matcher("$.items[*].id") };
+        assert!(matches(&any_item, body));
+
+        assert!(matches(&matcher("$.user"), body));
+        let no_error = JsonMatcher { exists: Some(false), ..matcher("$.error") };
+        assert!(matches(&no_error, body));
+        let not_ok = JsonMatcher { negative: true, ..ok };
+        assert!(!matches(&not_ok, body));
+    }
+
+    #[test]
+    fn check_rejects_bad_matchers() {
+        assert!(check(&matcher("$.ok")).is_ok());
+        assert!(check(&matcher("ok")).is_err());
+        assert!(check(&JsonMatcher { regex: Some("(".into()), ..matcher("$.ok") }).is_err());
+        assert!(
+            check(&JsonMatcher { min: Some(JsonLiteral(json!("one"))), ..matcher("$.n") }).is_err()
+        );
+    }
+}
@@ -1,16 +1,22 @@
 use anyhow::{anyhow, Result};
 use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine as _};
-use chrono::Utc;
+use chrono::{DateTime, Utc};
+use dashmap::DashMap;
 use ipnet::IpNet;
 use jsonwebtoken::{
     decode, decode_header, jwk::JwkSet, Algorithm, DecodingKey, Validation as JwtValidation,
 };
 use once_cell::sync::Lazy;
 use reqwest::{redirect::Policy, Client, Url};
+use ring::hmac;
 use serde::Deserialize;
+use serde_json::Value;
 use tokio::net::lookup_host;
 
-use super::utils::check_url_resolvable;
+use super::{rate_limit, utils::check_url_resolvable};
+use crate::{
+    entropy::calculate_shannon_entropy, util::redact_value, validation_body::ValidationIdentity,
+};
 
 /// One global, redirect-free client.  Building a `Client` is comparatively
 /// expensive; re-using it lets reqwest share its internal connection pool
@@ -32,6 +38,23 @@ const BLOCKED_NETS: &[&str] = &[
     "169.254.0.0/16", // loopback / link-local
 ];
 
+/// Candidate signing keys for HS256/384/512 tokens: secrets found anywhere in
+/// the current scan, mapped to where they were found.
+static HMAC_SECRETS: Lazy<DashMap<String, String>> = Lazy::new(DashMap::new);
+
+/// Cap on remembered candidates, so a noisy scan cannot make every HS* token
+/// cost unbounded HMAC work.
+const MAX_HMAC_SECRETS: usize = 10_000;
+
+/// Claims reported under their own names; everything else is a custom claim.
+const REGISTERED_CLAIMS: &[&str] = &["iss", "sub", "aud", "exp", "nbf", "iat", "jti"];
+
+/// Custom claims whose names contain one of these are always masked.
+const SENSITIVE_CLAIM_HINTS: &[&str] =
+    &["secret", "password", "passwd", "pwd", "token", "key", "credential", "session", "cookie"];
+
+const MAX_CLAIM_CHARS: usize = 200;
+
 //  aud is allowed to be either a string or an array, so let Serde flatten it.
 #[derive(Debug, Deserialize)]
 #[serde(untagged)]
@@ -125,9 +148,19 @@ pub async fn validate_jwt_with(token: &str, opts: &ValidateOptions) -> Result<(b
     let header = decode_header(token).map_err(|e| anyhow!("decode header: {e}"))?;
     let alg = header.alg;
 
-    // Proactively skip HMAC-signed JWTs to avoid ambiguous liveness results.
-    if matches!(alg, Algorithm::HS256 | Algorithm::HS384 | Algorithm::HS512) {
-        return Ok((false, format!("HMAC-signed JWTs are not validated ({alg:?})")));
+    // HMAC-signed tokens have no issuer keys to fetch; they are genuine only if
+    // a secret found in the scan produced the signature.
+    if is_hmac(alg) {
+        return Ok(match find_hmac_secret(token, alg) {
+            Some(source) => (
+                true,
+                format!("JWT signature verified with an HMAC secret from {source} (alg: {alg:?})"),
+            ),
+            None => (
+                false,
+                format!("HMAC-signed JWT ({alg:?}) not verified by any secret found in the scan"),
+            ),
+        });
     }
 
     let issuer = claims.iss.clone().unwrap_or_default();
@@ -169,9 +202,7 @@ pub async fn validate_jwt_with(token: &str, opts: &ValidateOptions) -> Result<(b
 
     // build discovery URL and fetch it (redirects disabled)
     let config_url = format!("{}/.well-known/openid-configuration", issuer.trim_end_matches('/'));
-    let cfg_resp = NO_REDIRECT_CLIENT
-        .get(&config_url)
-        .send()
+    let cfg_resp = rate_limit::send(NO_REDIRECT_CLIENT.get(&config_url))
         .await
         .map_err(|e| anyhow!("issuer discovery failed: {e}"))?;
 
@@ -219,8 +250,9 @@ pub async fn validate_jwt_with(token: &str, opts: &ValidateOptions) -> Result<(b
     check_url_resolvable(&url).await.map_err(|e| anyhow!("jwks uri unresolvable: {e}"))?;
 
     // fetch JWKS with redirect-free client
-    let jwks_resp =
-        NO_REDIRECT_CLIENT.get(url).send().await.map_err(|e| anyhow!("jwks fetch failed: {e}"))?;
+    let jwks_resp = rate_limit::send(NO_REDIRECT_CLIENT.get(url))
+        .await
+        .map_err(|e| anyhow!("jwks fetch failed: {e}"))?;
     if !jwks_resp.status().is_success() {
         return Ok((false, format!("jwks fetch failed: {}", jwks_resp.status())));
     }
@@ -249,6 +281,141 @@ pub async fn validate_jwt_with(token: &str, opts: &ValidateOptions) -> Result<(b
     Ok((true, format!("JWT valid (alg: {:?}, iss: {issuer}, aud: {:?})", alg, aud_strings)))
 }
 
+/// Remember a secret found in the scan as a possible signing key for HS*
+/// tokens. `source` says where it was found and is only built for new
+/// candidates.
+pub fn remember_hmac_secret(secret: &str, source: impl FnOnce() -> String) {
+    if secret.is_empty()
+        || secret.len() > 512
+        || looks_like_jwt(secret)
+        || HMAC_SECRETS.len() >= MAX_HMAC_SECRETS
+    {
+        return;
+    }
+    HMAC_SECRETS.entry(secret.to_string()).or_insert_with(source);
+}
+
+fn looks_like_jwt(value: &str) -> bool {
+    value.starts_with("ey") && value.matches('.').count() == 2
+}
+
+fn is_hmac(alg: Algorithm) -> bool {
+    matches!(alg, Algorithm::HS256 | Algorithm::HS384 | Algorithm::HS512)
+}
+
+/// Where the remembered secret that signed `token` was found, if any.
+fn find_hmac_secret(token: &str, alg: Algorithm) -> Option<String> {
+    let algorithm = match alg {
+        Algorithm::HS256 => hmac::HMAC_SHA256,
+        Algorithm::HS384 => hmac::HMAC_SHA384,
+        Algorithm::HS512 => hmac::HMAC_SHA512,
+        _ => return None,
+    };
+    let (signing_input, signature) = token.rsplit_once('.')?;
+    let signature = URL_SAFE_NO_PAD.decode(signature).ok()?;
+    HMAC_SECRETS
+        .iter()
+        .find(|candidate| {
+            let key = hmac::Key::new(algorithm, candidate.key().as_bytes());
+            hmac::verify(&key, signing_input.as_bytes(), &signature).is_ok()
+        })
+        .map(|candidate| candidate.value().clone())
+}
+
+/// Decode `token` for the report without trusting it: header fields,
+/// registered claims, the validity window, custom claims (secret-looking
+/// values masked) and, for HS* tokens, whether a secret found in the scan
+/// produced the signature. Empty when the token does not decode.
+pub fn describe_jwt(token: &str) -> ValidationIdentity {
+    let mut out = ValidationIdentity::new();
+    let decode_part = |part: Option<&str>| -> Option<serde_json::Map<String, Value>> {
+        let json = URL_SAFE_NO_PAD.decode(part?).ok()?;
+        match serde_json::from_slice(&json).ok()? {
+            Value::Object(map) => Some(map),
+            _ => None,
+        }
+    };
+    let mut parts = token.split('.');
+    let (Some(header), Some(claims)) = (decode_part(parts.next()), decode_part(parts.next()))
+    else {
+        return out;
+    };
+
+    for field in ["alg", "typ", "kid"] {
+        if let Some(value) = header.get(field) {
+            out.insert(field.to_string(), render_claim(value));
+        }
+    }
+    for field in ["iss", "sub", "jti"] {
+        if let Some(value) = claims.get(field) {
+            out.insert(field.to_string(), render_claim(value));
+        }
+    }
+    if let Some(aud) = claims.get("aud") {
+        let aud = match aud {
+            Value::Array(items) => items.iter().map(render_claim).collect::<Vec<_>>().join(", "),
+            other => render_claim(other),
+        };
+        out.insert("aud".to_string(), aud);
+    }
+
+    let timestamp = |field: &str| claims.get(field).and_then(Value::as_i64);
+    for field in ["iat", "nbf", "exp"] {
+        if let Some(ts) = timestamp(field) {
+            let when = DateTime::from_timestamp(ts, 0).map_or(ts.to_string(), |t| t.to_rfc3339());
+            out.insert(field.to_string(), when);
+        }
+    }
+    let now = Utc::now().timestamp();
+    let validity = match (timestamp("nbf"), timestamp("exp")) {
+        (Some(nbf), _) if now < nbf => "not yet valid",
+        (_, Some(exp)) if now > exp => "expired",
+        (_, Some(_)) => "valid",
+        _ => "no expiry",
+    };
+    out.insert("validity".to_string(), validity.to_string());
+
+    for (name, value) in
+        claims.iter().filter(|(name, _)| !REGISTERED_CLAIMS.contains(&name.as_str()))
+    {
+        out.insert(format!("claim.{name}"), mask_claim(name, value));
+    }
+
+    let alg = header.get("alg").and_then(Value::as_str).and_then(|a| a.parse::<Algorithm>().ok());
+    if let Some(alg) = alg.filter(|alg| is_hmac(*alg)) {
+        let signature = match find_hmac_secret(token, alg) {
+            Some(source) => format!("verified with secret from {source}"),
+            None => "not verified by any secret found in the scan".to_string(),
+        };
+        out.insert("signature".to_string(), signature);
+    }
+    out
+}
+
+fn render_claim(value: &Value) -> String {
+    let rendered = match value {
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    };
+    rendered.chars().take(MAX_CLAIM_CHARS).collect()
+}
+
+/// Render a custom claim, masking any value of a claim named like a secret
+/// and long high-entropy strings.
+fn mask_claim(name: &str, value: &Value) -> String {
+    let name = name.to_ascii_lowercase();
+    let sensitive_name = SENSITIVE_CLAIM_HINTS.iter().any(|hint| name.contains(hint));
+    let high_entropy = matches!(
+        value,
+        Value::String(s) if s.len() >= 16 && calculate_shannon_entropy(s.as_bytes()) >= 3.5
+    );
+    if sensitive_name || high_entropy {
+        redact_value(&value.to_string())
+    } else {
+        render_claim(value)
+    }
+}
+
 /// Helper: normalize aud into a flat Vec<String>
 fn extract_aud_strings(claims: &Claims) -> Vec<String> {
     match &claims.aud {
@@ -264,7 +431,9 @@ fn is_blocked_ip(ip: std::net::IpAddr) -> bool {
 
 #[cfg(test)]
 mod tests {
-    use super::{validate_jwt, validate_jwt_with, ValidateOptions};
+    use super::{
+        describe_jwt, remember_hmac_secret, validate_jwt, validate_jwt_with, ValidateOptions,
+    };
     use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine as _};
     use chrono::{Duration as ChronoDuration, Utc};
     use jsonwebtoken::{encode, EncodingKey, Header};
@@ -283,7 +452,7 @@ mod tests {
     }
 
     #[tokio::test]
-    async fn hmac_signed_tokens_skipped() {
+    async fn hmac_signed_tokens_need_a_secret_from_the_scan() {
         let mut header = Header::new(jsonwebtoken::Algorithm::HS256);
         header.kid = Some("dummy".into());
 
@@ -292,10 +461,52 @@ mod tests {
             "exp": (Utc::now() + ChronoDuration::minutes(5)).timestamp(),
         });
 
-        let token = encode(&header, &payload, &EncodingKey::from_secret(b"secret")).unwrap();
-        let res = validate_jwt(&token).await.unwrap();
+        let forged =
+            encode(&header, &payload, &EncodingKey::from_secret(b"not-in-the-scan")).unwrap();
+        let res = validate_jwt(&forged).await.unwrap();
         assert!(!res.0);
-        assert!(res.1.contains("HMAC-signed JWTs are not validated"));
+        assert!(res.1.contains("not verified by any secret found in the scan"), "{}", res.1);
+
+        remember_hmac_secret("kf-hs256-test-secret", || "kingfisher.generic.1 in .env".into());
+        let genuine =
+            encode(&header, &payload, &EncodingKey::from_secret(b"kf-hs256-test-secret")).unwrap();
+        let res = validate_jwt(&genuine).await.unwrap();
+        assert!(res.0, "{}", res.1);
+        assert!(res.1.contains("kingfisher.generic.1 in .env"), "{}", res.1);
+        assert_eq!(
+            describe_jwt(&genuine).get("signature").map(String::as_str),
+            Some("verified with secret from kingfisher.generic.1 in .env")
+        );
+    }
+
+    #[test]
+    fn describes_claims_and_masks_secrets() {
+        let header = Header::new(jsonwebtoken::Algorithm::HS512);
+        let payload = serde_json::json!({
+            "iss": "https://auth.example.com",
+            "sub": "user-42",
+            "aud": ["api", "admin"],
+            "iat": 1_700_000_000,
+            "exp": 1_700_003_600,
+            "role": "admin",
+            "refresh_token": "short",
+            "nonce": "Zx8q2LmP0vRt7WyK4dNb",
+        });
+        let token = encode(&header, &payload, &EncodingKey::from_secret(b"unknown")).unwrap();
+
+        let described = describe_jwt(&token);
+        let field = |name: &str| described.get(name).map(String::as_str);
+        assert_eq!(field("alg"), Some("HS512"));
+        assert_eq!(field("sub"), Some("user-42"));
+        assert_eq!(field("aud"), Some("api, admin"));
+        assert_eq!(field("exp"), Some("2023-11-14T23:13:20+00:00"));
+        assert_eq!(field("validity"), Some("expired"));
+        assert_eq!(field("claim.role"), Some("admin"));
+        assert!(field("claim.refresh_token").unwrap().starts_with("[REDACTED:"));
+        assert!(field("claim.nonce").unwrap().starts_with("[REDACTED:"));
+        assert_eq!(field("signature"), Some("not verified by any secret found in the scan"));
+
+        assert!(describe_jwt("not.a.jwt").is_empty());
     }
 
     #[tokio::test]
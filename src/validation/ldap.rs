@@ -0,0 +1,401 @@
+//! LDAP simple-bind validation: connect (LDAPS, or StartTLS on `ldap://`),
+//! bind with the DN and password, and ask the server who we are with the
+//! WhoAmI extended operation (RFC 4532). Nothing is searched or modified.
+
+use std::time::Duration;
+
+use anyhow::{anyhow, bail, Context, Result};
+use tokio::{
+    io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt},
+    net::TcpStream,
+    time::{error::Elapsed, timeout},
+};
+use tracing::debug;
+use url::Url;
+
+use super::{mysql::is_local_host, utils::tls_connect};
+
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+const LDAP_PORT: u16 = 389;
+const LDAPS_PORT: u16 = 636;
+const MAX_MESSAGE_LEN: usize = 1 << 20;
+
+const STARTTLS_OID: &str = "1.3.6.1.4.1.1466.20037";
+const WHOAMI_OID: &str = "1.3.6.1.4.1.4203.1.11.3";
+
+// BER tags of the protocol operations used here.
+const BIND_REQUEST: u8 = 0x60;
+const BIND_RESPONSE: u8 = 0x61;
+const UNBIND_REQUEST: u8 = 0x42;
+const EXTENDED_REQUEST: u8 = 0x77;
+const EXTENDED_RESPONSE: u8 = 0x78;
+
+const RESULT_SUCCESS: u32 = 0;
+const RESULT_INVALID_CREDENTIALS: u32 = 49;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LdapTarget {
+    pub host: String,
+    pub port: u16,
+    /// `ldaps://`: TLS from the first byte.
+    pub tls: bool,
+}
+
+pub fn parse_ldap_url(ldap_url: &str) -> Result<LdapTarget> {
+    let trimmed = ldap_url.trim();
+    if trimmed.is_empty() {
+        return Err(anyhow!("LDAP URL is empty"));
+    }
+
+    let lower = trimmed.to_ascii_lowercase();
+    let tls = if lower.starts_with("ldaps://") {
+        true
+    } else if lower.starts_with("ldap://") {
+        false
+    } else {
+        return Err(anyhow!("LDAP URL must start with ldap:// or ldaps://"));
+    };
+
+    let parsed = Url::parse(trimmed).map_err(|e| anyhow!("Failed to parse LDAP URL: {e}"))?;
+    let host = parsed
+        .host_str()
+        .map(|h| h.trim_matches(|c| c == '[' || c == ']'))
+        .filter(|h| !h.is_empty())
+        .ok_or_else(|| anyhow!("LDAP URL is missing a host"))?
+        .to_string();
+    let port = parsed.port().unwrap_or(if tls { LDAPS_PORT } else { LDAP_PORT });
+
+    Ok(LdapTarget { host, port, tls })
+}
+
+pub fn generate_ldap_cache_key(ldap_url: &str, bind_dn: &str, password: &str) -> String {
+    use sha1::{Digest, Sha1};
+
+    let mut hasher = Sha1::new();
+    hasher.update(format!("{ldap_url}\n{bind_dn}\n{password}").as_bytes());
+    format!("LDAP:{:x}", hasher.finalize())
+}
+
+// -----------------------------------------------------------------------------
+// Minimal BER encoding for the handful of LDAPv3 messages we send
+// -----------------------------------------------------------------------------
+fn tlv(tag: u8, value: &[u8]) -> Vec<u8> {
+    let mut out = vec![tag];
+    if value.len() < 0x80 {
+        out.push(value.len() as u8);
+    } else {
+        let len = value.len().to_be_bytes();
+        let skip = len.iter().take_while(|b| **b == 0).count();
+        out.push(0x80 | (len.len() - skip) as u8);
+        out.extend_from_slice(&len[skip..]);
+    }
+    out.extend_from_slice(value);
+    out
+}
+
+fn integer(tag: u8, value: u32) -> Vec<u8> {
+    let bytes = value.to_be_bytes();
+    let mut start = 0;
+    while start < 3 && bytes[start] == 0 && bytes[start + 1] & 0x80 == 0 {
+        start += 1;
+    }
+    tlv(tag, &bytes[start..])
+}
+
+fn message(id: u32, op: &[u8]) -> Vec<u8> {
+    tlv(0x30, &[integer(0x02, id), op.to_vec()].concat())
+}
+
+fn bind_request(bind_dn: &str, password: &str) -> Vec<u8> {
+    tlv(
+        BIND_REQUEST,
+        &[integer(0x02, 3), tlv(0x04, bind_dn.as_bytes()), tlv(0x80, password.as_bytes())].concat(),
+    )
+}
+
+fn extended_request(oid: &str) -> Vec<u8> {
+    tlv(EXTENDED_REQUEST, &tlv(0x80, oid.as_bytes()))
+}
+
+/// Split one TLV off `buf`: `(tag, value, rest)`.
+fn parse_tlv(buf: &[u8]) -> Result<(u8, &[u8], &[u8])> {
+    let truncated = || anyhow!("Truncated LDAP message");
+    let (&tag, rest) = buf.split_first().ok_or_else(truncated)?;
+    let (&first, mut rest) = rest.split_first().ok_or_else(truncated)?;
+    let len = if first & 0x80 == 0 {
+        usize::from(first)
+    } else {
+        let n = usize::from(first & 0x7f);
+        if n == 0 || n > 4 || rest.len() < n {
+            bail!("Unsupported LDAP length encoding");
+        }
+        let len = rest[..n].iter().fold(0usize, |acc, b| acc << 8 | usize::from(*b));
+        rest = &rest[n..];
+        len
+    };
+    if rest.len() < len {
+        return Err(truncated());
+    }
+    Ok((tag, &rest[..len], &rest[len..]))
+}
+
+fn parse_uint(value: &[u8]) -> Result<u32> {
+    if value.is_empty() || value.len() > 4 {
+        bail!("Unsupported LDAP integer");
+    }
+    Ok(value.iter().fold(0u32, |acc, b| acc << 8 | u32::from(*b)))
+}
+
+/// Read one LDAPMessage: `(message id, operation tag, operation body)`.
+async fn read_message<S: AsyncRead + Unpin>(conn: &mut S) -> Result<(u32, u8, Vec<u8>)> {
+    let mut head = [0u8; 2];
+    conn.read_exact(&mut head).await?;
+    if head[0] != 0x30 {
+        bail!("Malformed LDAP message");
+    }
+    let len = if head[1] & 0x80 == 0 {
+        usize::from(head[1])
+    } else {
+        let n = usize::from(head[1] & 0x7f);
+        if n == 0 || n > 4 {
+            bail!("Unsupported LDAP length encoding");
+        }
+        let mut len = [0u8; 4];
+        conn.read_exact(&mut len[4 - n..]).await?;
+        u32::from_be_bytes(len) as usize
+    };
+    if len > MAX_MESSAGE_LEN {
+        bail!("LDAP message exceeds {MAX_MESSAGE_LEN} bytes");
+    }
+    let mut body = vec![0u8; len];
+    conn.read_exact(&mut body).await?;
+
+    let (tag, id, rest) = parse_tlv(&body)?;
+    if tag != 0x02 {
+        bail!("Malformed LDAP message id");
+    }
+    let (op_tag, op, _controls) = parse_tlv(rest)?;
+    Ok((parse_uint(id)?, op_tag, op.to_vec()))
+}
+
+/// The parts of an LDAPResult we use, plus an extended response's value.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct LdapResult {
+    code: u32,
+    diagnostic: String,
+    response_value: Option<Vec<u8>>,
+}
+
+fn parse_result(op: &[u8]) -> Result<LdapResult> {
+    let (tag, code, rest) = parse_tlv(op)?;
+    if tag != 0x0a {
+        bail!("Malformed LDAP result");
+    }
+    let (_, _matched_dn, rest) = parse_tlv(rest)?;
+    let (_, diagnostic, mut rest) = parse_tlv(rest)?;
+    let mut response_value = None;
+    while !rest.is_empty() {
+        let (tag, value, next) = parse_tlv(rest)?;
+        if tag == 0x8b {
+            response_value = Some(value.to_vec());
+        }
+        rest = next;
+    }
+    Ok(LdapResult {
+        code: parse_uint(code)?,
+        diagnostic: String::from_utf8_lossy(diagnostic).into_owned(),
+        response_value,
+    })
+}
+
+async fn exchange<S: AsyncRead + AsyncWrite + Unpin>(
+    conn: &mut S,
+    id: u32,
+    op: &[u8],
+    expected_tag: u8,
+) -> Result<LdapResult> {
+    conn.write_all(&message(id, op)).await?;
+    conn.flush().await?;
+    let (reply_id, tag, body) = read_message(conn).await?;
+    if reply_id != id || tag != expected_tag {
+        // e.g. an unsolicited Notice of Disconnection (message id 0)
+        let detail = parse_result(&body).map(|r| r.diagnostic).unwrap_or_default();
+        bail!("Unexpected LDAP response (message {reply_id}, tag {tag:#04x}): {detail}");
+    }
+    parse_result(&body)
+}
+
+/// Bind, then ask WhoAmI. Returns the authorization identity (empty when the
+/// server does not support WhoAmI), or `None` when the credentials are
+/// rejected. Message ids start at `first_id`.
+async fn bind_and_whoami<S: AsyncRead + AsyncWrite + Unpin>(
+    conn: &mut S,
+    bind_dn: &str,
+    password: &str,
+    first_id: u32,
+) -> Result<Option<String>> {
+    let bind = exchange(conn, first_id, &bind_request(bind_dn, password), BIND_RESPONSE).await?;
+    match bind.code {
+        RESULT_SUCCESS => {}
+        RESULT_INVALID_CREDENTIALS => return Ok(None),
+        code => bail!("LDAP bind failed with result code {code}: {}", bind.diagnostic),
+    }
+
+    let whoami =
+        exchange(conn, first_id + 1, &extended_request(WHOAMI_OID), EXTENDED_RESPONSE).await?;
+    let authzid = if whoami.code == RESULT_SUCCESS {
+        whoami.response_value.map(|v| String::from_utf8_lossy(&v).into_owned()).unwrap_or_default()
+    } else {
+        debug!("LDAP WhoAmI failed with result code {}: {}", whoami.code, whoami.diagnostic);
+        String::new()
+    };
+
+    if let Err(e) = conn.write_all(&message(first_id + 2, &[UNBIND_REQUEST, 0x00])).await {
+        debug!("LDAP unbind failed: {e}");
+    }
+    Ok(Some(authzid))
+}
+
+/// Simple-bind as `bind_dn`. `ldaps://` uses TLS from the start; on `ldap://`
+/// StartTLS is requested first, and a server that refuses it never receives
+/// the password.
+pub async fn validate_ldap(
+    ldap_url: &str,
+    bind_dn: &str,
+    password: &str,
+) -> Result<(bool, Vec<String>)> {
+    let target = parse_ldap_url(ldap_url)?;
+    if bind_dn.trim().is_empty() {
+        return Err(anyhow!("LDAP bind DN is empty"));
+    }
+    // An empty password is an unauthenticated bind, which proves nothing.
+    if password.is_empty() {
+        return Err(anyhow!("LDAP bind password is empty"));
+    }
+
+    if is_local_host(&target.host) {
+        debug!("Skipping LDAP validation: host is localhost/loopback");
+        return Ok((false, vec!["skipped localhost/loopback host".into()]));
+    }
+
+    let res: Result<Result<(Option<String>, &str)>, Elapsed> = timeout(CONNECT_TIMEOUT, async {
+        let mut tcp = TcpStream::connect((target.host.as_str(), target.port))
+            .await
+            .with_context(|| format!("failed to connect to {}:{}", target.host, target.port))?;
+        if target.tls {
+            let mut tls = tls_connect(&target.host, tcp).await?;
+            return Ok((bind_and_whoami(&mut tls, bind_dn, password, 1).await?, "ldaps"));
+        }
+
+        let start_tls =
+            exchange(&mut tcp, 1, &extended_request(STARTTLS_OID), EXTENDED_RESPONSE).await?;
+        if start_tls.code == RESULT_SUCCESS {
+            let mut tls = tls_connect(&target.host, tcp).await?;
+            return Ok((bind_and_whoami(&mut tls, bind_dn, password, 2).await?, "starttls"));
+        }
+        bail!(
+            "server refused StartTLS ({}); refusing to send the bind password in cleartext",
+            start_tls.diagnostic
+        )
+    })
+    .await;
+
+    match res {
+        Ok(Ok((Some(authzid), transport))) => Ok((
+            true,
+            vec![
+                format!("bind_dn={bind_dn}"),
+                format!("host={}", target.host),
+                format!("whoami={}", if authzid.is_empty() { "unknown" } else { &authzid }),
+                format!("tls={transport}"),
+            ],
+        )),
+        Ok(Ok((None, _))) => Ok((false, vec!["invalid credentials".into()])),
+        Ok(Err(e)) => Err(anyhow!("LDAP connection failed: {e}")),
+        Err(_) => Err(anyhow!("LDAP connection timed out after {CONNECT_TIMEOUT:?}")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio::io::{duplex, DuplexStream};
+
+    use super::*;
+
+    #[test]
+    fn parse_ldap_url_defaults_ports() {
+        let t = parse_ldap_url("ldaps://dc01.corp.internal").unwrap();
+        assert_eq!(t, LdapTarget { host: "dc01.corp.internal".into(), port: 636, tls: true });
+        let t = parse_ldap_url("ldap://ldap.corp.internal:1389/dc=corp,dc=internal").unwrap();
+        assert_eq!((t.port, t.tls), (1389, false));
+
+        for bad in ["", "http://ldap.corp.internal", "ldap://"] {
+            assert!(parse_ldap_url(bad).is_err(), "{bad} should be rejected");
+        }
+    }
+
+    #[test]
+    fn ber_round_trips_long_lengths() {
+        let dn = format!("cn={},dc=corp,dc=internal", "a".repeat(300));
+        let encoded = message(7, &bind_request(&dn, "pw"));
+        let (tag, body, rest) = parse_tlv(&encoded).unwrap();
+        assert_eq!((tag, rest.len()), (0x30, 0));
+        let (_, id, op) = parse_tlv(body).unwrap();
+        assert_eq!(parse_uint(id).unwrap(), 7);
+        let (tag, bind, _) = parse_tlv(op).unwrap();
+        assert_eq!(tag, BIND_REQUEST);
+        let (_, version, rest) = parse_tlv(bind).unwrap();
+        assert_eq!(parse_uint(version).unwrap(), 3);
+        let (_, name, _) = parse_tlv(rest).unwrap();
+        assert_eq!(name, dn.as_bytes());
+        assert_eq!(integer(0x02, 128), vec![0x02, 0x02, 0x00, 0x80]);
+    }
+
+    fn response(id: u32, tag: u8, code: u32, value: Option<&str>) -> Vec<u8> {
+        let mut op = [integer(0x0a, code), tlv(0x04, b""), tlv(0x04, b"")].concat();
+        if let Some(value) = value {
+            op.extend(tlv(0x8b, value.as_bytes()));
+        }
+        message(id, &tlv(tag, &op))
+    }
+
+    /// Answer binds for `cn=admin,dc=corp,dc=internal` / `secret` and WhoAmI,
+    /// until the client unbinds or disconnects.
+    async fn stand_in(mut server: DuplexStream) {
+        while let Ok((id, tag, body)) = read_message(&mut server).await {
+            let reply = match tag {
+                BIND_REQUEST => {
+                    let (_, _, rest) = parse_tlv(&body).unwrap();
+                    let (_, dn, rest) = parse_tlv(rest).unwrap();
+                    let (_, pw, _) = parse_tlv(rest).unwrap();
+                    let ok = dn == b"cn=admin,dc=corp,dc=internal" && pw == b"secret";
+                    response(id, BIND_RESPONSE, if ok { 0 } else { 49 }, None)
+                }
+                EXTENDED_REQUEST => {
+                    response(id, EXTENDED_RESPONSE, 0, Some("dn:cn=admin,dc=corp,dc=internal"))
+                }
+                _ => break,
+            };
+            server.write_all(&reply).await.unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn bind_reports_whoami_and_rejects_bad_password() {
+        let (mut client, server) = duplex(4096);
+        let server = tokio::spawn(stand_in(server));
+        let authzid =
+            bind_and_whoami(&mut client, "cn=admin,dc=corp,dc=internal", "secret", 1).await;
+        drop(client);
+        server.await.unwrap();
+        assert_eq!(authzid.unwrap().as_deref(), Some("dn:cn=admin,dc=corp,dc=internal"));
+
+        let (mut client, server) = duplex(4096);
+        let server = tokio::spawn(stand_in(server));
+        let authzid =
+            bind_and_whoami(&mut client, "cn=admin,dc=corp,dc=internal", "wrong", 1).await;
+        drop(client);
+        server.await.unwrap();
+        assert_eq!(authzid.unwrap(), None);
+    }
+}
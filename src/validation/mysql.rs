@@ -59,7 +59,7 @@ pub fn generate_mysql_cache_key(mysql_url: &str) -> String {
     format!("MySQL:{:x}", hasher.finalize())
 }
 
-fn is_local_host(host: &str) -> bool {
+pub(super) fn is_local_host(host: &str) -> bool {
     let host = host.trim_matches(|c| c == '[' || c == ']').trim();
     let lower = host.to_ascii_lowercase();
 
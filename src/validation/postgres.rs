@@ -17,7 +17,7 @@ use tracing::debug;
 const CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
 
 static INIT_PROVIDER: Once = Once::new();
-fn ensure_crypto_provider() {
+pub(super) fn ensure_crypto_provider() {
     INIT_PROVIDER.call_once(|| {
         // If another part of the program already installed a provider,
         // ignore the error — we just need one global provider.
@@ -0,0 +1,394 @@
+//! Per-host request pacing for validation and access-map traffic.
+//!
+//! Every outbound validation request waits on a token bucket keyed by the
+//! destination host. Rules can be given their own rate with a rule-family
+//! override, `429 Too Many Requests` / `Retry-After` responses pause the host
+//! for everyone, and an optional budget caps the number of requests a run may
+//! send at all.
+
+use std::{
+    future::Future,
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+    time::{Duration, Instant},
+};
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use dashmap::DashMap;
+use http::StatusCode;
+use once_cell::sync::OnceCell;
+use reqwest::{header::HeaderMap, RequestBuilder, Response};
+use tracing::{debug, warn};
+
+use crate::{cli::commands::validation_rate::ValidationRateArgs, validation::egress};
+
+/// Per-host rate used when nothing else is configured; `0` leaves requests
+/// unpaced unless `--validation-rps` or a rule override asks for pacing.
+pub const DEFAULT_REQUESTS_PER_SECOND: f64 = 0.0;
+
+/// Pause applied to a host that answers 429 without a `Retry-After` header.
+const DEFAULT_COOLDOWN: Duration = Duration::from_secs(2);
+
+/// Longest `Retry-After` we are willing to honour; anything above is clamped.
+pub const MAX_RETRY_AFTER: Duration = Duration::from_secs(60);
+
+static LIMITER: OnceCell<RateLimiter> = OnceCell::new();
+
+tokio::task_local! {
+    static CURRENT_RULE: Arc<str>;
+}
+
+#[derive(Debug, Clone)]
+pub struct RateLimitConfig {
+    /// Requests per second allowed to a single host; `0` disables pacing.
+    pub requests_per_second: f64,
+    /// `(rule id or prefix, requests per second)` pairs; the longest matching
+    /// prefix wins and gets a bucket of its own.
+    pub rule_overrides: Vec<(String, f64)>,
+    /// Maximum number of requests for the whole run.
+    pub budget: Option<u64>,
+}
+
+impl Default for RateLimitConfig {
+    fn default() -> Self {
+        Self {
+            requests_per_second: DEFAULT_REQUESTS_PER_SECOND,
+            rule_overrides: Vec::new(),
+            budget: None,
+        }
+    }
+}
+
+impl From<&ValidationRateArgs> for RateLimitConfig {
+    fn from(args: &ValidationRateArgs) -> Self {
+        Self {
+            requests_per_second: args.requests_per_second,
+            rule_overrides: args.rule_rates.clone(),
+            budget: args.budget,
+        }
+    }
+}
+
+/// Returned once the run's validation budget has been spent.
+#[derive(Debug, Clone, Copy, thiserror::Error)]
+#[error("validation request budget of {budget} exhausted")]
+pub struct BudgetExhausted {
+    pub budget: u64,
+}
+
+#[derive(Debug)]
+struct Bucket {
+    rate: f64,
+    tokens: f64,
+    updated: Instant,
+    blocked_until: Option<Instant>,
+}
+
+impl Bucket {
+    fn new(rate: f64, now: Instant) -> Self {
+        Self { rate, tokens: burst(rate), updated: now, blocked_until: None }
+    }
+
+    /// Take one token and return how long the caller must wait before sending.
+    /// Tokens may go negative so concurrent callers queue up behind each other
+    /// instead of all waking at the same instant.
+    fn reserve(&mut self, now: Instant) -> Duration {
+        let start = match self.blocked_until {
+            Some(until) if until > now => until,
+            _ => now,
+        };
+        if self.rate <= 0.0 {
+            return start - now;
+        }
+        let elapsed = start.saturating_duration_since(self.updated).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.rate).min(burst(self.rate));
+        self.updated = self.updated.max(start);
+        self.tokens -= 1.0;
+
+        let deficit = if self.tokens < 0.0 { -self.tokens / self.rate } else { 0.0 };
+        (start - now) + Duration::from_secs_f64(deficit)
+    }
+
+    fn pause(&mut self, until: Instant) {
+        if self.blocked_until.is_none_or(|current| current < until) {
+            self.blocked_until = Some(until);
+        }
+        // Nothing refills while the host asked us to back off, but the first
+        // request after the pause goes straight out rather than paying for a
+        // token on top of the wait it already served.
+        self.tokens = 1.0;
+        self.updated = self.updated.max(until);
+    }
+}
+
+fn burst(rate: f64) -> f64 {
+    rate.max(1.0)
+}
+
+pub struct RateLimiter {
+    config: RateLimitConfig,
+    buckets: DashMap<String, Arc<Mutex<Bucket>>>,
+    spent: AtomicU64,
+    budget_warned: AtomicBool,
+}
+
+impl RateLimiter {
+    pub fn new(config: RateLimitConfig) -> Self {
+        Self {
+            config,
+            buckets: DashMap::new(),
+            spent: AtomicU64::new(0),
+            budget_warned: AtomicBool::new(false),
+        }
+    }
+
+    /// Bucket key and rate for `host`, taking the current rule into account.
+    fn bucket_for(&self, host: &str, rule_id: Option<&str>) -> (String, f64) {
+        let host = host.to_ascii_lowercase();
+        let family = rule_id.and_then(|rule_id| {
+            self.config
+                .rule_overrides
+                .iter()
+                .filter(|(pattern, _)| crate::allowlist::rule_matches(pattern, rule_id))
+                .max_by_key(|(pattern, _)| pattern.len())
+        });
+        match family {
+            Some((pattern, rate)) => (format!("{pattern}@{host}"), *rate),
+            None => (host, self.config.requests_per_second),
+        }
+    }
+
+    fn bucket(&self, key: String, rate: f64, now: Instant) -> Arc<Mutex<Bucket>> {
+        self.buckets
+            .entry(key)
+            .or_insert_with(|| Arc::new(Mutex::new(Bucket::new(rate, now))))
+            .clone()
+    }
+
+    fn spend(&self) -> Result<(), BudgetExhausted> {
+        let Some(budget) = self.config.budget else {
+            self.spent.fetch_add(1, Ordering::Relaxed);
+            return Ok(());
+        };
+        let previous = self.spent.fetch_add(1, Ordering::Relaxed);
+        if previous < budget {
+            return Ok(());
+        }
+        self.spent.fetch_sub(1, Ordering::Relaxed);
+        if !self.budget_warned.swap(true, Ordering::Relaxed) {
+            warn!("Validation request budget of {budget} reached; skipping further validation");
+        }
+        Err(BudgetExhausted { budget })
+    }
+
+    pub fn budget_exhausted(&self) -> bool {
+        self.config.budget.is_some_and(|budget| self.spent.load(Ordering::Relaxed) >= budget)
+    }
+
+    /// Number of requests sent (or about to be sent) so far.
+    pub fn requests_sent(&self) -> u64 {
+        self.spent.load(Ordering::Relaxed)
+    }
+
+    fn delay_at(&self, host: &str, rule_id: Option<&str>, now: Instant) -> Duration {
+        let (key, rate) = self.bucket_for(host, rule_id);
+        let bucket = self.bucket(key, rate, now);
+        let mut bucket = bucket.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        bucket.reserve(now)
+    }
+
+    /// Wait until a request to `host` may be sent, charging it to the budget.
+    pub async fn acquire(&self, host: &str) -> Result<(), BudgetExhausted> {
+        self.spend()?;
+        let rule_id = current_rule();
+        let delay = self.delay_at(host, rule_id.as_deref(), Instant::now());
+        if !delay.is_zero() {
+            debug!("Pacing validation request to {host} for {delay:?}");
+            tokio::time::sleep(delay).await;
+        }
+        Ok(())
+    }
+
+    /// Stop sending to `host` (for every rule family) for `duration`.
+    pub fn pause(&self, host: &str, duration: Duration) {
+        let now = Instant::now();
+        let until = now + duration.min(MAX_RETRY_AFTER);
+        let host = host.to_ascii_lowercase();
+        let suffix = format!("@{host}");
+        let (key, rate) = self.bucket_for(&host, None);
+        self.bucket(key, rate, now).lock().unwrap_or_else(|p| p.into_inner()).pause(until);
+        for entry in self.buckets.iter().filter(|e| e.key().ends_with(&suffix)) {
+            entry.value().lock().unwrap_or_else(|p| p.into_inner()).pause(until);
+        }
+    }
+
+    /// Look at a response from `host` and back off if it asked us to.
+    pub fn observe(&self, host: &str, status: StatusCode, headers: &HeaderMap) {
+        let hint = retry_after(headers);
+        let pause = match status {
+            StatusCode::TOO_MANY_REQUESTS => Some(hint.unwrap_or(DEFAULT_COOLDOWN)),
+            _ if status.is_server_error() => hint,
+            _ => None,
+        };
+        if let Some(pause) = pause {
+            debug!("{host} responded {status}; pausing requests for {pause:?}");
+            self.pause(host, pause);
+        }
+    }
+}
+
+/// Install the process-wide limiter. Only the first call has an effect.
+pub fn configure(config: RateLimitConfig) {
+    let _ = LIMITER.set(RateLimiter::new(config));
+}
+
+pub fn limiter() -> &'static RateLimiter {
+    LIMITER.get_or_init(|| RateLimiter::new(RateLimitConfig::default()))
+}
+
+/// Run `fut` with `rule_id` as the rule whose override applies to requests it sends.
+pub async fn with_rule<F: Future>(rule_id: &str, fut: F) -> F::Output {
+    CURRENT_RULE.scope(Arc::from(rule_id), fut).await
+}
+
+fn current_rule() -> Option<Arc<str>> {
+    CURRENT_RULE.try_with(Arc::clone).ok()
+}
+
+/// Check the egress policy and wait for the shared limiter before talking to
+/// `host` outside of reqwest, e.g. from an SDK client.
+pub async fn acquire(host: &str) -> Result<()> {
+    egress::policy().check_host(host)?;
+    limiter().acquire(host).await?;
+    Ok(())
+}
+
+/// Send `builder` through the shared limiter and record any back-off request
+/// in the response.
+pub async fn send(builder: RequestBuilder) -> Result<Response> {
+    let (client, request) = builder.build_split();
+    let request = request?;
+    let host = request.url().host_str().unwrap_or_default().to_string();
+    egress::policy().check_host(&host)?;
+    limiter().acquire(&host).await?;
+    let response = client.execute(request).await.map_err(egress::surface_redirect_denial)?;
+    limiter().observe(&host, response.status(), response.headers());
+    Ok(response)
+}
+
+/// Parse a `Retry-After` header given either as delta-seconds or an HTTP date.
+pub fn retry_after(headers: &HeaderMap) -> Option<Duration> {
+    let value = headers.get(reqwest::header::RETRY_AFTER)?.to_str().ok()?.trim();
+    let delay = match value.parse::<u64>() {
+        Ok(secs) => Duration::from_secs(secs),
+        Err(_) => {
+            let at = DateTime::parse_from_rfc2822(value).ok()?.with_timezone(&Utc);
+            (at - Utc::now()).to_std().unwrap_or(Duration::ZERO)
+        }
+    };
+    Some(delay.min(MAX_RETRY_AFTER))
+}
+
+#[cfg(test)]
+mod tests {
+    use reqwest::header::{HeaderValue, RETRY_AFTER};
+
+    use super::*;
+
+    fn config(rate: f64) -> RateLimitConfig {
+        RateLimitConfig { requests_per_second: rate, ..RateLimitConfig::default() }
+    }
+
+    #[test]
+    fn bucket_paces_after_burst() {
+        let limiter = RateLimiter::new(config(2.0));
+        let now = Instant::now();
+        assert_eq!(limiter.delay_at("api.example.com", None, now), Duration::ZERO);
+        assert_eq!(limiter.delay_at("api.example.com", None, now), Duration::ZERO);
+        assert_eq!(limiter.delay_at("api.example.com", None, now), Duration::from_millis(500));
+        assert_eq!(limiter.delay_at("api.example.com", None, now), Duration::from_secs(1));
+        // Other hosts have their own bucket.
+        assert_eq!(limiter.delay_at("other.example.com", None, now), Duration::ZERO);
+    }
+
+    #[test]
+    fn rule_override_gets_its_own_bucket() {
+        let limiter = RateLimiter::new(RateLimitConfig {
+            requests_per_second: 100.0,
+            rule_overrides: vec![("kingfisher.github".into(), 1.0)],
+            budget: None,
+        });
+        let now = Instant::now();
+        let gh = Some("kingfisher.github.2");
+        assert_eq!(limiter.delay_at("api.github.com", gh, now), Duration::ZERO);
+        assert_eq!(limiter.delay_at("api.github.com", gh, now), Duration::from_secs(1));
+        assert_eq!(
+            limiter.delay_at("api.github.com", Some("kingfisher.other.1"), now),
+            Duration::ZERO
+        );
+    }
+
+    #[test]
+    fn pause_delays_every_family_for_the_host() {
+        let limiter = RateLimiter::new(RateLimitConfig {
+            requests_per_second: 0.0,
+            rule_overrides: vec![("kingfisher.slack".into(), 5.0)],
+            budget: None,
+        });
+        let now = Instant::now();
+        limiter.delay_at("slack.com", Some("kingfisher.slack.1"), now);
+        limiter.pause("slack.com", Duration::from_secs(3));
+        assert!(limiter.delay_at("slack.com", None, Instant::now()) > Duration::from_secs(2));
+        assert!(
+            limiter.delay_at("slack.com", Some("kingfisher.slack.1"), Instant::now())
+                > Duration::from_secs(2)
+        );
+    }
+
+    #[test]
+    fn pause_does_not_stack_with_pacing() {
+        let limiter = RateLimiter::new(config(0.5));
+        assert_eq!(limiter.delay_at("api.example.com", None, Instant::now()), Duration::ZERO);
+        limiter.pause("api.example.com", Duration::from_secs(3));
+        let now = Instant::now();
+        let first = limiter.delay_at("api.example.com", None, now);
+        assert!(first > Duration::from_secs(2) && first <= Duration::from_secs(3));
+        // Pacing resumes after the request that ended the pause.
+        assert_eq!(limiter.delay_at("api.example.com", None, now), first + Duration::from_secs(2));
+    }
+
+    #[test]
+    fn default_config_does_not_pace() {
+        let limiter = RateLimiter::new(RateLimitConfig::default());
+        let now = Instant::now();
+        for _ in 0..100 {
+            assert_eq!(limiter.delay_at("api.example.com", None, now), Duration::ZERO);
+        }
+    }
+
+    #[test]
+    fn budget_is_enforced() {
+        let limiter = RateLimiter::new(RateLimitConfig { budget: Some(2), ..config(0.0) });
+        assert!(limiter.spend().is_ok());
+        assert!(!limiter.budget_exhausted());
+        assert!(limiter.spend().is_ok());
+        assert!(limiter.budget_exhausted());
+        assert_eq!(limiter.spend().unwrap_err().budget, 2);
+        assert_eq!(limiter.requests_sent(), 2);
+    }
+
+    #[test]
+    fn parses_retry_after_forms() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(retry_after(&headers), None);
+        headers.insert(RETRY_AFTER, HeaderValue::from_static("7"));
+        assert_eq!(retry_after(&headers), Some(Duration::from_secs(7)));
+        headers.insert(RETRY_AFTER, HeaderValue::from_static("86400"));
+        assert_eq!(retry_after(&headers), Some(MAX_RETRY_AFTER));
+        headers.insert(RETRY_AFTER, HeaderValue::from_static("Wed, 21 Oct 2015 07:28:00 GMT"));
+        assert_eq!(retry_after(&headers), Some(Duration::ZERO));
+    }
+}
@@ -1,6 +1,17 @@
+use std::sync::Arc;
+
+use anyhow::anyhow;
 use reqwest::Url;
-use tokio::net::lookup_host;
+use rustls::{client::ClientConfig, pki_types::ServerName, RootCertStore};
+use rustls_native_certs::{load_native_certs, CertificateResult};
+use tokio::{
+    io::{AsyncBufReadExt, AsyncRead, AsyncReadExt, BufReader},
+    net::{lookup_host, TcpStream},
+};
+use tokio_rustls::{client::TlsStream, TlsConnector};
+use tracing::debug;
 
+use super::postgres::ensure_crypto_provider;
 use crate::validation::SerializableCaptures;
 
 /// Return (NAME, value, start, end) for the captures we care about.
@@ -113,6 +124,47 @@ pub async fn check_url_resolvable(url: &Url) -> Result<(), Box<dyn std::error::E
     lookup_host(addr).await?.next().ok_or_else(|| "Failed to resolve URL".into()).map(|_| ())
 }
 
+/// Start a TLS session over `tcp`, verifying `host` against the native roots.
+/// Used by validators that speak their own protocol (SMTP STARTTLS, Redis).
+pub async fn tls_connect(host: &str, tcp: TcpStream) -> anyhow::Result<TlsStream<TcpStream>> {
+    ensure_crypto_provider();
+
+    let CertificateResult { certs, errors, .. } = load_native_certs();
+    for err in errors {
+        debug!("native-cert error: {err}");
+    }
+    let mut roots = RootCertStore::empty();
+    let _ = roots.add_parsable_certificates(certs);
+    let config = ClientConfig::builder().with_root_certificates(roots).with_no_client_auth();
+
+    let name =
+        ServerName::try_from(host.to_string()).map_err(|e| anyhow!("Invalid host {host}: {e}"))?;
+    TlsConnector::from(Arc::new(config))
+        .connect(name, tcp)
+        .await
+        .map_err(|e| anyhow!("TLS handshake with {host} failed: {e}"))
+}
+
+/// Longest reply line the line-based validators (SMTP, Redis) accept.
+pub const MAX_LINE_LEN: u64 = 1 << 16;
+
+/// Read one line of at most [`MAX_LINE_LEN`] bytes and strip its line ending,
+/// so a server that never sends a newline cannot grow the buffer until the
+/// session times out. `protocol` names the server in errors.
+pub async fn read_bounded_line<S: AsyncRead + Unpin>(
+    conn: &mut BufReader<S>,
+    protocol: &str,
+) -> anyhow::Result<String> {
+    let mut line = String::new();
+    if (&mut *conn).take(MAX_LINE_LEN).read_line(&mut line).await? == 0 {
+        anyhow::bail!("{protocol} server closed the connection");
+    }
+    if !line.ends_with('\n') {
+        anyhow::bail!("{protocol} reply line exceeds {MAX_LINE_LEN} bytes or was cut short");
+    }
+    Ok(line.trim_end_matches(['\r', '\n']).to_string())
+}
+
 // -----------------------------------------------------------------------------
 // tests
 // -----------------------------------------------------------------------------
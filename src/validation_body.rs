@@ -1,11 +1,15 @@
 use schemars::{gen::SchemaGenerator, schema::Schema, JsonSchema};
 use serde::{Deserialize, Deserializer, Serializer};
-use std::borrow::Cow;
+use std::{borrow::Cow, collections::BTreeMap};
 
 /// Storage for validation response payloads. `None` avoids heap allocation when validation is
 /// disabled or produces no body.
 pub type ValidationResponseBody = Option<Box<str>>;
 
+/// Identity fields (account id, username, scopes, ...) extracted from a successful validation
+/// response by a rule's `extract` block, keyed by field name.
+pub type ValidationIdentity = BTreeMap<String, String>;
+
 #[inline]
 pub fn from_string(body: impl Into<String>) -> ValidationResponseBody {
     let body = body.into();
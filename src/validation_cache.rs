@@ -0,0 +1,796 @@
+//! Encrypted on-disk cache of validation results, shared across runs.
+//!
+//! Entries are keyed on the same rule + secret fingerprint the in-process cache
+//! uses, so the secret itself is never written. The file holds a JSON document
+//! sealed with AES-256-GCM; response bodies can carry account details, so the
+//! whole payload is encrypted rather than individual fields.
+
+use std::{
+    collections::BTreeMap,
+    fs,
+    io::Write,
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicBool, Ordering},
+    time::Duration,
+};
+
+use anyhow::{anyhow, bail, Context, Result};
+use clap::ValueEnum;
+use dashmap::DashMap;
+use http::StatusCode;
+use ring::{
+    aead::{Aad, LessSafeKey, Nonce, UnboundKey, AES_256_GCM, NONCE_LEN},
+    pbkdf2,
+    rand::{SecureRandom, SystemRandom},
+};
+use serde::{Deserialize, Serialize};
+use strum::Display;
+use tracing::{debug, info, warn};
+
+use crate::cli::commands::{
+    output::OutputArgs,
+    validation_cache::{
+        ValidationCacheArgs, ValidationCacheCommand, ValidationCacheCommandArgs,
+        ValidationCacheInspectArgs, ValidationCacheOutputFormat, ValidationCachePurgeArgs,
+    },
+};
+use crate::validation_body::ValidationIdentity;
+
+/// Environment variable holding a passphrase to derive the cache key from.
+pub const CACHE_KEY_ENV_VAR: &str = "KINGFISHER_VALIDATION_CACHE_KEY";
+
+const FILE_MAGIC: &[u8] = b"KFVC1";
+const FORMAT_VERSION: u32 = 1;
+
+/// PBKDF2-HMAC-SHA256 rounds used to stretch a cache passphrase.
+const PASSPHRASE_ITERATIONS: u32 = 600_000;
+const SALT_LEN: usize = 16;
+
+/// Coarse outcome of a validation, used to pick how long it stays cached.
+#[derive(
+    Copy, Clone, Debug, Display, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, ValueEnum,
+)]
+#[serde(rename_all = "lowercase")]
+#[strum(serialize_all = "lowercase")]
+pub enum ResultClass {
+    /// The credential was confirmed active.
+    Valid,
+    /// The provider rejected the credential.
+    Invalid,
+    /// Timeouts, rate limiting, connection and server-side failures.
+    Error,
+}
+
+impl ResultClass {
+    /// Classify a validation outcome. Results that say nothing about the
+    /// secret, such as skipped validation or missing dependencies, are `None`.
+    pub fn of(is_valid: bool, status: StatusCode) -> Option<Self> {
+        if is_valid {
+            Some(Self::Valid)
+        } else if status == StatusCode::CONTINUE || status == StatusCode::PRECONDITION_REQUIRED {
+            None
+        } else if status.is_server_error()
+            // Validators report DNS and connection failures as BAD_REQUEST.
+            || status == StatusCode::BAD_REQUEST
+            || status == StatusCode::REQUEST_TIMEOUT
+            || status == StatusCode::TOO_MANY_REQUESTS
+        {
+            Some(Self::Error)
+        } else {
+            Some(Self::Invalid)
+        }
+    }
+}
+
+/// A cached validation result.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CacheEntry {
+    pub rule_id: String,
+    pub class: ResultClass,
+    pub status: u16,
+    pub body: String,
+    /// Identity fields extracted from the validation response.
+    #[serde(default, skip_serializing_if = "ValidationIdentity::is_empty")]
+    pub identity: ValidationIdentity,
+    /// Unix timestamp (seconds) when the result was stored.
+    pub stored_at: i64,
+    /// Unix timestamp (seconds) after which the result is no longer served.
+    pub expires_at: i64,
+}
+
+impl CacheEntry {
+    pub fn is_expired(&self, now: i64) -> bool {
+        now >= self.expires_at
+    }
+
+    pub fn status_code(&self) -> StatusCode {
+        StatusCode::from_u16(self.status).unwrap_or(StatusCode::OK)
+    }
+}
+
+/// Time-to-live for each [`ResultClass`]; a zero TTL keeps that class out of
+/// the cache.
+#[derive(Debug, Clone, Copy)]
+pub struct CacheTtls {
+    pub valid: Duration,
+    pub invalid: Duration,
+    pub error: Duration,
+}
+
+impl CacheTtls {
+    fn for_class(&self, class: ResultClass) -> Duration {
+        match class {
+            ResultClass::Valid => self.valid,
+            ResultClass::Invalid => self.invalid,
+            ResultClass::Error => self.error,
+        }
+    }
+}
+
+impl From<&ValidationCacheArgs> for CacheTtls {
+    fn from(args: &ValidationCacheArgs) -> Self {
+        Self { valid: args.ttl_valid, invalid: args.ttl_invalid, error: args.ttl_error }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct CacheDocument {
+    version: u32,
+    /// Entries keyed on the hex-encoded secret fingerprint.
+    entries: BTreeMap<String, CacheEntry>,
+}
+
+/// Validation results loaded from, and written back to, an encrypted file.
+pub struct ValidationCache {
+    path: PathBuf,
+    key: LessSafeKey,
+    ttls: CacheTtls,
+    entries: DashMap<u64, CacheEntry>,
+    dirty: AtomicBool,
+}
+
+impl ValidationCache {
+    /// Open the cache configured on the command line, if any.
+    pub fn from_args(args: &ValidationCacheArgs) -> Result<Option<Self>> {
+        match &args.path {
+            Some(path) => Self::open(path, args.into()).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// Open (or start) the cache at `path`. Expired entries are dropped on load.
+    pub fn open(path: &Path, ttls: CacheTtls) -> Result<Self> {
+        Self::load(path, ttls, false)
+    }
+
+    /// Open the cache at `path` keeping expired entries, for commands that
+    /// inspect or purge the file rather than validate with it.
+    pub fn open_for_maintenance(path: &Path) -> Result<Self> {
+        // TTLs only apply to new results, which maintenance never records.
+        Self::load(path, CacheTtls::from(&ValidationCacheArgs::default()), true)
+    }
+
+    fn load(path: &Path, ttls: CacheTtls, keep_expired: bool) -> Result<Self> {
+        let key = load_or_create_key(path)?;
+        let entries = DashMap::new();
+        let mut dropped = 0usize;
+        if path.exists() {
+            let sealed = fs::read(path)
+                .with_context(|| format!("Failed to read validation cache {}", path.display()))?;
+            let document = open_document(&key, &sealed)
+                .with_context(|| format!("Failed to load validation cache {}", path.display()))?;
+            let now = now();
+            for (fingerprint, entry) in document.entries {
+                let Ok(fingerprint) = u64::from_str_radix(&fingerprint, 16) else {
+                    continue;
+                };
+                if !keep_expired && entry.is_expired(now) {
+                    dropped += 1;
+                } else {
+                    entries.insert(fingerprint, entry);
+                }
+            }
+        }
+        debug!(
+            "Loaded {} validation cache entries from {} ({dropped} expired)",
+            entries.len(),
+            path.display()
+        );
+        Ok(Self {
+            path: path.to_path_buf(),
+            key,
+            ttls,
+            entries,
+            dirty: AtomicBool::new(dropped > 0),
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Look up an unexpired result for a secret fingerprint.
+    pub fn get(&self, fingerprint: u64) -> Option<CacheEntry> {
+        self.get_at(fingerprint, now())
+    }
+
+    fn get_at(&self, fingerprint: u64, now: i64) -> Option<CacheEntry> {
+        self.entries
+            .get(&fingerprint)
+            .filter(|entry| !entry.is_expired(now))
+            .map(|entry| entry.value().clone())
+    }
+
+    /// Store a validation result, unless its class has a zero TTL.
+    pub fn record(
+        &self,
+        fingerprint: u64,
+        rule_id: &str,
+        is_valid: bool,
+        status: StatusCode,
+        body: &str,
+    ) {
+        self.record_with_identity(
+            fingerprint,
+            rule_id,
+            is_valid,
+            status,
+            body,
+            &ValidationIdentity::new(),
+        );
+    }
+
+    /// Like [`Self::record`], also keeping the identity fields extracted from
+    /// the response.
+    pub fn record_with_identity(
+        &self,
+        fingerprint: u64,
+        rule_id: &str,
+        is_valid: bool,
+        status: StatusCode,
+        body: &str,
+        identity: &ValidationIdentity,
+    ) {
+        self.record_at(fingerprint, rule_id, is_valid, status, body, identity, now());
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn record_at(
+        &self,
+        fingerprint: u64,
+        rule_id: &str,
+        is_valid: bool,
+        status: StatusCode,
+        body: &str,
+        identity: &ValidationIdentity,
+        now: i64,
+    ) {
+        let Some(class) = ResultClass::of(is_valid, status) else {
+            return;
+        };
+        let ttl = self.ttls.for_class(class);
+        if ttl.is_zero() {
+            return;
+        }
+        let ttl_secs = i64::try_from(ttl.as_secs()).unwrap_or(i64::MAX);
+        self.entries.insert(
+            fingerprint,
+            CacheEntry {
+                rule_id: rule_id.to_string(),
+                class,
+                status: status.as_u16(),
+                body: body.to_string(),
+                identity: identity.clone(),
+                stored_at: now,
+                expires_at: now.saturating_add(ttl_secs),
+            },
+        );
+        self.dirty.store(true, Ordering::Relaxed);
+    }
+
+    /// All loaded entries ordered by rule id and fingerprint. Expired entries
+    /// are only present when the cache was opened with
+    /// [`Self::open_for_maintenance`].
+    pub fn entries(&self) -> Vec<(u64, CacheEntry)> {
+        let mut entries: Vec<_> =
+            self.entries.iter().map(|e| (*e.key(), e.value().clone())).collect();
+        entries.sort_by(|(fa, a), (fb, b)| (&a.rule_id, fa).cmp(&(&b.rule_id, fb)));
+        entries
+    }
+
+    /// Remove every entry for which `purge` returns true, returning how many
+    /// were removed.
+    pub fn purge(&self, purge: impl Fn(&CacheEntry) -> bool) -> usize {
+        let before = self.entries.len();
+        self.entries.retain(|_, entry| !purge(entry));
+        let removed = before - self.entries.len();
+        if removed > 0 {
+            self.dirty.store(true, Ordering::Relaxed);
+        }
+        removed
+    }
+
+    /// Write the cache back to disk if anything changed since it was opened.
+    pub fn save(&self) -> Result<()> {
+        if !self.dirty.swap(false, Ordering::Relaxed) {
+            return Ok(());
+        }
+        let document = CacheDocument {
+            version: FORMAT_VERSION,
+            entries: self
+                .entries
+                .iter()
+                .map(|e| (format!("{:016x}", e.key()), e.value().clone()))
+                .collect(),
+        };
+        let sealed = seal_document(&self.key, &document)?;
+        if let Some(parent) = self.path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            fs::create_dir_all(parent)?;
+        }
+        // Write next to the target and rename so concurrent readers never see
+        // a partially written file.
+        let dir =
+            self.path.parent().filter(|p| !p.as_os_str().is_empty()).unwrap_or(Path::new("."));
+        let mut tmp = tempfile::NamedTempFile::new_in(dir)
+            .with_context(|| format!("Failed to write validation cache {}", self.path.display()))?;
+        tmp.write_all(&sealed)?;
+        tmp.persist(&self.path)
+            .with_context(|| format!("Failed to write validation cache {}", self.path.display()))?;
+        debug!("Saved {} validation cache entries to {}", self.entries.len(), self.path.display());
+        Ok(())
+    }
+
+    /// Save, logging rather than failing: a cache that cannot be written
+    /// should not fail an otherwise successful scan.
+    pub fn save_or_warn(&self) {
+        if let Err(e) = self.save() {
+            warn!("{e:#}");
+        }
+    }
+}
+
+fn now() -> i64 {
+    chrono::Utc::now().timestamp()
+}
+
+/// `FILE.<ext>` next to the cache file.
+fn sidecar_path(cache_path: &Path, ext: &str) -> PathBuf {
+    let mut name = cache_path.as_os_str().to_owned();
+    name.push(".");
+    name.push(ext);
+    PathBuf::from(name)
+}
+
+fn key_path(cache_path: &Path) -> PathBuf {
+    sidecar_path(cache_path, "key")
+}
+
+fn salt_path(cache_path: &Path) -> PathBuf {
+    sidecar_path(cache_path, "salt")
+}
+
+fn load_or_create_key(cache_path: &Path) -> Result<LessSafeKey> {
+    let bytes = match std::env::var(CACHE_KEY_ENV_VAR) {
+        Ok(passphrase) if !passphrase.is_empty() => key_from_passphrase(&passphrase, cache_path)?,
+        _ => {
+            let key_path = key_path(cache_path);
+            if key_path.exists() {
+                let encoded = fs::read_to_string(&key_path).with_context(|| {
+                    format!("Failed to read validation cache key {}", key_path.display())
+                })?;
+                hex::decode(encoded.trim()).with_context(|| {
+                    format!("Invalid validation cache key in {}", key_path.display())
+                })?
+            } else if cache_path.exists() {
+                bail!(
+                    "Validation cache {} exists but its key file {} is missing; set {} or purge the cache",
+                    cache_path.display(),
+                    key_path.display(),
+                    CACHE_KEY_ENV_VAR
+                );
+            } else {
+                let mut key = [0u8; 32];
+                SystemRandom::new()
+                    .fill(&mut key)
+                    .map_err(|_| anyhow!("Failed to generate a validation cache key"))?;
+                if let Some(parent) = key_path.parent().filter(|p| !p.as_os_str().is_empty()) {
+                    fs::create_dir_all(parent)?;
+                }
+                write_private(&key_path, hex::encode(key).as_bytes())?;
+                info!("Created validation cache key {}", key_path.display());
+                key.to_vec()
+            }
+        }
+    };
+    let unbound = UnboundKey::new(&AES_256_GCM, &bytes)
+        .map_err(|_| anyhow!("Validation cache key must be 32 bytes"))?;
+    Ok(LessSafeKey::new(unbound))
+}
+
+/// Stretch `passphrase` with PBKDF2 and the random salt kept in `FILE.salt`,
+/// creating the salt alongside a new cache.
+fn key_from_passphrase(passphrase: &str, cache_path: &Path) -> Result<Vec<u8>> {
+    let salt_path = salt_path(cache_path);
+    let salt = if salt_path.exists() {
+        let encoded = fs::read_to_string(&salt_path).with_context(|| {
+            format!("Failed to read validation cache salt {}", salt_path.display())
+        })?;
+        hex::decode(encoded.trim())
+            .with_context(|| format!("Invalid validation cache salt in {}", salt_path.display()))?
+    } else if cache_path.exists() {
+        bail!(
+            "Validation cache {} exists but its salt file {} is missing; purge the cache",
+            cache_path.display(),
+            salt_path.display()
+        );
+    } else {
+        let mut salt = [0u8; SALT_LEN];
+        SystemRandom::new()
+            .fill(&mut salt)
+            .map_err(|_| anyhow!("Failed to generate a validation cache salt"))?;
+        if let Some(parent) = salt_path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            fs::create_dir_all(parent)?;
+        }
+        write_private(&salt_path, hex::encode(salt).as_bytes())?;
+        salt.to_vec()
+    };
+
+    let mut key = vec![0u8; 32];
+    pbkdf2::derive(
+        pbkdf2::PBKDF2_HMAC_SHA256,
+        std::num::NonZeroU32::new(PASSPHRASE_ITERATIONS).expect("non-zero iterations"),
+        &salt,
+        passphrase.as_bytes(),
+        &mut key,
+    );
+    Ok(key)
+}
+
+fn write_private(path: &Path, contents: &[u8]) -> Result<()> {
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let mut file =
+        options.open(path).with_context(|| format!("Failed to write {}", path.display()))?;
+    file.write_all(contents)?;
+    Ok(())
+}
+
+fn seal_document(key: &LessSafeKey, document: &CacheDocument) -> Result<Vec<u8>> {
+    let mut nonce = [0u8; NONCE_LEN];
+    SystemRandom::new().fill(&mut nonce).map_err(|_| anyhow!("Failed to generate a nonce"))?;
+    let mut payload = serde_json::to_vec(document)?;
+    key.seal_in_place_append_tag(
+        Nonce::assume_unique_for_key(nonce),
+        Aad::from(FILE_MAGIC),
+        &mut payload,
+    )
+    .map_err(|_| anyhow!("Failed to encrypt validation cache"))?;
+
+    let mut sealed = Vec::with_capacity(FILE_MAGIC.len() + NONCE_LEN + payload.len());
+    sealed.extend_from_slice(FILE_MAGIC);
+    sealed.extend_from_slice(&nonce);
+    sealed.extend_from_slice(&payload);
+    Ok(sealed)
+}
+
+fn open_document(key: &LessSafeKey, sealed: &[u8]) -> Result<CacheDocument> {
+    let Some(rest) = sealed.strip_prefix(FILE_MAGIC) else {
+        bail!("not a Kingfisher validation cache");
+    };
+    if rest.len() < NONCE_LEN {
+        bail!("validation cache is truncated");
+    }
+    let (nonce, ciphertext) = rest.split_at(NONCE_LEN);
+    let nonce = Nonce::try_assume_unique_for_key(nonce).map_err(|_| anyhow!("invalid nonce"))?;
+    let mut buf = ciphertext.to_vec();
+    let plaintext = key
+        .open_in_place(nonce, Aad::from(FILE_MAGIC), &mut buf)
+        .map_err(|_| anyhow!("decryption failed; the key does not match this cache"))?;
+    let document: CacheDocument = serde_json::from_slice(plaintext)?;
+    if document.version != FORMAT_VERSION {
+        bail!("unsupported validation cache version {}", document.version);
+    }
+    Ok(document)
+}
+
+// -----------------------------------------------------------------------------
+// `kingfisher validation-cache`
+// -----------------------------------------------------------------------------
+
+/// Run the `kingfisher validation-cache` subcommand.
+pub fn run(args: ValidationCacheCommandArgs) -> Result<()> {
+    match args.command {
+        ValidationCacheCommand::Inspect(args) => inspect(&args),
+        ValidationCacheCommand::Purge(args) => purge(&args),
+    }
+}
+
+fn open_existing(path: &Path) -> Result<ValidationCache> {
+    if !path.exists() {
+        bail!("Validation cache {} does not exist", path.display());
+    }
+    ValidationCache::open_for_maintenance(path)
+}
+
+#[derive(Serialize)]
+struct InspectedEntry {
+    fingerprint: String,
+    #[serde(flatten)]
+    entry: CacheEntry,
+}
+
+#[derive(Serialize, Default)]
+struct RuleTotals {
+    valid: usize,
+    invalid: usize,
+    error: usize,
+    expired: usize,
+}
+
+fn inspect(args: &ValidationCacheInspectArgs) -> Result<()> {
+    let cache = open_existing(&args.path)?;
+    let entries = cache.entries();
+    let mut writer = args.output_args.get_writer()?;
+    write_inspection(&mut writer, &entries, args.entries, &args.output_args, now())
+}
+
+fn write_inspection(
+    writer: &mut dyn Write,
+    entries: &[(u64, CacheEntry)],
+    list_entries: bool,
+    output_args: &OutputArgs<ValidationCacheOutputFormat>,
+    now: i64,
+) -> Result<()> {
+    let mut totals: BTreeMap<&str, RuleTotals> = BTreeMap::new();
+    for (_, entry) in entries {
+        let t = totals.entry(entry.rule_id.as_str()).or_default();
+        if entry.is_expired(now) {
+            t.expired += 1;
+            continue;
+        }
+        match entry.class {
+            ResultClass::Valid => t.valid += 1,
+            ResultClass::Invalid => t.invalid += 1,
+            ResultClass::Error => t.error += 1,
+        }
+    }
+
+    match (output_args.format, list_entries) {
+        (ValidationCacheOutputFormat::Json, true) => {
+            let listed: Vec<_> = entries
+                .iter()
+                .map(|(fp, entry)| InspectedEntry {
+                    fingerprint: format!("{fp:016x}"),
+                    entry: entry.clone(),
+                })
+                .collect();
+            serde_json::to_writer_pretty(&mut *writer, &listed)?;
+            writeln!(writer)?;
+        }
+        (ValidationCacheOutputFormat::Json, false) => {
+            serde_json::to_writer_pretty(&mut *writer, &totals)?;
+            writeln!(writer)?;
+        }
+        (ValidationCacheOutputFormat::Pretty, true) => {
+            for (fp, entry) in entries {
+                let expiry = if entry.is_expired(now) {
+                    "expired".to_string()
+                } else {
+                    let remaining = Duration::from_secs((entry.expires_at - now) as u64);
+                    format!("expires in {}", humantime::format_duration(remaining))
+                };
+                writeln!(
+                    writer,
+                    "{fp:016x}  {:<8} {:>3}  {}  ({expiry})",
+                    entry.class, entry.status, entry.rule_id
+                )?;
+            }
+        }
+        (ValidationCacheOutputFormat::Pretty, false) => {
+            writeln!(
+                writer,
+                "{:<48} {:>7} {:>7} {:>7} {:>7}",
+                "Rule", "valid", "invalid", "error", "expired"
+            )?;
+            for (rule, t) in &totals {
+                writeln!(
+                    writer,
+                    "{rule:<48} {:>7} {:>7} {:>7} {:>7}",
+                    t.valid, t.invalid, t.error, t.expired
+                )?;
+            }
+            writeln!(writer, "{} cached results", entries.len())?;
+        }
+    }
+    Ok(())
+}
+
+fn purge(args: &ValidationCachePurgeArgs) -> Result<()> {
+    let cache = open_existing(&args.path)?;
+    let now = now();
+    let removed = cache.purge(|entry| {
+        (args.rule.is_empty()
+            || args.rule.iter().any(|r| crate::allowlist::rule_matches(r, &entry.rule_id)))
+            && (args.class.is_empty() || args.class.contains(&entry.class))
+            && (!args.expired || entry.is_expired(now))
+    });
+    cache.save()?;
+    info!("Removed {removed} entries; {} remain in {}", cache.len(), cache.path().display());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ttls() -> CacheTtls {
+        CacheTtls {
+            valid: Duration::from_secs(60),
+            invalid: Duration::from_secs(600),
+            error: Duration::ZERO,
+        }
+    }
+
+    #[test]
+    fn entries_round_trip_through_encrypted_file() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("cache.bin");
+
+        let cache = ValidationCache::open(&path, ttls())?;
+        let identity = ValidationIdentity::from([("username".to_string(), "octocat".to_string())]);
+        cache.record_with_identity(
+            1,
+            "kingfisher.github.2",
+            true,
+            StatusCode::OK,
+            "{\"login\":\"octocat\"}",
+            &identity,
+        );
+        cache.record(2, "kingfisher.slack.2", false, StatusCode::UNAUTHORIZED, "invalid_auth");
+        cache.save()?;
+
+        let raw = fs::read(&path)?;
+        assert!(raw.starts_with(FILE_MAGIC));
+        assert!(!String::from_utf8_lossy(&raw).contains("octocat"));
+        assert!(key_path(&path).exists());
+
+        let reopened = ValidationCache::open(&path, ttls())?;
+        let entry = reopened.get(1).expect("valid entry");
+        assert_eq!(entry.class, ResultClass::Valid);
+        assert_eq!(entry.body, "{\"login\":\"octocat\"}");
+        assert_eq!(entry.identity, identity);
+        assert_eq!(reopened.get(2).map(|e| e.class), Some(ResultClass::Invalid));
+        Ok(())
+    }
+
+    #[test]
+    fn wrong_key_is_rejected() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("cache.bin");
+        let cache = ValidationCache::open(&path, ttls())?;
+        cache.record(1, "kingfisher.github.2", true, StatusCode::OK, "ok");
+        cache.save()?;
+
+        fs::write(key_path(&path), hex::encode([7u8; 32]))?;
+        assert!(ValidationCache::open(&path, ttls()).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn ttl_depends_on_result_class() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let cache = ValidationCache::open(&dir.path().join("cache.bin"), ttls())?;
+
+        let none = ValidationIdentity::new();
+        cache.record_at(1, "r", true, StatusCode::OK, "", &none, 1_000);
+        cache.record_at(2, "r", false, StatusCode::FORBIDDEN, "", &none, 1_000);
+        cache.record_at(3, "r", false, StatusCode::TOO_MANY_REQUESTS, "", &none, 1_000);
+        cache.record_at(4, "r", false, StatusCode::CONTINUE, "", &none, 1_000);
+        cache.record_at(5, "r", false, StatusCode::BAD_REQUEST, "", &none, 1_000);
+
+        assert!(cache.get_at(1, 1_059).is_some());
+        assert!(cache.get_at(1, 1_060).is_none());
+        assert!(cache.get_at(2, 1_599).is_some());
+        // Zero TTL for errors, and "not attempted" is never cached.
+        assert!(cache.get_at(3, 1_000).is_none());
+        assert!(cache.get_at(4, 1_000).is_none());
+        // Connection failures are errors, not evidence the secret is invalid.
+        assert_eq!(ResultClass::of(false, StatusCode::BAD_REQUEST), Some(ResultClass::Error));
+        assert_eq!(ResultClass::of(false, StatusCode::BAD_GATEWAY), Some(ResultClass::Error));
+        assert!(cache.get_at(5, 1_000).is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn passphrase_keys_are_salted_per_cache() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let first = dir.path().join("a.bin");
+        let second = dir.path().join("b.bin");
+
+        let key = key_from_passphrase("correct horse", &first)?;
+        assert!(salt_path(&first).exists());
+        assert_eq!(key_from_passphrase("correct horse", &first)?, key);
+        assert_ne!(key_from_passphrase("correct horse", &second)?, key);
+
+        fs::write(&first, b"sealed")?;
+        fs::remove_file(salt_path(&first))?;
+        assert!(key_from_passphrase("correct horse", &first).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn save_leaves_sibling_files_alone() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("cache.json");
+        fs::write(dir.path().join("cache.tmp"), "unrelated")?;
+
+        let cache = ValidationCache::open(&path, ttls())?;
+        cache.record(1, "r", true, StatusCode::OK, "");
+        cache.save()?;
+
+        assert_eq!(fs::read_to_string(dir.path().join("cache.tmp"))?, "unrelated");
+        assert_eq!(ValidationCache::open(&path, ttls())?.len(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn purge_filters_by_rule_and_class() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let cache = ValidationCache::open(&dir.path().join("cache.bin"), ttls())?;
+        cache.record(1, "kingfisher.aws.2", true, StatusCode::OK, "");
+        cache.record(2, "kingfisher.aws.2", false, StatusCode::FORBIDDEN, "");
+        cache.record(3, "kingfisher.github.2", false, StatusCode::UNAUTHORIZED, "");
+
+        let removed = cache.purge(|e| {
+            crate::allowlist::rule_matches("kingfisher.aws", &e.rule_id)
+                && e.class == ResultClass::Invalid
+        });
+        assert_eq!(removed, 1);
+        assert!(cache.get(1).is_some());
+        assert!(cache.get(2).is_none());
+        assert!(cache.get(3).is_some());
+        Ok(())
+    }
+
+    #[test]
+    fn maintenance_sees_expired_entries_and_inspect_is_read_only() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("cache.bin");
+        let cache = ValidationCache::open(&path, ttls())?;
+        cache.record_at(1, "r", true, StatusCode::OK, "", &ValidationIdentity::new(), 1_000);
+        cache.record(2, "r", false, StatusCode::FORBIDDEN, "");
+        cache.save()?;
+
+        assert_eq!(ValidationCache::open(&path, ttls())?.len(), 1);
+        let before = fs::read(&path)?;
+        inspect(&ValidationCacheInspectArgs {
+            path: path.clone(),
+            entries: true,
+            output_args: OutputArgs {
+                output: Some(dir.path().join("inspect.txt")),
+                format: ValidationCacheOutputFormat::Pretty,
+            },
+        })?;
+        assert_eq!(fs::read(&path)?, before);
+        assert!(fs::read_to_string(dir.path().join("inspect.txt"))?.contains("(expired)"));
+
+        let maintained = ValidationCache::open_for_maintenance(&path)?;
+        assert_eq!(maintained.len(), 2);
+        assert_eq!(maintained.purge(|e| e.is_expired(now())), 1);
+        assert!(maintained.get(2).is_some());
+        Ok(())
+    }
+}
@@ -0,0 +1,157 @@
+use clap::Parser;
+use tempfile::tempdir;
+
+use kingfisher::cli::{
+    commands::{
+        output::ReportOutputFormat,
+        scan::{ConfidenceLevel, ScanArgs, ScanOperation},
+    },
+    global::{Command, CommandLineArgs},
+};
+
+fn parse_scan(args: &[&str]) -> anyhow::Result<ScanArgs> {
+    let args = CommandLineArgs::try_parse_from(args)?;
+
+    let command = match args.command {
+        Command::Scan(scan_args) => scan_args,
+        other => panic!("unexpected command parsed: {:?}", other),
+    };
+
+    match command.into_operation()? {
+        ScanOperation::Scan(scan_args) => Ok(scan_args),
+        op => panic!("expected scan operation, got {:?}", op),
+    }
+}
+
+#[test]
+fn config_at_scan_root_is_applied() -> anyhow::Result<()> {
+    let dir = tempdir()?;
+    std::fs::write(
+        dir.path().join(".kingfisher.toml"),
+        r#"
+confidence = "high"
+exclude = ["*.lock"]
+skip-word = ["example"]
+ignore-comment = ["nosec"]
+validation-timeout = 30
+baseline-file = "baseline.yaml"
+format = "json"
+output = "findings.json"
+
+[[overrides]]
+path = "vendor"
+exclude = ["fixtures"]
+skip-rule = ["kingfisher.aws"]
+"#,
+    )?;
+
+    let root = dir.path().to_str().unwrap();
+    let scan_args = parse_scan(&["kingfisher", "scan", root, "--no-update-check"])?;
+
+    assert_eq!(scan_args.confidence, ConfidenceLevel::High);
+    assert_eq!(scan_args.validation_timeout, 30);
+    assert_eq!(scan_args.output_args.format, ReportOutputFormat::Json);
+    assert_eq!(scan_args.skip_word, vec!["example"]);
+    assert_eq!(scan_args.extra_ignore_comments, vec!["nosec"]);
+    assert_eq!(
+        scan_args.baseline_file.as_deref(),
+        Some(dir.path().join("baseline.yaml").as_path())
+    );
+    assert_eq!(
+        scan_args.output_args.output.as_deref(),
+        Some(dir.path().join("findings.json").as_path())
+    );
+    assert_eq!(scan_args.path_overrides[0].base_dir, dir.path());
+    assert!(scan_args.content_filtering_args.exclude.contains(&"*.lock".to_string()));
+    let anchored = format!("{}/vendor/fixtures", globset::escape(root));
+    assert!(scan_args.content_filtering_args.exclude.contains(&anchored));
+    assert!(!scan_args.content_filtering_args.exclude.contains(&"**/vendor/fixtures".to_string()));
+    assert_eq!(scan_args.path_overrides.len(), 1);
+
+    Ok(())
+}
+
+#[test]
+fn cli_flags_override_config_values() -> anyhow::Result<()> {
+    let dir = tempdir()?;
+    std::fs::write(
+        dir.path().join(".kingfisher.yml"),
+        "confidence: low\nvalidation-retries: 3\nskip-word:\n  - fromfile\n",
+    )?;
+
+    let root = dir.path().to_str().unwrap();
+    let scan_args = parse_scan(&[
+        "kingfisher",
+        "scan",
+        root,
+        "--confidence",
+        "high",
+        "--validation-retries",
+        "0",
+        "--skip-word",
+        "fromcli",
+        "--no-update-check",
+    ])?;
+
+    assert_eq!(scan_args.confidence, ConfidenceLevel::High);
+    assert_eq!(scan_args.validation_retries, 0);
+    assert_eq!(scan_args.skip_word, vec!["fromcli", "fromfile"]);
+
+    Ok(())
+}
+
+#[test]
+fn cli_values_equal_to_the_default_still_win() -> anyhow::Result<()> {
+    let dir = tempdir()?;
+    std::fs::write(
+        dir.path().join(".kingfisher.toml"),
+        "confidence = \"high\"\nformat = \"json\"\nload-builtins = false\n",
+    )?;
+
+    let root = dir.path().to_str().unwrap();
+    let scan_args = parse_scan(&[
+        "kingfisher",
+        "scan",
+        root,
+        "--confidence",
+        "medium",
+        "--format",
+        "pretty",
+        "--no-update-check",
+    ])?;
+
+    assert_eq!(scan_args.confidence, ConfidenceLevel::Medium);
+    assert_eq!(scan_args.output_args.format, ReportOutputFormat::Pretty);
+    assert!(!scan_args.rules.load_builtins);
+    Ok(())
+}
+
+#[test]
+fn explicit_config_path_is_used() -> anyhow::Result<()> {
+    let dir = tempdir()?;
+    let config = dir.path().join("policy.yaml");
+    std::fs::write(&config, "no-binary: true\n")?;
+    let target = tempdir()?;
+
+    let scan_args = parse_scan(&[
+        "kingfisher",
+        "scan",
+        target.path().to_str().unwrap(),
+        "--config",
+        config.to_str().unwrap(),
+        "--no-update-check",
+    ])?;
+
+    assert!(scan_args.content_filtering_args.no_binary);
+    Ok(())
+}
+
+#[test]
+fn invalid_config_is_rejected() -> anyhow::Result<()> {
+    let dir = tempdir()?;
+    std::fs::write(dir.path().join(".kingfisher.toml"), "confidence = \"extreme\"\n")?;
+
+    let root = dir.path().to_str().unwrap();
+    assert!(parse_scan(&["kingfisher", "scan", root, "--no-update-check"]).is_err());
+    Ok(())
+}
@@ -0,0 +1,110 @@
+use std::{fs, path::Path};
+
+use assert_cmd::Command;
+use predicates::prelude::*;
+use tempfile::tempdir;
+use wiremock::{
+    matchers::{header, method, path},
+    Mock, MockServer, ResponseTemplate,
+};
+
+const DEMO_RECORD: &str = r#"{"rule":{"name":"Demo Token","id":"custom.revoke.1"},"finding":{"snippet":"rvk_a1b2c3d4e5f6","fingerprint":"4242","confidence":"medium","entropy":"3.50","validation":{"status":"Active Credential","response":"ok"},"language":"Unknown","line":3,"column_start":0,"column_end":16,"path":"app.env"}}"#;
+
+fn revocable_rules(dir: &Path, server: &str) -> anyhow::Result<std::path::PathBuf> {
+    let rules = dir.join("revoke.yml");
+    fs::write(
+        &rules,
+        format!(
+            r#"
+rules:
+  - name: Demo Token
+    id: custom.revoke.1
+    pattern: '(rvk_[a-z0-9]{{12}})'
+    min_entropy: 0.0
+    confidence: low
+    examples:
+      - rvk_a1b2c3d4e5f6
+    revocation:
+      type: Http
+      content:
+        request:
+          method: DELETE
+          url: {server}/tokens/self
+          headers:
+            Authorization: Bearer {{{{ TOKEN }}}}
+          response_matcher:
+            - type: StatusMatch
+              status: [204]
+"#
+        ),
+    )?;
+    Ok(rules)
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn revoke_sends_revocation_and_writes_audit_log() -> anyhow::Result<()> {
+    let server = MockServer::start().await;
+    Mock::given(method("DELETE"))
+        .and(path("/tokens/self"))
+        .and(header("authorization", "Bearer rvk_a1b2c3d4e5f6"))
+        .respond_with(ResponseTemplate::new(204))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let dir = tempdir()?;
+    let rules = revocable_rules(dir.path(), &server.uri())?;
+    let report = dir.path().join("report.jsonl");
+    fs::write(&report, format!("{DEMO_RECORD}\n"))?;
+    let audit = dir.path().join("audit.jsonl");
+
+    Command::new(assert_cmd::cargo::cargo_bin!("kingfisher"))
+        .args(["revoke", "--report", report.to_str().unwrap(), "--fingerprint", "4242"])
+        .args(["--rules-path", rules.to_str().unwrap(), "--load-builtins=false", "--yes"])
+        .args(["--audit-log", audit.to_str().unwrap(), "--no-update-check"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Revoked [custom.revoke.1] app.env:3"));
+
+    let log = fs::read_to_string(&audit)?;
+    assert!(log.contains(r#""fingerprint":"4242""#), "{log}");
+    assert!(log.contains(r#""revoked":true"#), "{log}");
+    assert!(!log.contains("rvk_a1b2c3d4e5f6"), "audit log must not contain the secret: {log}");
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn revoke_requires_confirmation() -> anyhow::Result<()> {
+    let server = MockServer::start().await;
+    Mock::given(method("DELETE"))
+        .respond_with(ResponseTemplate::new(204))
+        .expect(0)
+        .mount(&server)
+        .await;
+
+    let dir = tempdir()?;
+    let rules = revocable_rules(dir.path(), &server.uri())?;
+    let report = dir.path().join("report.jsonl");
+    fs::write(&report, format!("{DEMO_RECORD}\n"))?;
+    let audit = dir.path().join("audit.jsonl");
+
+    Command::new(assert_cmd::cargo::cargo_bin!("kingfisher"))
+        .args(["revoke", "--report", report.to_str().unwrap(), "--fingerprint", "4242"])
+        .args(["--rules-path", rules.to_str().unwrap(), "--load-builtins=false"])
+        .args(["--audit-log", audit.to_str().unwrap(), "--no-update-check"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("pass --yes"));
+    assert!(!audit.exists());
+
+    Command::new(assert_cmd::cargo::cargo_bin!("kingfisher"))
+        .args(["revoke", "--report", report.to_str().unwrap(), "--fingerprint", "9999"])
+        .args(["--rules-path", rules.to_str().unwrap(), "--load-builtins=false", "--yes"])
+        .args(["--no-update-check"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("No finding with fingerprint 9999"));
+
+    Ok(())
+}
@@ -0,0 +1,76 @@
+use std::time::Duration;
+
+use assert_cmd::Command;
+use http::StatusCode;
+use kingfisher::validation_cache::{CacheTtls, ValidationCache};
+use predicates::prelude::*;
+use tempfile::tempdir;
+
+fn seed_cache(path: &std::path::Path) -> anyhow::Result<()> {
+    let ttls = CacheTtls {
+        valid: Duration::from_secs(3600),
+        invalid: Duration::from_secs(3600),
+        error: Duration::from_secs(3600),
+    };
+    let cache = ValidationCache::open(path, ttls)?;
+    cache.record(1, "kingfisher.github.2", true, StatusCode::OK, "{\"login\":\"octocat\"}");
+    cache.record(2, "kingfisher.github.2", false, StatusCode::UNAUTHORIZED, "Bad credentials");
+    cache.record(3, "kingfisher.slack.2", false, StatusCode::TOO_MANY_REQUESTS, "ratelimited");
+    cache.save()
+}
+
+#[test]
+fn inspect_and_purge_validation_cache() -> anyhow::Result<()> {
+    let dir = tempdir()?;
+    let path = dir.path().join("validation-cache.bin");
+    seed_cache(&path)?;
+
+    Command::new(assert_cmd::cargo::cargo_bin!("kingfisher"))
+        .args(["validation-cache", "inspect", path.to_str().unwrap(), "--format", "json"])
+        .assert()
+        .success()
+        .stdout(
+            predicate::str::contains("\"kingfisher.github.2\"")
+                .and(predicate::str::contains("\"kingfisher.slack.2\""))
+                .and(predicate::str::contains("octocat").not()),
+        );
+
+    Command::new(assert_cmd::cargo::cargo_bin!("kingfisher"))
+        .args([
+            "validation-cache",
+            "purge",
+            path.to_str().unwrap(),
+            "--rule",
+            "kingfisher.github",
+            "--class",
+            "invalid",
+        ])
+        .assert()
+        .success();
+
+    let output = Command::new(assert_cmd::cargo::cargo_bin!("kingfisher"))
+        .args([
+            "validation-cache",
+            "inspect",
+            path.to_str().unwrap(),
+            "--entries",
+            "--format",
+            "json",
+        ])
+        .output()?;
+    assert!(output.status.success());
+    let entries: Vec<serde_json::Value> = serde_json::from_slice(&output.stdout)?;
+    let classes: Vec<_> = entries.iter().map(|e| e["class"].as_str().unwrap()).collect();
+    assert_eq!(classes, vec!["valid", "error"]);
+
+    Ok(())
+}
+
+#[test]
+fn inspect_rejects_missing_cache() {
+    let dir = tempdir().unwrap();
+    Command::new(assert_cmd::cargo::cargo_bin!("kingfisher"))
+        .args(["validation-cache", "inspect", dir.path().join("nope.bin").to_str().unwrap()])
+        .assert()
+        .failure();
+}
@@ -25,6 +25,8 @@ fn make_rule(rule_id: &str, depends_on_rule: Vec<Option<DependsOnRule>>) -> Arc<
         references: vec![],
         validation: None,
         depends_on_rule,
+        extract: Default::default(),
+        revocation: None,
         pattern_requirements: None,
     }))
 }
@@ -53,9 +55,12 @@ fn make_match(rule: Arc<Rule>, blob_id: BlobId, value: &str) -> Match {
         validation_response_body: None,
         validation_response_status: 0,
         validation_success: false,
+        validation_identity: Default::default(),
+        dependency_sources: Default::default(),
         calculated_entropy: 0.0,
         visible: true,
         is_base64: false,
+        suppressed_by: None,
     }
 }
 
@@ -33,6 +33,8 @@ fn make_match(fp: u64, rule_id: &str) -> Match {
         references: vec![],
         validation: None,
         depends_on_rule: vec![],
+        extract: Default::default(),
+        revocation: None,
         pattern_requirements: None,
     };
     let rule = Arc::new(Rule::new(syntax));
@@ -59,9 +61,12 @@ fn make_match(fp: u64, rule_id: &str) -> Match {
         validation_response_body: None,
         validation_response_status: 0,
         validation_success: false,
+        validation_identity: Default::default(),
+        dependency_sources: Default::default(),
         calculated_entropy: 0.0,
         visible: true,
         is_base64: false,
+        suppressed_by: None,
     }
 }
 
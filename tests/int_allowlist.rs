@@ -4,6 +4,7 @@ use std::{
 };
 
 use anyhow::Result;
+use clap::Parser;
 use kingfisher::{
     cli::{
         commands::{
@@ -15,9 +16,15 @@ use kingfisher::{
             inputs::{ContentFilteringArgs, InputSpecifierArgs},
             output::{OutputArgs, ReportOutputFormat},
             rules::RuleSpecifierArgs,
-            scan::{ConfidenceLevel, ScanArgs},
+            scan::{ConfidenceLevel, ScanArgs, ScanOperation},
+            validation_cache::ValidationCacheArgs,
+            validation_dependency::ValidationDependencyArgs,
+            validation_dry_run::ValidationDryRunArgs,
+            validation_egress::ValidationEgressArgs,
+            validation_rate::ValidationRateArgs,
+            validation_ssh::ValidationSshArgs,
         },
-        global::Mode,
+        global::{Command, CommandLineArgs, Mode},
         GlobalArgs,
     },
     findings_store::FindingsStore,
@@ -118,10 +125,15 @@ fn run_skiplist(skip_regex: Vec<String>, skip_skipword: Vec<String>) -> Result<u
             gcs_prefix: None,
             gcs_service_account: None,
             docker_image: Vec::new(),
+            registry_repository: Vec::new(),
+            all_registry_repositories: false,
             git_clone: GitCloneMode::Bare,
             git_history: GitHistoryMode::Full,
             commit_metadata: true,
             repo_artifacts: false,
+            ci_logs: false,
+            ci_logs_max_age_days: 30,
+            ci_logs_max_runs: 20,
             scan_nested_repos: true,
             since_commit: None,
             branch: None,
@@ -158,6 +170,17 @@ fn run_skiplist(skip_regex: Vec<String>, skip_skipword: Vec<String>) -> Result<u
         no_inline_ignore: false,
         no_ignore_if_contains: false,
         validation_retries: 1,
+        config: None,
+        path_overrides: Vec::new(),
+        explicit_args: Default::default(),
+        allowlist: None,
+        show_suppressed: false,
+        validation_cache: ValidationCacheArgs::default(),
+        validation_rate: ValidationRateArgs::default(),
+        validation_egress: ValidationEgressArgs::default(),
+        validation_dry_run: ValidationDryRunArgs::default(),
+        validation_dependency: ValidationDependencyArgs::default(),
+        validation_ssh: ValidationSshArgs::default(),
         validation_timeout: 10,
     };
 
@@ -204,3 +227,91 @@ fn skip_skipword_filters_match() -> Result<()> {
     assert_eq!(count, 1);
     Ok(())
 }
+
+/// Number of findings left visible after applying `allowlist_yaml`.
+fn run_allowlist(allowlist_yaml: &str) -> Result<usize> {
+    let rt = Runtime::new().unwrap();
+    let work = TempDir::new()?;
+    let rules_dir = work.path().join("rules");
+    fs::create_dir_all(&rules_dir)?;
+    let inputs_dir = work.path().join("in");
+    fs::create_dir_all(&inputs_dir)?;
+
+    fs::write(
+        rules_dir.join("demo.yml"),
+        r#"rules:
+  - id: demo.token
+    name: Demo token
+    pattern: 'token_(\w+)'
+    confidence: low
+"#,
+    )?;
+    fs::write(inputs_dir.join("a.txt"), "token_realvalue\ntoken_testvalue\n")?;
+    let allowlist = work.path().join("allowlist.yml");
+    fs::write(&allowlist, allowlist_yaml)?;
+
+    let args = CommandLineArgs::try_parse_from([
+        "kingfisher",
+        "scan",
+        inputs_dir.join("a.txt").to_str().unwrap(),
+        "--rules-path",
+        rules_dir.to_str().unwrap(),
+        "--load-builtins",
+        "false",
+        "--confidence",
+        "low",
+        "--no-validate",
+        "--allowlist",
+        allowlist.to_str().unwrap(),
+        "--quiet",
+        "--no-update-check",
+    ])?;
+    let Command::Scan(command) = args.command else { panic!("expected scan command") };
+    let ScanOperation::Scan(scan_args) = command.into_operation()? else {
+        panic!("expected scan operation")
+    };
+
+    let loaded = RuleLoader::from_rule_specifiers(&scan_args.rules).load(&scan_args)?;
+    let resolved = loaded.resolve_enabled_rules()?;
+    let rules_db = Arc::new(RulesDatabase::from_rules(resolved.into_iter().cloned().collect())?);
+    let datastore = Arc::new(Mutex::new(FindingsStore::new(work.path().join("store"))));
+
+    rt.block_on(run_async_scan(
+        &args.global_args,
+        &scan_args,
+        Arc::clone(&datastore),
+        &rules_db,
+        &UpdateStatus::default(),
+    ))?;
+
+    let visible = datastore.lock().unwrap().get_num_matches();
+    Ok(visible)
+}
+
+#[test]
+fn allowlist_entry_filters_match() -> Result<()> {
+    let count = run_allowlist(
+        r#"entries:
+  - id: demo-fixture
+    rule: demo
+    path: "**/a.txt"
+    secret: "realvalue"
+    justification: Fixture value used in docs
+"#,
+    )?;
+    assert_eq!(count, 1);
+    Ok(())
+}
+
+#[test]
+fn expired_allowlist_entry_is_ignored() -> Result<()> {
+    let count = run_allowlist(
+        r#"entries:
+  - rule: demo.token
+    justification: Temporary exception
+    expires: 2000-01-01
+"#,
+    )?;
+    assert_eq!(count, 2);
+    Ok(())
+}
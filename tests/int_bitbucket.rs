@@ -16,6 +16,12 @@ use kingfisher::{
             output::{OutputArgs, ReportOutputFormat},
             rules::RuleSpecifierArgs,
             scan::{ConfidenceLevel, ScanArgs},
+            validation_cache::ValidationCacheArgs,
+            validation_dependency::ValidationDependencyArgs,
+            validation_dry_run::ValidationDryRunArgs,
+            validation_egress::ValidationEgressArgs,
+            validation_rate::ValidationRateArgs,
+            validation_ssh::ValidationSshArgs,
         },
         global::Mode,
         GlobalArgs,
@@ -118,10 +124,15 @@ fn test_bitbucket_remote_scan() -> Result<()> {
             gcs_prefix: None,
             gcs_service_account: None,
             docker_image: Vec::new(),
+            registry_repository: Vec::new(),
+            all_registry_repositories: false,
             git_clone: GitCloneMode::Bare,
             git_history: GitHistoryMode::Full,
             commit_metadata: true,
             repo_artifacts: false,
+            ci_logs: false,
+            ci_logs_max_age_days: 30,
+            ci_logs_max_runs: 20,
             scan_nested_repos: true,
             since_commit: None,
             branch: None,
@@ -158,6 +169,17 @@ fn test_bitbucket_remote_scan() -> Result<()> {
         no_inline_ignore: false,
         no_ignore_if_contains: false,
         validation_retries: 1,
+        config: None,
+        path_overrides: Vec::new(),
+        explicit_args: Default::default(),
+        allowlist: None,
+        show_suppressed: false,
+        validation_cache: ValidationCacheArgs::default(),
+        validation_rate: ValidationRateArgs::default(),
+        validation_egress: ValidationEgressArgs::default(),
+        validation_dry_run: ValidationDryRunArgs::default(),
+        validation_dependency: ValidationDependencyArgs::default(),
+        validation_ssh: ValidationSshArgs::default(),
         validation_timeout: 10,
     };
 
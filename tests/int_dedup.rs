@@ -20,6 +20,12 @@ use kingfisher::{
             output::{OutputArgs, ReportOutputFormat},
             rules::RuleSpecifierArgs,
             scan::{ConfidenceLevel, ScanArgs},
+            validation_cache::ValidationCacheArgs,
+            validation_dependency::ValidationDependencyArgs,
+            validation_dry_run::ValidationDryRunArgs,
+            validation_egress::ValidationEgressArgs,
+            validation_rate::ValidationRateArgs,
+            validation_ssh::ValidationSshArgs,
         },
         global::Mode,
         GlobalArgs,
@@ -137,11 +143,16 @@ rules:
             gcs_service_account: None,
             // Docker image scanning
             docker_image: Vec::new(),
+            registry_repository: Vec::new(),
+            all_registry_repositories: false,
             // git clone / history options
             git_clone: GitCloneMode::Bare,
             git_history: GitHistoryMode::Full,
             commit_metadata: true,
             repo_artifacts: false,
+            ci_logs: false,
+            ci_logs_max_age_days: 30,
+            ci_logs_max_runs: 20,
             scan_nested_repos: true,
             since_commit: None,
             branch: None,
@@ -178,6 +189,17 @@ rules:
         no_inline_ignore: false,
         no_ignore_if_contains: false,
         validation_retries: 1,
+        config: None,
+        path_overrides: Vec::new(),
+        explicit_args: Default::default(),
+        allowlist: None,
+        show_suppressed: false,
+        validation_cache: ValidationCacheArgs::default(),
+        validation_rate: ValidationRateArgs::default(),
+        validation_egress: ValidationEgressArgs::default(),
+        validation_dry_run: ValidationDryRunArgs::default(),
+        validation_dependency: ValidationDependencyArgs::default(),
+        validation_ssh: ValidationSshArgs::default(),
         validation_timeout: 10,
     };
 
@@ -0,0 +1,93 @@
+// tests/int_dependency_scope.rs
+use std::fs;
+
+use anyhow::Result;
+use assert_cmd::Command;
+use predicates::prelude::*;
+use tempfile::tempdir;
+use wiremock::{
+    matchers::{header, method, path, query_param},
+    Mock, MockServer, ResponseTemplate,
+};
+
+/// An app key in `config/prod.env` whose app id lives in `config/prod.yaml`
+/// is only validated once the dependency scope reaches the whole directory.
+#[tokio::test]
+async fn dependency_scope_pairs_values_across_files() -> Result<()> {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/check"))
+        .and(query_param("app_id", "myapp12345"))
+        .and(header("x-app-key", "demokey_a1b2c3d4e5f6"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("ok"))
+        .mount(&server)
+        .await;
+
+    let dir = tempdir()?;
+    let rules = dir.path().join("rules.yml");
+    fs::write(
+        &rules,
+        format!(
+            r#"
+rules:
+  - name: Demo App ID
+    id: demo.appid.1
+    pattern: 'DEMO_APP_ID=([a-z0-9]{{10}})'
+    min_entropy: 0.0
+    confidence: low
+    examples:
+      - DEMO_APP_ID=myapp12345
+  - name: Demo App Key
+    id: demo.appkey.1
+    pattern: 'DEMO_APP_KEY=(demokey_[a-z0-9]{{12}})'
+    min_entropy: 0.0
+    confidence: low
+    examples:
+      - DEMO_APP_KEY=demokey_a1b2c3d4e5f6
+    depends_on_rule:
+      - rule_id: demo.appid.1
+        variable: APPID
+    validation:
+      type: Http
+      content:
+        request:
+          method: GET
+          url: '{server}/check?app_id={{{{ APPID }}}}'
+          headers:
+            X-App-Key: '{{{{ TOKEN }}}}'
+          response_matcher:
+            - type: StatusMatch
+              status: [200]
+"#,
+            server = server.uri()
+        ),
+    )?;
+
+    let config = dir.path().join("input").join("config");
+    fs::create_dir_all(&config)?;
+    fs::write(config.join("prod.env"), "DEMO_APP_KEY=demokey_a1b2c3d4e5f6\n")?;
+    fs::write(config.join("prod.yaml"), "DEMO_APP_ID=myapp12345\n")?;
+    let input = dir.path().join("input");
+
+    let scan = |scope: &str| {
+        let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("kingfisher"));
+        cmd.args(["scan", input.to_str().unwrap(), "--rules-path", rules.to_str().unwrap()])
+            .args(["--load-builtins=false", "--format", "json", "--no-update-check"])
+            .args(["--dependency-scope", scope]);
+        cmd
+    };
+
+    // By default the key's own file must hold the app id.
+    scan("blob")
+        .assert()
+        .code(200)
+        .stdout(predicate::str::contains("missing dependent rules: demo.appid.1"));
+
+    scan("directory").assert().code(205).stdout(
+        predicate::str::contains("\"dependency_sources\"")
+            .and(predicate::str::contains("\"APPID\""))
+            .and(predicate::str::contains("config/prod.yaml")),
+    );
+
+    Ok(())
+}
@@ -0,0 +1,314 @@
+// tests/int_repo_artifacts.rs
+//
+// `--repo-artifacts` and `--ci-logs` fetchers against mocked Gitea, Hugging
+// Face, GitHub and GitLab APIs.
+use std::{
+    fs,
+    io::Write,
+    path::Path,
+    str::FromStr,
+    sync::{Arc, Mutex},
+};
+
+use anyhow::Result;
+use kingfisher::{
+    ci_logs::CiLogLimits, findings_store::FindingsStore, git_url::GitUrl, gitea, github, gitlab,
+    huggingface,
+};
+use serde_json::json;
+use tempfile::tempdir;
+use url::Url;
+use wiremock::{
+    matchers::{method, path, path_regex, query_param},
+    Mock, MockServer, ResponseTemplate,
+};
+use zip::{write::SimpleFileOptions, ZipWriter};
+
+fn datastore(root: &Path) -> Arc<Mutex<FindingsStore>> {
+    Arc::new(Mutex::new(FindingsStore::new(root.to_path_buf())))
+}
+
+fn link(ds: &Arc<Mutex<FindingsStore>>, file: &Path) -> Option<String> {
+    ds.lock().unwrap().repo_links().get(file).cloned()
+}
+
+fn artifact_zip() -> Result<Vec<u8>> {
+    let mut zip = ZipWriter::new(std::io::Cursor::new(Vec::new()));
+    zip.start_file("dist/.env", SimpleFileOptions::default())?;
+    zip.write_all(b"API_TOKEN=artifact-secret\n")?;
+    Ok(zip.finish()?.into_inner())
+}
+
+const LIMITS: CiLogLimits = CiLogLimits { max_age_days: 30, max_runs: 5 };
+
+#[tokio::test]
+async fn gitea_items_follow_pagination_and_link_back() -> Result<()> {
+    let server = MockServer::start().await;
+    let issues = "/api/v1/repos/acme/app/issues";
+    let issue = |number: u64| {
+        json!({
+            "number": number,
+            "title": format!("Issue {number}"),
+            "body": format!("token {number}"),
+            "html_url": format!("https://gitea.example/acme/app/issues/{number}"),
+        })
+    };
+    for (page, body) in
+        [("1", json!([issue(1), issue(2)])), ("2", json!([issue(3)])), ("3", json!([]))]
+    {
+        Mock::given(method("GET"))
+            .and(path(issues))
+            .and(query_param("page", page))
+            .respond_with(ResponseTemplate::new(200).set_body_json(body))
+            .expect(1)
+            .mount(&server)
+            .await;
+    }
+    Mock::given(method("GET"))
+        .and(path("/api/v1/repos/acme/app/releases"))
+        .and(query_param("page", "1"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!([{
+            "id": 9, "tag_name": "v1.0", "name": "First", "body": "notes",
+            "html_url": "https://gitea.example/acme/app/releases/tag/v1.0",
+        }])))
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/api/v1/repos/acme/app/releases"))
+        .and(query_param("page", "2"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!([])))
+        .mount(&server)
+        .await;
+
+    let tmp = tempdir()?;
+    let ds = datastore(tmp.path());
+    let repo = GitUrl::from_str(&format!("{}/acme/app.git", server.uri())).unwrap();
+    let api = Url::parse(&format!("{}/api/v1", server.uri()))?;
+    let dirs = gitea::fetch_repo_items(&repo, &api, false, tmp.path(), &ds).await?;
+
+    let issues_dir = tmp.path().join("gitea_issues/acme/app");
+    let releases_dir = tmp.path().join("gitea_releases/acme/app");
+    assert_eq!(dirs, vec![issues_dir.clone(), releases_dir.clone()]);
+    assert_eq!(fs::read_to_string(issues_dir.join("issue_3.md"))?, "# Issue 3\n\ntoken 3");
+    for number in 1..=3 {
+        assert_eq!(
+            link(&ds, &issues_dir.join(format!("issue_{number}.md"))),
+            Some(format!("https://gitea.example/acme/app/issues/{number}"))
+        );
+    }
+    assert_eq!(
+        link(&ds, &releases_dir.join("release_9.md")).as_deref(),
+        Some("https://gitea.example/acme/app/releases/tag/v1.0")
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn huggingface_pull_request_diffs_are_captured() -> Result<()> {
+    let server = MockServer::start().await;
+    let api = "/api/models/acme/model/discussions";
+    Mock::given(method("GET"))
+        .and(path(api))
+        .and(query_param("p", "0"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "discussions": [{"num": 1}],
+        })))
+        .mount(&server)
+        .await;
+    // No `count`: pages are followed until one comes back empty.
+    Mock::given(method("GET"))
+        .and(path(api))
+        .and(query_param("p", "1"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "discussions": [{"num": 2}],
+        })))
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path(api))
+        .and(query_param("p", "2"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({"discussions": []})))
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path(format!("{api}/1")))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "title": "Question",
+            "events": [{
+                "type": "comment",
+                "author": {"name": "alice"},
+                "data": {"latest": {"raw": "does this work?"}},
+            }],
+        })))
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path(format!("{api}/2")))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "title": "Add config",
+            "isPullRequest": true,
+            "events": [],
+            "diff": "+++ b/config.env\n+HF_TOKEN=hf_pr_secret\n",
+        })))
+        .mount(&server)
+        .await;
+
+    let tmp = tempdir()?;
+    let ds = datastore(tmp.path());
+    let repo = GitUrl::from_str(&format!("{}/acme/model.git", server.uri())).unwrap();
+    let dirs = huggingface::fetch_repo_items(
+        &repo,
+        &huggingface::AuthConfig::default(),
+        false,
+        tmp.path(),
+        &ds,
+    )
+    .await?;
+
+    let dir = tmp.path().join("huggingface_discussions/models/acme_model");
+    assert_eq!(dirs, vec![dir.clone()]);
+    assert!(fs::read_to_string(dir.join("discussion_1.md"))?.contains("## alice"));
+    assert!(!dir.join("pr_1.diff").exists());
+    let diff = dir.join("pr_2.diff");
+    assert!(fs::read_to_string(&diff)?.contains("HF_TOKEN=hf_pr_secret"));
+    assert_eq!(link(&ds, &diff), Some(format!("{}/acme/model/discussions/2/files", server.uri())));
+    Ok(())
+}
+
+#[tokio::test]
+async fn github_ci_logs_and_artifacts_are_downloaded() -> Result<()> {
+    let server = MockServer::start().await;
+    let actions = "/repos/acme/app/actions";
+    Mock::given(method("GET"))
+        .and(path(format!("{actions}/runs")))
+        .and(query_param("page", "1"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "workflow_runs": [{"id": 7, "html_url": "https://github.example/acme/app/actions/runs/7"}],
+        })))
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path(format!("{actions}/runs/7/jobs")))
+        .and(query_param("page", "1"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "jobs": [{"id": 11, "html_url": "https://github.example/acme/app/actions/runs/7/job/11"}],
+        })))
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path(format!("{actions}/jobs/11/logs")))
+        .respond_with(ResponseTemplate::new(200).set_body_string("echo GH_SECRET=job-secret\n"))
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path(format!("{actions}/runs/7/artifacts")))
+        .and(query_param("page", "1"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "artifacts": [{
+                "id": 5,
+                "size_in_bytes": 200,
+                "archive_download_url": format!("{}/download/5", server.uri()),
+            }],
+        })))
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path(format!("{actions}/runs/7/artifacts")))
+        .and(query_param("page", "2"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            // Unreachable: skipped with a warning instead of failing the scan.
+            "artifacts": [{
+                "id": 6,
+                "size_in_bytes": 200,
+                "archive_download_url": "http://127.0.0.1:1/download/6",
+            }],
+        })))
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path(format!("{actions}/runs/7/artifacts")))
+        .and(query_param("page", "3"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({"artifacts": []})))
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/download/5"))
+        .respond_with(ResponseTemplate::new(200).set_body_bytes(artifact_zip()?))
+        .mount(&server)
+        .await;
+
+    let tmp = tempdir()?;
+    let ds = datastore(tmp.path());
+    let repo = GitUrl::from_str("https://github.com/acme/app.git").unwrap();
+    let api = Url::parse(&server.uri())?;
+    let dirs = github::fetch_ci_logs(&repo, &api, false, tmp.path(), &LIMITS, &ds).await?;
+
+    let ci_dir = tmp.path().join("github_ci/acme/app");
+    assert_eq!(dirs, vec![ci_dir.clone()]);
+    let log = ci_dir.join("run_7/job_11.log");
+    assert_eq!(fs::read_to_string(&log)?, "echo GH_SECRET=job-secret\n");
+    assert_eq!(
+        link(&ds, &log).as_deref(),
+        Some("https://github.example/acme/app/actions/runs/7/job/11")
+    );
+    let env = ci_dir.join("run_7/artifacts/5/dist/.env");
+    assert_eq!(fs::read_to_string(&env)?, "API_TOKEN=artifact-secret\n");
+    assert_eq!(
+        link(&ds, &env).as_deref(),
+        Some("https://github.example/acme/app/actions/runs/7/artifacts/5")
+    );
+    assert!(!ci_dir.join("run_7/artifacts/6").exists());
+    Ok(())
+}
+
+#[tokio::test]
+async fn gitlab_ci_logs_and_artifacts_use_the_configured_api() -> Result<()> {
+    let server = MockServer::start().await;
+    let project = "^/api/v4/projects/group%2Fapp";
+    Mock::given(method("GET"))
+        .and(path_regex(format!("{project}/pipelines$")))
+        .and(query_param("page", "1"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!([{"id": 3}])))
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path_regex(format!("{project}/pipelines/3/jobs$")))
+        .and(query_param("page", "1"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!([{
+            "id": 21,
+            "web_url": "https://gitlab.example/group/app/-/jobs/21",
+            "artifacts_file": {"size": 200},
+        }])))
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path_regex(format!("{project}/jobs/21/trace$")))
+        .respond_with(ResponseTemplate::new(200).set_body_string("export GL_SECRET=trace\n"))
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path_regex(format!("{project}/jobs/21/artifacts$")))
+        .respond_with(ResponseTemplate::new(200).set_body_bytes(artifact_zip()?))
+        .mount(&server)
+        .await;
+
+    let tmp = tempdir()?;
+    let ds = datastore(tmp.path());
+    let repo = GitUrl::from_str(&format!("{}/group/app.git", server.uri())).unwrap();
+    let api = Url::parse(&server.uri())?;
+    let dirs = gitlab::fetch_ci_logs(&repo, &api, false, tmp.path(), &LIMITS, &ds).await?;
+
+    let ci_dir = tmp.path().join("gitlab_ci/group_app");
+    assert_eq!(dirs, vec![ci_dir.clone()]);
+    let log = ci_dir.join("pipeline_3/job_21.log");
+    assert_eq!(fs::read_to_string(&log)?, "export GL_SECRET=trace\n");
+    assert_eq!(link(&ds, &log).as_deref(), Some("https://gitlab.example/group/app/-/jobs/21"));
+    let env = ci_dir.join("pipeline_3/artifacts/job_21/dist/.env");
+    assert_eq!(
+        link(&ds, &env).as_deref(),
+        Some("https://gitlab.example/group/app/-/jobs/21/artifacts/file/dist/.env")
+    );
+    Ok(())
+}
@@ -0,0 +1,105 @@
+//! Live LDAP bind validation against OpenLDAP containers provisioned with
+//! `testcontainers`.
+//!
+//! Ignored by default because it requires Docker. Run it with:
+//! `cargo test --test live_ldap_validation -- --ignored`.
+//!
+//! Like the database tests, the containers are reached through the
+//! `kingfisherlocal` host alias, since localhost URLs are never validated.
+
+use std::{
+    process::Command,
+    time::{Duration, Instant},
+};
+
+use anyhow::{anyhow, Result};
+use kingfisher::validation::validate_ldap;
+use testcontainers::{clients::Cli, core::WaitFor, GenericImage};
+use tokio::{net::TcpStream, time::sleep};
+
+const HOST_ALIAS: &str = "kingfisherlocal";
+const STARTUP_TIMEOUT: Duration = Duration::from_secs(60);
+const STARTUP_POLL_INTERVAL: Duration = Duration::from_millis(250);
+const BIND_DN: &str = "cn=admin,dc=example,dc=org";
+/// CA the image signs its generated server certificate with.
+const CA_PATH: &str = "/container/service/slapd/assets/certs/ca.crt";
+
+async fn wait_for_port(host: &str, port: u16) -> Result<()> {
+    let deadline = Instant::now() + STARTUP_TIMEOUT;
+    loop {
+        match TcpStream::connect((host, port)).await {
+            Ok(_) => return Ok(()),
+            Err(err) if Instant::now() >= deadline => {
+                return Err(anyhow!("timed out waiting for {host}:{port}: {err}"));
+            }
+            Err(_) => sleep(STARTUP_POLL_INTERVAL).await,
+        }
+    }
+}
+
+fn openldap(tls: bool) -> GenericImage {
+    GenericImage::new("osixia/openldap", "1.5.0")
+        .with_env_var("LDAP_DOMAIN", "example.org")
+        .with_env_var("LDAP_ADMIN_PASSWORD", "secret")
+        .with_env_var("LDAP_TLS", tls.to_string())
+        // The generated certificate is issued for the container's HOSTNAME.
+        .with_env_var("HOSTNAME", HOST_ALIAS)
+        .with_env_var("LDAP_TLS_VERIFY_CLIENT", "never")
+        .with_wait_for(WaitFor::message_on_stderr("slapd starting"))
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+#[ignore]
+async fn binds_over_starttls_and_ldaps() -> Result<()> {
+    let docker = Cli::default();
+    let container = docker.run(openldap(true));
+    let ldap_port = container.get_host_port_ipv4(389);
+    let ldaps_port = container.get_host_port_ipv4(636);
+    wait_for_port(HOST_ALIAS, ldap_port).await?;
+
+    // Trust the container's CA; the native root loader honours SSL_CERT_FILE.
+    let ca = Command::new("docker").args(["exec", container.id(), "cat", CA_PATH]).output()?;
+    assert!(ca.status.success(), "could not read {CA_PATH}");
+    let dir = tempfile::tempdir()?;
+    let ca_file = dir.path().join("ca.crt");
+    std::fs::write(&ca_file, ca.stdout)?;
+    std::env::set_var("SSL_CERT_FILE", &ca_file);
+
+    let url = format!("ldap://{HOST_ALIAS}:{ldap_port}");
+    let (is_valid, metadata) = validate_ldap(&url, BIND_DN, "secret").await?;
+    assert!(is_valid, "expected StartTLS bind to succeed, got {metadata:?}");
+    assert!(metadata.iter().any(|e| e == "tls=starttls"), "{metadata:?}");
+    assert!(
+        metadata.iter().any(|e| e == "whoami=dn:cn=admin,dc=example,dc=org"),
+        "expected WhoAmI metadata in {metadata:?}"
+    );
+
+    let url = format!("ldaps://{HOST_ALIAS}:{ldaps_port}");
+    let (is_valid, metadata) = validate_ldap(&url, BIND_DN, "secret").await?;
+    assert!(is_valid, "expected LDAPS bind to succeed, got {metadata:?}");
+    assert!(metadata.iter().any(|e| e == "tls=ldaps"), "{metadata:?}");
+
+    let (is_valid, metadata) = validate_ldap(&url, BIND_DN, "not-the-password").await?;
+    assert!(!is_valid, "expected LDAP bind to be rejected, got {metadata:?}");
+
+    drop(container);
+    drop(docker);
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+#[ignore]
+async fn refuses_cleartext_bind_when_starttls_is_declined() -> Result<()> {
+    let docker = Cli::default();
+    let container = docker.run(openldap(false));
+    let port = container.get_host_port_ipv4(389);
+    wait_for_port(HOST_ALIAS, port).await?;
+
+    let url = format!("ldap://{HOST_ALIAS}:{port}");
+    let err = validate_ldap(&url, BIND_DN, "secret").await.unwrap_err();
+    assert!(err.to_string().contains("cleartext"), "{err}");
+
+    drop(container);
+    drop(docker);
+    Ok(())
+}